@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     net::{IpAddr, SocketAddr},
     num::{NonZeroU32, NonZeroU8},
     path::PathBuf,
@@ -33,6 +34,21 @@ pub struct PodcastSettings {
     pub max_download_retries: u8,
     /// Directory for downloaded Podcasts
     pub download_dir: PathBuf,
+    /// Minutes between automatic feed refreshes done by the server, `0` to only refresh manually
+    pub refresh_interval: u32,
+    /// Per-feed refresh intervals in minutes, keyed by feed url, overriding `refresh_interval`
+    pub feed_refresh_intervals: BTreeMap<String, u32>,
+}
+
+impl PodcastSettings {
+    /// Get the refresh interval in minutes for the feed at `url`, `0` meaning never automatically
+    #[must_use]
+    pub fn refresh_interval_for(&self, url: &str) -> u32 {
+        self.feed_refresh_intervals
+            .get(url)
+            .copied()
+            .unwrap_or(self.refresh_interval)
+    }
 }
 
 /// Get the default podcast dir, which uses OS-specific paths, or home/Music/podcast
@@ -52,6 +68,8 @@ impl Default for PodcastSettings {
             concurrent_downloads_max: NonZeroU8::new(3).unwrap(),
             max_download_retries: 3,
             download_dir: default_podcast_dir(),
+            refresh_interval: 60,
+            feed_refresh_intervals: BTreeMap::new(),
         }
     }
 }
@@ -261,6 +279,11 @@ pub struct PlayerSettings {
     pub random_track_quantity: NonZeroU32,
     /// Minimal amount of tracks a album needs to have before being chosen for "random album add"
     pub random_album_min_quantity: NonZeroU32,
+
+    /// Seconds before the sleep timer expires in which the volume is faded out
+    ///
+    /// `0` disables fading
+    pub sleep_timer_fade_out: u32,
 }
 
 /// Get the default Music dir, which uses OS-specific paths, or home/Music
@@ -289,6 +312,8 @@ impl Default for PlayerSettings {
 
             random_track_quantity: NonZeroU32::new(20).unwrap(),
             random_album_min_quantity: NonZeroU32::new(5).unwrap(),
+
+            sleep_timer_fade_out: 10,
         }
     }
 }
@@ -304,6 +329,12 @@ pub enum LoopMode {
     Playlist,
     /// Select a random track on each next track
     Random,
+    /// Play every track once in a random order, then start a new order
+    Shuffle,
+    /// Play the playlist once and stop after the last track
+    Stop,
+    /// Loop the album of the current track (the consecutive tracks with the same album)
+    Album,
 }
 
 impl LoopMode {
@@ -312,16 +343,48 @@ impl LoopMode {
             match self {
                 Self::Single => "🔂",
                 Self::Playlist => "🔁",
-                Self::Random => "🔀",
+                Self::Random => "🎲",
+                Self::Shuffle => "🔀",
+                Self::Stop => "⏹",
+                Self::Album => "💿",
             }
         } else {
             match self {
                 Self::Single => "single",
                 Self::Playlist => "playlist",
                 Self::Random => "random",
+                Self::Shuffle => "shuffle",
+                Self::Stop => "stop",
+                Self::Album => "album",
             }
         }
     }
+
+    /// Convert to the representation used in the gRPC protocol
+    #[must_use]
+    pub fn as_u32(self) -> u32 {
+        match self {
+            Self::Single => 0,
+            Self::Playlist => 1,
+            Self::Random => 2,
+            Self::Shuffle => 3,
+            Self::Stop => 4,
+            Self::Album => 5,
+        }
+    }
+
+    /// Convert from the representation used in the gRPC protocol, unknown values are the default
+    #[must_use]
+    pub fn from_u32(mode: u32) -> Self {
+        match mode {
+            0 => Self::Single,
+            2 => Self::Random,
+            3 => Self::Shuffle,
+            4 => Self::Stop,
+            5 => Self::Album,
+            _ => Self::Playlist,
+        }
+    }
 }
 
 /// Settings for the gRPC server (and potentially future ways to communicate)
@@ -354,7 +417,7 @@ mod v1_interop {
     use std::{error::Error, fmt::Display, num::TryFromIntError};
 
     use super::{
-        ComSettings, LoopMode, NonZeroU32, NonZeroU8, PlayerSettings, PodcastSettings,
+        BTreeMap, ComSettings, LoopMode, NonZeroU32, NonZeroU8, PlayerSettings, PodcastSettings,
         PositionYesNo, PositionYesNoLower, RememberLastPosition, ScanDepth, SeekStep,
         ServerSettings,
     };
@@ -447,6 +510,10 @@ mod v1_interop {
                 })?,
                 max_download_retries: value.podcast_max_retries.clamp(0, u8::MAX as usize) as u8,
                 download_dir: value.podcast_dir,
+
+                // not present in v1, use the defaults
+                refresh_interval: PodcastSettings::default().refresh_interval,
+                feed_refresh_intervals: BTreeMap::new(),
             };
 
             let player_settings = PlayerSettings {
@@ -484,6 +551,9 @@ mod v1_interop {
                         err,
                     )
                 })?,
+
+                // not present in v1, use the default
+                sleep_timer_fade_out: PlayerSettings::default().sleep_timer_fade_out,
             };
 
             Ok(Self {
@@ -517,7 +587,9 @@ mod v1_interop {
                 PodcastSettings {
                     concurrent_downloads_max: NonZeroU8::new(3).unwrap(),
                     max_download_retries: 3,
-                    download_dir: PathBuf::new()
+                    download_dir: PathBuf::new(),
+                    refresh_interval: 60,
+                    feed_refresh_intervals: BTreeMap::new(),
                 }
             );
 
@@ -559,6 +631,7 @@ mod v1_interop {
                     set_discord_status: true,
                     random_track_quantity: NonZeroU32::new(20).unwrap(),
                     random_album_min_quantity: NonZeroU32::new(5).unwrap(),
+                    sleep_timer_fade_out: 10,
                 }
             );
         }
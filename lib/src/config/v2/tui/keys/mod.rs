@@ -364,6 +364,13 @@ pub struct KeysPlayer {
 
     /// Key to save the current playlist as a "m3u" playlist
     pub save_playlist: KeyBinding,
+
+    /// Key to cycle through the sleep timer presets, cycling past the last preset cancels the timer
+    pub sleep_timer_cycle: KeyBinding,
+    /// Key to extend the sleep timer (by a set amount)
+    pub sleep_timer_extend: KeyBinding,
+    /// Key to cancel the sleep timer
+    pub sleep_timer_cancel: KeyBinding,
 }
 
 impl Default for KeysPlayer {
@@ -400,6 +407,17 @@ impl Default for KeysPlayer {
                 tuievents::KeyModifiers::CONTROL,
             )
             .into(),
+            sleep_timer_cycle: tuievents::Key::Char('z').into(),
+            sleep_timer_extend: tuievents::KeyEvent::new(
+                tuievents::Key::Char('Z'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            sleep_timer_cancel: tuievents::KeyEvent::new(
+                tuievents::Key::Char('z'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
         }
     }
 }
@@ -419,6 +437,10 @@ impl CheckConflict for KeysPlayer {
             (&self.toggle_prefetch, "toggle_prefetch"),
 
             (&self.save_playlist, "save_playlist"),
+
+            (&self.sleep_timer_cycle, "sleep_timer_cycle"),
+            (&self.sleep_timer_extend, "sleep_timer_extend"),
+            (&self.sleep_timer_cancel, "sleep_timer_cancel"),
         }
     }
 
@@ -665,6 +687,12 @@ pub struct KeysLibrary {
     pub youtube_search: KeyBinding,
     /// Key to open the tag editor on that node (only works for files)
     pub open_tag_editor: KeyBinding,
+    /// Key to add the selected track or directory to the front of the up-next queue
+    pub enqueue_next: KeyBinding,
+    /// Key to add the selected track or directory to the end of the up-next queue
+    pub enqueue_last: KeyBinding,
+    /// Key to open the report of likely duplicate tracks in the library
+    pub find_duplicates: KeyBinding,
 }
 
 impl Default for KeysLibrary {
@@ -689,6 +717,17 @@ impl Default for KeysLibrary {
             search: tuievents::Key::Char('/').into(),
             youtube_search: tuievents::Key::Char('s').into(),
             open_tag_editor: tuievents::Key::Char('t').into(),
+            enqueue_next: tuievents::Key::Char('e').into(),
+            enqueue_last: tuievents::KeyEvent::new(
+                tuievents::Key::Char('E'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            find_duplicates: tuievents::KeyEvent::new(
+                tuievents::Key::Char('D'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
         }
     }
 }
@@ -708,6 +747,9 @@ impl CheckConflict for KeysLibrary {
             (&self.search, "search"),
             (&self.youtube_search, "youtube_search"),
             (&self.open_tag_editor, "open_tag_editor"),
+            (&self.enqueue_next, "enqueue_next"),
+            (&self.enqueue_last, "enqueue_last"),
+            (&self.find_duplicates, "find_duplicates"),
         }
     }
 
@@ -779,6 +821,28 @@ pub struct KeysPlaylist {
     /// previously known as `cmus_lqueue`
     // NOTE: currently this can be somewhat broken sometimes, cause unknown
     pub add_random_album: KeyBinding,
+    /// Key to clear the up-next queue
+    pub clear_queue: KeyBinding,
+    /// Key to switch to the next named playlist
+    pub next_playlist: KeyBinding,
+    /// Key to switch to the previous named playlist
+    pub previous_playlist: KeyBinding,
+    /// Key to create a new empty named playlist
+    pub new_playlist: KeyBinding,
+    /// Key to rename the active named playlist
+    pub rename_playlist: KeyBinding,
+    /// Key to duplicate the active named playlist
+    pub duplicate_playlist: KeyBinding,
+    /// Key to delete the active named playlist
+    pub delete_playlist: KeyBinding,
+    /// Key to undo the last change to the playlist
+    pub undo: KeyBinding,
+    /// Key to redo the last undone change to the playlist
+    pub redo: KeyBinding,
+    /// Key to open the sort options for the playlist
+    pub sort: KeyBinding,
+    /// Key to open the duplicate removal options for the playlist
+    pub remove_duplicates: KeyBinding,
 }
 
 impl Default for KeysPlaylist {
@@ -810,6 +874,41 @@ impl Default for KeysPlaylist {
             )
             .into(),
             add_random_album: tuievents::Key::Char('s').into(),
+            clear_queue: tuievents::Key::Char('c').into(),
+            next_playlist: tuievents::Key::Char(']').into(),
+            previous_playlist: tuievents::Key::Char('[').into(),
+            new_playlist: tuievents::KeyEvent::new(
+                tuievents::Key::Char('n'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            rename_playlist: tuievents::KeyEvent::new(
+                tuievents::Key::Char('r'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            duplicate_playlist: tuievents::KeyEvent::new(
+                tuievents::Key::Char('y'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            delete_playlist: tuievents::KeyEvent::new(
+                tuievents::Key::Char('x'),
+                tuievents::KeyModifiers::CONTROL,
+            )
+            .into(),
+            undo: tuievents::Key::Char('u').into(),
+            redo: tuievents::KeyEvent::new(
+                tuievents::Key::Char('U'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            sort: tuievents::Key::Char('o').into(),
+            remove_duplicates: tuievents::KeyEvent::new(
+                tuievents::Key::Char('O'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
         }
     }
 }
@@ -828,6 +927,17 @@ impl CheckConflict for KeysPlaylist {
 
             (&self.add_random_songs, "add_random_songs"),
             (&self.add_random_album, "add_random_album"),
+            (&self.clear_queue, "clear_queue"),
+            (&self.next_playlist, "next_playlist"),
+            (&self.previous_playlist, "previous_playlist"),
+            (&self.new_playlist, "new_playlist"),
+            (&self.rename_playlist, "rename_playlist"),
+            (&self.duplicate_playlist, "duplicate_playlist"),
+            (&self.delete_playlist, "delete_playlist"),
+            (&self.undo, "undo"),
+            (&self.redo, "redo"),
+            (&self.sort, "sort"),
+            (&self.remove_duplicates, "remove_duplicates"),
         }
     }
 
@@ -891,6 +1001,20 @@ pub struct KeysPodcast {
     pub delete_feed: KeyBinding,
     /// Key to delete all the added feeds
     pub delete_all_feeds: KeyBinding,
+    /// Key to add the selected episode to the front of the up-next queue
+    pub enqueue_next: KeyBinding,
+    /// Key to add the selected episode to the end of the up-next queue
+    pub enqueue_last: KeyBinding,
+    /// Key to edit the automatic download and retention policy of the currently selected feed
+    pub download_policy: KeyBinding,
+    /// Key to cancel the running download of the currently selected episode
+    pub cancel_download: KeyBinding,
+    /// Key to show the download queue
+    pub download_queue: KeyBinding,
+    /// Key to show the notes of the currently selected episode
+    pub show_notes: KeyBinding,
+    /// Key to move the currently selected feed into a folder
+    pub set_folder: KeyBinding,
 }
 
 impl Default for KeysPodcast {
@@ -921,6 +1045,21 @@ impl Default for KeysPodcast {
                 tuievents::KeyModifiers::SHIFT,
             )
             .into(),
+            enqueue_next: tuievents::Key::Char('e').into(),
+            enqueue_last: tuievents::KeyEvent::new(
+                tuievents::Key::Char('E'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            download_policy: tuievents::Key::Char('a').into(),
+            cancel_download: tuievents::Key::Char('c').into(),
+            download_queue: tuievents::KeyEvent::new(
+                tuievents::Key::Char('Q'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
+            show_notes: tuievents::Key::Char('i').into(),
+            set_folder: tuievents::Key::Char('t').into(),
         }
     }
 }
@@ -937,6 +1076,13 @@ impl CheckConflict for KeysPodcast {
             (&self.delete_local_episode, "delete_local_episode"),
             (&self.delete_feed, "delete_feed"),
             (&self.delete_all_feeds, "delete_all_feeds"),
+            (&self.enqueue_next, "enqueue_next"),
+            (&self.enqueue_last, "enqueue_last"),
+            (&self.download_policy, "download_policy"),
+            (&self.cancel_download, "cancel_download"),
+            (&self.download_queue, "download_queue"),
+            (&self.show_notes, "show_notes"),
+            (&self.set_folder, "set_folder"),
         }
     }
 
@@ -1172,6 +1318,10 @@ pub struct KeysDatabase {
     pub add_selected: KeyBinding,
     /// Add all tracks in the Database view "Tracks" section
     pub add_all: KeyBinding,
+    /// Add the currently selected track to the front of the up-next queue
+    pub enqueue_next: KeyBinding,
+    /// Add the currently selected track to the end of the up-next queue
+    pub enqueue_last: KeyBinding,
 }
 
 impl Default for KeysDatabase {
@@ -1183,6 +1333,12 @@ impl Default for KeysDatabase {
                 tuievents::KeyModifiers::SHIFT,
             )
             .into(),
+            enqueue_next: tuievents::Key::Char('e').into(),
+            enqueue_last: tuievents::KeyEvent::new(
+                tuievents::Key::Char('E'),
+                tuievents::KeyModifiers::SHIFT,
+            )
+            .into(),
         }
     }
 }
@@ -1191,6 +1347,8 @@ impl CheckConflict for KeysDatabase {
     fn iter(&self) -> impl Iterator<Item = (&KeyBinding, &'static str)> {
         once_chain! {
             (&self.add_all, "add_all"),
+            (&self.enqueue_next, "enqueue_next"),
+            (&self.enqueue_last, "enqueue_last"),
         }
     }
 
@@ -1884,6 +2042,8 @@ mod v1_interop {
                 }
             };
 
+            let default_player = KeysPlayer::default();
+
             Self {
                 escape: value.global_esc.into(),
                 quit: value.global_quit.into(),
@@ -1914,6 +2074,10 @@ mod v1_interop {
                     speed_down: value.global_player_speed_down.into(),
                     toggle_prefetch: value.global_player_toggle_gapless.into(),
                     save_playlist: value.global_save_playlist.into(),
+                    // not present in v1, use the defaults
+                    sleep_timer_cycle: default_player.sleep_timer_cycle,
+                    sleep_timer_extend: default_player.sleep_timer_extend,
+                    sleep_timer_cancel: default_player.sleep_timer_cancel,
                 },
                 lyric_keys: KeysLyric {
                     adjust_offset_forwards: value.global_lyric_adjust_forward.into(),
@@ -1933,6 +2097,10 @@ mod v1_interop {
                     search: value.library_search.into(),
                     youtube_search: value.library_search_youtube.into(),
                     open_tag_editor: value.library_tag_editor_open.into(),
+                    // not present in v1, use the defaults
+                    enqueue_next: KeysLibrary::default().enqueue_next,
+                    enqueue_last: KeysLibrary::default().enqueue_last,
+                    find_duplicates: KeysLibrary::default().find_duplicates,
                 },
                 playlist_keys: KeysPlaylist {
                     delete: value.playlist_delete.into(),
@@ -1945,11 +2113,26 @@ mod v1_interop {
                     swap_down: value.playlist_swap_down.into(),
                     add_random_songs: value.playlist_add_random_tracks.into(),
                     add_random_album: value.playlist_add_random_album.into(),
+                    // not present in v1, use the defaults
+                    clear_queue: KeysPlaylist::default().clear_queue,
+                    next_playlist: KeysPlaylist::default().next_playlist,
+                    previous_playlist: KeysPlaylist::default().previous_playlist,
+                    new_playlist: KeysPlaylist::default().new_playlist,
+                    rename_playlist: KeysPlaylist::default().rename_playlist,
+                    duplicate_playlist: KeysPlaylist::default().duplicate_playlist,
+                    delete_playlist: KeysPlaylist::default().delete_playlist,
+                    undo: KeysPlaylist::default().undo,
+                    redo: KeysPlaylist::default().redo,
+                    sort: KeysPlaylist::default().sort,
+                    remove_duplicates: KeysPlaylist::default().remove_duplicates,
                 },
                 database_keys: KeysDatabase {
                     // this is weird, but the previous implementation used "global_right" as the loading key to not conflict
                     add_selected: value.global_right.into(),
                     add_all: value.database_add_all.into(),
+                    // not present in v1, use the defaults
+                    enqueue_next: KeysDatabase::default().enqueue_next,
+                    enqueue_last: KeysDatabase::default().enqueue_last,
                 },
                 podcast_keys: KeysPodcast {
                     search: value.podcast_search_add_feed.into(),
@@ -1961,6 +2144,14 @@ mod v1_interop {
                     delete_local_episode: podcast_delete_episode_key,
                     delete_feed: podcast_delete_feed_key,
                     delete_all_feeds: podcast_delete_all_feeds_key,
+                    // not present in v1, use the defaults
+                    enqueue_next: KeysPodcast::default().enqueue_next,
+                    enqueue_last: KeysPodcast::default().enqueue_last,
+                    download_policy: KeysPodcast::default().download_policy,
+                    cancel_download: KeysPodcast::default().cancel_download,
+                    download_queue: KeysPodcast::default().download_queue,
+                    show_notes: KeysPodcast::default().show_notes,
+                    set_folder: KeysPodcast::default().set_folder,
                 },
                 move_cover_art_keys: KeysMoveCoverArt {
                     move_left: value.global_xywh_move_left.into(),
@@ -2061,6 +2252,17 @@ mod v1_interop {
                     tuievents::KeyModifiers::CONTROL,
                 )
                 .into(),
+                sleep_timer_cycle: tuievents::Key::Char('z').into(),
+                sleep_timer_extend: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('Z'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                sleep_timer_cancel: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('z'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
             };
             assert_eq!(converted.player_keys, expected_player_keys);
 
@@ -2103,6 +2305,17 @@ mod v1_interop {
                 search: tuievents::Key::Char('/').into(),
                 youtube_search: tuievents::Key::Char('s').into(),
                 open_tag_editor: tuievents::Key::Char('t').into(),
+                enqueue_next: tuievents::Key::Char('e').into(),
+                enqueue_last: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('E'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                find_duplicates: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('D'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
             };
             assert_eq!(converted.library_keys, expected_library_keys);
 
@@ -2133,6 +2346,41 @@ mod v1_interop {
                     tuievents::KeyModifiers::SHIFT,
                 )
                 .into(),
+                clear_queue: tuievents::Key::Char('c').into(),
+                next_playlist: tuievents::Key::Char(']').into(),
+                previous_playlist: tuievents::Key::Char('[').into(),
+                new_playlist: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('n'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                rename_playlist: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('r'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                duplicate_playlist: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('y'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                delete_playlist: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('x'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
+                undo: tuievents::Key::Char('u').into(),
+                redo: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('U'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                sort: tuievents::Key::Char('o').into(),
+                remove_duplicates: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('O'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
             };
             assert_eq!(converted.playlist_keys, expected_playlist_keys);
 
@@ -2143,6 +2391,12 @@ mod v1_interop {
                     tuievents::KeyModifiers::SHIFT,
                 )
                 .into(),
+                enqueue_next: tuievents::Key::Char('e').into(),
+                enqueue_last: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('E'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
             };
             assert_eq!(converted.database_keys, expected_database_keys);
 
@@ -2172,6 +2426,21 @@ mod v1_interop {
                     tuievents::KeyModifiers::SHIFT,
                 )
                 .into(),
+                enqueue_next: tuievents::Key::Char('e').into(),
+                enqueue_last: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('E'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                download_policy: tuievents::Key::Char('a').into(),
+                cancel_download: tuievents::Key::Char('c').into(),
+                download_queue: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('Q'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                show_notes: tuievents::Key::Char('i').into(),
+                set_folder: tuievents::Key::Char('t').into(),
             };
             assert_eq!(converted.podcast_keys, expected_podcast_keys);
 
@@ -2296,6 +2565,17 @@ mod v1_interop {
                     tuievents::KeyModifiers::CONTROL,
                 )
                 .into(),
+                sleep_timer_cycle: tuievents::Key::Char('z').into(),
+                sleep_timer_extend: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('Z'),
+                    tuievents::KeyModifiers::SHIFT,
+                )
+                .into(),
+                sleep_timer_cancel: tuievents::KeyEvent::new(
+                    tuievents::Key::Char('z'),
+                    tuievents::KeyModifiers::CONTROL,
+                )
+                .into(),
             };
             assert_eq!(converted.player_keys, expected_player_keys);
         }
@@ -0,0 +1,568 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use lofty::prelude::AudioFile;
+use lofty::probe::Probe;
+use rusqlite::Result;
+use serde::Serialize;
+
+use super::relink::duration_fits;
+use super::track_db::{UNKNOWN_ARTIST, UNKNOWN_TITLE};
+use super::{DataBase, TrackDB};
+
+/// Why files were grouped as likely duplicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    /// The audio data is identical, ignoring ID3 and FLAC metadata
+    SameAudio,
+    /// Same artist and title (case-insensitive) with a duration within the tolerance
+    SameArtistTitle,
+    /// Same file name in different directories
+    ///
+    /// This is only a hint: it does not merge groups on its own and such files are never deleted.
+    SameFileName,
+}
+
+/// Which file of a group to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeepPolicy {
+    /// Keep the file with the highest bitrate, ties are broken by format
+    #[default]
+    Bitrate,
+    /// Keep the file with the best format (lossless first), ties are broken by bitrate
+    Format,
+}
+
+impl std::fmt::Display for KeepPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bitrate => write!(f, "bitrate"),
+            Self::Format => write!(f, "format"),
+        }
+    }
+}
+
+/// A single file in a [`DuplicateGroup`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateFile {
+    pub file: String,
+    pub artist: String,
+    pub title: String,
+    pub duration_secs: u64,
+    pub ext: String,
+    /// Bitrate in kbps, if the file could be read
+    pub bitrate_kbps: Option<u32>,
+    /// Size in bytes
+    pub size: u64,
+}
+
+impl DuplicateFile {
+    fn from_track(track: &TrackDB) -> Self {
+        let path = Path::new(&track.file);
+        let bitrate_kbps = Probe::open(path)
+            .and_then(Probe::read)
+            .ok()
+            .and_then(|v| v.properties().audio_bitrate());
+        let size = path.metadata().map(|v| v.len()).unwrap_or_default();
+
+        Self {
+            file: track.file.clone(),
+            artist: track.artist.clone(),
+            title: track.title.clone(),
+            duration_secs: track.duration.as_secs(),
+            ext: track.ext.clone(),
+            bitrate_kbps,
+            size,
+        }
+    }
+
+    /// Rank of the format, higher is better
+    fn format_rank(&self) -> u8 {
+        match self.ext.to_lowercase().as_str() {
+            "flac" | "wav" | "aiff" | "aif" | "ape" | "wv" => 3,
+            "opus" | "ogg" | "m4a" | "aac" => 2,
+            "mp3" => 1,
+            _ => 0,
+        }
+    }
+
+    /// Sort key for `policy`, higher is better
+    fn keep_key(&self, policy: KeepPolicy) -> (u32, u32, u64) {
+        let bitrate = self.bitrate_kbps.unwrap_or_default();
+        let format = u32::from(self.format_rank());
+        match policy {
+            KeepPolicy::Bitrate => (bitrate, format, self.size),
+            KeepPolicy::Format => (format, bitrate, self.size),
+        }
+    }
+}
+
+/// Files that are likely the same track
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateGroup {
+    pub reasons: Vec<DuplicateReason>,
+    pub files: Vec<DuplicateFile>,
+}
+
+impl DuplicateGroup {
+    /// Whether the group is only based on [`DuplicateReason::SameFileName`], in which case nothing is deleted
+    #[must_use]
+    pub fn is_hint(&self) -> bool {
+        self.reasons
+            .iter()
+            .all(|v| *v == DuplicateReason::SameFileName)
+    }
+
+    /// Get the index of the file to keep, the first one wins on ties
+    #[must_use]
+    pub fn best(&self, policy: KeepPolicy) -> usize {
+        let mut best = 0;
+        for (idx, file) in self.files.iter().enumerate().skip(1) {
+            if file.keep_key(policy) > self.files[best].keep_key(policy) {
+                best = idx;
+            }
+        }
+        best
+    }
+
+    /// Get all files except the one to keep, nothing for a [hint](Self::is_hint)
+    #[must_use]
+    pub fn files_to_delete(&self, policy: KeepPolicy) -> Vec<&str> {
+        if self.is_hint() {
+            return Vec::new();
+        }
+        let best = self.best(policy);
+        self.files
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != best)
+            .map(|(_, v)| v.file.as_str())
+            .collect()
+    }
+}
+
+/// All groups of likely duplicates in the library
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl DuplicateReport {
+    /// Get the files to delete in all groups, keeping one file per group
+    #[must_use]
+    pub fn files_to_delete(&self, policy: KeepPolicy) -> Vec<String> {
+        self.groups
+            .iter()
+            .flat_map(|v| v.files_to_delete(policy))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+/// Get the byte range of the audio data in `file`, excluding ID3 tags and FLAC metadata blocks
+fn audio_range(file: &mut File) -> io::Result<(u64, u64)> {
+    let len = file.metadata()?.len();
+    let mut start = 0;
+
+    let mut header = [0u8; 10];
+    if len >= 10 {
+        file.read_exact(&mut header)?;
+        if &header[..3] == b"ID3" {
+            // the size is "syncsafe", only 7 bits per byte are used
+            let size = header[6..10]
+                .iter()
+                .fold(0u64, |acc, v| (acc << 7) | u64::from(v & 0x7f));
+            let footer = if header[5] & 0x10 == 0 { 0 } else { 10 };
+            start = 10 + size + footer;
+        }
+    }
+
+    file.seek(SeekFrom::Start(start))?;
+    let mut magic = [0u8; 4];
+    if start + 4 <= len {
+        file.read_exact(&mut magic)?;
+        if &magic == b"fLaC" {
+            start += 4;
+            loop {
+                let mut block = [0u8; 4];
+                file.seek(SeekFrom::Start(start))?;
+                if file.read_exact(&mut block).is_err() {
+                    break;
+                }
+                let size = u64::from_be_bytes([0, 0, 0, 0, 0, block[1], block[2], block[3]]);
+                start += 4 + size;
+                if block[0] & 0x80 != 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut end = len;
+    if len >= start + 128 {
+        let mut tag = [0u8; 3];
+        file.seek(SeekFrom::Start(len - 128))?;
+        file.read_exact(&mut tag)?;
+        if &tag == b"TAG" {
+            end = len - 128;
+        }
+    }
+
+    Ok((start.min(end), end))
+}
+
+/// Hash the audio data of the file at `path`, so that files only differing in their tags compare equal
+///
+/// Only ID3 and FLAC metadata is skipped, other formats are hashed as a whole.
+fn audio_hash(path: &Path) -> io::Result<[u8; 16]> {
+    let mut file = File::open(path)?;
+    let (start, end) = audio_range(&mut file)?;
+    file.seek(SeekFrom::Start(start))?;
+
+    let mut context = md5::Context::new();
+    io::copy(&mut file.take(end - start), &mut context)?;
+
+    Ok(context.compute().0)
+}
+
+/// Minimal union-find to merge pairs into groups
+struct Groups {
+    parent: Vec<usize>,
+}
+
+impl Groups {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, idx: usize) -> usize {
+        let mut root = idx;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        self.parent[idx] = root;
+        root
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[b.max(a)] = a.min(b);
+        }
+    }
+}
+
+/// Pairs of tracks with the same audio or the same artist and title
+fn content_pairs(tracks: &[TrackDB]) -> Vec<(usize, usize, DuplicateReason)> {
+    let mut pairs: Vec<(usize, usize, DuplicateReason)> = Vec::new();
+
+    let mut by_duration: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut by_artist_title: HashMap<(String, String), Vec<usize>> = HashMap::new();
+    for (idx, track) in tracks.iter().enumerate() {
+        by_duration
+            .entry(track.duration.as_secs())
+            .or_default()
+            .push(idx);
+        if track.artist != UNKNOWN_ARTIST && track.title != UNKNOWN_TITLE {
+            by_artist_title
+                .entry((track.artist.to_lowercase(), track.title.to_lowercase()))
+                .or_default()
+                .push(idx);
+        }
+    }
+
+    for indexes in by_duration.values().filter(|v| v.len() > 1) {
+        let mut by_hash: HashMap<[u8; 16], usize> = HashMap::new();
+        for &idx in indexes {
+            let Ok(hash) = audio_hash(Path::new(&tracks[idx].file)) else {
+                continue;
+            };
+            if let Some(&first) = by_hash.get(&hash) {
+                pairs.push((first, idx, DuplicateReason::SameAudio));
+            } else {
+                by_hash.insert(hash, idx);
+            }
+        }
+    }
+
+    for indexes in by_artist_title.values().filter(|v| v.len() > 1) {
+        for (pos, &a) in indexes.iter().enumerate() {
+            for &b in &indexes[pos + 1..] {
+                if duration_fits(tracks[a].duration, tracks[b].duration) {
+                    pairs.push((a, b, DuplicateReason::SameArtistTitle));
+                }
+            }
+        }
+    }
+
+    pairs
+}
+
+/// Group `tracks` into likely duplicates, see [`DuplicateReason`] for the rules
+///
+/// Only matching content merges tracks into a group. Files with the same name are added as a reason to
+/// the group they all belong to, or reported as a separate hint group otherwise.
+///
+/// Audio is only hashed for tracks sharing a duration, as identical audio always has the same duration.
+#[must_use]
+pub fn find_duplicates(tracks: &[TrackDB]) -> DuplicateReport {
+    let pairs = content_pairs(tracks);
+
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (idx, track) in tracks.iter().enumerate() {
+        by_name.entry(&track.name).or_default().push(idx);
+    }
+
+    // only matching content merges groups, otherwise a shared file name would chain unrelated songs
+    let mut groups = Groups::new(tracks.len());
+    for &(a, b, _) in &pairs {
+        groups.union(a, b);
+    }
+
+    let mut members: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut reasons: HashMap<usize, BTreeSet<DuplicateReason>> = HashMap::new();
+    for &(a, b, reason) in &pairs {
+        let root = groups.find(a);
+        let entry = members.entry(root).or_default();
+        entry.push(a);
+        entry.push(b);
+        reasons.entry(root).or_default().insert(reason);
+    }
+
+    let mut hints: Vec<Vec<usize>> = Vec::new();
+    for indexes in by_name.values().filter(|v| v.len() > 1) {
+        let first = indexes[0];
+        if indexes
+            .iter()
+            .all(|idx| tracks[*idx].directory == tracks[first].directory)
+        {
+            continue;
+        }
+        let root = groups.find(first);
+        if members.contains_key(&root) && indexes.iter().all(|idx| groups.find(*idx) == root) {
+            reasons
+                .entry(root)
+                .or_default()
+                .insert(DuplicateReason::SameFileName);
+        } else {
+            hints.push(indexes.clone());
+        }
+    }
+
+    let mut report = DuplicateReport::default();
+    for mut indexes in hints {
+        indexes.sort_by(|a, b| tracks[*a].file.cmp(&tracks[*b].file));
+        report.groups.push(DuplicateGroup {
+            reasons: vec![DuplicateReason::SameFileName],
+            files: indexes
+                .into_iter()
+                .map(|idx| DuplicateFile::from_track(&tracks[idx]))
+                .collect(),
+        });
+    }
+    for (root, mut indexes) in members {
+        indexes.sort_by(|a, b| tracks[*a].file.cmp(&tracks[*b].file));
+        indexes.dedup();
+        report.groups.push(DuplicateGroup {
+            reasons: reasons
+                .remove(&root)
+                .unwrap_or_default()
+                .into_iter()
+                .collect(),
+            files: indexes
+                .into_iter()
+                .map(|idx| DuplicateFile::from_track(&tracks[idx]))
+                .collect(),
+        });
+    }
+    report
+        .groups
+        .sort_by(|a, b| (&a.files[0].file, a.is_hint()).cmp(&(&b.files[0].file, b.is_hint())));
+
+    report
+}
+
+impl DataBase {
+    /// Group all tracks in the library into likely duplicates, see [`find_duplicates`]
+    pub fn find_duplicates(&mut self) -> Result<DuplicateReport> {
+        Ok(find_duplicates(&self.get_all_records()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn track(file: &str, artist: &str, title: &str, secs: u64) -> TrackDB {
+        let path = Path::new(file);
+        TrackDB {
+            id: 0,
+            artist: artist.to_string(),
+            title: title.to_string(),
+            album: String::new(),
+            genre: String::new(),
+            file: file.to_string(),
+            duration: Duration::from_secs(secs),
+            name: path.file_name().unwrap().to_string_lossy().to_string(),
+            ext: path.extension().unwrap().to_string_lossy().to_string(),
+            directory: path.parent().unwrap().to_string_lossy().to_string(),
+            last_modified: String::new(),
+            last_position: Duration::default(),
+            album_artist: None,
+            composer: None,
+            track_number: None,
+            disc_number: None,
+            year: None,
+            rating: None,
+        }
+    }
+
+    fn files(report: &DuplicateReport) -> Vec<Vec<&str>> {
+        report
+            .groups
+            .iter()
+            .map(|v| v.files.iter().map(|v| v.file.as_str()).collect())
+            .collect()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("termusic-duplicates-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn should_not_group_unrelated_tracks_by_file_name() {
+        let tracks = vec![
+            track("/nonexistent/a/one.mp3", "Artist", "One", 200),
+            track("/nonexistent/b/one (1).flac", "artist", "ONE", 202),
+            track("/nonexistent/c/one.mp3", "Other", "Other", 100),
+            // outside the duration tolerance, likely a live version
+            track("/nonexistent/d/live.mp3", "Artist", "One", 260),
+            track("/nonexistent/e/x.mp3", UNKNOWN_ARTIST, UNKNOWN_TITLE, 100),
+            track("/nonexistent/f/y.mp3", UNKNOWN_ARTIST, UNKNOWN_TITLE, 100),
+        ];
+
+        let report = find_duplicates(&tracks);
+        assert_eq!(
+            files(&report),
+            vec![
+                vec!["/nonexistent/a/one.mp3", "/nonexistent/b/one (1).flac"],
+                vec!["/nonexistent/a/one.mp3", "/nonexistent/c/one.mp3"]
+            ]
+        );
+        assert_eq!(
+            report.groups[0].reasons,
+            vec![DuplicateReason::SameArtistTitle]
+        );
+        assert_eq!(
+            report.groups[1].reasons,
+            vec![DuplicateReason::SameFileName]
+        );
+        assert!(report.groups[1].is_hint());
+
+        // "Other - Other" only shares the file name and is never deleted
+        assert_eq!(
+            report.files_to_delete(KeepPolicy::Bitrate),
+            vec!["/nonexistent/a/one.mp3"]
+        );
+    }
+
+    #[test]
+    fn should_add_file_name_to_content_group() {
+        let tracks = vec![
+            track("/nonexistent/a/one.mp3", "Artist", "One", 200),
+            track("/nonexistent/b/one.mp3", "Artist", "One", 201),
+        ];
+
+        let report = find_duplicates(&tracks);
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(
+            report.groups[0].reasons,
+            vec![
+                DuplicateReason::SameArtistTitle,
+                DuplicateReason::SameFileName
+            ]
+        );
+        assert!(!report.groups[0].is_hint());
+    }
+
+    #[test]
+    fn should_keep_best_file_by_policy() {
+        let file = |file: &str, ext: &str, bitrate_kbps, size| DuplicateFile {
+            file: file.to_string(),
+            artist: String::new(),
+            title: String::new(),
+            duration_secs: 0,
+            ext: ext.to_string(),
+            bitrate_kbps,
+            size,
+        };
+        let group = DuplicateGroup {
+            reasons: vec![DuplicateReason::SameArtistTitle],
+            files: vec![
+                file("a.mp3", "mp3", Some(320), 100),
+                file("b.flac", "flac", Some(900), 300),
+                file("c.opus", "opus", Some(1000), 200),
+            ],
+        };
+
+        assert_eq!(group.best(KeepPolicy::Bitrate), 2);
+        assert_eq!(
+            group.files_to_delete(KeepPolicy::Bitrate),
+            vec!["a.mp3", "b.flac"]
+        );
+        assert_eq!(group.best(KeepPolicy::Format), 1);
+        assert_eq!(
+            group.files_to_delete(KeepPolicy::Format),
+            vec!["a.mp3", "c.opus"]
+        );
+    }
+
+    #[test]
+    fn should_hash_audio_without_tags() {
+        let dir = temp_dir("hash");
+        let audio = [0xffu8, 0xfb, 0x90, 0x00, 1, 2, 3, 4, 5, 6, 7, 8];
+
+        // ID3v2 header with a 4 byte body
+        let mut tagged = b"ID3\x04\x00\x00\x00\x00\x00\x04body".to_vec();
+        tagged.extend_from_slice(&audio);
+        // ID3v1 tag
+        tagged.extend_from_slice(b"TAG");
+        tagged.extend_from_slice(&[b' '; 125]);
+
+        let mut other = audio.to_vec();
+        other[11] = 0;
+
+        std::fs::write(dir.join("plain.mp3"), audio).unwrap();
+        std::fs::write(dir.join("tagged.mp3"), &tagged).unwrap();
+        std::fs::write(dir.join("other.mp3"), other).unwrap();
+
+        let plain = audio_hash(&dir.join("plain.mp3")).unwrap();
+        assert_eq!(audio_hash(&dir.join("tagged.mp3")).unwrap(), plain);
+        assert_ne!(audio_hash(&dir.join("other.mp3")).unwrap(), plain);
+
+        let file = |name: &str, title: &str| {
+            track(&dir.join(name).to_string_lossy(), "Artist", title, 100)
+        };
+        let report = find_duplicates(&[
+            file("plain.mp3", "Plain"),
+            file("tagged.mp3", "Tagged"),
+            file("other.mp3", "Other"),
+        ]);
+        assert_eq!(report.groups.len(), 1);
+        assert_eq!(report.groups[0].reasons, vec![DuplicateReason::SameAudio]);
+        assert_eq!(report.groups[0].files.len(), 2);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
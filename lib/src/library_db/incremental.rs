@@ -0,0 +1,240 @@
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use rusqlite::{params, Connection, OptionalExtension, Result};
+
+use super::track_db::TrackDBInsertable;
+use super::DataBase;
+use crate::config::v2::server::ScanDepth;
+use crate::track::Track;
+use crate::utils::filetype_supported;
+
+/// Delete the rows of `path` and of every file below it, returns the number of deleted rows
+fn delete_path(conn: &Connection, path: &str) -> Result<usize> {
+    let prefix = format!("{}/", path.trim_end_matches('/'));
+    conn.execute(
+        "DELETE FROM tracks WHERE file = ?1 OR substr(file, 1, length(?2)) = ?2",
+        params![path, prefix],
+    )
+}
+
+/// Insert or replace the row of the file at `path`, unless the row is already up-to-date
+///
+/// Returns `true` if the row changed.
+fn upsert_file(conn: &Connection, path: &Path) -> Result<bool> {
+    let file = path.to_string_lossy();
+    let last_modified: Option<String> = conn
+        .query_row(
+            "SELECT last_modified FROM tracks WHERE file = ?1",
+            [&file],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    let modified = path
+        .metadata()
+        .and_then(|v| v.modified())
+        .map(|v| v.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs())
+        .unwrap_or_default();
+    if let Some(last_modified) = last_modified {
+        if last_modified.parse::<u64>().is_ok_and(|v| modified <= v) {
+            return Ok(false);
+        }
+    }
+
+    let track = match Track::read_from_path(path, true) {
+        Ok(v) => v,
+        Err(err) => {
+            warn!("Could not read \"{}\": {err:#}", path.display());
+            return Ok(false);
+        }
+    };
+
+    TrackDBInsertable::from(&track).replace_track(conn)?;
+
+    Ok(true)
+}
+
+impl DataBase {
+    /// Apply changes to individual paths, like reported by a filesystem watcher
+    ///
+    /// Supported files are inserted or updated if they changed since the last sync, directories are scanned
+    /// for such files and paths that do not exist anymore are removed, including everything below them.
+    /// Returns `true` if any row changed.
+    ///
+    /// # Errors
+    ///
+    /// - if any database operation fails
+    pub fn sync_paths(&mut self, paths: &[PathBuf]) -> Result<bool> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let mut changed = false;
+
+        for path in paths {
+            if !path.exists() {
+                changed |= delete_path(&tx, &path.to_string_lossy())? > 0;
+            } else if path.is_dir() {
+                let mut walker = walkdir::WalkDir::new(path).follow_links(true);
+                if let ScanDepth::Limited(limit) = self.max_depth {
+                    walker = walker.max_depth(usize::try_from(limit).unwrap_or(usize::MAX));
+                }
+
+                for entry in walker
+                    .into_iter()
+                    .filter_map(std::result::Result::ok)
+                    .filter(|f| f.file_type().is_file())
+                    .filter(|f| filetype_supported(&f.path().to_string_lossy()))
+                {
+                    changed |= upsert_file(&tx, entry.path())?;
+                }
+            } else if filetype_supported(&path.to_string_lossy()) {
+                changed |= upsert_file(&tx, path)?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{gen_data_base, insert_track};
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs::File;
+    use std::time::Duration;
+
+    fn files(db: &mut DataBase) -> Vec<String> {
+        let mut files: Vec<String> = db
+            .get_all_records()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.file)
+            .collect();
+        files.sort();
+        files
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "termusic-incremental-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Write a silent 8 kHz mono 16-bit wav file that is `secs` long, modified at `modified` seconds
+    fn write_wav(path: &Path, secs: u32, modified: u64) {
+        let data_len = 8000 * 2 * secs;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        // PCM, 1 channel, sample rate, byte rate, block align, bits per sample
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        wav.resize(wav.len() + data_len as usize, 0);
+        std::fs::write(path, wav).unwrap();
+
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(modified))
+            .unwrap();
+    }
+
+    fn duration(db: &mut DataBase, path: &Path) -> Duration {
+        db.get_record_by_path(&path.to_string_lossy())
+            .unwrap()
+            .duration
+    }
+
+    #[test]
+    fn should_sync_changed_files() {
+        let dir = temp_dir("changed");
+        let album = dir.join("album");
+        std::fs::create_dir_all(&album).unwrap();
+        let one = album.join("one.wav");
+        write_wav(&one, 1, 1_000_000);
+        std::fs::write(album.join("cover.txt"), "not audio").unwrap();
+        let mut db = gen_data_base();
+
+        // a new directory is scanned for supported files
+        assert!(db.sync_paths(std::slice::from_ref(&dir)).unwrap());
+        assert_eq!(files(&mut db), vec![one.to_string_lossy().to_string()]);
+        assert_eq!(duration(&mut db, &one), Duration::from_secs(1));
+
+        // unsupported files are skipped, even when given directly
+        assert!(!db.sync_paths(&[album.join("cover.txt")]).unwrap());
+
+        // a file that is not newer than its row is not read again
+        write_wav(&one, 2, 1_000_000);
+        assert!(!db.sync_paths(std::slice::from_ref(&one)).unwrap());
+        assert!(!db.sync_paths(std::slice::from_ref(&dir)).unwrap());
+        assert_eq!(duration(&mut db, &one), Duration::from_secs(1));
+
+        // but a newer one is
+        write_wav(&one, 2, 2_000_000);
+        assert!(db.sync_paths(std::slice::from_ref(&one)).unwrap());
+        assert_eq!(duration(&mut db, &one), Duration::from_secs(2));
+
+        // a rescan only adds what is new below the directory
+        let two = album.join("two.wav");
+        write_wav(&two, 3, 1_000_000);
+        assert!(db.sync_paths(std::slice::from_ref(&album)).unwrap());
+        assert_eq!(
+            files(&mut db),
+            vec![
+                one.to_string_lossy().to_string(),
+                two.to_string_lossy().to_string()
+            ]
+        );
+        assert_eq!(duration(&mut db, &one), Duration::from_secs(2));
+
+        // and a removed directory removes all of them
+        std::fs::remove_dir_all(&album).unwrap();
+        assert!(db.sync_paths(&[album]).unwrap());
+        assert!(files(&mut db).is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_delete_missing_paths() {
+        let mut db = gen_data_base();
+        insert_track(&db, "/nonexistent/a/one.mp3", "Artist", "One", 100);
+        insert_track(&db, "/nonexistent/a/two.mp3", "Artist", "Two", 100);
+        insert_track(&db, "/nonexistent/ab/three.mp3", "Artist", "Three", 100);
+        insert_track(&db, "/nonexistent/b/four.mp3", "Artist", "Four", 100);
+
+        // a removed directory removes everything below it, but not siblings sharing the prefix
+        assert!(db.sync_paths(&[PathBuf::from("/nonexistent/a")]).unwrap());
+        assert_eq!(
+            files(&mut db),
+            vec!["/nonexistent/ab/three.mp3", "/nonexistent/b/four.mp3"]
+        );
+
+        // a removed file
+        assert!(db
+            .sync_paths(&[PathBuf::from("/nonexistent/b/four.mp3")])
+            .unwrap());
+        assert_eq!(files(&mut db), vec!["/nonexistent/ab/three.mp3"]);
+
+        // unknown paths do not change anything
+        assert!(!db
+            .sync_paths(&[PathBuf::from("/nonexistent/c/five.mp3")])
+            .unwrap());
+        assert_eq!(files(&mut db), vec!["/nonexistent/ab/three.mp3"]);
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::{bail, Context, Result};
 use rusqlite::Connection;
 
 /// The Current Database schema version this application is meant to run against
-pub(super) const DB_VERSION: u32 = 2;
+pub(super) const DB_VERSION: u32 = 6;
 /// The Lowest Database schema version this application supports migration up against
 ///
 /// Expection being "0" as that indicates a fresh database
@@ -68,6 +68,34 @@ fn apply_migrations(conn: &Connection, mut user_version: u32) -> Result<()> {
         user_version = set_user_version(conn, 2)?;
     }
 
+    if user_version == 2 {
+        // Version 3 adds named playlists
+        conn.execute_batch(include_str!("./migrations/003.sql"))
+            .context("Database version 3 could not be applied")?;
+        user_version = set_user_version(conn, 3)?;
+    }
+
+    if user_version == 3 {
+        // Version 4 adds the full-text search index
+        conn.execute_batch(include_str!("./migrations/004.sql"))
+            .context("Database version 4 could not be applied")?;
+        user_version = set_user_version(conn, 4)?;
+    }
+
+    if user_version == 4 {
+        // Version 5 adds the extended tag columns
+        conn.execute_batch(include_str!("./migrations/005.sql"))
+            .context("Database version 5 could not be applied")?;
+        user_version = set_user_version(conn, 5)?;
+    }
+
+    if user_version == 5 {
+        // Version 6 adds the rating column
+        conn.execute_batch(include_str!("./migrations/006.sql"))
+            .context("Database version 6 could not be applied")?;
+        user_version = set_user_version(conn, 6)?;
+    }
+
     Ok(())
 }
 
@@ -83,7 +111,7 @@ mod tests {
 
         assert_eq!(0, get_user_version(&conn).unwrap());
         migrate(&conn).unwrap();
-        assert_eq!(2, get_user_version(&conn).unwrap());
+        assert_eq!(6, get_user_version(&conn).unwrap());
 
         let all_tracks: Vec<String> = {
             let mut prep = conn.prepare("SELECT name FROM sqlite_schema WHERE type ='table' AND name NOT LIKE 'sqlite_%';").unwrap();
@@ -93,6 +121,109 @@ mod tests {
                 .collect()
         };
 
-        assert_eq!(&all_tracks, &["tracks"]);
+        assert_eq!(
+            &all_tracks,
+            &[
+                "tracks",
+                "playlists",
+                "playlist_entries",
+                // the full-text index and its shadow tables
+                "tracks_fts",
+                "tracks_fts_data",
+                "tracks_fts_idx",
+                "tracks_fts_docsize",
+                "tracks_fts_config"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_migrate_from_2() {
+        let conn = gen_database();
+
+        conn.execute_batch(include_str!("./migrations/002.sql"))
+            .unwrap();
+        set_user_version(&conn, 2).unwrap();
+
+        migrate(&conn).unwrap();
+        assert_eq!(6, get_user_version(&conn).unwrap());
+
+        let count: u32 = conn
+            .query_row("SELECT COUNT(*) FROM playlists", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(0, count);
+    }
+    #[test]
+    fn should_index_existing_tracks_when_migrating_from_3() {
+        let conn = gen_database();
+
+        conn.execute_batch(include_str!("./migrations/002.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("./migrations/003.sql"))
+            .unwrap();
+        set_user_version(&conn, 3).unwrap();
+        conn.execute(
+            "INSERT INTO tracks (artist, title, file) VALUES ('Artist', 'Title', '/a.mp3')",
+            [],
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+        assert_eq!(6, get_user_version(&conn).unwrap());
+
+        let count: u32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM tracks_fts WHERE tracks_fts MATCH 'title'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(1, count);
+    }
+
+    #[test]
+    fn should_add_tag_columns_when_migrating_from_4() {
+        let conn = gen_database();
+
+        conn.execute_batch(include_str!("./migrations/002.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("./migrations/003.sql"))
+            .unwrap();
+        conn.execute_batch(include_str!("./migrations/004.sql"))
+            .unwrap();
+        set_user_version(&conn, 4).unwrap();
+        conn.execute(
+            "INSERT INTO tracks (artist, title, file, last_modified) VALUES ('Artist', 'Title', '/a.mp3', '100')",
+            [],
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+        assert_eq!(6, get_user_version(&conn).unwrap());
+
+        let (last_modified, album_artist): (String, Option<String>) = conn
+            .query_row("SELECT last_modified, album_artist FROM tracks", [], |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .unwrap();
+        assert_eq!("0", last_modified);
+        assert_eq!(None, album_artist);
+
+        // the re-created index still contains the existing track and follows new ones
+        conn.execute(
+            "INSERT INTO tracks (artist, title, file, composer) VALUES ('Other', 'Other', '/b.mp3', 'Bach')",
+            [],
+        )
+        .unwrap();
+        for query in ["title", "bach"] {
+            let count: u32 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM tracks_fts WHERE tracks_fts MATCH ?1",
+                    [query],
+                    |r| r.get(0),
+                )
+                .unwrap();
+            assert_eq!(1, count, "{query}");
+        }
     }
 }
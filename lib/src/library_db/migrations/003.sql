@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS playlists(
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    current_index INTEGER NOT NULL DEFAULT 0,
+    last_position INTEGER NOT NULL DEFAULT 0,
+    active INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS playlist_entries(
+    playlist_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    file TEXT NOT NULL,
+    PRIMARY KEY (playlist_id, position)
+);
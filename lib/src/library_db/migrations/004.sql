@@ -0,0 +1,30 @@
+CREATE VIRTUAL TABLE IF NOT EXISTS tracks_fts USING fts5(
+    artist,
+    title,
+    album,
+    genre,
+    name,
+    content = 'tracks',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER IF NOT EXISTS tracks_fts_insert AFTER INSERT ON tracks BEGIN
+    INSERT INTO tracks_fts(rowid, artist, title, album, genre, name)
+    VALUES (new.id, new.artist, new.title, new.album, new.genre, new.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS tracks_fts_delete AFTER DELETE ON tracks BEGIN
+    INSERT INTO tracks_fts(tracks_fts, rowid, artist, title, album, genre, name)
+    VALUES ('delete', old.id, old.artist, old.title, old.album, old.genre, old.name);
+END;
+
+CREATE TRIGGER IF NOT EXISTS tracks_fts_update AFTER UPDATE ON tracks BEGIN
+    INSERT INTO tracks_fts(tracks_fts, rowid, artist, title, album, genre, name)
+    VALUES ('delete', old.id, old.artist, old.title, old.album, old.genre, old.name);
+    INSERT INTO tracks_fts(rowid, artist, title, album, genre, name)
+    VALUES (new.id, new.artist, new.title, new.album, new.genre, new.name);
+END;
+
+-- index the tracks that existed before this version
+INSERT INTO tracks_fts(tracks_fts) VALUES ('rebuild');
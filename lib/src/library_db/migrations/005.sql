@@ -0,0 +1,46 @@
+ALTER TABLE tracks ADD COLUMN album_artist TEXT;
+ALTER TABLE tracks ADD COLUMN composer TEXT;
+ALTER TABLE tracks ADD COLUMN track_number INTEGER;
+ALTER TABLE tracks ADD COLUMN disc_number INTEGER;
+ALTER TABLE tracks ADD COLUMN year INTEGER;
+
+-- force the next sync to re-read all tags, so that existing tracks get the new columns
+UPDATE tracks SET last_modified = '0';
+
+-- the full-text index cannot gain columns, so it is re-created with the album artist and composer
+DROP TRIGGER IF EXISTS tracks_fts_insert;
+DROP TRIGGER IF EXISTS tracks_fts_delete;
+DROP TRIGGER IF EXISTS tracks_fts_update;
+DROP TABLE IF EXISTS tracks_fts;
+
+CREATE VIRTUAL TABLE tracks_fts USING fts5(
+    artist,
+    title,
+    album,
+    genre,
+    name,
+    album_artist,
+    composer,
+    content = 'tracks',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER tracks_fts_insert AFTER INSERT ON tracks BEGIN
+    INSERT INTO tracks_fts(rowid, artist, title, album, genre, name, album_artist, composer)
+    VALUES (new.id, new.artist, new.title, new.album, new.genre, new.name, new.album_artist, new.composer);
+END;
+
+CREATE TRIGGER tracks_fts_delete AFTER DELETE ON tracks BEGIN
+    INSERT INTO tracks_fts(tracks_fts, rowid, artist, title, album, genre, name, album_artist, composer)
+    VALUES ('delete', old.id, old.artist, old.title, old.album, old.genre, old.name, old.album_artist, old.composer);
+END;
+
+CREATE TRIGGER tracks_fts_update AFTER UPDATE ON tracks BEGIN
+    INSERT INTO tracks_fts(tracks_fts, rowid, artist, title, album, genre, name, album_artist, composer)
+    VALUES ('delete', old.id, old.artist, old.title, old.album, old.genre, old.name, old.album_artist, old.composer);
+    INSERT INTO tracks_fts(rowid, artist, title, album, genre, name, album_artist, composer)
+    VALUES (new.id, new.artist, new.title, new.album, new.genre, new.name, new.album_artist, new.composer);
+END;
+
+INSERT INTO tracks_fts(tracks_fts) VALUES ('rebuild');
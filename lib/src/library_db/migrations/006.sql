@@ -0,0 +1,4 @@
+ALTER TABLE tracks ADD COLUMN rating INTEGER;
+
+-- force the next sync to re-read all tags, so that existing tracks get their rating
+UPDATE tracks SET last_modified = '0';
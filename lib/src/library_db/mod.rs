@@ -28,14 +28,27 @@ use crate::utils::{filetype_supported, get_app_config_path, get_pin_yin};
 use anyhow::Context;
 use parking_lot::Mutex;
 use rusqlite::{params, Connection, Error, Result};
+use std::collections::HashSet;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, UNIX_EPOCH};
 use track_db::TrackDBInsertable;
 
+mod duplicates;
+mod incremental;
 mod migration;
+mod playlist_db;
+mod query;
+mod relink;
+mod search;
 mod track_db;
 
+pub use duplicates::{
+    find_duplicates, DuplicateFile, DuplicateGroup, DuplicateReason, DuplicateReport, KeepPolicy,
+};
+pub use playlist_db::PlaylistDB;
+pub use query::{Query, QueryError};
+pub use relink::{RelinkEntry, RelinkReport, RelinkStatus};
 pub use track_db::TrackDB;
 
 pub struct DataBase {
@@ -46,30 +59,58 @@ pub struct DataBase {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchCriteria {
     Artist,
+    /// The album artist, or the artist for tracks without one, so that compilations are not split up
+    AlbumArtist,
     Album,
-
-    // TODO: the values below are current unused
+    /// A single genre, tags with multiple genres like `Rock; Pop` are listed under each of them
     Genre,
     Directory,
+    /// Playlist files in the music directories, which are not part of the database
     Playlist,
 }
 
 impl From<usize> for SearchCriteria {
     fn from(u_index: usize) -> Self {
         match u_index {
-            1 => Self::Album,
-            2 => Self::Genre,
-            3 => Self::Directory,
-            4 => Self::Playlist,
+            1 => Self::AlbumArtist,
+            2 => Self::Album,
+            3 => Self::Genre,
+            4 => Self::Directory,
+            5 => Self::Playlist,
             /* 0 | */ _ => Self::Artist,
         }
     }
 }
 
+impl SearchCriteria {
+    /// Get the SQL expression to group the `tracks` by, `None` if the criteria is not stored in the database
+    fn sql_column(self) -> Option<&'static str> {
+        let column = match self {
+            Self::Artist => "artist",
+            Self::AlbumArtist => "COALESCE(NULLIF(album_artist, ''), artist)",
+            Self::Album => "album",
+            Self::Genre => "genre",
+            Self::Directory => "directory",
+            Self::Playlist => return None,
+        };
+
+        Some(column)
+    }
+}
+
+/// Split a genre tag with multiple values, like `Rock; Pop` or `Rock/Pop`
+fn split_genres(genre: &str) -> impl Iterator<Item = &str> {
+    genre
+        .split([';', ',', '/', '\0'])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
 impl std::fmt::Display for SearchCriteria {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Artist => write!(f, "artist"),
+            Self::AlbumArtist => write!(f, "album artist"),
             Self::Album => write!(f, "album"),
             Self::Genre => write!(f, "genre"),
             Self::Directory => write!(f, "directory"),
@@ -96,13 +137,13 @@ impl DataBase {
         Ok(Self { conn, max_depth })
     }
 
-    /// Insert multiple tracks into the database
+    /// Insert multiple tracks into the database, replacing existing rows of the same files
     fn add_records(conn: &Arc<Mutex<Connection>>, tracks: Vec<Track>) -> Result<()> {
         let mut conn = conn.lock();
         let tx = conn.transaction()?;
 
         for track in tracks {
-            TrackDBInsertable::from(&track).insert_track(&tx)?;
+            TrackDBInsertable::from(&track).replace_track(&tx)?;
         }
 
         tx.commit()?;
@@ -236,33 +277,62 @@ impl DataBase {
         Ok(vec)
     }
 
-    /// Get Tracks by [`SearchCriteria`]
+    /// Get Tracks by [`SearchCriteria`], in album order: by album, disc and track number
+    ///
+    /// Always empty for [`SearchCriteria::Playlist`].
     pub fn get_record_by_criteria(
         &mut self,
         criteria_val: &str,
         criteria: &SearchCriteria,
     ) -> Result<Vec<TrackDB>> {
-        let search_str = format!("SELECT * FROM tracks WHERE {criteria} = ?");
+        let Some(column) = criteria.sql_column() else {
+            return Ok(Vec::new());
+        };
         let conn = self.conn.lock();
-        let mut stmt = conn.prepare(&search_str)?;
 
-        let mut vec_records: Vec<TrackDB> = stmt
-            .query_map([criteria_val], TrackDB::try_from_row_named)?
-            .flatten()
-            .collect();
+        let mut vec_records: Vec<TrackDB> = if *criteria == SearchCriteria::Genre {
+            let value = criteria_val.to_lowercase();
+            let mut stmt = conn.prepare("SELECT * FROM tracks WHERE genre IS NOT NULL")?;
+            let vec = stmt
+                .query_map([], TrackDB::try_from_row_named)?
+                .flatten()
+                .filter(|v| split_genres(&v.genre).any(|genre| genre.to_lowercase() == value))
+                .collect();
+            vec
+        } else {
+            let mut stmt = conn.prepare(&format!("SELECT * FROM tracks WHERE {column} = ?"))?;
+            let vec = stmt
+                .query_map([criteria_val], TrackDB::try_from_row_named)?
+                .flatten()
+                .collect();
+            vec
+        };
 
         // Left for debug
         // error!("criteria_val: {}", criteria_val);
         // error!("criteria: {}", criteria);
         // error!("vec: {:?}", vec_records);
 
-        vec_records.sort_by_cached_key(|k| get_pin_yin(&k.name));
+        vec_records.sort_by_cached_key(|k| {
+            (
+                get_pin_yin(&k.album),
+                k.disc_number,
+                k.track_number,
+                get_pin_yin(&k.name),
+            )
+        });
         Ok(vec_records)
     }
 
     /// Get a list of available distinct [`SearchCriteria`] (ie get Artist names deduplicated)
+    ///
+    /// Genres are split into single genres and deduplicated ignoring case.
+    /// Always empty for [`SearchCriteria::Playlist`].
     pub fn get_criterias(&mut self, criteria: &SearchCriteria) -> Result<Vec<String>> {
-        let search_str = format!("SELECT DISTINCT {criteria} FROM tracks");
+        let Some(column) = criteria.sql_column() else {
+            return Ok(Vec::new());
+        };
+        let search_str = format!("SELECT DISTINCT {column} FROM tracks");
         let conn = self.conn.lock();
         let mut stmt = conn.prepare(&search_str)?;
 
@@ -274,6 +344,16 @@ impl DataBase {
             .flatten()
             .collect();
 
+        if *criteria == SearchCriteria::Genre {
+            let mut seen = HashSet::new();
+            vec = vec
+                .iter()
+                .flat_map(|v| split_genres(v))
+                .filter(|v| seen.insert(v.to_lowercase()))
+                .map(str::to_string)
+                .collect();
+        }
+
         vec.sort_by_cached_key(|k| get_pin_yin(k));
         Ok(vec)
     }
@@ -326,14 +406,201 @@ impl DataBase {
 
         Err(Error::QueryReturnedNoRows)
     }
+
+    /// Get all Tracks with the given file name (without directory)
+    pub fn get_records_by_name(&mut self, name: &str) -> Result<Vec<TrackDB>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT * FROM tracks WHERE name = ?")?;
+
+        let vec_records: Vec<TrackDB> = stmt
+            .query_map([name], TrackDB::try_from_row_named)?
+            .flatten()
+            .collect();
+
+        Ok(vec_records)
+    }
+
+    /// Get all Tracks with the given title and, if set, artist, ignoring case
+    pub fn get_records_by_title(
+        &mut self,
+        title: &str,
+        artist: Option<&str>,
+    ) -> Result<Vec<TrackDB>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT * FROM tracks WHERE title = ?1 COLLATE NOCASE AND (?2 IS NULL OR artist = ?2 COLLATE NOCASE)",
+        )?;
+
+        let vec_records: Vec<TrackDB> = stmt
+            .query_map(params![title, artist], TrackDB::try_from_row_named)?
+            .flatten()
+            .collect();
+
+        Ok(vec_records)
+    }
 }
 
 #[cfg(test)]
 mod test_utils {
+    use super::track_db::TrackDBInsertable;
+    use super::{migration, DataBase};
+    use crate::config::v2::server::ScanDepth;
+    use parking_lot::Mutex;
     use rusqlite::Connection;
+    use std::sync::Arc;
+    use std::time::Duration;
 
     /// Open a new In-Memory sqlite database
     pub fn gen_database() -> Connection {
         Connection::open_in_memory().expect("open db failed")
     }
+
+    /// Open a new In-Memory [`DataBase`] with all migrations applied
+    pub fn gen_data_base() -> DataBase {
+        let conn = gen_database();
+        migration::migrate(&conn).expect("migrate db failed");
+
+        DataBase {
+            conn: Arc::new(Mutex::new(conn)),
+            max_depth: ScanDepth::Unlimited,
+        }
+    }
+
+    /// Insert a track with the given values and placeholders for everything else
+    pub fn insert_track(db: &DataBase, file: &str, artist: &str, title: &str, secs: u64) {
+        let name = file.rsplit('/').next().unwrap();
+        TrackDBInsertable {
+            artist,
+            title,
+            album: "Album",
+            genre: "Genre",
+            file,
+            duration: Duration::from_secs(secs),
+            name,
+            ext: "mp3",
+            directory: "/library",
+            last_modified: "0".to_string(),
+            last_position: Duration::default(),
+            album_artist: None,
+            composer: None,
+            track_number: None,
+            disc_number: None,
+            year: None,
+            rating: None,
+        }
+        .insert_track(&db.conn.lock())
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_utils::{gen_data_base, insert_track};
+    use super::track_db::TrackDBInsertable;
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_group_by_album_artist_in_album_order() {
+        let mut db = gen_data_base();
+        for (name, artist, album_artist, disc, track) in [
+            (
+                "b.mp3",
+                "Artist A",
+                Some("Various Artists"),
+                Some(1),
+                Some(2),
+            ),
+            (
+                "a.mp3",
+                "Artist B",
+                Some("Various Artists"),
+                Some(2),
+                Some(1),
+            ),
+            (
+                "c.mp3",
+                "Artist B",
+                Some("Various Artists"),
+                Some(1),
+                Some(1),
+            ),
+            ("d.mp3", "Artist B", None, None, None),
+        ] {
+            TrackDBInsertable {
+                artist,
+                title: name,
+                album: "Album",
+                genre: "Genre",
+                file: &format!("/library/{name}"),
+                duration: Duration::from_secs(100),
+                name,
+                ext: "mp3",
+                directory: "/library",
+                last_modified: "0".to_string(),
+                last_position: Duration::default(),
+                album_artist,
+                composer: None,
+                track_number: track,
+                disc_number: disc,
+                year: None,
+                rating: None,
+            }
+            .insert_track(&db.conn.lock())
+            .unwrap();
+        }
+
+        assert_eq!(
+            db.get_criterias(&SearchCriteria::AlbumArtist).unwrap(),
+            vec!["Artist B", "Various Artists"]
+        );
+
+        let names: Vec<String> = db
+            .get_record_by_criteria("Various Artists", &SearchCriteria::AlbumArtist)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.name)
+            .collect();
+        assert_eq!(names, vec!["c.mp3", "b.mp3", "a.mp3"]);
+    }
+
+    #[test]
+    fn should_split_genres() {
+        let mut db = gen_data_base();
+        insert_track(&db, "/library/one.mp3", "Artist", "One", 100);
+        insert_track(&db, "/library/two.mp3", "Artist", "Two", 100);
+        insert_track(&db, "/library/three.mp3", "Artist", "Three", 100);
+        for (name, genre) in [
+            ("one.mp3", "Rock; Pop"),
+            ("two.mp3", "pop/Jazz"),
+            ("three.mp3", "Rock"),
+        ] {
+            db.conn
+                .lock()
+                .execute(
+                    "UPDATE tracks SET genre = ?1 WHERE name = ?2",
+                    [genre, name],
+                )
+                .unwrap();
+        }
+
+        assert_eq!(
+            db.get_criterias(&SearchCriteria::Genre).unwrap(),
+            vec!["Jazz", "Pop", "Rock"]
+        );
+
+        let mut names: Vec<String> = db
+            .get_record_by_criteria("pop", &SearchCriteria::Genre)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["one.mp3", "two.mp3"]);
+
+        assert!(db
+            .get_criterias(&SearchCriteria::Playlist)
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use rusqlite::{params, Error, OptionalExtension, Result, Row};
+
+use super::DataBase;
+
+/// A struct representing a named playlist in the database, without its entries
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlaylistDB {
+    pub id: u64,
+    pub name: String,
+    /// The index of the track that was current when this playlist was last active
+    pub current_index: usize,
+    /// The position in the current track when this playlist was last active
+    pub last_position: Duration,
+    /// Whether this playlist is the one currently loaded into `playlist.toml`
+    pub active: bool,
+    /// Amount of entries stored for this playlist
+    pub track_count: usize,
+}
+
+impl PlaylistDB {
+    /// Try to convert a given row to a [`PlaylistDB`] instance, using column names to resolve the values
+    pub fn try_from_row_named(row: &Row<'_>) -> Result<Self, rusqlite::Error> {
+        // NOTE: all the names in "get" below are the *column names* as defined in migrations/003.sql#table_playlists (pseudo link)
+        // and the "track_count" alias used in "PLAYLIST_SELECT"
+        let last_position_u64: u64 = row.get("last_position")?;
+        Ok(PlaylistDB {
+            id: row.get("id")?,
+            name: row.get("name")?,
+            current_index: row.get("current_index")?,
+            last_position: Duration::from_secs(last_position_u64),
+            active: row.get("active")?,
+            track_count: row.get("track_count")?,
+        })
+    }
+}
+
+/// Common select for [`PlaylistDB::try_from_row_named`], needs a "WHERE" or "ORDER BY" appended
+const PLAYLIST_SELECT: &str = "SELECT playlists.*, (SELECT COUNT(*) FROM playlist_entries WHERE playlist_id = playlists.id) AS track_count FROM playlists";
+
+impl DataBase {
+    /// Get all named playlists, in creation order
+    pub fn playlist_list(&mut self) -> Result<Vec<PlaylistDB>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!("{PLAYLIST_SELECT} ORDER BY id"))?;
+        let vec: Vec<PlaylistDB> = stmt
+            .query_map([], PlaylistDB::try_from_row_named)?
+            .flatten()
+            .collect();
+        Ok(vec)
+    }
+
+    /// Get a single named playlist by id
+    pub fn playlist_get(&mut self, id: u64) -> Result<PlaylistDB> {
+        let conn = self.conn.lock();
+        conn.query_row(
+            &format!("{PLAYLIST_SELECT} WHERE id = ?1"),
+            params![id],
+            PlaylistDB::try_from_row_named,
+        )
+    }
+
+    /// Get the playlist currently marked as active, if any
+    pub fn playlist_get_active(&mut self) -> Result<Option<PlaylistDB>> {
+        let conn = self.conn.lock();
+        conn.query_row(
+            &format!("{PLAYLIST_SELECT} WHERE active = 1"),
+            [],
+            PlaylistDB::try_from_row_named,
+        )
+        .optional()
+    }
+
+    /// Create a new empty playlist, returns the id of the new playlist
+    ///
+    /// Errors if the name is already taken
+    pub fn playlist_create(&mut self, name: &str) -> Result<u64> {
+        let conn = self.conn.lock();
+        conn.execute("INSERT INTO playlists (name) VALUES (?1)", params![name])?;
+        Ok(conn.last_insert_rowid().unsigned_abs())
+    }
+
+    /// Rename a playlist
+    ///
+    /// Errors if the name is already taken or the playlist does not exist
+    pub fn playlist_rename(&mut self, id: u64, name: &str) -> Result<()> {
+        let conn = self.conn.lock();
+        let changed = conn.execute(
+            "UPDATE playlists SET name = ?1 WHERE id = ?2",
+            params![name, id],
+        )?;
+        if changed == 0 {
+            return Err(Error::QueryReturnedNoRows);
+        }
+        Ok(())
+    }
+
+    /// Copy a playlist including its entries and state under a new name, returns the id of the new playlist
+    ///
+    /// The copy is never marked as active
+    pub fn playlist_duplicate(&mut self, id: u64, name: &str) -> Result<u64> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        let changed = tx.execute(
+            "INSERT INTO playlists (name, current_index, last_position) SELECT ?1, current_index, last_position FROM playlists WHERE id = ?2",
+            params![name, id],
+        )?;
+        if changed == 0 {
+            return Err(Error::QueryReturnedNoRows);
+        }
+        let new_id = tx.last_insert_rowid().unsigned_abs();
+        tx.execute(
+            "INSERT INTO playlist_entries (playlist_id, position, file) SELECT ?1, position, file FROM playlist_entries WHERE playlist_id = ?2",
+            params![new_id, id],
+        )?;
+        tx.commit()?;
+        Ok(new_id)
+    }
+
+    /// Delete a playlist and all its entries
+    pub fn playlist_delete(&mut self, id: u64) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM playlist_entries WHERE playlist_id = ?1",
+            params![id],
+        )?;
+        let changed = tx.execute("DELETE FROM playlists WHERE id = ?1", params![id])?;
+        if changed == 0 {
+            return Err(Error::QueryReturnedNoRows);
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Get the ordered file paths / urls of a playlist
+    pub fn playlist_get_entries(&mut self, id: u64) -> Result<Vec<String>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT file FROM playlist_entries WHERE playlist_id = ?1 ORDER BY position",
+        )?;
+        let vec: Vec<String> = stmt
+            .query_map(params![id], |row| row.get(0))?
+            .flatten()
+            .collect();
+        Ok(vec)
+    }
+
+    /// Replace all entries of a playlist with `entries`, keeping the given order
+    pub fn playlist_set_entries<S: AsRef<str>>(&mut self, id: u64, entries: &[S]) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM playlist_entries WHERE playlist_id = ?1",
+            params![id],
+        )?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO playlist_entries (playlist_id, position, file) VALUES (?1, ?2, ?3)",
+            )?;
+            for (position, file) in entries.iter().enumerate() {
+                stmt.execute(params![id, position, file.as_ref()])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Store the current track index and position of a playlist
+    pub fn playlist_set_state(
+        &mut self,
+        id: u64,
+        current_index: usize,
+        last_position: Duration,
+    ) -> Result<()> {
+        let conn = self.conn.lock();
+        conn.execute(
+            "UPDATE playlists SET current_index = ?1, last_position = ?2 WHERE id = ?3",
+            params![current_index, last_position.as_secs(), id],
+        )?;
+        Ok(())
+    }
+
+    /// Mark the given playlist as the only active one
+    pub fn playlist_set_active(&mut self, id: u64) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute("UPDATE playlists SET active = 0", [])?;
+        let changed = tx.execute("UPDATE playlists SET active = 1 WHERE id = ?1", params![id])?;
+        if changed == 0 {
+            return Err(Error::QueryReturnedNoRows);
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::gen_data_base;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    #[test]
+    fn should_manage_playlists() {
+        let mut db = gen_data_base();
+
+        let first = db.playlist_create("First").unwrap();
+        let second = db.playlist_create("Second").unwrap();
+        assert!(db.playlist_create("First").is_err());
+
+        db.playlist_set_entries(first, &["/a.mp3", "/b.mp3", "/c.mp3"])
+            .unwrap();
+        db.playlist_set_state(first, 2, Duration::from_secs(30))
+            .unwrap();
+        db.playlist_set_active(first).unwrap();
+
+        let copy = db.playlist_duplicate(first, "Copy").unwrap();
+        assert_eq!(
+            db.playlist_get_entries(copy).unwrap(),
+            &["/a.mp3", "/b.mp3", "/c.mp3"]
+        );
+        let copy_info = db.playlist_get(copy).unwrap();
+        assert_eq!(copy_info.current_index, 2);
+        assert_eq!(copy_info.last_position, Duration::from_secs(30));
+        assert!(!copy_info.active);
+
+        db.playlist_rename(second, "Renamed").unwrap();
+        assert!(db.playlist_rename(second, "Copy").is_err());
+
+        db.playlist_set_active(second).unwrap();
+        assert_eq!(db.playlist_get_active().unwrap().unwrap().id, second);
+
+        db.playlist_delete(first).unwrap();
+        assert!(db.playlist_get_entries(first).unwrap().is_empty());
+
+        let names: Vec<(String, usize)> = db
+            .playlist_list()
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.name, v.track_count))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("Renamed".to_string(), 0), ("Copy".to_string(), 3)]
+        );
+    }
+
+    #[test]
+    fn should_keep_entry_order() {
+        let mut db = gen_data_base();
+
+        let id = db.playlist_create("Order").unwrap();
+        db.playlist_set_entries(id, &["/c.mp3", "/a.mp3", "/b.mp3"])
+            .unwrap();
+        db.playlist_set_entries(id, &["/b.mp3", "/c.mp3"]).unwrap();
+
+        assert_eq!(db.playlist_get_entries(id).unwrap(), &["/b.mp3", "/c.mp3"]);
+    }
+}
@@ -0,0 +1,794 @@
+//! A small query language to filter the library, like `artist:radiohead -live duration:<4m`
+//!
+//! - words without a field match the start of words in the artist, title, album, genre, file name,
+//!   album artist or composer
+//! - `field:value` only matches in that field, `field:=value` matches the whole value
+//! - `duration` supports `<`, `<=`, `>`, `>=` and `=` with values like `4m`, `3m30s`, `3:30` or `210`
+//! - `year`, `track`, `disc` and `rating` (1 to 5 stars) support the same comparisons with whole numbers,
+//!   like `year:>=1990`
+//! - values with spaces need to be quoted: `genre:"post rock"`
+//! - terms are combined with `AND` (the default), `OR` and grouped with `(` `)`
+//! - `-term` or `NOT term` excludes matches
+
+use std::fmt::{Display, Write};
+use std::time::Duration;
+
+use rusqlite::types::Value;
+
+use super::{DataBase, TrackDB};
+
+/// A error while parsing a [`Query`], with the character position in the input it happened at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl QueryError {
+    fn new(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position,
+        }
+    }
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (at {})", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// The `:` without a explicit operator
+    Match,
+    Equal,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl Comparison {
+    fn as_sql(self) -> &'static str {
+        match self {
+            Comparison::Match | Comparison::Equal => "=",
+            Comparison::Less => "<",
+            Comparison::LessEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterEqual => ">=",
+        }
+    }
+}
+
+/// The fields that can be used as `field:value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Artist,
+    Title,
+    Album,
+    Genre,
+    /// The file name
+    Name,
+    /// The full path
+    File,
+    Directory,
+    AlbumArtist,
+    Composer,
+    Duration,
+    Year,
+    /// The track number
+    Track,
+    /// The disc number
+    Disc,
+    /// The rating from 1 to 5 stars
+    Rating,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        let field = match name.to_lowercase().as_str() {
+            "artist" => Field::Artist,
+            "title" => Field::Title,
+            "album" => Field::Album,
+            "genre" => Field::Genre,
+            "name" | "filename" => Field::Name,
+            "file" | "path" => Field::File,
+            "directory" | "dir" => Field::Directory,
+            "albumartist" | "album_artist" => Field::AlbumArtist,
+            "composer" => Field::Composer,
+            "duration" => Field::Duration,
+            "year" => Field::Year,
+            "track" | "tracknumber" => Field::Track,
+            "disc" | "discnumber" => Field::Disc,
+            "rating" => Field::Rating,
+            _ => return None,
+        };
+
+        Some(field)
+    }
+
+    /// The column in `tracks`
+    fn column(self) -> &'static str {
+        match self {
+            Field::Artist => "artist",
+            Field::Title => "title",
+            Field::Album => "album",
+            Field::Genre => "genre",
+            Field::Name => "name",
+            Field::File => "file",
+            Field::Directory => "directory",
+            Field::AlbumArtist => "album_artist",
+            Field::Composer => "composer",
+            Field::Duration => "duration",
+            Field::Year => "year",
+            Field::Track => "track_number",
+            Field::Disc => "disc_number",
+            Field::Rating => "rating",
+        }
+    }
+
+    /// Whether the column is part of the full-text index
+    fn is_indexed(self) -> bool {
+        matches!(
+            self,
+            Field::Artist
+                | Field::Title
+                | Field::Album
+                | Field::Genre
+                | Field::Name
+                | Field::AlbumArtist
+                | Field::Composer
+        )
+    }
+
+    /// Whether the column holds whole numbers
+    fn is_number(self) -> bool {
+        matches!(
+            self,
+            Field::Year | Field::Track | Field::Disc | Field::Rating
+        )
+    }
+}
+
+/// A single condition of a [`Query`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Match everything, like the input `*`
+    All,
+    /// Words starting with the value in any indexed field
+    Words(String),
+    /// A text field containing the value, or equal to it with [`Comparison::Equal`]
+    Text {
+        field: Field,
+        comparison: Comparison,
+        value: String,
+    },
+    Duration {
+        comparison: Comparison,
+        value: Duration,
+    },
+    /// A number field compared to the value
+    Number {
+        field: Field,
+        comparison: Comparison,
+        value: u32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Filter(Filter),
+}
+
+/// A parsed library query, see the [module documentation](self) for the syntax
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Query {
+    /// `None` if the query matches everything
+    pub expr: Option<Expr>,
+}
+
+impl Query {
+    /// Parse the query syntax
+    ///
+    /// # Errors
+    ///
+    /// - if the input is not valid query syntax
+    pub fn parse(input: &str) -> Result<Self, QueryError> {
+        let tokens = lex(input)?;
+        if tokens.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let mut parser = Parser {
+            tokens,
+            index: 0,
+            end: input.chars().count(),
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(QueryError::new("unmatched \")\"", token.position));
+        }
+
+        Ok(Self { expr: Some(expr) })
+    }
+
+    /// Get the words of a query that only consists of words without fields, which can be ranked by relevance
+    #[must_use]
+    pub fn plain_words(&self) -> Option<String> {
+        fn collect<'a>(expr: &'a Expr, words: &mut Vec<&'a str>) -> bool {
+            match expr {
+                Expr::Filter(Filter::Words(word)) => {
+                    words.push(word);
+                    true
+                }
+                Expr::Filter(Filter::All) => true,
+                Expr::And(exprs) => exprs.iter().all(|v| collect(v, words)),
+                _ => false,
+            }
+        }
+
+        let mut words = Vec::new();
+        if let Some(expr) = &self.expr {
+            if !collect(expr, &mut words) {
+                return None;
+            }
+        }
+
+        Some(words.join(" "))
+    }
+
+    /// Get the sql condition for the `tracks` table and its parameters
+    #[must_use]
+    pub fn to_sql(&self) -> (String, Vec<Value>) {
+        let mut sql = String::new();
+        let mut params = Vec::new();
+        match &self.expr {
+            Some(expr) => expr_to_sql(expr, &mut sql, &mut params),
+            None => sql.push('1'),
+        }
+
+        (sql, params)
+    }
+}
+
+/// Quote a value for a FTS5 query, matching the start of words
+fn fts_prefix(value: &str) -> String {
+    format!("\"{}\"*", value.replace('"', "\"\""))
+}
+
+/// Escape the wildcards of a `LIKE` pattern, with `\` as escape character
+fn like_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn expr_to_sql(expr: &Expr, sql: &mut String, params: &mut Vec<Value>) {
+    let mut join = |exprs: &[Expr], operator: &str, sql: &mut String| {
+        sql.push('(');
+        for (i, expr) in exprs.iter().enumerate() {
+            if i > 0 {
+                sql.push_str(operator);
+            }
+            expr_to_sql(expr, sql, params);
+        }
+        sql.push(')');
+    };
+
+    match expr {
+        Expr::And(exprs) => join(exprs, " AND ", sql),
+        Expr::Or(exprs) => join(exprs, " OR ", sql),
+        Expr::Not(expr) => {
+            sql.push_str("NOT ");
+            expr_to_sql(expr, sql, params);
+        }
+        Expr::Filter(Filter::All) => sql.push('1'),
+        Expr::Filter(Filter::Words(words)) => {
+            sql.push_str("id IN (SELECT rowid FROM tracks_fts WHERE tracks_fts MATCH ?)");
+            params.push(Value::Text(fts_prefix(words)));
+        }
+        Expr::Filter(Filter::Text {
+            field,
+            comparison,
+            value,
+        }) => {
+            let column = field.column();
+            if *comparison == Comparison::Equal {
+                let _ = write!(sql, "{column} = ? COLLATE NOCASE");
+                params.push(Value::Text(value.clone()));
+            } else if field.is_indexed() {
+                sql.push_str("id IN (SELECT rowid FROM tracks_fts WHERE tracks_fts MATCH ?)");
+                params.push(Value::Text(format!("{column} : {}", fts_prefix(value))));
+            } else {
+                let _ = write!(sql, "{column} LIKE ? ESCAPE '\\'");
+                params.push(Value::Text(format!("%{}%", like_escape(value))));
+            }
+        }
+        Expr::Filter(Filter::Duration { comparison, value }) => {
+            let _ = write!(sql, "duration {} ?", comparison.as_sql());
+            params.push(Value::Integer(
+                i64::try_from(value.as_secs()).unwrap_or(i64::MAX),
+            ));
+        }
+        Expr::Filter(Filter::Number {
+            field,
+            comparison,
+            value,
+        }) => {
+            let _ = write!(sql, "{} {} ?", field.column(), comparison.as_sql());
+            params.push(Value::Integer(i64::from(*value)));
+        }
+    }
+}
+
+/// Parse durations like `4m`, `3m30s`, `1h`, `3:30` or `210` (seconds)
+///
+/// Returns `None` for invalid values and for values that do not fit in `u64` seconds
+fn parse_duration(value: &str) -> Option<Duration> {
+    if value.contains(':') {
+        let mut secs: u64 = 0;
+        for part in value.split(':') {
+            secs = secs
+                .checked_mul(60)?
+                .checked_add(part.parse::<u64>().ok()?)?;
+        }
+        return Some(Duration::from_secs(secs));
+    }
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let mut secs: u64 = 0;
+    let mut number = String::new();
+    for ch in value.chars() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
+            continue;
+        }
+        let unit = match ch {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        let part = number.parse::<u64>().ok()?.checked_mul(unit)?;
+        secs = part.checked_add(secs)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return None;
+    }
+
+    Some(Duration::from_secs(secs))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    LParen,
+    RParen,
+    Or,
+    And,
+    Not,
+    Term {
+        field: Option<String>,
+        comparison: Comparison,
+        value: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn lex(input: &str) -> Result<Vec<Token>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        let position = i;
+        if ch.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let kind = match ch {
+            '(' => Some(TokenKind::LParen),
+            ')' => Some(TokenKind::RParen),
+            '-' if chars.get(i + 1).is_some_and(|v| !v.is_whitespace()) => Some(TokenKind::Not),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            tokens.push(Token { kind, position });
+            i += 1;
+            continue;
+        }
+
+        let mut field = None;
+        let mut comparison = Comparison::Match;
+        let mut value = String::new();
+        let mut quoted = false;
+        while let Some(&ch) = chars.get(i) {
+            if ch.is_whitespace() || ch == '(' || ch == ')' {
+                break;
+            }
+            i += 1;
+            match ch {
+                '"' => {
+                    quoted = true;
+                    loop {
+                        match chars.get(i) {
+                            Some('"') => {
+                                i += 1;
+                                break;
+                            }
+                            Some(&ch) => {
+                                value.push(ch);
+                                i += 1;
+                            }
+                            None => {
+                                return Err(QueryError::new("missing closing quote", i));
+                            }
+                        }
+                    }
+                }
+                ':' if field.is_none() && !quoted => {
+                    field = Some(std::mem::take(&mut value));
+                    let operator = [
+                        (">=", Comparison::GreaterEqual),
+                        ("<=", Comparison::LessEqual),
+                        (">", Comparison::Greater),
+                        ("<", Comparison::Less),
+                        ("=", Comparison::Equal),
+                    ]
+                    .into_iter()
+                    .find(|(op, _)| {
+                        op.chars()
+                            .enumerate()
+                            .all(|(n, v)| chars.get(i + n) == Some(&v))
+                    });
+                    if let Some((op, cmp)) = operator {
+                        comparison = cmp;
+                        i += op.len();
+                    }
+                }
+                ch => value.push(ch),
+            }
+        }
+
+        let kind = match (field.is_none() && !quoted).then_some(value.as_str()) {
+            Some("OR") => TokenKind::Or,
+            Some("AND") => TokenKind::And,
+            Some("NOT") => TokenKind::Not,
+            _ => TokenKind::Term {
+                field,
+                comparison,
+                value,
+            },
+        };
+        tokens.push(Token { kind, position });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+    /// Position of the end of the input, for errors
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn position(&self) -> usize {
+        self.peek().map_or(self.end, |v| v.position)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, QueryError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.peek().is_some_and(|v| v.kind == TokenKind::Or) {
+            self.index += 1;
+            exprs.push(self.parse_and()?);
+        }
+
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, QueryError> {
+        let mut exprs = Vec::new();
+        while let Some(token) = self.peek() {
+            match token.kind {
+                TokenKind::Or | TokenKind::RParen => break,
+                TokenKind::And => self.index += 1,
+                _ => exprs.push(self.parse_unary()?),
+            }
+        }
+
+        match exprs.len() {
+            0 => Err(QueryError::new("expected a search term", self.position())),
+            1 => Ok(exprs.remove(0)),
+            _ => Ok(Expr::And(exprs)),
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, QueryError> {
+        let position = self.position();
+        let Some(token) = self.next() else {
+            return Err(QueryError::new("expected a search term", position));
+        };
+
+        match token.kind {
+            TokenKind::Not => Ok(Expr::Not(Box::new(self.parse_unary()?))),
+            TokenKind::LParen => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token {
+                        kind: TokenKind::RParen,
+                        ..
+                    }) => Ok(expr),
+                    _ => Err(QueryError::new("missing \")\"", self.end)),
+                }
+            }
+            TokenKind::Term {
+                field,
+                comparison,
+                value,
+            } => Ok(Expr::Filter(term_filter(
+                field.as_deref(),
+                comparison,
+                value,
+                token.position,
+            )?)),
+            TokenKind::Or | TokenKind::And | TokenKind::RParen => {
+                Err(QueryError::new("expected a search term", token.position))
+            }
+        }
+    }
+}
+
+fn term_filter(
+    field: Option<&str>,
+    comparison: Comparison,
+    value: String,
+    position: usize,
+) -> Result<Filter, QueryError> {
+    let Some(name) = field else {
+        // "*" and "?" were the wildcards before this syntax existed
+        let word = value.trim_matches(['*', '?']);
+        return Ok(if word.is_empty() {
+            Filter::All
+        } else {
+            Filter::Words(word.to_string())
+        });
+    };
+
+    let Some(field) = Field::from_name(name) else {
+        return Err(QueryError::new(
+            format!("unknown field \"{name}\""),
+            position,
+        ));
+    };
+    if value.is_empty() {
+        return Err(QueryError::new(
+            format!("missing value for \"{name}\""),
+            position,
+        ));
+    }
+
+    if field == Field::Duration {
+        let Some(value) = parse_duration(&value) else {
+            return Err(QueryError::new(
+                format!("invalid duration \"{value}\""),
+                position,
+            ));
+        };
+        return Ok(Filter::Duration { comparison, value });
+    }
+
+    if field.is_number() {
+        let Ok(value) = value.parse() else {
+            return Err(QueryError::new(
+                format!("invalid number \"{value}\""),
+                position,
+            ));
+        };
+        return Ok(Filter::Number {
+            field,
+            comparison,
+            value,
+        });
+    }
+
+    if !matches!(comparison, Comparison::Match | Comparison::Equal) {
+        return Err(QueryError::new(
+            format!("\"{name}\" can not be compared, only \"{name}:value\" or \"{name}:=value\""),
+            position,
+        ));
+    }
+
+    Ok(Filter::Text {
+        field,
+        comparison,
+        value,
+    })
+}
+
+impl DataBase {
+    /// Get all tracks matching the query, ranked by relevance if it only consists of plain words
+    ///
+    /// # Errors
+    ///
+    /// - if the database query fails
+    pub fn query_tracks(&mut self, query: &Query) -> rusqlite::Result<Vec<TrackDB>> {
+        if let Some(words) = query.plain_words() {
+            return self.search_tracks(&words);
+        }
+
+        let (condition, params) = query.to_sql();
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(&format!("SELECT * FROM tracks WHERE {condition}"))?;
+        let vec: Vec<TrackDB> = stmt
+            .query_map(
+                rusqlite::params_from_iter(params),
+                TrackDB::try_from_row_named,
+            )?
+            .flatten()
+            .collect();
+
+        Ok(vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{gen_data_base, insert_track};
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn words(value: &str) -> Expr {
+        Expr::Filter(Filter::Words(value.to_string()))
+    }
+
+    fn text(field: Field, value: &str) -> Expr {
+        Expr::Filter(Filter::Text {
+            field,
+            comparison: Comparison::Match,
+            value: value.to_string(),
+        })
+    }
+
+    #[test]
+    fn should_parse() {
+        assert_eq!(Query::parse("  ").unwrap(), Query::default());
+        assert_eq!(
+            Query::parse("artist:radiohead genre:\"post rock\" -live duration:<4m")
+                .unwrap()
+                .expr,
+            Some(Expr::And(vec![
+                text(Field::Artist, "radiohead"),
+                text(Field::Genre, "post rock"),
+                Expr::Not(Box::new(words("live"))),
+                Expr::Filter(Filter::Duration {
+                    comparison: Comparison::Less,
+                    value: Duration::from_secs(240),
+                }),
+            ]))
+        );
+        assert_eq!(
+            Query::parse("(a OR b) AND NOT title:=c").unwrap().expr,
+            Some(Expr::And(vec![
+                Expr::Or(vec![words("a"), words("b")]),
+                Expr::Not(Box::new(Expr::Filter(Filter::Text {
+                    field: Field::Title,
+                    comparison: Comparison::Equal,
+                    value: "c".to_string(),
+                }))),
+            ]))
+        );
+    }
+
+    #[test]
+    fn should_report_errors() {
+        let error = |input: &str| Query::parse(input).unwrap_err();
+
+        assert_eq!(
+            error("mood:happy"),
+            QueryError::new("unknown field \"mood\"", 0)
+        );
+        assert_eq!(error("a (b"), QueryError::new("missing \")\"", 4));
+        assert_eq!(error("a b)"), QueryError::new("unmatched \")\"", 3));
+        assert_eq!(error("a OR"), QueryError::new("expected a search term", 4));
+        assert_eq!(error("x \"a"), QueryError::new("missing closing quote", 4));
+        assert_eq!(
+            error("duration:<4x"),
+            QueryError::new("invalid duration \"4x\"", 0)
+        );
+        assert_eq!(
+            error("a year:199x"),
+            QueryError::new("invalid number \"199x\"", 2)
+        );
+        assert_eq!(error("artist:>a").position, 0);
+        assert_eq!(
+            error("duration:>999999999999999999:0"),
+            QueryError::new("invalid duration \"999999999999999999:0\"", 0)
+        );
+    }
+
+    #[test]
+    fn should_parse_durations() {
+        assert_eq!(parse_duration("4m"), Some(Duration::from_secs(240)));
+        assert_eq!(parse_duration("3m30s"), Some(Duration::from_secs(210)));
+        assert_eq!(parse_duration("3:30"), Some(Duration::from_secs(210)));
+        assert_eq!(parse_duration("1:00:00"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("210"), Some(Duration::from_secs(210)));
+        assert_eq!(parse_duration("3m30"), None);
+        assert_eq!(parse_duration("999999999999999999:0"), None);
+        assert_eq!(parse_duration("18446744073709551615h"), None);
+        assert_eq!(parse_duration("18446744073709551615s1s"), None);
+    }
+
+    #[test]
+    fn should_query_tracks() {
+        let mut db = gen_data_base();
+        insert_track(&db, "/library/a/one.mp3", "Radiohead", "Creep", 240);
+        insert_track(&db, "/library/a/two.mp3", "Radiohead", "Creep (Live)", 300);
+        insert_track(&db, "/library/b/three.mp3", "Other", "Live Forever", 200);
+        db.conn
+            .lock()
+            .execute(
+                "UPDATE tracks SET year = 1994, composer = 'Noel Gallagher', rating = 4 WHERE name = 'three.mp3'",
+                [],
+            )
+            .unwrap();
+
+        let mut files = |input: &str| -> Vec<String> {
+            let mut files: Vec<String> = db
+                .query_tracks(&Query::parse(input).unwrap())
+                .unwrap()
+                .into_iter()
+                .map(|v| v.file)
+                .collect();
+            files.sort();
+            files
+        };
+
+        assert_eq!(files("artist:radio -live"), vec!["/library/a/one.mp3"]);
+        assert_eq!(
+            files("duration:<=4m"),
+            vec!["/library/a/one.mp3", "/library/b/three.mp3"]
+        );
+        assert_eq!(
+            files("title:=creep OR name:three"),
+            vec!["/library/a/one.mp3", "/library/b/three.mp3"]
+        );
+        assert_eq!(files("file:\"a/t\""), vec!["/library/a/two.mp3"]);
+        assert_eq!(files("year:<2000"), vec!["/library/b/three.mp3"]);
+        assert_eq!(files("composer:noel"), vec!["/library/b/three.mp3"]);
+        assert_eq!(files("rating:>=4"), vec!["/library/b/three.mp3"]);
+        assert_eq!(files("*").len(), 3);
+    }
+}
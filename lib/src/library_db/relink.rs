@@ -0,0 +1,229 @@
+use std::ffi::OsStr;
+use std::time::Duration;
+
+use rusqlite::Result;
+
+use super::{DataBase, TrackDB};
+use crate::playlist::{PlaylistEntry, PlaylistValue};
+
+/// Maximal difference in duration for a library track to still be considered the same as a playlist entry
+const DURATION_TOLERANCE: Duration = Duration::from_secs(3);
+
+/// The outcome of relinking a single playlist entry
+#[derive(Debug, Clone, PartialEq)]
+pub enum RelinkStatus {
+    /// The entry resolves as-is, either a existing file or a url
+    Found,
+    /// The entry did not resolve, but uniquely matched this library file
+    Relinked(String),
+    /// The entry did not resolve and matched multiple library files
+    Ambiguous(Vec<String>),
+    /// The entry did not resolve and did not match any library file
+    Missing,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelinkEntry {
+    pub entry: PlaylistEntry,
+    pub status: RelinkStatus,
+}
+
+/// Result of [`DataBase::relink`], in playlist order
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RelinkReport {
+    pub entries: Vec<RelinkEntry>,
+}
+
+impl RelinkReport {
+    /// Check whether any entry did not resolve as-is, in which case the report should be shown before adding the tracks
+    #[must_use]
+    pub fn needs_review(&self) -> bool {
+        self.entries.iter().any(|v| v.status != RelinkStatus::Found)
+    }
+
+    /// Get the files or urls to add, ambiguous and missing entries are skipped
+    #[must_use]
+    pub fn files(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter_map(|v| match &v.status {
+                RelinkStatus::Found => Some(v.entry.location.to_string()),
+                RelinkStatus::Relinked(file) => Some(file.clone()),
+                RelinkStatus::Ambiguous(_) | RelinkStatus::Missing => None,
+            })
+            .collect()
+    }
+
+    /// Count the entries for which `filter` returns `true`
+    #[must_use]
+    pub fn count(&self, filter: impl Fn(&RelinkStatus) -> bool) -> usize {
+        self.entries.iter().filter(|v| filter(&v.status)).count()
+    }
+}
+
+impl DataBase {
+    /// Try to relink playlist entries that do not resolve to a existing file against the library
+    ///
+    /// Entries are matched by file name first, then by artist and title tags, then by title alone within [`DURATION_TOLERANCE`].
+    /// If there are multiple candidates and the entry has a duration, only candidates within the tolerance are kept.
+    pub fn relink(&mut self, entries: Vec<PlaylistEntry>) -> Result<RelinkReport> {
+        let mut report = RelinkReport::default();
+
+        for entry in entries {
+            let status = match &entry.location {
+                PlaylistValue::Url(_) => RelinkStatus::Found,
+                PlaylistValue::Path(path) if path.exists() => RelinkStatus::Found,
+                PlaylistValue::Path(_) => {
+                    let mut candidates = self.relink_candidates(&entry)?;
+                    match candidates.len() {
+                        0 => RelinkStatus::Missing,
+                        1 => RelinkStatus::Relinked(candidates.remove(0).file),
+                        _ => RelinkStatus::Ambiguous(
+                            candidates.into_iter().map(|v| v.file).collect(),
+                        ),
+                    }
+                }
+            };
+
+            report.entries.push(RelinkEntry { entry, status });
+        }
+
+        Ok(report)
+    }
+
+    /// Find the library tracks that may be the given entry, see [`relink`](Self::relink)
+    fn relink_candidates(&mut self, entry: &PlaylistEntry) -> Result<Vec<TrackDB>> {
+        let PlaylistValue::Path(path) = &entry.location else {
+            return Ok(Vec::new());
+        };
+
+        if let Some(name) = path.file_name().and_then(OsStr::to_str) {
+            let candidates = self.get_records_by_name(name)?;
+            if !candidates.is_empty() {
+                return Ok(narrow_by_duration(candidates, entry.duration));
+            }
+        }
+
+        let Some(title) = entry.title.as_deref() else {
+            return Ok(Vec::new());
+        };
+
+        if let Some(artist) = entry.artist.as_deref() {
+            let candidates = self.get_records_by_title(title, Some(artist))?;
+            if !candidates.is_empty() {
+                return Ok(narrow_by_duration(candidates, entry.duration));
+            }
+        }
+
+        // a title alone is too common to be used without a matching duration
+        let Some(duration) = entry.duration else {
+            return Ok(Vec::new());
+        };
+        let candidates = self
+            .get_records_by_title(title, None)?
+            .into_iter()
+            .filter(|v| duration_fits(v.duration, duration))
+            .collect();
+
+        Ok(candidates)
+    }
+}
+
+/// Check whether both durations are within [`DURATION_TOLERANCE`] of each other
+pub(super) fn duration_fits(a: Duration, b: Duration) -> bool {
+    a.saturating_sub(b).max(b.saturating_sub(a)) <= DURATION_TOLERANCE
+}
+
+/// Keep only the candidates within [`DURATION_TOLERANCE`] if there are multiple and at least one fits
+fn narrow_by_duration(candidates: Vec<TrackDB>, duration: Option<Duration>) -> Vec<TrackDB> {
+    let Some(duration) = duration else {
+        return candidates;
+    };
+    if candidates.len() < 2 {
+        return candidates;
+    }
+
+    let fitting: Vec<TrackDB> = candidates
+        .iter()
+        .filter(|v| duration_fits(v.duration, duration))
+        .cloned()
+        .collect();
+
+    if fitting.is_empty() {
+        candidates
+    } else {
+        fitting
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{gen_data_base, insert_track};
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn entry(
+        path: &str,
+        artist: Option<&str>,
+        title: Option<&str>,
+        secs: Option<u64>,
+    ) -> PlaylistEntry {
+        PlaylistEntry {
+            location: PlaylistValue::Path(path.into()),
+            title: title.map(ToString::to_string),
+            artist: artist.map(ToString::to_string),
+            album: None,
+            duration: secs.map(Duration::from_secs),
+        }
+    }
+
+    #[test]
+    fn should_relink_entries() {
+        let mut db = gen_data_base();
+        insert_track(&db, "/library/a/one.mp3", "Artist", "One", 100);
+        insert_track(&db, "/library/a/two.mp3", "Artist", "Two", 200);
+        insert_track(&db, "/library/b/two.mp3", "Other", "Two", 300);
+        insert_track(&db, "/library/a/three.mp3", "Artist", "Three", 150);
+        insert_track(&db, "/library/b/three.mp3", "Other", "Three", 150);
+
+        let report = db
+            .relink(vec![
+                // by file name
+                entry("/old/one.mp3", None, None, None),
+                // by file name, narrowed by duration
+                entry("/old/two.mp3", None, None, Some(301)),
+                // by artist and title
+                entry("/old/renamed.mp3", Some("artist"), Some("one"), None),
+                // by title and duration, which fits both
+                entry("/old/renamed.mp3", None, Some("Three"), Some(151)),
+                // title without duration is not enough
+                entry("/old/renamed.mp3", None, Some("Two"), None),
+            ])
+            .unwrap();
+
+        let statuses: Vec<RelinkStatus> = report.entries.iter().map(|v| v.status.clone()).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                RelinkStatus::Relinked("/library/a/one.mp3".to_string()),
+                RelinkStatus::Relinked("/library/b/two.mp3".to_string()),
+                RelinkStatus::Relinked("/library/a/one.mp3".to_string()),
+                RelinkStatus::Ambiguous(vec![
+                    "/library/a/three.mp3".to_string(),
+                    "/library/b/three.mp3".to_string()
+                ]),
+                RelinkStatus::Missing,
+            ]
+        );
+        assert!(report.needs_review());
+        assert_eq!(
+            report.files(),
+            vec![
+                "/library/a/one.mp3",
+                "/library/b/two.mp3",
+                "/library/a/one.mp3"
+            ]
+        );
+        assert_eq!(report.count(|v| *v == RelinkStatus::Missing), 1);
+    }
+}
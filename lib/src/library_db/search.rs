@@ -0,0 +1,106 @@
+use rusqlite::Result;
+
+use super::{DataBase, TrackDB};
+
+/// Convert user input into a FTS5 query: every word has to match the start of a word in any column
+///
+/// Returns `None` if the input does not contain any word, like the match-all input `*`
+fn fts_query(input: &str) -> Option<String> {
+    let words: Vec<String> = input
+        .split_whitespace()
+        .map(|v| v.trim_matches('*'))
+        .filter(|v| !v.is_empty())
+        // quote every word so that FTS5 syntax like "OR" or "-" in the input is searched for literally
+        .map(|v| format!("\"{}\"*", v.replace('"', "\"\"")))
+        .collect();
+
+    if words.is_empty() {
+        None
+    } else {
+        Some(words.join(" "))
+    }
+}
+
+impl DataBase {
+    /// Search the artist, title, album, genre, file name, album artist and composer of all tracks, best matches first
+    ///
+    /// Matching ignores case and diacritics, and every word of `input` may be the start of a word.
+    /// Returns all tracks if `input` does not contain any word.
+    ///
+    /// # Errors
+    ///
+    /// - if the database query fails
+    pub fn search_tracks(&mut self, input: &str) -> Result<Vec<TrackDB>> {
+        let Some(query) = fts_query(input) else {
+            return self.get_all_records();
+        };
+
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT tracks.* FROM tracks_fts
+            JOIN tracks ON tracks.id = tracks_fts.rowid
+            WHERE tracks_fts MATCH ?1
+            ORDER BY rank",
+        )?;
+        let vec: Vec<TrackDB> = stmt
+            .query_map([query], TrackDB::try_from_row_named)?
+            .flatten()
+            .collect();
+
+        Ok(vec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_utils::{gen_data_base, insert_track};
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_build_query() {
+        assert_eq!(fts_query("*"), None);
+        assert_eq!(fts_query("  "), None);
+        assert_eq!(
+            fts_query("beat OR \"x"),
+            Some("\"beat\"* \"OR\"* \"\"\"x\"*".to_string())
+        );
+    }
+
+    #[test]
+    fn should_search_tracks() {
+        let mut db = gen_data_base();
+        insert_track(&db, "/library/a/one.mp3", "Beyoncé", "Halo", 100);
+        insert_track(&db, "/library/a/two.mp3", "Artist", "Beautiful Day", 200);
+        insert_track(&db, "/library/b/halo.mp3", "Other", "Three", 300);
+
+        let files =
+            |tracks: Vec<TrackDB>| -> Vec<String> { tracks.into_iter().map(|v| v.file).collect() };
+
+        // case- and diacritic-insensitive prefix
+        assert_eq!(
+            files(db.search_tracks("beyon").unwrap()),
+            vec!["/library/a/one.mp3"]
+        );
+        // word order does not matter
+        assert_eq!(
+            files(db.search_tracks("day beau").unwrap()),
+            vec!["/library/a/two.mp3"]
+        );
+        // matches in the title and in the file name
+        let mut halo = files(db.search_tracks("HALO").unwrap());
+        halo.sort();
+        assert_eq!(halo, vec!["/library/a/one.mp3", "/library/b/halo.mp3"]);
+        assert_eq!(db.search_tracks("*").unwrap().len(), 3);
+
+        // the index follows deletions
+        db.conn
+            .lock()
+            .execute("DELETE FROM tracks WHERE file = '/library/a/one.mp3'", [])
+            .unwrap();
+        assert_eq!(
+            files(db.search_tracks("halo").unwrap()),
+            vec!["/library/b/halo.mp3"]
+        );
+    }
+}
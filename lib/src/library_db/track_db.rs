@@ -1,6 +1,6 @@
 use std::time::{Duration, UNIX_EPOCH};
 
-use rusqlite::{named_params, Connection, Row};
+use rusqlite::{named_params, Connection, OptionalExtension, Row};
 
 use crate::{const_str, track::Track};
 
@@ -19,6 +19,13 @@ pub struct TrackDB {
     pub directory: String,
     pub last_modified: String,
     pub last_position: Duration,
+    pub album_artist: Option<String>,
+    pub composer: Option<String>,
+    pub track_number: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub year: Option<u32>,
+    /// Rating from 1 to 5 stars
+    pub rating: Option<u8>,
 }
 
 impl TrackDB {
@@ -41,6 +48,12 @@ impl TrackDB {
             directory: row.get(9)?,
             last_modified: row.get(10)?,
             last_position: Duration::from_secs(last_position_u64),
+            album_artist: row.get(12)?,
+            composer: row.get(13)?,
+            track_number: row.get(14)?,
+            disc_number: row.get(15)?,
+            year: row.get(16)?,
+            rating: row.get(17)?,
         })
     }
 
@@ -62,6 +75,12 @@ impl TrackDB {
             directory: row.get("directory")?,
             last_modified: row.get("last_modified")?,
             last_position: Duration::from_secs(last_position_u64),
+            album_artist: row.get("album_artist")?,
+            composer: row.get("composer")?,
+            track_number: row.get("track_number")?,
+            disc_number: row.get("disc_number")?,
+            year: row.get("year")?,
+            rating: row.get("rating")?,
         })
     }
 }
@@ -84,6 +103,12 @@ pub struct TrackDBInsertable<'a> {
     pub directory: &'a str,
     pub last_modified: String,
     pub last_position: Duration,
+    pub album_artist: Option<&'a str>,
+    pub composer: Option<&'a str>,
+    pub track_number: Option<u32>,
+    pub disc_number: Option<u32>,
+    pub year: Option<u32>,
+    pub rating: Option<u8>,
 }
 
 const_str! {
@@ -113,6 +138,12 @@ impl<'a> From<&'a Track> for TrackDBInsertable<'a> {
                 .as_secs()
                 .to_string(),
             last_position: Duration::default(),
+            album_artist: value.album_artist(),
+            composer: value.composer(),
+            track_number: value.track_number(),
+            disc_number: value.disc_number(),
+            year: value.year(),
+            rating: value.rating(),
         }
     }
 }
@@ -122,8 +153,8 @@ impl TrackDBInsertable<'_> {
     #[inline]
     pub fn insert_track(&self, con: &Connection) -> Result<usize, rusqlite::Error> {
         con.execute(
-            "INSERT INTO tracks (artist, title, album, genre, file, duration, name, ext, directory, last_modified, last_position) 
-            values (:artist, :title, :album, :genre, :file, :duration, :name, :ext, :directory, :last_modified, :last_position)",
+            "INSERT INTO tracks (artist, title, album, genre, file, duration, name, ext, directory, last_modified, last_position, album_artist, composer, track_number, disc_number, year, rating) 
+            values (:artist, :title, :album, :genre, :file, :duration, :name, :ext, :directory, :last_modified, :last_position, :album_artist, :composer, :track_number, :disc_number, :year, :rating)",
             named_params![
                 ":artist": &self.artist,
                 ":title": &self.title,
@@ -136,7 +167,32 @@ impl TrackDBInsertable<'_> {
                 ":directory": &self.directory,
                 ":last_modified": &self.last_modified,
                 ":last_position": &self.last_position.as_secs().to_string(),
+                ":album_artist": &self.album_artist,
+                ":composer": &self.composer,
+                ":track_number": &self.track_number,
+                ":disc_number": &self.disc_number,
+                ":year": &self.year,
+                ":rating": &self.rating,
             ],
         )
     }
+
+    /// Replace all rows of the same file in the `tracks` table with the current [`TrackDBInsertable`]
+    ///
+    /// The `last_position` of a replaced row is kept.
+    pub fn replace_track(mut self, con: &Connection) -> Result<usize, rusqlite::Error> {
+        let last_position: Option<u64> = con
+            .query_row(
+                "SELECT last_position FROM tracks WHERE file = ?1",
+                [self.file],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(last_position) = last_position {
+            self.last_position = Duration::from_secs(last_position);
+        }
+
+        con.execute("DELETE FROM tracks WHERE file = ?1", [self.file])?;
+        self.insert_track(con)
+    }
 }
@@ -1,9 +1,12 @@
+use std::fmt::Write;
+use std::time::Duration;
+
 use anyhow::Result;
-use quick_xml::escape::unescape;
+use quick_xml::escape::{escape, unescape};
 use quick_xml::events::Event;
 use quick_xml::Reader;
 
-use super::PlaylistValue;
+use super::{PlaylistEntry, PlaylistValue};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct ASXItem {
@@ -11,6 +14,8 @@ pub struct ASXItem {
     pub title: String,
     /// According to the spec, a `entry` SHOULD contain exactly one `ref` (all after the first are ignored for our purposes)
     pub location: PlaylistValue,
+    /// According to the spec, a `entry` MAY contain one `author` (all after the first are ignored)
+    pub author: Option<String>,
 }
 
 /// A temporary storage to build a [`ASXItem`] while still being in a element and not having all values
@@ -18,6 +23,7 @@ pub struct ASXItem {
 struct PrivateItem {
     pub title: Option<String>,
     pub ref_href: Option<PlaylistValue>,
+    pub author: Option<String>,
 }
 
 impl PrivateItem {
@@ -28,6 +34,7 @@ impl PrivateItem {
                 // SAFETY: unwrap is safe here because it is checked by `check_required_values` to be `Some`
                 title: self.title.take().unwrap(),
                 location: self.ref_href.take().unwrap(),
+                author: self.author.take(),
             });
         }
 
@@ -121,6 +128,10 @@ pub fn decode(content: &str) -> Result<Vec<ASXItem>> {
                     item.title
                         .replace(unescape(&decoder.decode(&e)?)?.to_string());
                 }
+                if path == "asx/entry/author" && item.author.is_none() {
+                    item.author
+                        .replace(unescape(&decoder.decode(&e)?)?.to_string());
+                }
             }
             Ok(Event::Eof) => break,
             Err(e) => {
@@ -135,6 +146,45 @@ pub fn decode(content: &str) -> Result<Vec<ASXItem>> {
     Ok(list)
 }
 
+/// Write a ASX playlist, with `title`, `author` and `duration` where known
+///
+/// As `title` is required by [`decode`], the location is used if there is no title.
+pub fn encode(entries: &[PlaylistEntry]) -> String {
+    let mut asx = String::from("<asx version=\"3.0\">\n");
+    for entry in entries {
+        let location = entry.location.to_uri_string();
+        asx.push_str("  <entry>\n");
+        let title = entry.title.as_deref().unwrap_or(&location);
+        let _ = writeln!(asx, "    <title>{}</title>", escape(title));
+        if let Some(artist) = &entry.artist {
+            let _ = writeln!(asx, "    <author>{}</author>", escape(artist));
+        }
+        if let Some(duration) = entry.duration {
+            let _ = writeln!(
+                asx,
+                "    <duration value=\"{}\"/>",
+                duration_formatted(duration)
+            );
+        }
+        let _ = writeln!(asx, "    <ref href=\"{}\"/>", escape(&location));
+        asx.push_str("  </entry>\n");
+    }
+    asx.push_str("</asx>\n");
+    asx
+}
+
+/// Format `duration` as `hh:mm:ss.fff`, the only form ASX readers are required to understand
+fn duration_formatted(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!(
+        "{:0>2}:{:0>2}:{:0>2}.{:0>3}",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60,
+        duration.subsec_millis()
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -187,4 +237,29 @@ mod tests {
         let items = decode(s).unwrap();
         assert_eq!(items.len(), 0);
     }
+
+    #[test]
+    fn should_round_trip() {
+        let entries = crate::playlist::tests::entries();
+        let asx = encode(&entries);
+
+        let items = decode(&asx).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].location, entries[0].location);
+        assert_eq!(items[0].title, "Title");
+        assert_eq!(items[0].author.as_deref(), Some("Artist"));
+        assert_eq!(items[1].location, entries[1].location);
+        assert_eq!(items[1].title, "Radio");
+    }
+
+    #[test]
+    fn should_write_full_duration() {
+        let asx = encode(&crate::playlist::tests::entries());
+        assert!(asx.contains("<duration value=\"00:03:05.000\"/>"));
+
+        assert_eq!(
+            duration_formatted(Duration::from_millis(3_723_450)),
+            "01:02:03.450"
+        );
+    }
 }
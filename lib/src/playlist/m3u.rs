@@ -2,24 +2,48 @@
 
 // TODO: resolve relative paths
 
-use super::PlaylistValue;
+use std::fmt::Write;
+use std::time::Duration;
+
+use super::{PlaylistEntry, PlaylistValue};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct M3UItem {
     pub url: PlaylistValue,
+    /// The title of a preceding `#EXTINF` line, commonly in the form of `Artist - Title`
+    pub title: Option<String>,
+    /// The duration of a preceding `#EXTINF` line, if not negative
+    pub duration: Option<Duration>,
+    /// The album of a preceding `#EXTALB` line
+    pub album: Option<String>,
 }
 
 /// M3U(8) is a de-facto standart (meaning there is no formal standard), where each line that does not start with `#` is a entry, separated by newlines
 ///
+/// Values from `#EXTINF` and `#EXTALB` lines are applied to the next entry.
+///
 /// <https://en.wikipedia.org/wiki/M3U#File_format>
 pub fn decode(content: &str) -> Vec<M3UItem> {
     let lines = content.lines();
     let mut list = vec![];
+    let mut title = None;
+    let mut duration = None;
+    let mut album = None;
     for line in lines {
         if line.is_empty() {
             continue;
         }
 
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            (duration, title) = parse_extinf(info);
+            continue;
+        }
+
+        if let Some(value) = line.strip_prefix("#EXTALB:") {
+            album = Some(value.to_string()).filter(|v| !v.is_empty());
+            continue;
+        }
+
         if line.starts_with('#') {
             continue;
         }
@@ -39,11 +63,56 @@ pub fn decode(content: &str) -> Vec<M3UItem> {
             continue;
         }
 
-        list.push(M3UItem { url: p_value });
+        list.push(M3UItem {
+            url: p_value,
+            title: title.take(),
+            duration: duration.take(),
+            album: album.take(),
+        });
     }
     list
 }
 
+/// Parse the value of a `#EXTINF:` line, in the form of `duration [attributes],title`
+fn parse_extinf(info: &str) -> (Option<Duration>, Option<String>) {
+    let (duration, title) = info.split_once(',').unwrap_or((info, ""));
+    // there may be attributes after the duration, separated by a space
+    let duration = duration
+        .split_whitespace()
+        .next()
+        .and_then(|v| v.parse::<f64>().ok())
+        .filter(|v| *v >= 0.0)
+        .map(Duration::from_secs_f64);
+    let title = Some(title.trim().to_string()).filter(|v| !v.is_empty());
+
+    (duration, title)
+}
+
+/// Write a extended M3U playlist, with `#EXTINF` (duration and `Artist - Title`) and `#EXTALB` lines where known
+///
+/// <https://en.wikipedia.org/wiki/M3U#Extended_M3U>
+pub fn encode(entries: &[PlaylistEntry]) -> String {
+    let mut m3u = String::from("#EXTM3U\n");
+    for entry in entries {
+        let title = entry.display_title();
+        if title.is_some() || entry.duration.is_some() {
+            let _ = writeln!(
+                m3u,
+                "#EXTINF:{},{}",
+                entry
+                    .duration
+                    .map_or(-1, |v| i64::try_from(v.as_secs()).unwrap_or(-1)),
+                title.unwrap_or_default()
+            );
+        }
+        if let Some(album) = &entry.album {
+            let _ = writeln!(m3u, "#EXTALB:{album}");
+        }
+        let _ = writeln!(m3u, "{}", entry.location);
+    }
+    m3u
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +139,25 @@ https://somewhere.url/path"#;
             PlaylistValue::Url(Url::parse("https://somewhere.url/path").unwrap())
         );
     }
+
+    #[test]
+    fn should_encode_extended() {
+        let entries = crate::playlist::tests::entries();
+        let m3u = encode(&entries);
+
+        assert!(m3u.starts_with("#EXTM3U\n"));
+        assert!(m3u.contains("#EXTINF:185,Artist - Title\n#EXTALB:Album\n"));
+        assert!(m3u.contains("#EXTINF:-1,Radio\n"));
+
+        let results = decode(&m3u);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, entries[0].location);
+        assert_eq!(results[0].title.as_deref(), Some("Artist - Title"));
+        assert_eq!(results[0].duration, Some(Duration::from_secs(185)));
+        assert_eq!(results[0].album.as_deref(), Some("Album"));
+        assert_eq!(results[1].url, entries[1].location);
+        assert_eq!(results[1].title.as_deref(), Some("Radio"));
+        assert_eq!(results[1].duration, None);
+        assert_eq!(results[1].album, None);
+    }
 }
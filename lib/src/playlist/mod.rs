@@ -1,6 +1,8 @@
 //! This is a very simple url extractor for different kinds of playlist formats: M3U, PLS, ASX, XSPF
 //!
 //! It is not optimized yet and does create a lot of strings on the way.
+//!
+//! All formats can also be written again, see [`encode`].
 
 mod asx;
 mod m3u;
@@ -12,11 +14,13 @@ use std::{
     fmt::Display,
     path::{Path, PathBuf},
     str::FromStr,
+    time::Duration,
 };
 
 use anyhow::{anyhow, Context, Result};
 use reqwest::Url;
 
+use crate::track::Track;
 use crate::utils;
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -75,7 +79,7 @@ impl PlaylistValue {
         };
 
         // do nothing if path is already absolute
-        if !path.is_absolute() {
+        if path.is_absolute() {
             return;
         }
 
@@ -94,6 +98,206 @@ impl PlaylistValue {
 
         Ok(Self::Path(PathBuf::from_str(line)?))
     }
+
+    /// If the current value is a absolute [`PlaylistValue::Path`], make it relative to `base`
+    ///
+    /// The path is kept as-is if no relative path can be built (like being on a different drive)
+    pub fn relativize(&mut self, base: &Path) {
+        let Self::Path(path) = self else {
+            return;
+        };
+
+        if !path.is_absolute() {
+            return;
+        }
+
+        if let Some(new_path) = pathdiff::diff_paths(&*path, base) {
+            *path = new_path;
+        }
+    }
+
+    /// Get the value as a URI for formats that require one, absolute paths become `file://` urls
+    ///
+    /// Relative paths are kept as-is, as that is what most players (and [`decode`]) expect
+    fn to_uri_string(&self) -> String {
+        match self {
+            Self::Path(path) if path.is_absolute() => Url::from_file_path(path)
+                .map_or_else(|()| path.display().to_string(), |v| v.to_string()),
+            _ => self.to_string(),
+        }
+    }
+}
+
+/// The playlist formats that can be written via [`encode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaylistFormat {
+    /// Extended M3U, with `#EXTINF` lines
+    #[default]
+    M3U,
+    PLS,
+    XSPF,
+    ASX,
+}
+
+impl PlaylistFormat {
+    /// Get the format from the extension of `path`, `None` if it is not a known playlist extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "m3u" | "m3u8" => Some(Self::M3U),
+            "pls" => Some(Self::PLS),
+            "xspf" => Some(Self::XSPF),
+            "asx" => Some(Self::ASX),
+            _ => None,
+        }
+    }
+
+    /// The extension commonly used for this format
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::M3U => "m3u",
+            Self::PLS => "pls",
+            Self::XSPF => "xspf",
+            Self::ASX => "asx",
+        }
+    }
+}
+
+/// A entry to write with [`encode`], formats only write the values they support
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct PlaylistEntry {
+    pub location: PlaylistValue,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    /// `None` if the duration is unknown, like for radio streams
+    pub duration: Option<Duration>,
+}
+
+impl PlaylistEntry {
+    /// Create a entry from a track, `None` if the track has no file or url
+    pub fn from_track(track: &Track) -> Option<Self> {
+        let location = PlaylistValue::try_from_str(track.file()?).ok()?;
+        let duration = Some(track.duration()).filter(|v| !v.is_zero());
+
+        Some(Self {
+            location,
+            title: track.title().map(ToString::to_string),
+            artist: track.artist().map(ToString::to_string),
+            album: track.album().map(ToString::to_string),
+            duration,
+        })
+    }
+
+    /// Create a entry from formats that only have a single title field, in the form of `Artist - Title`
+    fn from_display_title(
+        location: PlaylistValue,
+        display_title: Option<&str>,
+        album: Option<String>,
+        duration: Option<Duration>,
+    ) -> Self {
+        let (artist, title) = match display_title.map(|v| v.split_once(" - ").ok_or(v)) {
+            Some(Ok((artist, title))) => (Some(artist.to_string()), Some(title.to_string())),
+            Some(Err(title)) => (None, Some(title.to_string())),
+            None => (None, None),
+        };
+
+        Self {
+            location,
+            title,
+            artist,
+            album,
+            duration,
+        }
+    }
+
+    /// The title to display for formats that only have a single title field, in the form of `Artist - Title`
+    fn display_title(&self) -> Option<String> {
+        let title = self.title.as_deref()?;
+        match self.artist.as_deref() {
+            Some(artist) => Some(format!("{artist} - {title}")),
+            None => Some(title.to_string()),
+        }
+    }
+}
+
+/// Encode `entries` as a playlist in the given `format`.
+///
+/// If `relative_to` is set, absolute paths are written relative to that directory (usually the directory the playlist is saved in),
+/// otherwise they are written as-is.
+pub fn encode(
+    format: PlaylistFormat,
+    entries: &[PlaylistEntry],
+    relative_to: Option<&Path>,
+) -> String {
+    let relative_entries: Vec<PlaylistEntry>;
+    let entries = if let Some(base) = relative_to {
+        relative_entries = entries
+            .iter()
+            .cloned()
+            .map(|mut v| {
+                v.location.relativize(base);
+                v
+            })
+            .collect();
+        &relative_entries
+    } else {
+        entries
+    };
+
+    match format {
+        PlaylistFormat::M3U => m3u::encode(entries),
+        PlaylistFormat::PLS => pls::encode(entries),
+        PlaylistFormat::XSPF => xspf::encode(entries),
+        PlaylistFormat::ASX => asx::encode(entries),
+    }
+}
+
+/// Decode playlist content string into entries with all values the format provides.
+/// It checks for M3U, PLS, XSPF and ASX content in the string.
+///
+/// Titles in the form of `Artist - Title` (as written by M3U and PLS) are split into artist and title.
+pub fn decode_entries(content: &str) -> Result<Vec<PlaylistEntry>> {
+    let content_small = content.to_lowercase();
+
+    let list = if content_small.contains("<playlist") {
+        xspf::decode(content)?
+            .into_iter()
+            .map(|v| PlaylistEntry {
+                location: v.location,
+                title: v.title,
+                artist: v.creator,
+                album: v.album,
+                duration: v.duration,
+            })
+            .collect()
+    } else if content_small.contains("<asx") {
+        asx::decode(content)?
+            .into_iter()
+            .map(|v| PlaylistEntry {
+                location: v.location,
+                title: Some(v.title),
+                artist: v.author,
+                album: None,
+                duration: None,
+            })
+            .collect()
+    } else if content_small.contains("[playlist]") {
+        pls::decode(content)
+            .into_iter()
+            .map(|v| PlaylistEntry::from_display_title(v.url, v.title.as_deref(), None, v.duration))
+            .collect()
+    } else {
+        m3u::decode(content)
+            .into_iter()
+            .map(|v| {
+                PlaylistEntry::from_display_title(v.url, v.title.as_deref(), v.album, v.duration)
+            })
+            .collect()
+    };
+
+    Ok(list)
 }
 
 /// Decode playlist content string. It checks for M3U, PLS, XSPF and ASX content in the string.
@@ -126,36 +330,9 @@ impl PlaylistValue {
 /// }
 /// ```
 pub fn decode(content: &str) -> Result<Vec<PlaylistValue>> {
-    let mut set: Vec<PlaylistValue> = vec![];
-    let content_small = content.to_lowercase();
-
-    if content_small.contains("<playlist") {
-        let items = xspf::decode(content)?;
-        set.reserve(items.len());
-        for item in items {
-            set.push(item.location);
-        }
-    } else if content_small.contains("<asx") {
-        let items = asx::decode(content)?;
-        set.reserve(items.len());
-        for item in items {
-            set.push(item.location);
-        }
-    } else if content_small.contains("[playlist]") {
-        let items = pls::decode(content);
-        set.reserve(items.len());
-        for item in items {
-            set.push(item.url);
-        }
-    } else {
-        let items = m3u::decode(content);
-        set.reserve(items.len());
-        for item in items {
-            set.push(item.url);
-        }
-    }
+    let entries = decode_entries(content)?;
 
-    Ok(set)
+    Ok(entries.into_iter().map(|v| v.location).collect())
 }
 
 #[cfg(test)]
@@ -163,6 +340,26 @@ mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    /// Entries covering a local file with all values and a stream with only a title
+    pub fn entries() -> Vec<PlaylistEntry> {
+        vec![
+            PlaylistEntry {
+                location: PlaylistValue::Path("/music/a b.mp3".into()),
+                title: Some("Title".to_string()),
+                artist: Some("Artist".to_string()),
+                album: Some("Album".to_string()),
+                duration: Some(Duration::from_secs(185)),
+            },
+            PlaylistEntry {
+                location: PlaylistValue::Url(Url::parse("http://radio.example/stream").unwrap()),
+                title: Some("Radio".to_string()),
+                artist: None,
+                album: None,
+                duration: None,
+            },
+        ]
+    }
+
     #[test]
     fn should_parse_xspf() {
         let s = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -213,6 +410,43 @@ Title1=mytitle
         );
     }
 
+    #[test]
+    fn should_encode_relative_paths() {
+        let entries = [PlaylistEntry {
+            location: PlaylistValue::Path("/music/album/track.mp3".into()),
+            title: None,
+            artist: None,
+            album: None,
+            duration: None,
+        }];
+
+        let relative = encode(PlaylistFormat::M3U, &entries, Some(Path::new("/music")));
+        assert_eq!(
+            decode(&relative).unwrap(),
+            vec![PlaylistValue::Path("album/track.mp3".into())]
+        );
+
+        let absolute = encode(PlaylistFormat::M3U, &entries, None);
+        assert_eq!(
+            decode(&absolute).unwrap(),
+            vec![PlaylistValue::Path("/music/album/track.mp3".into())]
+        );
+    }
+
+    #[test]
+    fn should_get_format_from_path() {
+        assert_eq!(
+            PlaylistFormat::from_path(Path::new("/a/list.XSPF")),
+            Some(PlaylistFormat::XSPF)
+        );
+        assert_eq!(
+            PlaylistFormat::from_path(Path::new("list.m3u8")),
+            Some(PlaylistFormat::M3U)
+        );
+        assert_eq!(PlaylistFormat::from_path(Path::new("list.txt")), None);
+        assert_eq!(PlaylistFormat::from_path(Path::new("list")), None);
+    }
+
     #[test]
     fn should_parse_m3u() {
         let playlist = "/some/absolute/unix/path.mp3";
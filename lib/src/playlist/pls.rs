@@ -1,19 +1,24 @@
 //! Decode File and Title parts from simple playlist PLS files
 
 use std::collections::{hash_map::Entry, HashMap};
+use std::fmt::Write;
+use std::time::Duration;
 
-use super::PlaylistValue;
+use super::{PlaylistEntry, PlaylistValue};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct PLSItem {
     pub title: Option<String>,
     pub url: PlaylistValue,
+    /// From `Length`, if not negative
+    pub duration: Option<Duration>,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
 struct PrivateItem {
     pub title: Option<String>,
     pub url: Option<PlaylistValue>,
+    pub duration: Option<Duration>,
 }
 
 /// PLS is a file format similar in style to INI (but does not have a official standard).
@@ -104,6 +109,16 @@ pub fn decode(content: &str) -> Vec<PLSItem> {
             }
         }
 
+        if let Some(remainder) = line.strip_prefix("Length") {
+            let Some((num, length)) = parse_id(remainder, line) else {
+                continue;
+            };
+
+            // "-1" is used for unknown lengths
+            let duration = length.parse::<u64>().ok().map(Duration::from_secs);
+            list.entry(num).or_default().duration = duration;
+        }
+
         if let Some(remainder) = line.strip_prefix("NumberOfEntries") {
             let Some((_, remainder)) = remainder.split_once('=') else {
                 warn!("Malformed line: {:#?}", line);
@@ -136,6 +151,7 @@ pub fn decode(content: &str) -> Vec<PLSItem> {
                     title: v.1.title,
                     // Safe unwrap, because of the filter
                     url: v.1.url.unwrap(),
+                    duration: v.1.duration,
                 },
             )
         })
@@ -158,6 +174,27 @@ pub fn decode(content: &str) -> Vec<PLSItem> {
     list.into_iter().map(|v| v.1).collect()
 }
 
+/// Write a PLS playlist (version 2), with `Title` (`Artist - Title`) and `Length` for each entry
+pub fn encode(entries: &[PlaylistEntry]) -> String {
+    let mut pls = String::from("[playlist]\n");
+    for (num, entry) in (1..).zip(entries) {
+        let _ = writeln!(pls, "File{num}={}", entry.location);
+        if let Some(title) = entry.display_title() {
+            let _ = writeln!(pls, "Title{num}={title}");
+        }
+        let _ = writeln!(
+            pls,
+            "Length{num}={}",
+            entry
+                .duration
+                .map_or(-1, |v| i64::try_from(v.as_secs()).unwrap_or(-1))
+        );
+    }
+    let _ = writeln!(pls, "NumberOfEntries={}", entries.len());
+    pls.push_str("Version=2\n");
+    pls
+}
+
 /// Parse a Entry id from the start of the value until the first `=`.
 ///
 /// Returns the parsed number and the remainder after the first `=`.
@@ -260,4 +297,22 @@ Title1=mytitle
         );
         assert_eq!(items[0].title, Some("mytitle".to_string()));
     }
+
+    #[test]
+    fn should_round_trip() {
+        let entries = crate::playlist::tests::entries();
+        let pls = encode(&entries);
+
+        assert!(pls.contains("Length1=185\n"));
+        assert!(pls.contains("Length2=-1\n"));
+        assert!(pls.contains("NumberOfEntries=2\n"));
+
+        let results = decode(&pls);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].url, entries[0].location);
+        assert_eq!(results[0].title.as_deref(), Some("Artist - Title"));
+        assert_eq!(results[0].duration, Some(Duration::from_secs(185)));
+        assert_eq!(results[1].url, entries[1].location);
+        assert_eq!(results[1].title.as_deref(), Some("Radio"));
+    }
 }
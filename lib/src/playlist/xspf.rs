@@ -1,9 +1,12 @@
+use std::fmt::Write;
+use std::time::Duration;
+
 use anyhow::Result;
-use quick_xml::escape::unescape;
+use quick_xml::escape::{escape, unescape};
 use quick_xml::events::Event;
 use quick_xml::Reader;
 
-use super::PlaylistValue;
+use super::{PlaylistEntry, PlaylistValue};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct XSPFItem {
@@ -13,6 +16,12 @@ pub struct XSPFItem {
     pub location: PlaylistValue,
     /// According to the spec, a `track` MAY contain zero or more `identifier` (only last will be used here though)
     pub identifier: Option<String>,
+    /// According to the spec, a `track` MAY contain exactly one `creator`
+    pub creator: Option<String>,
+    /// According to the spec, a `track` MAY contain exactly one `album`
+    pub album: Option<String>,
+    /// According to the spec, a `track` MAY contain exactly one `duration`, in milliseconds
+    pub duration: Option<Duration>,
 }
 
 /// A temporary storage to build a [`XSPFItem`] while still being in a element and not having all values
@@ -21,6 +30,9 @@ struct PrivateItem {
     pub title: Option<String>,
     pub location: Option<PlaylistValue>,
     pub identifier: Option<String>,
+    pub creator: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<Duration>,
 }
 
 impl PrivateItem {
@@ -31,6 +43,9 @@ impl PrivateItem {
                 title: self.title.take(),
                 location,
                 identifier: self.identifier.take(),
+                creator: self.creator.take(),
+                album: self.album.take(),
+                duration: self.duration.take(),
             });
         }
 
@@ -93,6 +108,24 @@ pub fn decode(content: &str) -> Result<Vec<XSPFItem>> {
                         .identifier
                         .replace(unescape(&decoder.decode(&e)?)?.to_string());
                 }
+                if path == "playlist/tracklist/track/creator" {
+                    current_item
+                        .creator
+                        .replace(unescape(&decoder.decode(&e)?)?.to_string());
+                }
+                if path == "playlist/tracklist/track/album" {
+                    current_item
+                        .album
+                        .replace(unescape(&decoder.decode(&e)?)?.to_string());
+                }
+                if path == "playlist/tracklist/track/duration" {
+                    current_item.duration = decoder
+                        .decode(&e)?
+                        .trim()
+                        .parse::<u64>()
+                        .ok()
+                        .map(Duration::from_millis);
+                }
             }
             Ok(Event::Eof) => break,
             Err(e) => {
@@ -107,6 +140,38 @@ pub fn decode(content: &str) -> Result<Vec<XSPFItem>> {
     Ok(list)
 }
 
+/// Write a XSPF playlist, with `title`, `creator`, `album` and `duration` where known
+///
+/// Absolute paths are written as `file://` urls, as `location` is required to be a URI.
+pub fn encode(entries: &[PlaylistEntry]) -> String {
+    let mut xspf = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<playlist version=\"1\" xmlns=\"http://xspf.org/ns/0/\">\n  <trackList>\n",
+    );
+    for entry in entries {
+        xspf.push_str("    <track>\n");
+        let _ = writeln!(
+            xspf,
+            "      <location>{}</location>",
+            escape(&entry.location.to_uri_string())
+        );
+        if let Some(title) = &entry.title {
+            let _ = writeln!(xspf, "      <title>{}</title>", escape(title));
+        }
+        if let Some(artist) = &entry.artist {
+            let _ = writeln!(xspf, "      <creator>{}</creator>", escape(artist));
+        }
+        if let Some(album) = &entry.album {
+            let _ = writeln!(xspf, "      <album>{}</album>", escape(album));
+        }
+        if let Some(duration) = entry.duration {
+            let _ = writeln!(xspf, "      <duration>{}</duration>", duration.as_millis());
+        }
+        xspf.push_str("    </track>\n");
+    }
+    xspf.push_str("  </trackList>\n</playlist>\n");
+    xspf
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,4 +237,24 @@ mod tests {
         let items = decode(s).unwrap();
         assert_eq!(items.len(), 2);
     }
+
+    #[test]
+    fn should_round_trip() {
+        let mut entries = crate::playlist::tests::entries();
+        entries[0].title = Some("Title & <Co>".to_string());
+        let xspf = encode(&entries);
+
+        assert!(xspf.contains("<location>file:///music/a%20b.mp3</location>"));
+        assert!(xspf.contains("<duration>185000</duration>"));
+
+        let items = decode(&xspf).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].location, entries[0].location);
+        assert_eq!(items[0].title.as_deref(), Some("Title & <Co>"));
+        assert_eq!(items[0].creator.as_deref(), Some("Artist"));
+        assert_eq!(items[0].album.as_deref(), Some("Album"));
+        assert_eq!(items[0].duration, Some(Duration::from_secs(185)));
+        assert_eq!(items[1].location, entries[1].location);
+        assert_eq!(items[1].title.as_deref(), Some("Radio"));
+    }
 }
@@ -0,0 +1,344 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{named_params, params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+
+use crate::podcast::feed::{FeedAuth, FeedHeaders};
+use crate::podcast::EpData;
+
+use super::{convert_date, PodcastDBId};
+
+/// State of a episode in the download queue
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadStatus {
+    /// Waiting for a free download slot
+    Queued,
+    Running,
+    /// Not started until resumed
+    Paused,
+    /// The last attempt failed, not started again until retried
+    Failed,
+    Done,
+}
+
+impl DownloadStatus {
+    #[must_use]
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Queued => 0,
+            Self::Running => 1,
+            Self::Paused => 2,
+            Self::Failed => 3,
+            Self::Done => 4,
+        }
+    }
+
+    /// Convert the stored value back, unknown values are treated as [`DownloadStatus::Queued`]
+    #[must_use]
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Running,
+            2 => Self::Paused,
+            3 => Self::Failed,
+            4 => Self::Done,
+            _ => Self::Queued,
+        }
+    }
+}
+
+impl Display for DownloadStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Queued => "queued",
+            Self::Running => "running",
+            Self::Paused => "paused",
+            Self::Failed => "failed",
+            Self::Done => "done",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A change to the download queue requested by a client, which the server applies to the given episodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DownloadAction {
+    /// Add to the end of the queue, or queue again if failed, finished or paused
+    Queue,
+    /// Pause a queued or running item, or resume a paused one
+    TogglePause,
+    /// Remove from the queue, canceling it if it is running
+    Remove,
+    MoveUp,
+    MoveDown,
+    /// Queue all failed items again, ignores the episodes
+    RetryFailed,
+    /// Remove all items that are not running, ignores the episodes
+    Clear,
+}
+
+impl DownloadAction {
+    /// Get the gRPC representation of the action
+    #[must_use]
+    pub fn as_u32(self) -> u32 {
+        match self {
+            Self::Queue => 1,
+            Self::TogglePause => 2,
+            Self::Remove => 3,
+            Self::MoveUp => 4,
+            Self::MoveDown => 5,
+            Self::RetryFailed => 6,
+            Self::Clear => 7,
+        }
+    }
+
+    /// Convert the gRPC representation back to a action, returns [`None`] for unknown values
+    #[must_use]
+    pub fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            1 => Some(Self::Queue),
+            2 => Some(Self::TogglePause),
+            3 => Some(Self::Remove),
+            4 => Some(Self::MoveUp),
+            5 => Some(Self::MoveDown),
+            6 => Some(Self::RetryFailed),
+            7 => Some(Self::Clear),
+            _ => None,
+        }
+    }
+}
+
+/// A episode in the download queue, with everything needed to download it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadItem {
+    pub episode_id: PodcastDBId,
+    pub pod_id: PodcastDBId,
+    pub podcast_title: String,
+    pub title: String,
+    pub url: String,
+    pub pubdate: Option<DateTime<Utc>>,
+    pub status: DownloadStatus,
+    /// Why the last attempt failed, if it did
+    pub error: Option<String>,
+    /// Credentials of the podcast, also needed for its episode downloads
+    pub auth: FeedAuth,
+}
+
+impl DownloadItem {
+    /// Try to convert a given row to a [`DownloadItem`] instance, using column names to resolve the values
+    ///
+    /// Expects the columns of [`get_all`].
+    pub fn try_from_row_named(row: &Row<'_>) -> Result<Self, rusqlite::Error> {
+        // NOTE: all the names in "get" below are the *column names* as defined in migrations/003.sql#table_downloads (pseudo link)
+        let headers: Option<String> = row.get("auth_headers")?;
+        let headers: FeedHeaders = headers.and_then(|v| v.parse().ok()).unwrap_or_default();
+        Ok(Self {
+            episode_id: row.get("episode_id")?,
+            pod_id: row.get("podcast_id")?,
+            podcast_title: row.get("podcast_title")?,
+            title: row.get("title")?,
+            url: row.get("url")?,
+            pubdate: convert_date(&row.get("pubdate")),
+            status: DownloadStatus::from_u8(row.get("status")?),
+            error: row.get("error")?,
+            auth: FeedAuth {
+                username: row.get("auth_username")?,
+                password: row.get("auth_password")?,
+                headers,
+            },
+        })
+    }
+
+    /// Get the data to download this episode with
+    #[must_use]
+    pub fn ep_data(&self) -> EpData {
+        EpData {
+            id: self.episode_id,
+            pod_id: self.pod_id,
+            title: self.title.clone(),
+            url: self.url.clone(),
+            pubdate: self.pubdate,
+            file_path: None,
+        }
+    }
+}
+
+/// Add a episode to the end of the queue, or queue it again if it already is in the queue but not running
+pub fn enqueue(episode_id: PodcastDBId, con: &Connection) -> Result<usize, rusqlite::Error> {
+    let mut stmt = con.prepare_cached(
+        "INSERT INTO downloads (episode_id, status, position, error)
+            VALUES (:epid, :queued, (SELECT IFNULL(MAX(position), 0) + 1 FROM downloads), NULL)
+            ON CONFLICT (episode_id) DO UPDATE SET status = :queued, error = NULL
+            WHERE status != :running;",
+    )?;
+    stmt.execute(named_params![
+        ":epid": episode_id,
+        ":queued": DownloadStatus::Queued.as_u8(),
+        ":running": DownloadStatus::Running.as_u8(),
+    ])
+}
+
+/// Get the whole queue in order, including finished and failed items
+pub fn get_all(con: &Connection) -> Result<Vec<DownloadItem>, rusqlite::Error> {
+    let mut stmt = con.prepare_cached(
+        "SELECT downloads.episode_id, downloads.status, downloads.error,
+            episodes.podcast_id, episodes.title, episodes.url, episodes.pubdate,
+            podcasts.title AS podcast_title,
+            podcasts.auth_username, podcasts.auth_password, podcasts.auth_headers
+            FROM downloads
+            INNER JOIN episodes ON episodes.id = downloads.episode_id
+            INNER JOIN podcasts ON podcasts.id = episodes.podcast_id
+            ORDER BY downloads.position;",
+    )?;
+    let items = stmt
+        .query_map([], DownloadItem::try_from_row_named)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(items)
+}
+
+pub fn set_status(
+    episode_id: PodcastDBId,
+    status: DownloadStatus,
+    error: Option<&str>,
+    con: &Connection,
+) -> Result<usize, rusqlite::Error> {
+    let mut stmt =
+        con.prepare_cached("UPDATE downloads SET status = ?, error = ? WHERE episode_id = ?;")?;
+    stmt.execute(params![status.as_u8(), error, episode_id])
+}
+
+/// Change all items with status `from` to `to`
+pub fn set_all_status(
+    from: DownloadStatus,
+    to: DownloadStatus,
+    con: &Connection,
+) -> Result<usize, rusqlite::Error> {
+    let mut stmt =
+        con.prepare_cached("UPDATE downloads SET status = ?, error = NULL WHERE status = ?;")?;
+    stmt.execute(params![to.as_u8(), from.as_u8()])
+}
+
+/// Swap the position of a item with the one before it, or after it if `down` is set
+///
+/// Returns `false` if the item is already the first / last.
+pub fn swap_with_neighbour(
+    episode_id: PodcastDBId,
+    down: bool,
+    con: &Connection,
+) -> Result<bool, rusqlite::Error> {
+    let position: i64 = con.query_row(
+        "SELECT position FROM downloads WHERE episode_id = ?;",
+        params![episode_id],
+        |row| row.get(0),
+    )?;
+    let query = if down {
+        "SELECT episode_id, position FROM downloads WHERE position > ? ORDER BY position ASC LIMIT 1;"
+    } else {
+        "SELECT episode_id, position FROM downloads WHERE position < ? ORDER BY position DESC LIMIT 1;"
+    };
+    let Some((other_id, other_position)): Option<(PodcastDBId, i64)> = con
+        .query_row(query, params![position], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()?
+    else {
+        return Ok(false);
+    };
+
+    let mut stmt = con.prepare_cached("UPDATE downloads SET position = ? WHERE episode_id = ?;")?;
+    stmt.execute(params![other_position, episode_id])?;
+    stmt.execute(params![position, other_id])?;
+
+    Ok(true)
+}
+
+/// Delete a item from the queue
+pub fn delete(episode_id: PodcastDBId, con: &Connection) -> Result<usize, rusqlite::Error> {
+    let mut stmt = con.prepare_cached("DELETE FROM downloads WHERE episode_id = ?;")?;
+    stmt.execute(params![episode_id])
+}
+
+/// Delete all items that are not running
+pub fn delete_all_idle(con: &Connection) -> Result<usize, rusqlite::Error> {
+    let mut stmt = con.prepare_cached("DELETE FROM downloads WHERE status != ?;")?;
+    stmt.execute(params![DownloadStatus::Running.as_u8()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::migration::migrate;
+    use super::super::test_utils::gen_database;
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn gen_queue_database() -> Connection {
+        let conn = gen_database();
+        migrate(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO podcasts (id, title, url, last_checked) VALUES (1, 'Pod', 'https://example.com/feed', 0)",
+            [],
+        )
+        .unwrap();
+        for id in 1..=3 {
+            conn.execute(
+                "INSERT INTO episodes (id, podcast_id, title, url, description) VALUES (?, 1, ?, ?, '')",
+                params![id, format!("Episode {id}"), format!("https://example.com/{id}.mp3")],
+            )
+            .unwrap();
+        }
+
+        conn
+    }
+
+    fn queue(conn: &Connection) -> Vec<(PodcastDBId, DownloadStatus)> {
+        get_all(conn)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.episode_id, v.status))
+            .collect()
+    }
+
+    #[test]
+    fn should_queue_in_order_and_requeue_finished() {
+        let conn = gen_queue_database();
+        for id in [2, 1, 3] {
+            enqueue(id, &conn).unwrap();
+        }
+        set_status(1, DownloadStatus::Running, None, &conn).unwrap();
+        set_status(3, DownloadStatus::Failed, Some("no response"), &conn).unwrap();
+
+        let items = get_all(&conn).unwrap();
+        assert_eq!(items[0].podcast_title, "Pod");
+        assert_eq!(items[2].error.as_deref(), Some("no response"));
+
+        // running items are left alone, failed ones are queued again in place
+        enqueue(1, &conn).unwrap();
+        enqueue(3, &conn).unwrap();
+        assert_eq!(
+            queue(&conn),
+            vec![
+                (2, DownloadStatus::Queued),
+                (1, DownloadStatus::Running),
+                (3, DownloadStatus::Queued)
+            ]
+        );
+
+        assert_eq!(delete_all_idle(&conn).unwrap(), 2);
+        assert_eq!(queue(&conn), vec![(1, DownloadStatus::Running)]);
+    }
+
+    #[test]
+    fn should_reorder() {
+        let conn = gen_queue_database();
+        for id in 1..=3 {
+            enqueue(id, &conn).unwrap();
+        }
+
+        assert!(swap_with_neighbour(3, false, &conn).unwrap());
+        assert!(!swap_with_neighbour(1, false, &conn).unwrap());
+        assert!(!swap_with_neighbour(2, true, &conn).unwrap());
+        let order: Vec<PodcastDBId> = queue(&conn).into_iter().map(|v| v.0).collect();
+        assert_eq!(order, vec![1, 3, 2]);
+    }
+}
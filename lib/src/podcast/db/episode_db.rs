@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 use rusqlite::{named_params, params, Connection, Row};
 
 use crate::podcast::episode::EpisodeNoId;
+use crate::podcast::namespace::EpisodeExtras;
 
 use super::{convert_date, PodcastDBId};
 
@@ -22,6 +23,10 @@ pub struct EpisodeDB {
     pub hidden: bool,
     pub last_position: Option<i64>,
     pub image_url: Option<String>,
+    pub played_at: Option<DateTime<Utc>>,
+    pub season: Option<i64>,
+    pub number: Option<i64>,
+    pub extras: EpisodeExtras,
 }
 
 impl EpisodeDB {
@@ -41,6 +46,10 @@ impl EpisodeDB {
             hidden: row.get("hidden")?,
             last_position: row.get("last_position")?,
             image_url: row.get("image_url")?,
+            played_at: convert_date(&row.get("played_at")),
+            season: row.get("season")?,
+            number: row.get("episode_number")?,
+            extras: extras_from_row(row)?,
         })
     }
 
@@ -60,10 +69,22 @@ impl EpisodeDB {
             hidden: row.get("hidden")?,
             last_position: row.get("last_position")?,
             image_url: row.get("image_url")?,
+            played_at: convert_date(&row.get("played_at")),
+            season: row.get("season")?,
+            number: row.get("episode_number")?,
+            extras: extras_from_row(row)?,
         })
     }
 }
 
+/// Read the `extras` json column, invalid json is treated like no extras
+fn extras_from_row(row: &Row<'_>) -> Result<EpisodeExtras, rusqlite::Error> {
+    let extras: Option<String> = row.get("extras")?;
+    Ok(extras
+        .and_then(|v| serde_json::from_str(&v).ok())
+        .unwrap_or_default())
+}
+
 /// A struct representing a episode in a podcast in the database to be inserted
 ///
 /// This is required as some fields are auto-generated by the database compared to [`EpisodeDB`]
@@ -82,6 +103,9 @@ pub struct EpisodeDBInsertable<'a> {
     pub hidden: bool,
     pub last_position: Option<i64>,
     pub image_url: Option<&'a str>,
+    pub season: Option<i64>,
+    pub number: Option<i64>,
+    pub extras: &'a EpisodeExtras,
 }
 
 impl<'a> EpisodeDBInsertable<'a> {
@@ -99,7 +123,18 @@ impl<'a> EpisodeDBInsertable<'a> {
             hidden: false,
             last_position: Some(0),
             image_url: value.image_url.as_deref(),
+            season: value.season,
+            number: value.number,
+            extras: &value.extras,
+        }
+    }
+
+    /// Get the json to store in the `extras` column, `None` if there are no extras
+    fn extras_json(&self) -> Option<String> {
+        if self.extras.is_empty() {
+            return None;
         }
+        serde_json::to_string(self.extras).ok()
     }
 
     /// Insert the current [`EpisodeDBInsertable`] into the `episodes` table
@@ -107,8 +142,10 @@ impl<'a> EpisodeDBInsertable<'a> {
     pub fn insert_episode(&self, con: &Connection) -> Result<usize, rusqlite::Error> {
         let mut stmt = con.prepare_cached(
             "INSERT INTO episodes (podcast_id, title, url, guid,
-                description, pubdate, duration, played, hidden, last_position, image_url)
-                VALUES (:podid, :title, :url, :guid, :description, :pubdate, :duration, :played, :hidden, :last_position, :image_url);",
+                description, pubdate, duration, played, hidden, last_position, image_url,
+                season, episode_number, extras)
+                VALUES (:podid, :title, :url, :guid, :description, :pubdate, :duration, :played, :hidden, :last_position, :image_url,
+                :season, :episode_number, :extras);",
         )?;
         stmt.execute(named_params![
             ":podid": self.pod_id,
@@ -122,6 +159,9 @@ impl<'a> EpisodeDBInsertable<'a> {
             ":hidden": self.hidden,
             ":last_position": self.last_position,
             ":image_url": self.image_url,
+            ":season": self.season,
+            ":episode_number": self.number,
+            ":extras": self.extras_json(),
         ])
     }
 
@@ -141,7 +181,9 @@ impl<'a> EpisodeDBInsertable<'a> {
         let mut stmt = con.prepare_cached(
             "UPDATE episodes SET title = :title, url = :url,
                     guid = :guid, description = :description, pubdate = :pubdate,
-                    duration = :duration, image_url = :image_url WHERE id = :epid;",
+                    duration = :duration, image_url = :image_url,
+                    season = :season, episode_number = :episode_number, extras = :extras
+                    WHERE id = :epid;",
         )?;
         stmt.execute(named_params![
             ":title": self.title,
@@ -151,6 +193,9 @@ impl<'a> EpisodeDBInsertable<'a> {
             ":pubdate": self.pubdate.map(|v| v.timestamp()),
             ":duration": self.duration,
             ":image_url": self.duration,
+            ":season": self.season,
+            ":episode_number": self.number,
+            ":extras": self.extras_json(),
             ":epid": id,
         ])
     }
@@ -78,10 +78,11 @@ pub fn delete_file(id: PodcastDBId, con: &Connection) -> Result<usize, rusqlite:
 ///
 /// This does *not* remove the actual (on disk) files!
 pub fn delete_files(ids: &[PodcastDBId], con: &Connection) -> Result<usize, rusqlite::Error> {
-    // convert list of episode ids into a comma-separated String
-    let episode_list: Vec<String> = ids.iter().map(std::string::ToString::to_string).collect();
-    let episodes = episode_list.join(", ");
+    let mut deleted = 0;
+    let mut stmt = con.prepare_cached("DELETE FROM files WHERE episode_id = ?;")?;
+    for id in ids {
+        deleted += stmt.execute(params![id])?;
+    }
 
-    let mut stmt = con.prepare_cached("DELETE FROM files WHERE episode_id = (?);")?;
-    stmt.execute(params![episodes])
+    Ok(deleted)
 }
@@ -3,7 +3,7 @@ use rusqlite::{params, Connection};
 use semver::Version;
 
 /// The Current Database schema version this application is meant to run against
-pub(super) const DB_VERSION: u32 = 1;
+pub(super) const DB_VERSION: u32 = 7;
 /// The Lowest Database schema version this application supports migration up against
 ///
 /// Expection being "0" as that indicates a fresh database
@@ -68,6 +68,42 @@ fn apply_migrations(conn: &Connection, mut user_version: u32) -> Result<()> {
         user_version = set_user_version(conn, 1)?;
     }
 
+    if user_version == 1 {
+        conn.execute_batch(include_str!("./migrations/002.sql"))
+            .context("PodcastDatabase version 2 could not be applied")?;
+        user_version = set_user_version(conn, 2)?;
+    }
+
+    if user_version == 2 {
+        conn.execute_batch(include_str!("./migrations/003.sql"))
+            .context("PodcastDatabase version 3 could not be applied")?;
+        user_version = set_user_version(conn, 3)?;
+    }
+
+    if user_version == 3 {
+        conn.execute_batch(include_str!("./migrations/004.sql"))
+            .context("PodcastDatabase version 4 could not be applied")?;
+        user_version = set_user_version(conn, 4)?;
+    }
+
+    if user_version == 4 {
+        conn.execute_batch(include_str!("./migrations/005.sql"))
+            .context("PodcastDatabase version 5 could not be applied")?;
+        user_version = set_user_version(conn, 5)?;
+    }
+
+    if user_version == 5 {
+        conn.execute_batch(include_str!("./migrations/006.sql"))
+            .context("PodcastDatabase version 6 could not be applied")?;
+        user_version = set_user_version(conn, 6)?;
+    }
+
+    if user_version == 6 {
+        conn.execute_batch(include_str!("./migrations/007.sql"))
+            .context("PodcastDatabase version 7 could not be applied")?;
+        user_version = set_user_version(conn, 7)?;
+    }
+
     Ok(())
 }
 
@@ -135,7 +171,7 @@ mod tests {
 
         assert_eq!(0, get_user_version(&conn).unwrap());
         migrate(&conn).unwrap();
-        assert_eq!(1, get_user_version(&conn).unwrap());
+        assert_eq!(DB_VERSION, get_user_version(&conn).unwrap());
 
         let all_tracks: Vec<String> = {
             let mut prep = conn.prepare("SELECT name FROM sqlite_schema WHERE type ='table' AND name NOT LIKE 'sqlite_%';").unwrap();
@@ -145,6 +181,51 @@ mod tests {
                 .collect()
         };
 
-        assert_eq!(&all_tracks, &["podcasts", "episodes", "files", "version"]);
+        assert_eq!(
+            &all_tracks,
+            &["podcasts", "episodes", "files", "version", "downloads"]
+        );
+    }
+
+    #[test]
+    fn should_add_policy_columns_when_migrating_from_1() {
+        let conn = gen_database();
+        conn.execute_batch(include_str!("./migrations/001.sql"))
+            .unwrap();
+        set_user_version(&conn, 1).unwrap();
+        conn.execute(
+            "INSERT INTO podcasts (title, url, last_checked) VALUES ('Pod', 'https://example.com/feed', 0)",
+            [],
+        )
+        .unwrap();
+
+        migrate(&conn).unwrap();
+        assert_eq!(DB_VERSION, get_user_version(&conn).unwrap());
+
+        let (auto_download, keep_last): (u32, Option<u32>) = conn
+            .query_row(
+                "SELECT auto_download, keep_last FROM podcasts WHERE id = 1",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((0, None), (auto_download, keep_last));
+
+        let etag: Option<String> = conn
+            .query_row("SELECT etag FROM podcasts WHERE id = 1", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(None, etag);
+
+        let funding: Option<String> = conn
+            .query_row("SELECT funding FROM podcasts WHERE id = 1", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(None, funding);
+
+        let folder: Option<String> = conn
+            .query_row("SELECT folder FROM podcasts WHERE id = 1", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(None, folder);
     }
 }
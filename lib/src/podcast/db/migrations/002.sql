@@ -0,0 +1,8 @@
+-- per-podcast automatic download and retention policy, see "PodcastPolicy"
+ALTER TABLE podcasts ADD COLUMN auto_download INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE podcasts ADD COLUMN keep_last INTEGER;
+ALTER TABLE podcasts ADD COLUMN delete_played_after_days INTEGER;
+ALTER TABLE podcasts ADD COLUMN max_size_mb INTEGER;
+
+-- when a episode was marked as played, used for "delete_played_after_days"
+ALTER TABLE episodes ADD COLUMN played_at INTEGER;
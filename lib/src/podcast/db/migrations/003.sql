@@ -0,0 +1,10 @@
+-- persistent episode download queue, see "DownloadItem"
+CREATE TABLE IF NOT EXISTS downloads (
+    episode_id INTEGER PRIMARY KEY NOT NULL,
+    -- see "DownloadStatus"
+    status INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    -- why the last attempt failed, if it did
+    error TEXT,
+    FOREIGN KEY (episode_id) REFERENCES episodes(id) ON DELETE CASCADE
+);
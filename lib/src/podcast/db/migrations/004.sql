@@ -0,0 +1,4 @@
+-- validators of the last fetched feed, to skip unchanged feeds, see "FeedCache"
+ALTER TABLE podcasts ADD COLUMN etag TEXT;
+ALTER TABLE podcasts ADD COLUMN last_modified TEXT;
+ALTER TABLE podcasts ADD COLUMN content_hash TEXT;
@@ -0,0 +1,5 @@
+-- credentials and extra headers of private feeds, see "FeedAuth"
+ALTER TABLE podcasts ADD COLUMN auth_username TEXT;
+ALTER TABLE podcasts ADD COLUMN auth_password TEXT;
+-- "Name: value" pairs separated by "|", see "FeedHeaders"
+ALTER TABLE podcasts ADD COLUMN auth_headers TEXT;
@@ -0,0 +1,7 @@
+-- data of the "podcast:" namespace, see "namespace.rs"
+ALTER TABLE episodes ADD COLUMN season INTEGER;
+ALTER TABLE episodes ADD COLUMN episode_number INTEGER;
+-- json of "EpisodeExtras", transcripts, chapters, persons and soundbites
+ALTER TABLE episodes ADD COLUMN extras TEXT;
+-- json list of "Funding"
+ALTER TABLE podcasts ADD COLUMN funding TEXT;
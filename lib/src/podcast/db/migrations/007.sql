@@ -0,0 +1,2 @@
+-- user-defined folder of a podcast, "NULL" for podcasts not in a folder
+ALTER TABLE podcasts ADD COLUMN folder TEXT;
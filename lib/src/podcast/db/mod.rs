@@ -11,10 +11,14 @@ use regex::Regex;
 use rusqlite::{params, Connection};
 use semver::Version;
 
-use super::{Episode, EpisodeNoId, Podcast, PodcastNoId};
+use super::feed::FeedCache;
+use super::{Episode, EpisodeNoId, Podcast, PodcastNoId, PodcastPolicy};
 use crate::track::Track;
 use podcast_db::{PodcastDB, PodcastDBInsertable};
 
+pub use download_db::{DownloadAction, DownloadItem, DownloadStatus};
+
+mod download_db;
 mod episode_db;
 mod file_db;
 mod migration;
@@ -33,6 +37,8 @@ pub type PodcastDBId = i64;
 pub struct SyncResult {
     pub added: u64,
     pub updated: u64,
+    /// The feed did not change since the last sync, so nothing was updated
+    pub skipped: bool,
 }
 
 /// Struct holding a sqlite database connection, with methods to interact
@@ -124,6 +130,113 @@ impl Database {
         Ok(())
     }
 
+    /// Updates the download and retention policy of a podcast.
+    pub fn set_podcast_policy(
+        &self,
+        podcast_id: PodcastDBId,
+        policy: &PodcastPolicy,
+    ) -> Result<()> {
+        podcast_db::update_policy(podcast_id, policy, &self.conn)?;
+
+        Ok(())
+    }
+
+    /// Moves a podcast into `folder`, or out of any folder with `None`.
+    pub fn set_podcast_folder(&self, podcast_id: PodcastDBId, folder: Option<&str>) -> Result<()> {
+        podcast_db::update_folder(podcast_id, folder, &self.conn)?;
+
+        Ok(())
+    }
+
+    /// Deletes the downloaded files the retention policy of `podcast` no longer allows and
+    /// removes their file listings.
+    ///
+    /// Returns the ids of the episodes whose file was removed.
+    pub fn enforce_retention(&self, podcast: &Podcast) -> Result<Vec<PodcastDBId>> {
+        let mut removed = Vec::new();
+        for ep in podcast
+            .policy
+            .episodes_to_remove(&podcast.episodes, Utc::now())
+        {
+            let Some(path) = &ep.path else {
+                continue;
+            };
+            match std::fs::remove_file(path) {
+                Ok(()) => removed.push(ep.id),
+                // the file is already gone, so only the listing is left to remove
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => removed.push(ep.id),
+                Err(err) => warn!("Could not delete \"{}\": {err}", path.display()),
+            }
+        }
+        self.remove_files(&removed)?;
+
+        Ok(removed)
+    }
+
+    /// Adds episodes to the end of the download queue, failed or finished ones are queued again.
+    pub fn queue_downloads(&self, episode_ids: &[PodcastDBId]) -> Result<()> {
+        for id in episode_ids {
+            download_db::enqueue(*id, &self.conn)?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the whole download queue in order.
+    pub fn get_download_queue(&self) -> Result<Vec<DownloadItem>> {
+        Ok(download_db::get_all(&self.conn)?)
+    }
+
+    /// Updates the status of a item in the download queue.
+    pub fn set_download_status(
+        &self,
+        episode_id: PodcastDBId,
+        status: DownloadStatus,
+        error: Option<&str>,
+    ) -> Result<()> {
+        download_db::set_status(episode_id, status, error, &self.conn)?;
+
+        Ok(())
+    }
+
+    /// Queues all failed downloads again.
+    pub fn retry_failed_downloads(&self) -> Result<()> {
+        download_db::set_all_status(DownloadStatus::Failed, DownloadStatus::Queued, &self.conn)?;
+
+        Ok(())
+    }
+
+    /// Queues all downloads marked as running again, for when their downloads were interrupted
+    /// by the server exiting.
+    pub fn requeue_running_downloads(&self) -> Result<()> {
+        download_db::set_all_status(DownloadStatus::Running, DownloadStatus::Queued, &self.conn)?;
+
+        Ok(())
+    }
+
+    /// Moves a item in the download queue one place up, or down if `down` is set.
+    ///
+    /// Returns `false` if it could not be moved any further.
+    pub fn move_download(&self, episode_id: PodcastDBId, down: bool) -> Result<bool> {
+        Ok(download_db::swap_with_neighbour(
+            episode_id, down, &self.conn,
+        )?)
+    }
+
+    /// Removes a item from the download queue.
+    pub fn remove_download(&self, episode_id: PodcastDBId) -> Result<()> {
+        download_db::delete(episode_id, &self.conn)?;
+
+        Ok(())
+    }
+
+    /// Removes all items from the download queue which are not running.
+    pub fn clear_download_queue(&self) -> Result<()> {
+        download_db::delete_all_idle(&self.conn)?;
+
+        Ok(())
+    }
+
     /// Removes a podcast, all episodes, and files from the database.
     pub fn remove_podcast(&self, podcast_id: PodcastDBId) -> Result<()> {
         podcast_db::delete_podcast(podcast_id, &self.conn)?;
@@ -141,6 +254,22 @@ impl Database {
         Ok(result)
     }
 
+    /// Records that the feed of an existing podcast did not change, only storing the
+    /// check time and the validators for the next conditional request.
+    pub fn update_podcast_unchanged(
+        &self,
+        pod_id: PodcastDBId,
+        cache: &FeedCache,
+    ) -> Result<SyncResult> {
+        podcast_db::update_cache(pod_id, Utc::now(), cache, &self.conn)?;
+
+        Ok(SyncResult {
+            added: 0,
+            updated: 0,
+            skipped: true,
+        })
+    }
+
     /// Updates metadata about episodes that already exist in database,
     /// or inserts new episodes.
     ///
@@ -223,6 +352,7 @@ impl Database {
         Ok(SyncResult {
             added: inserted,
             updated,
+            skipped: false,
         })
     }
 
@@ -242,6 +372,9 @@ impl Database {
             && new_ep.guid == old_ep.guid
             && new_ep.description == old_ep.description
             && new_ep.duration == old_ep.duration
+            && new_ep.season == old_ep.season
+            && new_ep.number == old_ep.number
+            && new_ep.extras == old_ep.extras
             && pd_match)
         {
             return true;
@@ -253,8 +386,8 @@ impl Database {
     pub fn set_played_status(&self, episode_id: PodcastDBId, played: bool) -> Result<()> {
         let mut stmt = self
             .conn
-            .prepare_cached("UPDATE episodes SET played = ? WHERE id = ?;")?;
-        stmt.execute(params![played, episode_id])?;
+            .prepare_cached("UPDATE episodes SET played = ?, played_at = ? WHERE id = ?;")?;
+        stmt.execute(params![played, played_at(played), episode_id])?;
         Ok(())
     }
 
@@ -268,8 +401,9 @@ impl Database {
         let tx = conn.transaction()?;
 
         for episode_id in episode_id_vec {
-            let mut stmt = tx.prepare_cached("UPDATE episodes SET played = ? WHERE id = ?;")?;
-            stmt.execute(params![played, episode_id])?;
+            let mut stmt =
+                tx.prepare_cached("UPDATE episodes SET played = ?, played_at = ? WHERE id = ?;")?;
+            stmt.execute(params![played, played_at(played), episode_id])?;
         }
         tx.commit()?;
         Ok(())
@@ -313,6 +447,11 @@ impl Database {
                     last_checked: podcast.last_checked,
                     episodes,
                     image_url: podcast.image_url,
+                    policy: podcast.policy,
+                    cache: podcast.cache,
+                    auth: podcast.auth,
+                    funding: podcast.funding,
+                    folder: podcast.folder,
                 })
             })
             .collect::<Result<_, rusqlite::Error>>()?;
@@ -357,6 +496,10 @@ impl Database {
                     played: episode.played,
                     last_position: episode.last_position,
                     image_url: episode.image_url,
+                    played_at: episode.played_at,
+                    season: episode.season,
+                    number: episode.number,
+                    extras: episode.extras,
                 })
             })?
             .flatten()
@@ -412,6 +555,11 @@ impl Database {
     }
 }
 
+/// Get the `played_at` value for a new `played` status
+fn played_at(played: bool) -> Option<i64> {
+    played.then(|| Utc::now().timestamp())
+}
+
 /// Helper function converting an (optional) Unix timestamp to a
 /// `DateTime`<Utc> object
 fn convert_date(result: &Result<i64, rusqlite::Error>) -> Option<DateTime<Utc>> {
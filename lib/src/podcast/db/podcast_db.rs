@@ -2,7 +2,9 @@ use chrono::{DateTime, Utc};
 use rusqlite::{named_params, params, Connection, Row};
 
 use super::{convert_date, PodcastDBId};
-use crate::podcast::PodcastNoId;
+use crate::podcast::feed::{FeedAuth, FeedCache, FeedHeaders};
+use crate::podcast::namespace::Funding;
+use crate::podcast::{PodcastNoId, PodcastPolicy};
 
 /// A struct representing a podcast feed in the database
 #[derive(Debug, Clone)]
@@ -15,6 +17,11 @@ pub struct PodcastDB {
     pub explicit: Option<bool>,
     pub last_checked: DateTime<Utc>,
     pub image_url: Option<String>,
+    pub policy: PodcastPolicy,
+    pub cache: FeedCache,
+    pub auth: FeedAuth,
+    pub funding: Vec<Funding>,
+    pub folder: Option<String>,
 }
 
 impl PodcastDB {
@@ -23,6 +30,13 @@ impl PodcastDB {
         // NOTE: all the names in "get" below are the *column names* as defined in migrations/001.sql#table_podcasts (pseudo link)
         let last_checked =
             convert_date(&row.get("last_checked")).ok_or(rusqlite::Error::InvalidQuery)?;
+        let headers: Option<String> = row.get("auth_headers")?;
+        // headers are validated before they are stored, so this only ignores manual edits
+        let headers: FeedHeaders = headers.and_then(|v| v.parse().ok()).unwrap_or_default();
+        let funding: Option<String> = row.get("funding")?;
+        let funding = funding
+            .and_then(|v| serde_json::from_str(&v).ok())
+            .unwrap_or_default();
         Ok(PodcastDB {
             id: row.get("id")?,
             title: row.get("title")?,
@@ -32,6 +46,24 @@ impl PodcastDB {
             explicit: row.get("explicit")?,
             last_checked,
             image_url: row.get("image_url")?,
+            policy: PodcastPolicy {
+                auto_download: row.get("auto_download")?,
+                keep_last: row.get("keep_last")?,
+                delete_played_after_days: row.get("delete_played_after_days")?,
+                max_size_mb: row.get("max_size_mb")?,
+            },
+            cache: FeedCache {
+                etag: row.get("etag")?,
+                last_modified: row.get("last_modified")?,
+                content_hash: row.get("content_hash")?,
+            },
+            auth: FeedAuth {
+                username: row.get("auth_username")?,
+                password: row.get("auth_password")?,
+                headers,
+            },
+            funding,
+            folder: row.get("folder")?,
         })
     }
 }
@@ -50,6 +82,10 @@ pub struct PodcastDBInsertable<'a> {
     pub explicit: Option<bool>,
     pub last_checked: DateTime<Utc>,
     pub image_url: Option<&'a str>,
+    pub cache: &'a FeedCache,
+    pub auth: &'a FeedAuth,
+    pub funding: &'a [Funding],
+    pub folder: Option<&'a str>,
 }
 
 impl<'a> From<&'a PodcastNoId> for PodcastDBInsertable<'a> {
@@ -62,6 +98,10 @@ impl<'a> From<&'a PodcastNoId> for PodcastDBInsertable<'a> {
             explicit: value.explicit,
             last_checked: value.last_checked,
             image_url: value.image_url.as_deref(),
+            cache: &value.cache,
+            auth: &value.auth,
+            funding: &value.funding,
+            folder: value.folder.as_deref(),
         }
     }
 }
@@ -71,8 +111,10 @@ impl PodcastDBInsertable<'_> {
     #[inline]
     pub fn insert_podcast(&self, con: &Connection) -> Result<usize, rusqlite::Error> {
         let mut stmt = con.prepare_cached(
-            "INSERT INTO podcasts (title, url, description, author, explicit, last_checked, image_url)
-            VALUES (:title, :url, :description, :author, :explicit, :last_checked, :image_url);",
+            "INSERT INTO podcasts (title, url, description, author, explicit, last_checked, image_url,
+            etag, last_modified, content_hash, auth_username, auth_password, auth_headers, funding, folder)
+            VALUES (:title, :url, :description, :author, :explicit, :last_checked, :image_url,
+            :etag, :last_modified, :content_hash, :auth_username, :auth_password, :auth_headers, :funding, :folder);",
         )?;
         stmt.execute(named_params![
             ":title": self.title,
@@ -81,7 +123,15 @@ impl PodcastDBInsertable<'_> {
             ":author": self.author,
             ":explicit": self.explicit,
             ":last_checked": self.last_checked.timestamp(),
-            ":image_url": self.image_url
+            ":image_url": self.image_url,
+            ":etag": self.cache.etag,
+            ":last_modified": self.cache.last_modified,
+            ":content_hash": self.cache.content_hash,
+            ":auth_username": self.auth.username,
+            ":auth_password": self.auth.password,
+            ":auth_headers": (!self.auth.headers.0.is_empty()).then(|| self.auth.headers.to_string()),
+            ":funding": self.funding_json(),
+            ":folder": self.folder,
         ])
     }
 
@@ -94,7 +144,9 @@ impl PodcastDBInsertable<'_> {
     ) -> Result<usize, rusqlite::Error> {
         let mut stmt = con.prepare_cached(
             "UPDATE podcasts SET title = :title, url = :url, description = :description,
-            author = :author, explicit = :explicit, last_checked = :last_checked
+            author = :author, explicit = :explicit, last_checked = :last_checked,
+            etag = :etag, last_modified = :last_modified, content_hash = :content_hash,
+            funding = :funding
             WHERE id = :id;",
         )?;
         stmt.execute(named_params![
@@ -104,9 +156,75 @@ impl PodcastDBInsertable<'_> {
             ":author": self.author,
             ":explicit": self.explicit,
             ":last_checked": self.last_checked.timestamp(),
+            ":etag": self.cache.etag,
+            ":last_modified": self.cache.last_modified,
+            ":content_hash": self.cache.content_hash,
+            ":funding": self.funding_json(),
             ":id": id,
         ])
     }
+
+    /// Get the json to store in the `funding` column, `None` if there are no links
+    fn funding_json(&self) -> Option<String> {
+        if self.funding.is_empty() {
+            return None;
+        }
+        serde_json::to_string(self.funding).ok()
+    }
+}
+
+/// Update only the check time and feed validators of a podcast by id, for when its feed did not change
+pub fn update_cache(
+    id: PodcastDBId,
+    last_checked: DateTime<Utc>,
+    cache: &FeedCache,
+    con: &Connection,
+) -> Result<usize, rusqlite::Error> {
+    let mut stmt = con.prepare_cached(
+        "UPDATE podcasts SET last_checked = :last_checked,
+        etag = :etag, last_modified = :last_modified, content_hash = :content_hash
+        WHERE id = :id;",
+    )?;
+    stmt.execute(named_params![
+        ":last_checked": last_checked.timestamp(),
+        ":etag": cache.etag,
+        ":last_modified": cache.last_modified,
+        ":content_hash": cache.content_hash,
+        ":id": id,
+    ])
+}
+
+/// Update the download and retention policy of a podcast by id
+pub fn update_policy(
+    id: PodcastDBId,
+    policy: &PodcastPolicy,
+    con: &Connection,
+) -> Result<usize, rusqlite::Error> {
+    let mut stmt = con.prepare_cached(
+        "UPDATE podcasts SET auto_download = :auto_download, keep_last = :keep_last,
+        delete_played_after_days = :delete_played_after_days, max_size_mb = :max_size_mb
+        WHERE id = :id;",
+    )?;
+    stmt.execute(named_params![
+        ":auto_download": policy.auto_download,
+        ":keep_last": policy.keep_last,
+        ":delete_played_after_days": policy.delete_played_after_days,
+        ":max_size_mb": policy.max_size_mb,
+        ":id": id,
+    ])
+}
+
+/// Update the folder of a podcast by id, `None` to remove it from its folder
+pub fn update_folder(
+    id: PodcastDBId,
+    folder: Option<&str>,
+    con: &Connection,
+) -> Result<usize, rusqlite::Error> {
+    let mut stmt = con.prepare_cached("UPDATE podcasts SET folder = :folder WHERE id = :id;")?;
+    stmt.execute(named_params![
+        ":folder": folder,
+        ":id": id,
+    ])
 }
 
 /// Delete a podcast by id
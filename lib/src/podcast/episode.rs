@@ -4,7 +4,7 @@ use chrono::{DateTime, Utc};
 
 use crate::utils::StringUtils;
 
-use super::{Menuable, EPISODE_DURATION_LENGTH, EPISODE_PUBDATE_LENGTH};
+use super::{namespace::EpisodeExtras, Menuable, EPISODE_DURATION_LENGTH, EPISODE_PUBDATE_LENGTH};
 
 /// Struct holding data about an individual podcast episode. Most of this
 /// is metadata, but if the episode has been downloaded to the local
@@ -24,6 +24,12 @@ pub struct Episode {
     pub played: bool,
     pub last_position: Option<i64>,
     pub image_url: Option<String>,
+    /// When the episode was last marked as played
+    pub played_at: Option<DateTime<Utc>>,
+    pub season: Option<i64>,
+    /// The episode number, in the season if there is one
+    pub number: Option<i64>,
+    pub extras: EpisodeExtras,
 }
 
 impl Episode {
@@ -41,6 +47,16 @@ impl Episode {
             None => "--:--:--".to_string(),
         }
     }
+
+    /// Formats the season and episode number like `S02E05`, if the feed has them.
+    pub fn format_season_episode(&self) -> Option<String> {
+        match (self.season, self.number) {
+            (Some(season), Some(number)) => Some(format!("S{season:02}E{number:02}")),
+            (Some(season), None) => Some(format!("S{season:02}")),
+            (None, Some(number)) => Some(format!("E{number:02}")),
+            (None, None) => None,
+        }
+    }
 }
 
 impl Menuable for Episode {
@@ -110,4 +126,7 @@ pub struct EpisodeNoId {
     pub pubdate: Option<DateTime<Utc>>,
     pub duration: Option<i64>,
     pub image_url: Option<String>,
+    pub season: Option<i64>,
+    pub number: Option<i64>,
+    pub extras: EpisodeExtras,
 }
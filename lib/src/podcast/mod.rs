@@ -4,32 +4,43 @@
 pub mod db;
 #[allow(clippy::module_name_repetitions)]
 pub mod episode;
+pub mod feed;
+pub mod namespace;
 // repetetive name, but will do for now
 #[allow(clippy::module_inception)]
 mod podcast;
+mod policy;
+pub mod show_notes;
+pub mod transcript;
 
 use crate::config::v2::server::PodcastSettings;
+use crate::config::ServerOverlay;
 use crate::taskpool::TaskPool;
 use crate::types::{Msg, PCMsg};
-use db::Database;
+use db::{Database, DownloadStatus};
 use episode::{Episode, EpisodeNoId};
+use feed::{FeedAuth, FeedCache};
 #[allow(clippy::module_name_repetitions)]
 pub use podcast::{Podcast, PodcastNoId};
+pub use policy::PodcastPolicy;
 
 use anyhow::{bail, Context, Result};
-use bytes::Buf;
 use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
 use opml::{Body, Head, Outline, OPML};
 use regex::Regex;
-use reqwest::ClientBuilder;
+use reqwest::header::{HeaderMap, CONTENT_TYPE, RANGE};
+use reqwest::{ClientBuilder, StatusCode};
 use rfc822_sanitizer::parse_from_rfc2822_with_fallback;
 use rss::{Channel, Item};
 use sanitize_filename::{sanitize_with_options, Options};
-use std::fs::File;
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
 use std::io::Write as _;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU8, Ordering};
 use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
 use std::time::Duration;
 
 // How many columns we need, minimum, before we display the
@@ -68,15 +79,38 @@ pub struct PodcastFeed {
     pub id: Option<i64>,
     pub url: String,
     pub title: Option<String>,
+    pub auth: FeedAuth,
+    /// Folder to add the podcast to, like the path of the outlines a feed is nested in of a OPML file
+    pub folder: Option<String>,
 }
 
 impl PodcastFeed {
+    /// Create a feed for `url`, credentials in `url` are moved into [`PodcastFeed::auth`]
     pub fn new(id: Option<i64>, url: &str, title: Option<String>) -> Self {
+        let (url, auth) = FeedAuth::split_from_url(url);
         Self {
             id,
-            url: url.to_string(),
+            url,
             title,
+            auth,
+            folder: None,
+        }
+    }
+
+    /// Use the credentials and headers of `auth`, unless it is empty
+    #[must_use]
+    pub fn with_auth(mut self, auth: FeedAuth) -> Self {
+        if !auth.is_empty() {
+            self.auth = auth;
         }
+        self
+    }
+
+    /// Add the podcast to `folder`
+    #[must_use]
+    pub fn with_folder(mut self, folder: Option<String>) -> Self {
+        self.folder = folder;
+        self
     }
 }
 
@@ -86,13 +120,13 @@ impl PodcastFeed {
 pub fn check_feed(feed: PodcastFeed, max_retries: usize, tp: &TaskPool, tx_to_main: Sender<Msg>) {
     tp.execute(async move {
         let _ = tx_to_main.send(Msg::Podcast(PCMsg::FetchPodcastStart(feed.url.clone())));
-        match get_feed_data(&feed.url, max_retries).await {
+        match get_feed_data(&feed.url, max_retries, &feed.auth).await {
             Ok(pod) => match feed.id {
                 Some(id) => {
-                    let _ = tx_to_main.send(Msg::Podcast(PCMsg::SyncData((id, pod))));
+                    let _ = tx_to_main.send(Msg::Podcast(PCMsg::SyncData((id, Box::new(pod)))));
                 }
                 None => {
-                    let _ = tx_to_main.send(Msg::Podcast(PCMsg::NewData(pod)));
+                    let _ = tx_to_main.send(Msg::Podcast(PCMsg::NewData(Box::new(pod))));
                 }
             },
             Err(err) => {
@@ -104,25 +138,95 @@ pub fn check_feed(feed: PodcastFeed, max_retries: usize, tp: &TaskPool, tx_to_ma
 }
 
 /// Given a URL, this attempts to pull the data about a podcast and its
-/// episodes from an RSS feed.
-async fn get_feed_data(url: &str, mut max_retries: usize) -> Result<PodcastNoId> {
+/// episodes from a RSS, Atom or JSON feed.
+async fn get_feed_data(url: &str, max_retries: usize, auth: &FeedAuth) -> Result<PodcastNoId> {
+    let resp = send_feed_request(url, max_retries, auth, HeaderMap::new()).await?;
+    let (body, content_type, cache) = read_feed_response(resp).await?;
+
+    let mut pod = feed::parse_feed(&body, content_type.as_deref(), url)?;
+    pod.cache = cache;
+    pod.auth = auth.clone();
+    Ok(pod)
+}
+
+/// Result of [`get_feed_data_if_changed`]
+enum FeedFetch {
+    /// The feed did not change, with the validators to store for the next request
+    Unchanged(FeedCache),
+    Changed(Box<PodcastNoId>),
+}
+
+/// Like [`get_feed_data`], but the feed is only downloaded and parsed if it changed since `cache`.
+///
+/// A feed counts as unchanged if the server answers the conditional request with `304 Not Modified`,
+/// or if the body has the same hash as before.
+async fn get_feed_data_if_changed(
+    url: &str,
+    max_retries: usize,
+    auth: &FeedAuth,
+    cache: &FeedCache,
+) -> Result<FeedFetch> {
+    let resp = send_feed_request(url, max_retries, auth, cache.request_headers()).await?;
+    if resp.status() == StatusCode::NOT_MODIFIED {
+        let mut cache = cache.clone();
+        cache.update_from_headers(resp.headers());
+        return Ok(FeedFetch::Unchanged(cache));
+    }
+
+    let (body, content_type, new_cache) = read_feed_response(resp).await?;
+    if new_cache.content_hash == cache.content_hash {
+        return Ok(FeedFetch::Unchanged(new_cache));
+    }
+
+    let mut pod = feed::parse_feed(&body, content_type.as_deref(), url)?;
+    pod.cache = new_cache;
+    Ok(FeedFetch::Changed(Box::new(pod)))
+}
+
+/// Request a feed, or a file linked in it, with its credentials and the given extra `headers`, retrying until there is a response
+async fn send_feed_request(
+    url: &str,
+    mut max_retries: usize,
+    auth: &FeedAuth,
+    headers: HeaderMap,
+) -> Result<reqwest::Response> {
     let agent = ClientBuilder::new()
         .connect_timeout(Duration::from_secs(5))
         .build()?;
 
-    let resp: reqwest::Response = loop {
-        let response = agent.get(url).send().await;
+    loop {
+        let response = auth
+            .apply(agent.get(url))
+            .headers(headers.clone())
+            .send()
+            .await;
         if let Ok(resp) = response {
-            break resp;
+            return Ok(resp);
         }
         max_retries -= 1;
         if max_retries == 0 {
             bail!("No response from feed");
         }
-    };
+    }
+}
+
+/// Read the body of a feed response, with its content type and the validators to cache
+///
+/// Responses that are not successful are an error with their status, instead of being read as a feed.
+async fn read_feed_response(
+    resp: reqwest::Response,
+) -> Result<(bytes::Bytes, Option<String>, FeedCache)> {
+    let resp = resp.error_for_status()?;
+    let content_type = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(ToString::to_string);
+    let mut cache = FeedCache::from_headers(resp.headers());
+    let body = resp.bytes().await?;
+    cache.content_hash = Some(feed::content_hash(&body));
 
-    let channel = Channel::read_from(resp.bytes().await?.reader())?;
-    Ok(parse_feed_data(channel, url))
+    Ok((body, content_type, cache))
 }
 
 /// Given a Channel with the RSS feed data, this parses the data about a
@@ -152,11 +256,14 @@ fn parse_feed_data(channel: Channel, url: &str) -> PodcastNoId {
         image_url = itunes.image().map(std::string::ToString::to_string);
     }
 
+    let prefix = namespace::prefix(&channel).to_string();
+    let funding = namespace::parse_funding(channel.extensions(), &prefix);
+
     let mut episodes = Vec::new();
     let items = channel.into_items();
     if !items.is_empty() {
         for item in &items {
-            episodes.push(parse_episode_data(item));
+            episodes.push(parse_episode_data(item, &prefix));
         }
     }
 
@@ -169,6 +276,10 @@ fn parse_feed_data(channel: Channel, url: &str) -> PodcastNoId {
         last_checked,
         episodes,
         image_url,
+        cache: FeedCache::default(),
+        auth: FeedAuth::default(),
+        funding,
+        folder: None,
     }
 }
 
@@ -177,7 +288,9 @@ fn parse_feed_data(channel: Channel, url: &str) -> PodcastNoId {
 /// podcast RSS feeds that a feed should adhere to, but this does try to
 /// make some attempt to account for the possibility that a feed might
 /// not be valid according to the spec.
-fn parse_episode_data(item: &Item) -> EpisodeNoId {
+///
+/// `prefix` is the one the `podcast:` namespace is bound to in the feed.
+fn parse_episode_data(item: &Item, prefix: &str) -> EpisodeNoId {
     let title = item.title().unwrap_or("").to_string();
     let url = match item.enclosure() {
         Some(enc) => enc.url().to_string(),
@@ -193,11 +306,16 @@ fn parse_episode_data(item: &Item) -> EpisodeNoId {
         .and_then(|pd| parse_from_rfc2822_with_fallback(pd).ok())
         .map(std::convert::Into::into);
 
+    let (mut season, mut number) = namespace::parse_season_episode(item.extensions(), prefix);
+    let extras = namespace::parse_episode_extras(item.extensions(), prefix);
+
     let mut duration = None;
     let mut image_url = None;
     if let Some(itunes) = item.itunes_ext() {
         duration = duration_to_int(itunes.duration()).map(i64::from);
         image_url = itunes.image().map(std::string::ToString::to_string);
+        season = season.or_else(|| itunes.season().and_then(|v| v.trim().parse().ok()));
+        number = number.or_else(|| itunes.episode().and_then(|v| v.trim().parse().ok()));
     }
 
     EpisodeNoId {
@@ -208,6 +326,9 @@ fn parse_episode_data(item: &Item) -> EpisodeNoId {
         pubdate,
         duration,
         image_url,
+        season,
+        number,
+        extras,
     }
 }
 
@@ -313,9 +434,13 @@ pub fn import_from_opml(db_path: &Path, config: &PodcastSettings, file: &Path) -
     let mut failure = false;
     while let Some(message) = rx_to_main.iter().next() {
         match message {
-            Msg::Podcast(PCMsg::NewData(pod)) => {
+            Msg::Podcast(PCMsg::NewData(mut pod)) => {
                 msg_counter += 1;
                 let title = pod.title.clone();
+                pod.folder = podcast_list
+                    .iter()
+                    .find(|feed| feed.url == pod.url)
+                    .and_then(|feed| feed.folder.clone());
                 let db_result = db_inst.insert_podcast(&pod);
                 match db_result {
                     Ok(_) => {
@@ -359,10 +484,13 @@ pub fn import_from_opml(db_path: &Path, config: &PodcastSettings, file: &Path) -
 
 /// Exports all podcasts to OPML format, either printing to stdout or
 /// exporting to a file.
-pub fn export_to_opml(db_path: &Path, file: &Path) -> Result<()> {
+///
+/// The username and password of private feeds are only included in their urls with `with_credentials`,
+/// extra headers are never exported.
+pub fn export_to_opml(db_path: &Path, file: &Path, with_credentials: bool) -> Result<()> {
     let db_inst = Database::new(db_path)?;
     let podcast_list = db_inst.get_podcasts()?;
-    let opml = export_opml_feeds(&podcast_list);
+    let opml = export_opml_feeds(&podcast_list, with_credentials);
 
     let xml = opml.to_string().context("Could not create OPML format")?;
 
@@ -377,13 +505,42 @@ pub fn export_to_opml(db_path: &Path, file: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Separates the levels of nested podcast folders, like `News/Local`
+pub const FOLDER_SEPARATOR: char = '/';
+
+/// Clean up a folder path as entered by a user, removing surrounding whitespace and empty levels
+///
+/// Returns `None` if no level is left.
+#[must_use]
+pub fn normalize_folder(folder: &str) -> Option<String> {
+    let levels: Vec<&str> = folder
+        .split(FOLDER_SEPARATOR)
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .collect();
+    if levels.is_empty() {
+        return None;
+    }
+
+    Some(levels.join(&FOLDER_SEPARATOR.to_string()))
+}
+
 /// Import a list of podcast feeds from an OPML file. Supports
 /// v1.0, v1.1, and v2.0 OPML files.
+///
+/// Feeds nested in outlines without a feed are put into a folder with the path of those outlines,
+/// like `News/Local`. A [`FOLDER_SEPARATOR`] in a outline name is replaced, as it would add a level.
 fn import_opml_feeds(xml: &str) -> Result<Vec<PodcastFeed>> {
     let opml = OPML::from_str(xml)?;
     let mut feeds = Vec::new();
-    for pod in opml.body.outlines {
-        if pod.xml_url.is_some() {
+    collect_opml_feeds(opml.body.outlines, None, &mut feeds);
+    Ok(feeds)
+}
+
+/// Add the feeds of `outlines` and their children to `feeds`, see [`import_opml_feeds`]
+fn collect_opml_feeds(outlines: Vec<Outline>, folder: Option<&str>, feeds: &mut Vec<PodcastFeed>) {
+    for pod in outlines {
+        if let Some(xml_url) = pod.xml_url {
             // match against title attribute first -- if this is
             // not set or empty, then match against the text
             // attribute; this must be set, but can be empty
@@ -394,14 +551,29 @@ fn import_opml_feeds(xml: &str) -> Result<Vec<PodcastFeed>> {
                     Some(pod.text)
                 }
             });
-            feeds.push(PodcastFeed::new(None, &pod.xml_url.unwrap(), title));
+            feeds.push(
+                PodcastFeed::new(None, &xml_url, title).with_folder(folder.map(str::to_string)),
+            );
+        } else {
+            let name = pod
+                .title
+                .as_deref()
+                .unwrap_or(&pod.text)
+                .trim()
+                .replace(FOLDER_SEPARATOR, "-");
+            let child_folder = match folder {
+                _ if name.is_empty() => folder.map(str::to_string),
+                Some(folder) => Some(format!("{folder}{FOLDER_SEPARATOR}{name}")),
+                None => Some(name),
+            };
+            collect_opml_feeds(pod.outlines, child_folder.as_deref(), feeds);
         }
     }
-    Ok(feeds)
 }
 
-/// Converts the current set of podcast feeds to the OPML format
-fn export_opml_feeds(podcasts: &[Podcast]) -> OPML {
+/// Converts the current set of podcast feeds to the OPML format, podcasts in a folder are nested in a
+/// outline for each level of the folder path
+fn export_opml_feeds(podcasts: &[Podcast], with_credentials: bool) -> OPML {
     let date = Utc::now();
     let mut opml = OPML {
         head: Some(Head {
@@ -412,17 +584,40 @@ fn export_opml_feeds(podcasts: &[Podcast]) -> OPML {
         ..Default::default()
     };
 
-    let mut outlines = Vec::new();
+    let mut outlines: Vec<Outline> = Vec::new();
 
     for pod in podcasts {
         // opml.add_feed(&pod.title, &pod.url);
-        outlines.push(Outline {
+        let url = if with_credentials {
+            pod.auth.join_into_url(&pod.url)
+        } else {
+            FeedAuth::redact_url(&pod.url)
+        };
+        let outline = Outline {
             text: pod.title.clone(),
             r#type: Some("rss".to_string()),
-            xml_url: Some(pod.url.clone()),
+            xml_url: Some(url),
             title: Some(pod.title.clone()),
             ..Outline::default()
-        });
+        };
+
+        let mut children = &mut outlines;
+        let levels = pod.folder.iter().flat_map(|v| v.split(FOLDER_SEPARATOR));
+        for level in levels.filter(|v| !v.is_empty()) {
+            let existing = children
+                .iter()
+                .position(|v| v.xml_url.is_none() && v.text == level);
+            let index = existing.unwrap_or_else(|| {
+                children.push(Outline {
+                    text: level.to_string(),
+                    title: Some(level.to_string()),
+                    ..Outline::default()
+                });
+                children.len() - 1
+            });
+            children = &mut children[index].outlines;
+        }
+        children.push(outline);
     }
 
     opml.body = Body { outlines };
@@ -440,106 +635,835 @@ pub struct EpData {
     pub file_path: Option<PathBuf>,
 }
 
-/// This is the function the main controller uses to indicate new files to download.
+impl From<&Episode> for EpData {
+    fn from(ep: &Episode) -> Self {
+        Self {
+            id: ep.id,
+            pod_id: ep.pod_id,
+            title: ep.title.clone(),
+            url: ep.url.clone(),
+            pubdate: ep.pubdate,
+            file_path: None,
+        }
+    }
+}
+
+/// Stops a running episode download once [`CancelToken::cancel`] or [`CancelToken::pause`] was called on any clone of it
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicU8>);
+
+impl CancelToken {
+    const RUN: u8 = 0;
+    const CANCEL: u8 = 1;
+    const PAUSE: u8 = 2;
+
+    /// Stop the download and delete what was downloaded so far
+    pub fn cancel(&self) {
+        self.0.store(Self::CANCEL, Ordering::Relaxed);
+    }
+
+    /// Stop the download, but keep what was downloaded so far to resume from later
+    pub fn pause(&self) {
+        self.0.store(Self::PAUSE, Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn is_canceled(&self) -> bool {
+        self.0.load(Ordering::Relaxed) == Self::CANCEL
+    }
+
+    #[must_use]
+    pub fn is_paused(&self) -> bool {
+        self.0.load(Ordering::Relaxed) == Self::PAUSE
+    }
+
+    /// Whether the download should stop, either way
+    fn should_stop(&self) -> bool {
+        self.0.load(Ordering::Relaxed) != Self::RUN
+    }
+}
+
+/// How a episode download with [`download_file`] ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DownloadResult {
+    /// The episode was downloaded to the `file_path` of its data
+    Complete(EpData),
+    /// Stopped by [`CancelToken::cancel`], the partial file is deleted
+    Canceled(EpData),
+    /// Stopped by [`CancelToken::pause`], the partial file is kept to resume from
+    Paused(EpData),
+    ResponseError(EpData),
+    FileCreateError(EpData),
+    FileWriteError(EpData),
+}
+
+impl DownloadResult {
+    /// Get the data of the downloaded episode
+    #[must_use]
+    pub fn ep_data(&self) -> &EpData {
+        match self {
+            Self::Complete(ep_data)
+            | Self::Canceled(ep_data)
+            | Self::Paused(ep_data)
+            | Self::ResponseError(ep_data)
+            | Self::FileCreateError(ep_data)
+            | Self::FileWriteError(ep_data) => ep_data,
+        }
+    }
+}
+
+/// Get the path the download of `ep_data` into `dir` is stored at until it is complete
+///
+/// Downloads are resumed from this file, so it is left in place when a download fails.
+#[must_use]
+pub fn partial_download_path(dir: &Path, ep_data: &EpData) -> PathBuf {
+    dir.join(format!("{}.part", episode_file_stem(ep_data)))
+}
+
+/// Get the name of the directory the episodes of the podcast titled `pod_title` are downloaded into
+#[must_use]
+pub fn podcast_dir_name(pod_title: &str) -> String {
+    sanitize_with_options(
+        pod_title,
+        Options {
+            truncate: true,
+            windows: true, // for simplicity, we'll just use Windows-friendly paths for everyone
+            replacement: "",
+        },
+    )
+}
+
+/// Get the file name of a downloaded episode, without extension
+fn episode_file_stem(ep_data: &EpData) -> String {
+    let file_name = sanitize_with_options(
+        &ep_data.title,
+        Options {
+            truncate: true,
+            windows: true, // for simplicity, we'll just use Windows-friendly paths for everyone
+            replacement: "",
+        },
+    );
+
+    match ep_data.pubdate {
+        Some(pubdate) => format!("{file_name}_{}", pubdate.format("%Y%m%d_%H%M%S")),
+        None => file_name,
+    }
+}
+
+/// What changed while syncing a podcast with [`sync_podcast`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeedSyncReport {
+    /// Episodes that were new in the feed
+    pub added: u64,
+    /// Episodes added to the download queue because of the podcast's policy
+    pub queued: usize,
+    /// Episode files deleted because of the podcast's policy
+    pub removed: usize,
+    /// The feed did not change, so it was not parsed again
+    pub skipped: bool,
+}
+
+impl FeedSyncReport {
+    /// Whether anything in the database changed
+    #[must_use]
+    pub fn changed(&self) -> bool {
+        self.added > 0 || self.queued > 0 || self.removed > 0
+    }
+}
+
+/// Fetch the feed of `podcast`, store its new data and apply its download and retention policy.
 ///
-/// It uses the taskpool to start jobs for every episode to be downloaded.
-/// New jobs can be requested by the user while there are still ongoing jobs.
+/// The feed is only downloaded and stored again if it changed since the last sync, see [`FeedCache`].
+/// Episodes to download are only added to the download queue, which is run separately.
+/// Episodes that already are in the queue are left as they are, so paused or failed downloads stay that way.
 ///
-/// If `tx_to_main` is closed, no errors will be throws and the task will continue
-pub fn download_list(
-    episodes: Vec<EpData>,
-    dest: &Path,
-    max_retries: usize,
-    tp: &TaskPool,
-    tx_to_main: &Sender<Msg>,
-) {
-    // parse episode details and push to queue
-    for ep in episodes {
-        let tx = tx_to_main.clone();
-        let dest2 = dest.to_path_buf();
-        tp.execute(async move {
-            let _ = tx.send(Msg::Podcast(PCMsg::DLStart(ep.clone())));
-            let result = download_file(ep, dest2, max_retries).await;
-            let _ = tx.send(Msg::Podcast(result));
-        });
+/// Unlike [`check_feed`], everything is done in the current task
+/// and the results go directly into `db`, so that this can run without a TUI.
+///
+/// # Errors
+///
+/// - if the feed could not be fetched or parsed
+/// - if the database could not be updated
+pub async fn sync_podcast(
+    db: &Database,
+    podcast: &Podcast,
+    config: &ServerOverlay,
+) -> Result<FeedSyncReport> {
+    let max_retries = usize::from(config.settings.podcast.max_download_retries);
+    let fetch =
+        get_feed_data_if_changed(&podcast.url, max_retries, &podcast.auth, &podcast.cache).await?;
+    let result = match fetch {
+        FeedFetch::Changed(data) => db.update_podcast(podcast.id, &data)?,
+        FeedFetch::Unchanged(cache) => db.update_podcast_unchanged(podcast.id, &cache)?,
+    };
+    let mut report = FeedSyncReport {
+        added: result.added,
+        skipped: result.skipped,
+        ..Default::default()
+    };
+
+    // reload, as the episodes (and possibly the title) changed
+    let Some(podcast) = db.get_podcasts()?.into_iter().find(|v| v.id == podcast.id) else {
+        return Ok(report);
+    };
+    if podcast.policy.is_manual() {
+        return Ok(report);
     }
+
+    report.removed = db.enforce_retention(&podcast)?.len();
+
+    let in_queue: HashSet<i64> = db
+        .get_download_queue()?
+        .into_iter()
+        .filter(|v| v.status != DownloadStatus::Done)
+        .map(|v| v.episode_id)
+        .collect();
+    let to_queue: Vec<i64> = podcast
+        .policy
+        .episodes_to_download(&podcast.episodes)
+        .into_iter()
+        .map(|v| v.id)
+        .filter(|id| !in_queue.contains(id))
+        .collect();
+    db.queue_downloads(&to_queue)?;
+    report.queued = to_queue.len();
+
+    Ok(report)
 }
 
-/// Downloads a file to a local filepath, returning `DownloadMsg` variant
-/// indicating success or failure.
-async fn download_file(
+/// Get the file extension for a episode from the response's content type
+fn extension_from_response(response: &reqwest::Response) -> &'static str {
+    let Some(content_type) = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+    else {
+        error!("The response doesn't contain a content type, using \"mp3\" as fallback!");
+        return "mp3";
+    };
+
+    match content_type {
+        "audio/x-m4a" | "audio/mp4" => "m4a",
+        "audio/x-matroska" => "mka",
+        "audio/flac" => "flac",
+        "video/quicktime" => "mov",
+        "video/mp4" => "mp4",
+        "video/x-m4v" => "m4v",
+        "video/x-matroska" => "mkv",
+        "video/webm" => "webm",
+        // "audio/mpeg" => "mp3",
+        // fallback
+        _ => "mp3",
+    }
+}
+
+/// How the transfer of a single response ended
+enum Transfer {
+    Complete,
+    /// Canceled or paused by the [`CancelToken`]
+    Stopped,
+    /// The connection broke, resume with a new request
+    Interrupted,
+    WriteError,
+}
+
+/// What to do with the response to a download request, which asks for the rest of a partial file if there is one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseAction {
+    /// Write the body to the partial file, appending to it if the server sent only the requested range
+    Write { append: bool },
+    /// The partial file does not fit the remote file anymore, delete it and start over
+    Restart,
+    /// The request failed, try again
+    Retry,
+}
+
+impl ResponseAction {
+    fn from_status(status: StatusCode) -> Self {
+        if status == StatusCode::RANGE_NOT_SATISFIABLE {
+            Self::Restart
+        } else if status.is_success() {
+            // a server not supporting ranges sends the whole file with 200
+            Self::Write {
+                append: status == StatusCode::PARTIAL_CONTENT,
+            }
+        } else {
+            Self::Retry
+        }
+    }
+}
+
+/// Downloads a episode into the directory `destination_path`, returning how the download ended.
+///
+/// The data is written to the [`partial_download_path`] first, which is resumed with a HTTP Range request if it already exists,
+/// also when the connection breaks during the transfer.
+/// `on_progress` is called with the downloaded and the total bytes, if known, about every percent.
+///
+/// # Panics
+///
+/// - if the HTTP client could not be created
+pub async fn download_file(
     mut ep_data: EpData,
     destination_path: PathBuf,
+    auth: &FeedAuth,
     mut max_retries: usize,
-) -> PCMsg {
+    cancel: &CancelToken,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> DownloadResult {
     let agent = ClientBuilder::new()
         .connect_timeout(Duration::from_secs(10))
         .build()
         .expect("reqwest client build failed");
 
-    let response: reqwest::Response = loop {
-        let response = agent.get(&ep_data.url).send().await;
-        if let Ok(resp) = response {
-            break resp;
+    let part_path = partial_download_path(&destination_path, &ep_data);
+
+    loop {
+        if cancel.is_paused() {
+            return DownloadResult::Paused(ep_data);
         }
-        max_retries -= 1;
+        if cancel.is_canceled() {
+            let _ = std::fs::remove_file(&part_path);
+            return DownloadResult::Canceled(ep_data);
+        }
+
+        let offset = part_path.metadata().map_or(0, |v| v.len());
+        let mut request = auth.apply(agent.get(&ep_data.url));
+        if offset > 0 {
+            request = request.header(RANGE, format!("bytes={offset}-"));
+        }
+
+        let response = request.send().await.ok();
+        let action = response.as_ref().map_or(ResponseAction::Retry, |v| {
+            ResponseAction::from_status(v.status())
+        });
+        if action == ResponseAction::Restart {
+            // counted as a retry, as a server may keep refusing the range
+            let _ = std::fs::remove_file(&part_path);
+        }
+
+        if let (Some(response), ResponseAction::Write { append }) = (response, action) {
+            let ext = extension_from_response(&response);
+            let file = if append {
+                OpenOptions::new().append(true).open(&part_path)
+            } else {
+                File::create(&part_path)
+            };
+            let Ok(mut dst) = file else {
+                return DownloadResult::FileCreateError(ep_data);
+            };
+
+            let done = if append { offset } else { 0 };
+            match transfer(response, &mut dst, done, cancel, &mut on_progress).await {
+                Transfer::Complete => {
+                    let file_path =
+                        destination_path.join(format!("{}.{ext}", episode_file_stem(&ep_data)));
+                    if std::fs::rename(&part_path, &file_path).is_err() {
+                        return DownloadResult::FileWriteError(ep_data);
+                    }
+                    ep_data.file_path = Some(file_path);
+                    return DownloadResult::Complete(ep_data);
+                }
+                Transfer::Stopped if cancel.is_paused() => return DownloadResult::Paused(ep_data),
+                Transfer::Stopped => {
+                    drop(dst);
+                    let _ = std::fs::remove_file(&part_path);
+                    return DownloadResult::Canceled(ep_data);
+                }
+                Transfer::WriteError => return DownloadResult::FileWriteError(ep_data),
+                Transfer::Interrupted => (),
+            }
+        }
+
+        max_retries = max_retries.saturating_sub(1);
         if max_retries == 0 {
-            return PCMsg::DLResponseError(ep_data);
+            return DownloadResult::ResponseError(ep_data);
         }
-    };
+    }
+}
 
-    // figure out the file type
-    let ext = if let Some(content_type) = response
-        .headers()
-        .get("content-type")
-        .and_then(|v| v.to_str().ok())
-    {
-        match content_type {
-            "audio/x-m4a" | "audio/mp4" => "m4a",
-            "audio/x-matroska" => "mka",
-            "audio/flac" => "flac",
-            "video/quicktime" => "mov",
-            "video/mp4" => "mp4",
-            "video/x-m4v" => "m4v",
-            "video/x-matroska" => "mkv",
-            "video/webm" => "webm",
-            // "audio/mpeg" => "mp3",
-            // fallback
-            _ => "mp3",
+/// Write the body of `response` to `dst`, which already contains `done` bytes
+async fn transfer(
+    mut response: reqwest::Response,
+    dst: &mut File,
+    mut done: u64,
+    cancel: &CancelToken,
+    on_progress: &mut impl FnMut(u64, Option<u64>),
+) -> Transfer {
+    let total = response.content_length().map(|len| len + done);
+    // report about every percent, or every MiB if the size is unknown
+    let step = total.map_or(1024 * 1024, |v| (v / 100).max(1));
+    let mut reported = done;
+    on_progress(done, total);
+
+    loop {
+        if cancel.should_stop() {
+            return Transfer::Stopped;
         }
-    } else {
-        error!("The response doesn't contain a content type, using \"mp3\" as fallback!");
-        "mp3"
-    };
 
-    let mut file_name = sanitize_with_options(
-        &ep_data.title,
-        Options {
-            truncate: true,
-            windows: true, // for simplicity, we'll just use Windows-friendly paths for everyone
-            replacement: "",
-        },
-    );
+        match response.chunk().await {
+            Ok(Some(bytes)) => {
+                if dst.write_all(&bytes).is_err() {
+                    return Transfer::WriteError;
+                }
+                done += bytes.len() as u64;
+                if done - reported >= step {
+                    reported = done;
+                    on_progress(done, total);
+                }
+            }
+            Ok(None) => return Transfer::Complete,
+            Err(err) => {
+                warn!("Download of \"{}\" interrupted: {err}", response.url());
+                return Transfer::Interrupted;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
 
-    if let Some(pubdate) = ep_data.pubdate {
-        file_name = format!("{file_name}_{}", pubdate.format("%Y%m%d_%H%M%S"));
+    fn podcast(title: &str, folder: Option<&str>) -> Podcast {
+        Podcast {
+            id: 1,
+            title: title.to_string(),
+            sort_title: title.to_lowercase(),
+            url: format!("https://example.com/{title}.xml"),
+            description: None,
+            author: None,
+            explicit: None,
+            last_checked: Utc::now(),
+            episodes: Vec::new(),
+            image_url: None,
+            policy: PodcastPolicy::default(),
+            cache: FeedCache::default(),
+            auth: FeedAuth::default(),
+            funding: Vec::new(),
+            folder: folder.map(str::to_string),
+        }
     }
 
-    let mut file_path = destination_path;
-    file_path.push(format!("{file_name}.{ext}"));
+    #[test]
+    fn should_import_nested_outlines_as_folders() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0">
+  <head><title>Feeds</title></head>
+  <body>
+    <outline type="rss" text="Loose" xmlUrl="https://example.com/loose.xml"/>
+    <outline text="News">
+      <outline type="rss" text="Daily" xmlUrl="https://example.com/daily.xml"/>
+      <outline text="Local">
+        <outline type="rss" text="Town" xmlUrl="https://example.com/town.xml"/>
+      </outline>
+    </outline>
+  </body>
+</opml>"#;
+        let feeds: Vec<(Option<String>, Option<String>)> = import_opml_feeds(xml)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.title, v.folder))
+            .collect();
+        assert_eq!(
+            feeds,
+            vec![
+                (Some("Loose".to_string()), None),
+                (Some("Daily".to_string()), Some("News".to_string())),
+                (Some("Town".to_string()), Some("News/Local".to_string())),
+            ]
+        );
+    }
 
-    let Ok(mut dst) = File::create(&file_path) else {
-        return PCMsg::DLFileCreateError(ep_data);
-    };
+    #[test]
+    fn should_export_url_tokens_only_with_credentials() {
+        let mut pod = podcast("Private", None);
+        pod.url = "https://www.patreon.com/rss/private?auth=abc123".to_string();
+
+        let url = |with_credentials| {
+            export_opml_feeds(&[pod.clone()], with_credentials)
+                .body
+                .outlines[0]
+                .xml_url
+                .clone()
+        };
+        assert_eq!(
+            url(false).as_deref(),
+            Some("https://www.patreon.com/rss/private")
+        );
+        assert_eq!(url(true).as_deref(), Some(pod.url.as_str()));
+    }
 
-    ep_data.file_path = Some(file_path);
+    #[test]
+    fn should_export_folders_as_nested_outlines() {
+        let podcasts = [
+            podcast("A", Some("Tech")),
+            podcast("B", None),
+            podcast("C", Some("Tech")),
+        ];
+        let opml = export_opml_feeds(&podcasts, false);
+        let outlines = &opml.body.outlines;
+        assert_eq!(outlines.len(), 2);
+        assert_eq!(outlines[0].text, "Tech");
+        assert_eq!(outlines[0].xml_url, None);
+        let children: Vec<&str> = outlines[0]
+            .outlines
+            .iter()
+            .map(|v| v.text.as_str())
+            .collect();
+        assert_eq!(children, vec!["A", "C"]);
+        assert_eq!(outlines[1].text, "B");
 
-    let Ok(bytes) = response.bytes().await else {
-        return PCMsg::DLFileCreateError(ep_data);
-    };
+        // and importing it again restores the folders
+        let xml = opml.to_string().unwrap();
+        let folders: Vec<Option<String>> = import_opml_feeds(&xml)
+            .unwrap()
+            .into_iter()
+            .map(|v| v.folder)
+            .collect();
+        assert_eq!(
+            folders,
+            vec![Some("Tech".to_string()), Some("Tech".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn should_roundtrip_nested_folders() {
+        let podcasts = [
+            podcast("Town", Some("News/Local")),
+            podcast("Daily", Some("News")),
+            podcast("World", Some("News/Global")),
+        ];
+        let opml = export_opml_feeds(&podcasts, false);
+        let news = &opml.body.outlines;
+        assert_eq!(news.len(), 1);
+        let children: Vec<&str> = news[0].outlines.iter().map(|v| v.text.as_str()).collect();
+        assert_eq!(children, vec!["Local", "Daily", "Global"]);
+        assert_eq!(news[0].outlines[0].outlines[0].text, "Town");
+
+        let xml = opml.to_string().unwrap();
+        let folders: Vec<(Option<String>, Option<String>)> = import_opml_feeds(&xml)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.title, v.folder))
+            .collect();
+        assert_eq!(
+            folders,
+            vec![
+                (Some("Town".to_string()), Some("News/Local".to_string())),
+                (Some("Daily".to_string()), Some("News".to_string())),
+                (Some("World".to_string()), Some("News/Global".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_normalize_folders() {
+        assert_eq!(
+            normalize_folder(" News / Local/ ").as_deref(),
+            Some("News/Local")
+        );
+        assert_eq!(normalize_folder(" / "), None);
+    }
+
+    /// Body served by [`serve`]d test downloads
+    const BODY: &[u8] = b"0123456789";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("termusic-download-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Serve http requests on a local port with `respond`, which gets the start of the requested range
+    ///
+    /// Returns the url and the ranges of all requests so far.
+    fn serve(
+        respond: impl Fn(Option<u64>) -> (u16, Vec<u8>) + Send + 'static,
+    ) -> (String, Arc<std::sync::Mutex<Vec<Option<u64>>>>) {
+        use std::io::Read;
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/episode.mp3", listener.local_addr().unwrap());
+        let ranges = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = ranges.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    let Ok(len @ 1..) = stream.read(&mut buf) else {
+                        break;
+                    };
+                    request.extend_from_slice(&buf[..len]);
+                }
+                let range = String::from_utf8_lossy(&request).lines().find_map(|line| {
+                    line.to_lowercase()
+                        .strip_prefix("range: bytes=")?
+                        .trim_end_matches('-')
+                        .parse()
+                        .ok()
+                });
+                requests.lock().unwrap().push(range);
+
+                let (status, body) = respond(range);
+                let head = format!(
+                    "HTTP/1.1 {status} Test\r\nContent-Type: audio/mpeg\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                );
+                let _ = stream.write_all(head.as_bytes());
+                let _ = stream.write_all(&body);
+            }
+        });
+
+        (url, ranges)
+    }
+
+    /// Respond like a server supporting ranges for [`BODY`]
+    #[allow(clippy::cast_possible_truncation)]
+    fn ranged(range: Option<u64>) -> (u16, Vec<u8>) {
+        match range.map(|v| v as usize) {
+            Some(start) if start >= BODY.len() => (416, Vec::new()),
+            Some(start) => (206, BODY[start..].to_vec()),
+            None => (200, BODY.to_vec()),
+        }
+    }
+
+    async fn download(
+        url: &str,
+        dir: &Path,
+        max_retries: usize,
+        cancel: &CancelToken,
+    ) -> DownloadResult {
+        let ep_data = EpData {
+            id: 1,
+            pod_id: 1,
+            title: "Episode".to_string(),
+            url: url.to_string(),
+            pubdate: None,
+            file_path: None,
+        };
+        download_file(
+            ep_data,
+            dir.to_path_buf(),
+            &FeedAuth::default(),
+            max_retries,
+            cancel,
+            |_, _| (),
+        )
+        .await
+    }
+
+    fn downloaded(msg: DownloadResult) -> Vec<u8> {
+        match msg {
+            DownloadResult::Complete(EpData {
+                file_path: Some(path),
+                ..
+            }) => std::fs::read(path).unwrap(),
+            msg => panic!("download did not complete: {msg:?}"),
+        }
+    }
+
+    #[test]
+    fn should_decide_by_response_status() {
+        assert_eq!(
+            ResponseAction::from_status(StatusCode::PARTIAL_CONTENT),
+            ResponseAction::Write { append: true }
+        );
+        assert_eq!(
+            ResponseAction::from_status(StatusCode::OK),
+            ResponseAction::Write { append: false }
+        );
+        assert_eq!(
+            ResponseAction::from_status(StatusCode::RANGE_NOT_SATISFIABLE),
+            ResponseAction::Restart
+        );
+        assert_eq!(
+            ResponseAction::from_status(StatusCode::SERVICE_UNAVAILABLE),
+            ResponseAction::Retry
+        );
+    }
+
+    #[tokio::test]
+    async fn should_check_the_feed_response_status() {
+        let fetch = |status: u16| async move {
+            let (url, _) = serve(move |_| (status, b"<html>page</html>".to_vec()));
+            let cache = FeedCache {
+                etag: Some("\"v1\"".to_string()),
+                ..Default::default()
+            };
+            get_feed_data_if_changed(&url, 1, &FeedAuth::default(), &cache).await
+        };
+
+        assert!(matches!(fetch(304).await, Ok(FeedFetch::Unchanged(_))));
+        for status in [401, 403, 404, 500] {
+            let err = fetch(status).await.err().unwrap();
+            assert!(format!("{err:#}").contains(&status.to_string()), "{err:#}");
+        }
+    }
+
+    #[tokio::test]
+    async fn should_queue_policy_downloads_only_once() {
+        let feed = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0"><channel><title>Pod</title><link>https://example.com</link><description>Pod</description>
+<item><title>Three</title><guid>3</guid><pubDate>Wed, 03 Jan 2024 00:00:00 GMT</pubDate><enclosure url="https://example.com/3.mp3" type="audio/mpeg" length="1"/></item>
+<item><title>Two</title><guid>2</guid><pubDate>Tue, 02 Jan 2024 00:00:00 GMT</pubDate><enclosure url="https://example.com/2.mp3" type="audio/mpeg" length="1"/></item>
+<item><title>One</title><guid>1</guid><pubDate>Mon, 01 Jan 2024 00:00:00 GMT</pubDate><enclosure url="https://example.com/1.mp3" type="audio/mpeg" length="1"/></item>
+</channel></rss>"#;
+        let (url, _) = serve(move |_| (200, feed.as_bytes().to_vec()));
+        let dir = temp_dir("queue");
+        let db = Database::new(&dir).unwrap();
+        db.insert_podcast(&get_feed_data(&url, 1, &FeedAuth::default()).await.unwrap())
+            .unwrap();
+        let podcast = db.get_podcasts().unwrap().remove(0);
+        let policy = PodcastPolicy {
+            auto_download: 2,
+            ..Default::default()
+        };
+        db.set_podcast_policy(podcast.id, &policy).unwrap();
+        let queue = |db: &Database| -> Vec<(String, DownloadStatus)> {
+            db.get_download_queue()
+                .unwrap()
+                .into_iter()
+                .map(|v| (v.title, v.status))
+                .collect()
+        };
+
+        // only queued, nothing is downloaded while syncing
+        let report = sync_podcast(&db, &podcast, &ServerOverlay::default())
+            .await
+            .unwrap();
+        assert_eq!(report.queued, 2);
+        assert_eq!(
+            queue(&db),
+            vec![
+                ("Three".to_string(), DownloadStatus::Queued),
+                ("Two".to_string(), DownloadStatus::Queued)
+            ]
+        );
+
+        // a paused download stays paused on the next sync
+        let three = db.get_download_queue().unwrap()[0].episode_id;
+        db.set_download_status(three, DownloadStatus::Paused, None)
+            .unwrap();
+        let report = sync_podcast(&db, &podcast, &ServerOverlay::default())
+            .await
+            .unwrap();
+        assert_eq!(report.queued, 0);
+        assert_eq!(
+            queue(&db),
+            vec![
+                ("Three".to_string(), DownloadStatus::Paused),
+                ("Two".to_string(), DownloadStatus::Queued)
+            ]
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_resume_partial_download() {
+        let dir = temp_dir("resume");
+        std::fs::write(dir.join("Episode.part"), &BODY[..4]).unwrap();
+        let (url, ranges) = serve(ranged);
+
+        let msg = download(&url, &dir, 3, &CancelToken::default()).await;
+        assert_eq!(downloaded(msg), BODY);
+        assert_eq!(*ranges.lock().unwrap(), vec![Some(4)]);
+        assert!(!dir.join("Episode.part").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_restart_if_the_range_is_ignored() {
+        let dir = temp_dir("ignored");
+        std::fs::write(dir.join("Episode.part"), b"xxxx").unwrap();
+        let (url, ranges) = serve(|_| (200, BODY.to_vec()));
+
+        let msg = download(&url, &dir, 3, &CancelToken::default()).await;
+        assert_eq!(downloaded(msg), BODY);
+        assert_eq!(*ranges.lock().unwrap(), vec![Some(4)]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_restart_if_the_range_is_not_satisfiable() {
+        let dir = temp_dir("unsatisfiable");
+        std::fs::write(dir.join("Episode.part"), b"0123456789abcd").unwrap();
+        let (url, ranges) = serve(ranged);
+
+        let msg = download(&url, &dir, 3, &CancelToken::default()).await;
+        assert_eq!(downloaded(msg), BODY);
+        assert_eq!(*ranges.lock().unwrap(), vec![Some(14), None]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_count_unsatisfiable_ranges_as_retries() {
+        let dir = temp_dir("retries");
+        std::fs::write(dir.join("Episode.part"), b"0123").unwrap();
+        let (url, ranges) = serve(|_| (416, Vec::new()));
+
+        let msg = download(&url, &dir, 3, &CancelToken::default()).await;
+        assert!(matches!(msg, DownloadResult::ResponseError(_)), "{msg:?}");
+        assert_eq!(ranges.lock().unwrap().len(), 3);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_keep_part_on_pause_and_remove_it_on_cancel() {
+        let dir = temp_dir("cancel");
+        let part = dir.join("Episode.part");
+        std::fs::write(&part, b"0123").unwrap();
+        let (url, ranges) = serve(ranged);
+        let cancel = CancelToken::default();
+
+        cancel.pause();
+        let msg = download(&url, &dir, 3, &cancel).await;
+        assert!(matches!(msg, DownloadResult::Paused(_)), "{msg:?}");
+        assert!(part.exists());
+
+        cancel.cancel();
+        let msg = download(&url, &dir, 3, &cancel).await;
+        assert!(matches!(msg, DownloadResult::Canceled(_)), "{msg:?}");
+        assert!(!part.exists());
+        assert!(ranges.lock().unwrap().is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_remove_part_when_canceled_during_the_transfer() {
+        let dir = temp_dir("transfer");
+        let (url, _) = serve(ranged);
+        let cancel = CancelToken::default();
+        let ep_data = EpData {
+            id: 1,
+            pod_id: 1,
+            title: "Episode".to_string(),
+            url,
+            pubdate: None,
+            file_path: None,
+        };
+
+        let msg = download_file(
+            ep_data,
+            dir.clone(),
+            &FeedAuth::default(),
+            3,
+            &cancel,
+            |_, _| cancel.cancel(),
+        )
+        .await;
+        assert!(matches!(msg, DownloadResult::Canceled(_)), "{msg:?}");
+        assert!(!dir.join("Episode.part").exists());
 
-    match std::io::copy(&mut bytes.reader(), &mut dst) {
-        Ok(_) => PCMsg::DLComplete(ep_data),
-        Err(_) => PCMsg::DLFileWriteError(ep_data),
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
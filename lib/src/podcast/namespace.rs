@@ -0,0 +1,328 @@
+//! Parse the Podcasting 2.0 `podcast:` namespace, see <https://podcastindex.org/namespace/1.0>
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use rss::extension::{Extension, ExtensionMap};
+use rss::Channel;
+use serde::{Deserialize, Serialize};
+
+/// The namespace uri, as the prefix it is bound to may differ between feeds
+const NAMESPACE: &str = "https://podcastindex.org/namespace/1.0";
+/// The prefix used if the feed does not declare the namespace
+const DEFAULT_PREFIX: &str = "podcast";
+
+/// Data of a episode from the `podcast:` namespace, besides its season and episode number
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpisodeExtras {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transcripts: Vec<Transcript>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chapters: Option<Chapters>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub persons: Vec<Person>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub soundbites: Vec<Soundbite>,
+}
+
+impl EpisodeExtras {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Get the first transcript that can be shown in sync with the playback
+    #[must_use]
+    pub fn synced_transcript(&self) -> Option<&Transcript> {
+        self.transcripts.iter().find(|v| v.is_time_synced())
+    }
+}
+
+/// `<podcast:transcript>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub url: String,
+    pub mime_type: String,
+    pub language: Option<String>,
+    /// `captions` if the transcript is meant as closed captions
+    pub rel: Option<String>,
+}
+
+impl Transcript {
+    /// Whether the transcript has timestamps, see [`super::transcript::parse_transcript`]
+    #[must_use]
+    pub fn is_time_synced(&self) -> bool {
+        super::transcript::TranscriptFormat::from_mime_type(&self.mime_type).is_some()
+    }
+}
+
+/// `<podcast:chapters>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chapters {
+    pub url: String,
+    pub mime_type: String,
+}
+
+/// `<podcast:person>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Person {
+    pub name: String,
+    /// Like `host` or `guest`, `host` if not given
+    pub role: Option<String>,
+    pub group: Option<String>,
+    pub img: Option<String>,
+    pub href: Option<String>,
+}
+
+/// `<podcast:soundbite>`, a short part of the episode suited to preview it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Soundbite {
+    pub start_time: Duration,
+    pub duration: Duration,
+    pub title: Option<String>,
+}
+
+/// `<podcast:funding>` of a podcast
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Funding {
+    pub url: String,
+    /// What to show for the link, may be empty
+    pub text: String,
+}
+
+/// Get the prefix the namespace is bound to in `channel`
+#[must_use]
+pub fn prefix(channel: &Channel) -> &str {
+    channel
+        .namespaces()
+        .iter()
+        .find(|(_, uri)| uri.trim_end_matches('/') == NAMESPACE)
+        .map_or(DEFAULT_PREFIX, |(prefix, _)| prefix.as_str())
+}
+
+fn elements<'a>(extensions: &'a ExtensionMap, prefix: &str, name: &str) -> &'a [Extension] {
+    extensions
+        .get(prefix)
+        .and_then(|v| v.get(name))
+        .map_or(&[], Vec::as_slice)
+}
+
+fn attr(ext: &Extension, name: &str) -> Option<String> {
+    ext.attrs()
+        .get(name)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+fn text(ext: &Extension) -> Option<String> {
+    ext.value()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Parse a number like `3` or `315.5` for the season and episode, fractions are cut off
+fn number(ext: &Extension) -> Option<i64> {
+    let value = ext.value()?.trim();
+    #[allow(clippy::cast_possible_truncation)]
+    value
+        .parse()
+        .ok()
+        .or_else(|| value.parse::<f64>().ok().map(|v| v.trunc() as i64))
+}
+
+fn seconds(value: Option<&String>) -> Option<Duration> {
+    value
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .and_then(|v| Duration::try_from_secs_f64(v).ok())
+}
+
+/// Get the `<podcast:season>` and `<podcast:episode>` of a item
+#[must_use]
+pub fn parse_season_episode(extensions: &ExtensionMap, prefix: &str) -> (Option<i64>, Option<i64>) {
+    let season = elements(extensions, prefix, "season")
+        .iter()
+        .find_map(number);
+    let episode = elements(extensions, prefix, "episode")
+        .iter()
+        .find_map(number);
+
+    (season, episode)
+}
+
+/// Get the [`EpisodeExtras`] of a item
+#[must_use]
+pub fn parse_episode_extras(extensions: &ExtensionMap, prefix: &str) -> EpisodeExtras {
+    let transcripts = elements(extensions, prefix, "transcript")
+        .iter()
+        .filter_map(|ext| {
+            Some(Transcript {
+                url: attr(ext, "url")?,
+                mime_type: attr(ext, "type")?,
+                language: attr(ext, "language"),
+                rel: attr(ext, "rel"),
+            })
+        })
+        .collect();
+    let chapters = elements(extensions, prefix, "chapters")
+        .iter()
+        .find_map(|ext| {
+            Some(Chapters {
+                url: attr(ext, "url")?,
+                mime_type: attr(ext, "type").unwrap_or_default(),
+            })
+        });
+    let persons = elements(extensions, prefix, "person")
+        .iter()
+        .filter_map(|ext| {
+            Some(Person {
+                name: text(ext)?,
+                role: attr(ext, "role"),
+                group: attr(ext, "group"),
+                img: attr(ext, "img"),
+                href: attr(ext, "href"),
+            })
+        })
+        .collect();
+    let soundbites = elements(extensions, prefix, "soundbite")
+        .iter()
+        .filter_map(|ext| {
+            let attrs: &BTreeMap<String, String> = ext.attrs();
+            Some(Soundbite {
+                start_time: seconds(attrs.get("startTime"))?,
+                duration: seconds(attrs.get("duration"))?,
+                title: text(ext),
+            })
+        })
+        .collect();
+
+    EpisodeExtras {
+        transcripts,
+        chapters,
+        persons,
+        soundbites,
+    }
+}
+
+/// Get the `<podcast:funding>` links of a channel
+#[must_use]
+pub fn parse_funding(extensions: &ExtensionMap, prefix: &str) -> Vec<Funding> {
+    elements(extensions, prefix, "funding")
+        .iter()
+        .filter_map(|ext| {
+            Some(Funding {
+                url: attr(ext, "url")?,
+                text: text(ext).unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    // uses a different prefix than usual, to check that it is resolved by the namespace uri
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:pc="https://podcastindex.org/namespace/1.0" xmlns:itunes="http://www.itunes.com/dtds/podcast-1.0.dtd">
+  <channel>
+    <title>Namespaced Show</title>
+    <pc:funding url="https://example.com/donate">Support the show</pc:funding>
+    <item>
+      <title>Episode 5</title>
+      <enclosure url="https://example.com/5.mp3" length="1000" type="audio/mpeg"/>
+      <pc:season>2</pc:season>
+      <pc:episode>5.5</pc:episode>
+      <pc:transcript url="https://example.com/5.html" type="text/html"/>
+      <pc:transcript url="https://example.com/5.vtt" type="text/vtt" language="en" rel="captions"/>
+      <pc:chapters url="https://example.com/5.json" type="application/json+chapters"/>
+      <pc:person role="guest" href="https://example.com/jane">Jane Doe</pc:person>
+      <pc:person>John Doe</pc:person>
+      <pc:soundbite startTime="73.5" duration="60">The best part</pc:soundbite>
+      <pc:soundbite startTime="invalid" duration="60"/>
+    </item>
+    <item>
+      <title>Episode 4</title>
+      <enclosure url="https://example.com/4.mp3" length="1000" type="audio/mpeg"/>
+    </item>
+  </channel>
+</rss>"#;
+
+    #[test]
+    fn should_parse_namespace() {
+        let channel = Channel::read_from(FEED.as_bytes()).unwrap();
+        let prefix = prefix(&channel);
+        assert_eq!(prefix, "pc");
+
+        assert_eq!(
+            parse_funding(channel.extensions(), prefix),
+            vec![Funding {
+                url: "https://example.com/donate".to_string(),
+                text: "Support the show".to_string(),
+            }]
+        );
+
+        let item = &channel.items()[0];
+        assert_eq!(
+            parse_season_episode(item.extensions(), prefix),
+            (Some(2), Some(5))
+        );
+
+        let extras = parse_episode_extras(item.extensions(), prefix);
+        assert_eq!(extras.transcripts.len(), 2);
+        assert_eq!(
+            extras.synced_transcript().map(|v| v.url.as_str()),
+            Some("https://example.com/5.vtt")
+        );
+        assert_eq!(
+            extras.chapters.map(|v| v.url),
+            Some("https://example.com/5.json".to_string())
+        );
+        assert_eq!(
+            extras.persons[0],
+            Person {
+                name: "Jane Doe".to_string(),
+                role: Some("guest".to_string()),
+                group: None,
+                img: None,
+                href: Some("https://example.com/jane".to_string()),
+            }
+        );
+        assert_eq!(extras.persons[1].role, None);
+        assert_eq!(
+            extras.soundbites,
+            vec![Soundbite {
+                start_time: Duration::from_millis(73_500),
+                duration: Duration::from_secs(60),
+                title: Some("The best part".to_string()),
+            }]
+        );
+
+        let item = &channel.items()[1];
+        assert_eq!(
+            parse_season_episode(item.extensions(), prefix),
+            (None, None)
+        );
+        assert!(parse_episode_extras(item.extensions(), prefix).is_empty());
+    }
+
+    #[test]
+    fn should_store_extras_as_json() {
+        let extras = EpisodeExtras {
+            persons: vec![Person {
+                name: "Jane Doe".to_string(),
+                role: None,
+                group: None,
+                img: None,
+                href: None,
+            }],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&extras).unwrap();
+        assert!(!json.contains("transcripts"));
+        assert_eq!(extras, serde_json::from_str(&json).unwrap());
+        assert_eq!(
+            EpisodeExtras::default(),
+            serde_json::from_str("{}").unwrap()
+        );
+    }
+}
@@ -6,7 +6,9 @@ use crate::utils::StringUtils;
 
 use super::{
     episode::{Episode, EpisodeNoId},
-    Menuable, PODCAST_UNPLAYED_TOTALS_LENGTH,
+    feed::{FeedAuth, FeedCache},
+    namespace::Funding,
+    Menuable, PodcastPolicy, PODCAST_UNPLAYED_TOTALS_LENGTH,
 };
 
 /// Struct holding data about an individual podcast feed. This includes a
@@ -23,6 +25,12 @@ pub struct Podcast {
     pub last_checked: DateTime<Utc>,
     pub episodes: Vec<Episode>,
     pub image_url: Option<String>,
+    pub policy: PodcastPolicy,
+    pub cache: FeedCache,
+    pub auth: FeedAuth,
+    pub funding: Vec<Funding>,
+    /// Folder path, with nested folders separated by [`FOLDER_SEPARATOR`](super::FOLDER_SEPARATOR)
+    pub folder: Option<String>,
 }
 
 impl Podcast {
@@ -33,6 +41,18 @@ impl Podcast {
             .map(|ep| usize::from(!ep.is_played()))
             .sum()
     }
+
+    /// Group the episodes by season for display, newest season and episode number first.
+    ///
+    /// Episodes without a season come last, ties keep the newest published first.
+    pub fn sort_episodes_by_season(&mut self) {
+        self.episodes.sort_by(|a, b| {
+            b.season
+                .cmp(&a.season)
+                .then(b.number.cmp(&a.number))
+                .then(b.pubdate.cmp(&a.pubdate))
+        });
+    }
 }
 
 impl Menuable for Podcast {
@@ -100,4 +120,12 @@ pub struct PodcastNoId {
     pub last_checked: DateTime<Utc>,
     pub episodes: Vec<EpisodeNoId>,
     pub image_url: Option<String>,
+    /// Validators of the fetched feed, to skip it on the next sync if it did not change
+    pub cache: FeedCache,
+    /// Credentials the feed was fetched with, only stored when the podcast is added
+    pub auth: FeedAuth,
+    /// Links to support the podcast, from `<podcast:funding>`
+    pub funding: Vec<Funding>,
+    /// User-defined folder, only stored when the podcast is added
+    pub folder: Option<String>,
 }
@@ -0,0 +1,297 @@
+use std::cmp::Reverse;
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use chrono::{DateTime, Duration, Utc};
+
+use super::episode::Episode;
+
+/// Per-podcast settings for automatic downloads and how long downloaded files are kept
+///
+/// Parsed from and displayed as space separated `key=value` pairs, like `auto=3 keep=10 days=7 size=500`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PodcastPolicy {
+    /// Download the newest N unplayed episodes after each sync, `0` to only download manually
+    pub auto_download: u32,
+    /// Only keep the files of the newest N downloaded episodes
+    pub keep_last: Option<u32>,
+    /// Delete the files of played episodes after N days
+    pub delete_played_after_days: Option<u32>,
+    /// Delete the files of the oldest episodes once all files together are bigger than N MiB
+    pub max_size_mb: Option<u64>,
+}
+
+impl PodcastPolicy {
+    /// Whether nothing is automatically downloaded or deleted
+    #[must_use]
+    pub fn is_manual(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Get the newest `auto_download` unplayed episodes that are not downloaded yet
+    #[must_use]
+    pub fn episodes_to_download<'a>(&self, episodes: &'a [Episode]) -> Vec<&'a Episode> {
+        newest_first(episodes)
+            .into_iter()
+            .filter(|v| !v.played)
+            .take(usize::try_from(self.auto_download).unwrap_or(usize::MAX))
+            .filter(|v| v.path.is_none())
+            .collect()
+    }
+
+    /// Get the downloaded episodes whose files should be deleted at `now`
+    #[must_use]
+    pub fn episodes_to_remove<'a>(
+        &self,
+        episodes: &'a [Episode],
+        now: DateTime<Utc>,
+    ) -> Vec<&'a Episode> {
+        let mut total_size: u64 = 0;
+        let max_size = self.max_size_mb.map(|v| v.saturating_mul(1024 * 1024));
+
+        newest_first(episodes)
+            .into_iter()
+            .filter(|v| v.path.is_some())
+            .enumerate()
+            .filter(|(idx, ep)| {
+                let path = ep.path.as_deref().unwrap_or(Path::new(""));
+                total_size = total_size.saturating_add(path.metadata().map_or(0, |v| v.len()));
+
+                let too_many = self
+                    .keep_last
+                    .is_some_and(|keep| *idx >= usize::try_from(keep).unwrap_or(usize::MAX));
+                let too_big = max_size.is_some_and(|max| total_size > max);
+                let expired = self.delete_played_after_days.is_some_and(|days| {
+                    ep.played && played_since(ep, path) < now - Duration::days(i64::from(days))
+                });
+
+                too_many || too_big || expired
+            })
+            .map(|(_, ep)| ep)
+            .collect()
+    }
+}
+
+/// Order the episodes by publish date, regardless of how they are grouped for display
+///
+/// The sort is stable, so episodes without a date keep their relative order at the end.
+fn newest_first(episodes: &[Episode]) -> Vec<&Episode> {
+    let mut episodes: Vec<&Episode> = episodes.iter().collect();
+    episodes.sort_by_key(|v| Reverse(v.pubdate));
+    episodes
+}
+
+/// Get when a episode was played, episodes played before this was tracked use the file's modification time
+fn played_since(ep: &Episode, path: &Path) -> DateTime<Utc> {
+    ep.played_at.unwrap_or_else(|| {
+        path.metadata()
+            .and_then(|v| v.modified())
+            .map_or(DateTime::<Utc>::MIN_UTC, DateTime::<Utc>::from)
+    })
+}
+
+impl Display for PodcastPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_manual() {
+            return write!(f, "manual");
+        }
+
+        let mut parts = Vec::new();
+        if self.auto_download > 0 {
+            parts.push(format!("auto={}", self.auto_download));
+        }
+        if let Some(keep) = self.keep_last {
+            parts.push(format!("keep={keep}"));
+        }
+        if let Some(days) = self.delete_played_after_days {
+            parts.push(format!("days={days}"));
+        }
+        if let Some(size) = self.max_size_mb {
+            parts.push(format!("size={size}"));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl FromStr for PodcastPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut policy = Self::default();
+        for part in s.split_whitespace() {
+            if part == "manual" {
+                continue;
+            }
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow!("expected \"key=value\", found \"{part}\""))?;
+            let number = || -> anyhow::Result<u64> {
+                value
+                    .parse()
+                    .map_err(|_| anyhow!("invalid number \"{value}\" for \"{key}\""))
+            };
+            let small = || -> anyhow::Result<u32> { Ok(u32::try_from(number()?)?) };
+            match key {
+                "auto" => policy.auto_download = small()?,
+                "keep" => policy.keep_last = Some(small()?),
+                "days" => policy.delete_played_after_days = Some(small()?),
+                "size" => policy.max_size_mb = Some(number()?),
+                _ => bail!("unknown key \"{key}\", expected one of auto, keep, days, size"),
+            }
+        }
+
+        Ok(policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn episode(id: i64, played: bool, downloaded: bool) -> Episode {
+        Episode {
+            id,
+            played,
+            path: downloaded.then(|| PathBuf::from(format!("/nonexistent/{id}.mp3"))),
+            ..Default::default()
+        }
+    }
+
+    fn ids(episodes: &[&Episode]) -> Vec<i64> {
+        episodes.iter().map(|v| v.id).collect()
+    }
+
+    #[test]
+    fn should_parse_and_display() {
+        let policy: PodcastPolicy = "auto=3 keep=10 days=7 size=500".parse().unwrap();
+        assert_eq!(
+            policy,
+            PodcastPolicy {
+                auto_download: 3,
+                keep_last: Some(10),
+                delete_played_after_days: Some(7),
+                max_size_mb: Some(500),
+            }
+        );
+        assert_eq!(policy.to_string(), "auto=3 keep=10 days=7 size=500");
+
+        assert_eq!(
+            "".parse::<PodcastPolicy>().unwrap(),
+            PodcastPolicy::default()
+        );
+        assert_eq!(PodcastPolicy::default().to_string(), "manual");
+        assert!("keep".parse::<PodcastPolicy>().is_err());
+        assert!("keep=-1".parse::<PodcastPolicy>().is_err());
+        assert!("other=1".parse::<PodcastPolicy>().is_err());
+    }
+
+    #[test]
+    fn should_download_newest_unplayed() {
+        let episodes = vec![
+            episode(5, false, false),
+            episode(4, true, false),
+            episode(3, false, true),
+            episode(2, false, false),
+            episode(1, false, false),
+        ];
+        let policy = PodcastPolicy {
+            auto_download: 3,
+            ..Default::default()
+        };
+
+        // 3 is already downloaded but counts towards the newest 3
+        assert_eq!(ids(&policy.episodes_to_download(&episodes)), vec![5, 2]);
+        assert!(PodcastPolicy::default()
+            .episodes_to_download(&episodes)
+            .is_empty());
+    }
+
+    #[test]
+    fn should_remove_by_retention() {
+        let now = Utc::now();
+        let mut played_long_ago = episode(4, true, true);
+        played_long_ago.played_at = Some(now - Duration::days(10));
+        let mut played_recently = episode(3, true, true);
+        played_recently.played_at = Some(now - Duration::days(1));
+        let episodes = vec![
+            episode(6, false, true),
+            episode(5, false, false),
+            played_long_ago,
+            played_recently,
+            episode(2, false, true),
+            episode(1, false, true),
+        ];
+
+        let keep = PodcastPolicy {
+            keep_last: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(ids(&keep.episodes_to_remove(&episodes, now)), vec![2, 1]);
+
+        let days = PodcastPolicy {
+            delete_played_after_days: Some(7),
+            ..Default::default()
+        };
+        assert_eq!(ids(&days.episodes_to_remove(&episodes, now)), vec![4]);
+
+        assert!(PodcastPolicy::default()
+            .episodes_to_remove(&episodes, now)
+            .is_empty());
+    }
+
+    #[test]
+    fn should_order_by_date_with_seasons() {
+        let now = Utc::now();
+        let dated = |id: i64, days: i64, season: Option<i64>, downloaded: bool| Episode {
+            pubdate: Some(now - Duration::days(days)),
+            season,
+            number: season.map(|_| id),
+            ..episode(id, false, downloaded)
+        };
+        // grouped like the episode list shows them: seasons first, seasonless episodes last
+        let mut podcast_episodes = vec![
+            dated(6, 40, Some(2), true),
+            dated(5, 50, Some(2), false),
+            dated(2, 80, Some(1), true),
+            dated(1, 90, Some(1), false),
+            dated(8, 1, None, false),
+            dated(7, 2, None, true),
+            dated(4, 60, None, false),
+            dated(3, 70, None, true),
+        ];
+
+        let download = PodcastPolicy {
+            auto_download: 4,
+            ..Default::default()
+        };
+        // 7 and 6 are already downloaded but count towards the newest 4
+        assert_eq!(
+            ids(&download.episodes_to_download(&podcast_episodes)),
+            vec![8, 5]
+        );
+
+        let keep = PodcastPolicy {
+            keep_last: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            ids(&keep.episodes_to_remove(&podcast_episodes, now)),
+            vec![3, 2]
+        );
+
+        // the same result in database order
+        podcast_episodes.sort_by_key(|v| Reverse(v.pubdate));
+        assert_eq!(
+            ids(&download.episodes_to_download(&podcast_episodes)),
+            vec![8, 5]
+        );
+        assert_eq!(
+            ids(&keep.episodes_to_remove(&podcast_episodes, now)),
+            vec![3, 2]
+        );
+    }
+}
@@ -0,0 +1,554 @@
+//! Convert the HTML show notes of a episode into styled and wrapped lines for the terminal
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use unicode_width::UnicodeWidthStr;
+
+lazy_static! {
+    /// Regex for a opening, closing or self-closing tag, or a comment
+    static ref RE_TAG: Regex = Regex::new(r"(?s)<!--.*?-->|<(/?)([a-zA-Z][a-zA-Z0-9]*)([^<>]*)>").expect("Regex error");
+
+    /// Regex for the `href` attribute of a `<a>` tag
+    static ref RE_HREF: Regex = Regex::new(r#"(?i)href\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).expect("Regex error");
+
+    /// Regex for tags that start a new line, to decide whether the notes are plain text
+    static ref RE_BLOCK_TAG: Regex = Regex::new(r"(?i)<(br|p|div|li|h[1-6])[\s/>]").expect("Regex error");
+
+    /// Regex for links written as plain text
+    static ref RE_URL: Regex = Regex::new(r"https?://[^\s<>]+").expect("Regex error");
+
+    /// Regex for timestamps like `1:02:03` or `12:34`
+    static ref RE_TIMESTAMP: Regex = Regex::new(r"\b(?:(\d{1,2}):)?([0-5]?\d):([0-5]\d)\b").expect("Regex error");
+}
+
+/// How a part of a line is shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NoteStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteSpan {
+    pub text: String,
+    pub style: NoteStyle,
+    /// Index into [`ShowNotes::links`] if this is the number of a link, like `[1]`
+    pub link: Option<usize>,
+}
+
+/// A wrapped line of the show notes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoteLine {
+    pub spans: Vec<NoteSpan>,
+    /// Timestamps in the line that can be seeked to
+    pub timestamps: Vec<Duration>,
+    /// Indexes into [`ShowNotes::links`] of the links numbered in the line
+    pub links: Vec<usize>,
+}
+
+impl NoteLine {
+    fn new(spans: Vec<NoteSpan>) -> Self {
+        let text: String = spans.iter().map(|v| v.text.as_str()).collect();
+        let timestamps = RE_TIMESTAMP
+            .captures_iter(&text)
+            .filter_map(|cap| {
+                let part = |idx: usize| cap.get(idx).map_or(Some(0), |v| v.as_str().parse().ok());
+                Some(Duration::from_secs(
+                    part(1)? * 3600 + part(2)? * 60 + part(3)?,
+                ))
+            })
+            .collect();
+        let links = spans.iter().filter_map(|v| v.link).collect();
+
+        Self {
+            spans,
+            timestamps,
+            links,
+        }
+    }
+
+    /// The text of the line without styles
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|v| v.text.as_str()).collect()
+    }
+}
+
+/// Show notes converted from HTML, links are numbered in the text and listed at the end
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShowNotes {
+    pub lines: Vec<NoteLine>,
+    pub links: Vec<String>,
+}
+
+impl ShowNotes {
+    /// Convert the HTML (or plain text) `notes` into lines at most `width` wide,
+    /// unless a single word is wider
+    #[must_use]
+    pub fn from_html(notes: &str, width: usize) -> Self {
+        // plain text notes only have line breaks to separate paragraphs
+        let notes = if RE_BLOCK_TAG.is_match(notes) {
+            notes.to_string()
+        } else {
+            notes.replace("\r\n", "\n").replace('\n', "<br>")
+        };
+
+        let mut builder = Builder::default();
+        let mut last_end = 0;
+        for cap in RE_TAG.captures_iter(&notes) {
+            let Some(all) = cap.get(0) else {
+                continue;
+            };
+            builder.text(&notes[last_end..all.start()]);
+            last_end = all.end();
+            if let Some(name) = cap.get(2) {
+                let closing = !cap[1].is_empty();
+                builder.tag(&name.as_str().to_lowercase(), closing, &cap[3]);
+            }
+        }
+        builder.text(&notes[last_end..]);
+        builder.end_block(false);
+
+        let Builder {
+            mut blocks, links, ..
+        } = builder;
+        // no blank lines at the end
+        while blocks.last().is_some_and(|v| v.spans.is_empty()) {
+            blocks.pop();
+        }
+
+        let mut lines: Vec<NoteLine> = blocks
+            .iter()
+            .flat_map(|block| wrap(block, width))
+            .map(NoteLine::new)
+            .collect();
+
+        if !links.is_empty() {
+            lines.push(NoteLine::default());
+            lines.push(NoteLine::new(vec![NoteSpan {
+                text: "Links:".to_string(),
+                style: NoteStyle {
+                    bold: true,
+                    ..Default::default()
+                },
+                link: None,
+            }]));
+            for (idx, url) in links.iter().enumerate() {
+                lines.push(NoteLine::new(vec![
+                    NoteSpan {
+                        text: format!("[{}] ", idx + 1),
+                        style: NoteStyle::default(),
+                        link: Some(idx),
+                    },
+                    NoteSpan {
+                        text: url.clone(),
+                        style: NoteStyle {
+                            underlined: true,
+                            ..Default::default()
+                        },
+                        link: None,
+                    },
+                ]));
+            }
+        }
+
+        Self { lines, links }
+    }
+}
+
+/// A paragraph, heading or list item before it is wrapped
+#[derive(Debug, Default)]
+struct Block {
+    /// Shown before the first line, like a list marker, the other lines are indented by its width
+    prefix: String,
+    spans: Vec<NoteSpan>,
+}
+
+#[derive(Debug, Default)]
+struct Builder {
+    blocks: Vec<Block>,
+    current: Block,
+    bold: usize,
+    italic: usize,
+    underlined: usize,
+    /// Open lists, with the number of the last item for ordered lists
+    lists: Vec<Option<usize>>,
+    /// Index of the link of the open `<a>`
+    link: Option<usize>,
+    links: Vec<String>,
+    /// Inside of a `<script>` or `<style>`
+    skip: usize,
+}
+
+impl Builder {
+    fn style(&self) -> NoteStyle {
+        NoteStyle {
+            bold: self.bold > 0,
+            italic: self.italic > 0,
+            underlined: self.underlined > 0,
+        }
+    }
+
+    fn push(&mut self, text: &str, link: Option<usize>) {
+        let style = self.style();
+        if let Some(last) = self.current.spans.last_mut() {
+            if last.style == style && last.link.is_none() && link.is_none() {
+                last.text.push_str(text);
+                return;
+            }
+        }
+        self.current.spans.push(NoteSpan {
+            text: text.to_string(),
+            style,
+            link,
+        });
+    }
+
+    fn add_link(&mut self, url: &str) -> usize {
+        if let Some(idx) = self.links.iter().position(|v| v == url) {
+            return idx;
+        }
+        self.links.push(url.to_string());
+        self.links.len() - 1
+    }
+
+    fn push_link_ref(&mut self, idx: usize) {
+        self.push(&format!(" [{}]", idx + 1), Some(idx));
+    }
+
+    fn text(&mut self, text: &str) {
+        if self.skip > 0 || text.is_empty() {
+            return;
+        }
+        let decoded = escaper::decode_html(text).unwrap_or_else(|_| text.to_string());
+        // collapse whitespace like a browser does, it is only kept between words
+        let words = decoded.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut collapsed = String::new();
+        if decoded.starts_with(char::is_whitespace) && !self.current.spans.is_empty() {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(&words);
+        if decoded.ends_with(char::is_whitespace) && !words.is_empty() {
+            collapsed.push(' ');
+        }
+        if collapsed.is_empty() {
+            return;
+        }
+
+        if self.link.is_some() {
+            self.push(&collapsed, None);
+            return;
+        }
+        // number links written as plain text as well
+        let mut last_end = 0;
+        for url in RE_URL.find_iter(&collapsed) {
+            let url_text = url
+                .as_str()
+                .trim_end_matches(['.', ',', ';', ':', ')', '!', '?']);
+            let end = url.start() + url_text.len();
+            self.push(&collapsed[last_end..end], None);
+            let idx = self.add_link(url_text);
+            self.push_link_ref(idx);
+            last_end = end;
+        }
+        self.push(&collapsed[last_end..], None);
+    }
+
+    /// Finish the current block, and add a blank line after it if `blank` is set
+    fn end_block(&mut self, blank: bool) {
+        let mut block = std::mem::take(&mut self.current);
+        if let Some(last) = block.spans.last_mut() {
+            last.text.truncate(last.text.trim_end().len());
+        }
+        block.spans.retain(|v| !v.text.is_empty());
+        if !block.spans.is_empty() {
+            self.blocks.push(block);
+        }
+        if blank && self.blocks.last().is_some_and(|v| !v.spans.is_empty()) {
+            self.blocks.push(Block::default());
+        }
+    }
+
+    /// Start a new line, keeping empty lines unlike [`Builder::end_block`]
+    fn line_break(&mut self) {
+        let block = std::mem::take(&mut self.current);
+        // at most one blank line in a row
+        if !block.spans.is_empty() || self.blocks.last().is_some_and(|v| !v.spans.is_empty()) {
+            self.blocks.push(block);
+        }
+    }
+
+    fn tag(&mut self, name: &str, closing: bool, attrs: &str) {
+        match name {
+            "script" | "style" => {
+                if closing {
+                    self.skip = self.skip.saturating_sub(1);
+                } else {
+                    self.skip += 1;
+                }
+            }
+            "br" => self.line_break(),
+            "p" | "div" | "blockquote" | "pre" | "table" | "section" | "article" => {
+                self.end_block(true);
+            }
+            "tr" | "dt" | "dd" => self.end_block(false),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.end_block(true);
+                if closing {
+                    self.bold = self.bold.saturating_sub(1);
+                    self.underlined = self.underlined.saturating_sub(1);
+                } else {
+                    self.bold += 1;
+                    self.underlined += 1;
+                }
+            }
+            "ul" | "ol" => {
+                if closing {
+                    self.lists.pop();
+                } else {
+                    self.lists.push((name == "ol").then_some(0));
+                }
+                self.end_block(closing && self.lists.is_empty());
+            }
+            "li" => {
+                self.end_block(false);
+                if closing {
+                    return;
+                }
+                let indent = "  ".repeat(self.lists.len().saturating_sub(1));
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{number}. ")
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.current.prefix = format!("{indent}{marker}");
+            }
+            "b" | "strong" => {
+                if closing {
+                    self.bold = self.bold.saturating_sub(1);
+                } else {
+                    self.bold += 1;
+                }
+            }
+            "i" | "em" => {
+                if closing {
+                    self.italic = self.italic.saturating_sub(1);
+                } else {
+                    self.italic += 1;
+                }
+            }
+            "u" => {
+                if closing {
+                    self.underlined = self.underlined.saturating_sub(1);
+                } else {
+                    self.underlined += 1;
+                }
+            }
+            "a" => {
+                if closing {
+                    if let Some(idx) = self.link.take() {
+                        self.underlined = self.underlined.saturating_sub(1);
+                        self.push_link_ref(idx);
+                    }
+                    return;
+                }
+                let href = RE_HREF.captures(attrs).and_then(|cap| {
+                    cap.get(1)
+                        .or(cap.get(2))
+                        .or(cap.get(3))
+                        .map(|v| v.as_str().trim().to_string())
+                });
+                let href = href.map(|v| escaper::decode_html(&v).unwrap_or(v));
+                if let Some(href) = href.filter(|v| {
+                    v.starts_with("http://")
+                        || v.starts_with("https://")
+                        || v.starts_with("mailto:")
+                }) {
+                    if self.link.is_none() {
+                        self.underlined += 1;
+                    }
+                    self.link = Some(self.add_link(&href));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Wrap a block into lines of at most `width`, words wider than that get a line of their own
+fn wrap(block: &Block, width: usize) -> Vec<Vec<NoteSpan>> {
+    if block.spans.is_empty() {
+        return vec![Vec::new()];
+    }
+
+    // split into words, remembering whether a space was before them
+    let mut words: Vec<(bool, NoteSpan)> = Vec::new();
+    let mut space_before = false;
+    for span in &block.spans {
+        let mut rest = span.text.as_str();
+        while !rest.is_empty() {
+            let trimmed = rest.trim_start();
+            space_before |= trimmed.len() != rest.len();
+            rest = trimmed;
+            if rest.is_empty() {
+                break;
+            }
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            words.push((
+                space_before,
+                NoteSpan {
+                    text: rest[..end].to_string(),
+                    style: span.style,
+                    link: span.link,
+                },
+            ));
+            space_before = false;
+            rest = &rest[end..];
+        }
+    }
+
+    let indent = " ".repeat(block.prefix.width());
+    let new_line = |prefix: &str| -> (Vec<NoteSpan>, usize) {
+        if prefix.is_empty() {
+            return (Vec::new(), 0);
+        }
+        let span = NoteSpan {
+            text: prefix.to_string(),
+            style: NoteStyle::default(),
+            link: None,
+        };
+        (vec![span], prefix.width())
+    };
+
+    let mut lines = Vec::new();
+    let (mut line, mut line_width) = new_line(&block.prefix);
+    let mut line_has_words = false;
+    for (space_before, mut word) in words {
+        let space = usize::from(space_before && line_has_words);
+        let word_width = word.text.width();
+        if line_has_words && line_width + space + word_width > width {
+            lines.push(std::mem::take(&mut line));
+            (line, line_width) = new_line(&indent);
+            line_has_words = false;
+        }
+        if space_before && line_has_words {
+            line_width += 1;
+            // keep the space out of underlined links
+            match line.last_mut() {
+                Some(last) if !last.style.underlined => last.text.push(' '),
+                _ => word.text.insert(0, ' '),
+            }
+        }
+        line_width += word_width;
+        line_has_words = true;
+        match line.last_mut() {
+            Some(last)
+                if last.style == word.style && last.link.is_none() && word.link.is_none() =>
+            {
+                last.text.push_str(&word.text);
+            }
+            _ => line.push(word),
+        }
+    }
+    lines.push(line);
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn texts(notes: &ShowNotes) -> Vec<String> {
+        notes.lines.iter().map(NoteLine::text).collect()
+    }
+
+    #[test]
+    fn should_format_html() {
+        let notes = ShowNotes::from_html(
+            r#"<h2>In this episode</h2>
+<p>We talk about <b>Rust</b> and <em>terminals</em>.</p>
+<ul>
+  <li>Intro at 0:30</li>
+  <li>Main topic at 1:02:03, see <a href="https://example.com/a?b=1&amp;c=2">the docs</a></li>
+</ul>
+<ol><li>first</li><li>second</li></ol>
+<p>Visit https://example.com/shop. Or <a href='https://example.com/a?b=1&c=2'>again</a></p>"#,
+            80,
+        );
+
+        assert_eq!(
+            texts(&notes),
+            vec![
+                "In this episode",
+                "",
+                "We talk about Rust and terminals.",
+                "",
+                "• Intro at 0:30",
+                "• Main topic at 1:02:03, see the docs [1]",
+                "",
+                "1. first",
+                "2. second",
+                "",
+                "Visit https://example.com/shop [2]. Or again [1]",
+                "",
+                "Links:",
+                "[1] https://example.com/a?b=1&c=2",
+                "[2] https://example.com/shop",
+            ]
+        );
+        assert!(notes.lines[0].spans[0].style.bold);
+        assert!(notes.lines[0].spans[0].style.underlined);
+        assert_eq!(
+            notes.lines[2].spans[1],
+            NoteSpan {
+                text: "Rust ".to_string(),
+                style: NoteStyle {
+                    bold: true,
+                    ..Default::default()
+                },
+                link: None,
+            }
+        );
+
+        assert_eq!(notes.lines[4].timestamps, vec![Duration::from_secs(30)]);
+        assert_eq!(notes.lines[5].timestamps, vec![Duration::from_secs(3723)]);
+        assert_eq!(notes.lines[5].links, vec![0]);
+        assert_eq!(notes.lines[10].links, vec![1, 0]);
+        assert_eq!(notes.lines[14].links, vec![1]);
+    }
+
+    #[test]
+    fn should_keep_lines_of_plain_text() {
+        let notes =
+            ShowNotes::from_html("First line\nSecond &amp; line\n\n\n\nNew paragraph\n", 80);
+        assert_eq!(
+            texts(&notes),
+            vec!["First line", "Second & line", "", "New paragraph"]
+        );
+    }
+
+    #[test]
+    fn should_wrap_and_indent_list_items() {
+        let notes = ShowNotes::from_html(
+            "<ul><li>one two three four</li></ul><p>a verylongwordthatdoesnotfit b</p>",
+            12,
+        );
+        assert_eq!(
+            texts(&notes),
+            vec![
+                "• one two",
+                "  three four",
+                "",
+                "a",
+                "verylongwordthatdoesnotfit",
+                "b",
+            ]
+        );
+    }
+}
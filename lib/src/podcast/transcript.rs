@@ -0,0 +1,274 @@
+//! Parse time-synced podcast transcripts into a [`Lyric`], to show them like lyrics
+use anyhow::{anyhow, bail, Context, Result};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+
+use super::feed::FeedAuth;
+use super::namespace::Transcript;
+use crate::songtag::lrc::{Lyric, UnsyncedCaption};
+
+lazy_static! {
+    /// Regex for a VTT voice tag, like `<v Jane Doe>` or `<v.loud Jane>`
+    static ref RE_VOICE_TAG: Regex = Regex::new(r"<v(?:\.[^\s>]*)?\s+([^>]+)>").expect("Regex error");
+
+    /// Regex for the remaining VTT / SRT formatting tags
+    static ref RE_TAGS: Regex = Regex::new(r"</?[^<>]*>").expect("Regex error");
+}
+
+/// The transcript formats that have timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    Srt,
+    WebVtt,
+    /// See <https://github.com/Podcastindex-org/podcast-namespace/blob/main/transcripts/transcripts.md#json>
+    Json,
+}
+
+impl TranscriptFormat {
+    /// Get the format from the `type` of a `<podcast:transcript>`, `None` for formats without timestamps like HTML
+    #[must_use]
+    pub fn from_mime_type(mime_type: &str) -> Option<Self> {
+        match mime_type.trim().to_lowercase().as_str() {
+            "application/x-subrip" | "application/srt" | "text/srt" => Some(Self::Srt),
+            "text/vtt" => Some(Self::WebVtt),
+            "application/json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a transcript in `format` into captions, shown with the same timing as lyrics
+///
+/// # Errors
+///
+/// - if the JSON of a [`TranscriptFormat::Json`] transcript is invalid
+/// - if the transcript has no captions
+pub fn parse_transcript(body: &str, format: TranscriptFormat) -> Result<Lyric> {
+    let unsynced_captions = match format {
+        TranscriptFormat::Srt | TranscriptFormat::WebVtt => parse_cues(body),
+        TranscriptFormat::Json => parse_json(body)?,
+    };
+    if unsynced_captions.is_empty() {
+        bail!("The transcript has no captions");
+    }
+
+    let mut lyric = Lyric {
+        offset: 0,
+        lang_extension: None,
+        unsynced_captions,
+    };
+    // JSON transcripts often have a segment per word
+    if format == TranscriptFormat::Json {
+        lyric.merge_adjacent();
+    }
+
+    Ok(lyric)
+}
+
+/// Download a time-synced transcript with the credentials of its podcast and parse it
+///
+/// # Errors
+///
+/// - if the transcript has no timestamps, see [`Transcript::is_time_synced`]
+/// - if there is no successful response
+/// - if the transcript cannot be parsed, see [`parse_transcript`]
+pub async fn fetch_transcript(
+    transcript: &Transcript,
+    auth: &FeedAuth,
+    max_retries: usize,
+) -> Result<Lyric> {
+    let format = TranscriptFormat::from_mime_type(&transcript.mime_type)
+        .ok_or_else(|| anyhow!("Transcript type {} has no timestamps", transcript.mime_type))?;
+    let resp = super::send_feed_request(
+        &transcript.url,
+        max_retries,
+        auth,
+        reqwest::header::HeaderMap::new(),
+    )
+    .await?
+    .error_for_status()?;
+    let body = resp.text().await.context("read transcript")?;
+
+    parse_transcript(&body, format)
+}
+
+/// Parse the cues of SRT and VTT, which both are blocks of a `start --> end` line followed by the text
+fn parse_cues(body: &str) -> Vec<UnsyncedCaption> {
+    let mut captions = Vec::new();
+    let mut lines = body.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        let Some((start, _)) = line.split_once("-->") else {
+            continue;
+        };
+        let Some(time_stamp) = parse_timestamp(start) else {
+            continue;
+        };
+
+        let text: Vec<String> = lines
+            .by_ref()
+            .take_while(|v| !v.is_empty())
+            .map(|v| {
+                let v = RE_VOICE_TAG.replace_all(v, "$1: ");
+                RE_TAGS.replace_all(&v, "").trim().to_string()
+            })
+            .collect();
+        captions.push(UnsyncedCaption::new(time_stamp, text.join(" ")));
+    }
+
+    captions
+}
+
+/// Parse a timestamp like `01:02:03,456`, `01:02:03.456` or `02:03.456` into milliseconds
+fn parse_timestamp(value: &str) -> Option<i64> {
+    let value = value.trim().replace(',', ".");
+    let mut ms: i64 = 0;
+    for (idx, part) in value.rsplit(':').enumerate() {
+        let factor = match idx {
+            0 => {
+                let seconds: f64 = part.parse().ok()?;
+                #[allow(clippy::cast_possible_truncation)]
+                {
+                    ms += (seconds * 1000.0).round() as i64;
+                }
+                continue;
+            }
+            1 => 60_000,
+            2 => 3_600_000,
+            _ => return None,
+        };
+        ms += part.parse::<i64>().ok()? * factor;
+    }
+
+    Some(ms)
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonTranscript {
+    segments: Vec<JsonSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonSegment {
+    speaker: Option<String>,
+    /// In seconds
+    start_time: f64,
+    body: String,
+}
+
+/// Parse the segments of a JSON transcript, the speaker is added whenever it changes
+fn parse_json(body: &str) -> Result<Vec<UnsyncedCaption>> {
+    let transcript: JsonTranscript = serde_json::from_str(body)?;
+    let mut last_speaker = None;
+    let captions = transcript
+        .segments
+        .into_iter()
+        .map(|segment| {
+            #[allow(clippy::cast_possible_truncation)]
+            let time_stamp = (segment.start_time * 1000.0).round() as i64;
+            let text = match segment.speaker {
+                Some(speaker) if last_speaker.as_ref() != Some(&speaker) => {
+                    let text = format!("{speaker}: {}", segment.body.trim());
+                    last_speaker = Some(speaker);
+                    text
+                }
+                _ => segment.body.trim().to_string(),
+            };
+            UnsyncedCaption::new(time_stamp, text)
+        })
+        .collect();
+
+    Ok(captions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    fn captions(body: &str, format: TranscriptFormat) -> Vec<UnsyncedCaption> {
+        parse_transcript(body, format).unwrap().unsynced_captions
+    }
+
+    #[test]
+    fn should_detect_format() {
+        assert_eq!(
+            TranscriptFormat::from_mime_type("application/x-subrip"),
+            Some(TranscriptFormat::Srt)
+        );
+        assert_eq!(
+            TranscriptFormat::from_mime_type("text/VTT"),
+            Some(TranscriptFormat::WebVtt)
+        );
+        assert_eq!(TranscriptFormat::from_mime_type("text/html"), None);
+        assert_eq!(TranscriptFormat::from_mime_type("text/plain"), None);
+    }
+
+    #[test]
+    fn should_parse_srt() {
+        let srt = "1\r\n00:00:01,500 --> 00:00:04,000\r\nHello and welcome\r\nto the show\r\n\r\n2\r\n01:00:05,000 --> 01:00:07,000\r\n<i>Bye</i>\r\n";
+        assert_eq!(
+            captions(srt, TranscriptFormat::Srt),
+            vec![
+                UnsyncedCaption::new(1500, "Hello and welcome to the show".to_string()),
+                UnsyncedCaption::new(3_605_000, "Bye".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_vtt() {
+        let vtt = "WEBVTT
+
+NOTE this is ignored
+
+intro
+00:01.000 --> 00:04.000 align:start
+<v Jane Doe>Hello</v>
+
+00:00:10.250 --> 00:00:12.000
+<v.loud John>Hi <b>Jane</b>
+";
+        assert_eq!(
+            captions(vtt, TranscriptFormat::WebVtt),
+            vec![
+                UnsyncedCaption::new(1000, "Jane Doe: Hello".to_string()),
+                UnsyncedCaption::new(10_250, "John: Hi Jane".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_parse_json() {
+        let json = r#"{
+  "version": "1.0.0",
+  "segments": [
+    { "speaker": "Jane", "startTime": 0.5, "endTime": 0.9, "body": "Hello" },
+    { "speaker": "Jane", "startTime": 1.0, "endTime": 1.4, "body": "there" },
+    { "speaker": "John", "startTime": 5.0, "endTime": 6.0, "body": "Hi" }
+  ]
+}"#;
+        let lyric = parse_transcript(json, TranscriptFormat::Json).unwrap();
+        // words close to each other are merged into one line
+        assert_eq!(
+            lyric.unsynced_captions,
+            vec![
+                UnsyncedCaption::new(500, "Jane: Hello  there".to_string()),
+                UnsyncedCaption::new(5000, "John: Hi".to_string()),
+            ]
+        );
+        // like lyrics, the line is shown 2 seconds early
+        assert_eq!(
+            lyric.get_text(Duration::from_secs(3)).as_deref(),
+            Some("John: Hi")
+        );
+    }
+
+    #[test]
+    fn should_error_without_captions() {
+        assert!(parse_transcript("WEBVTT\n", TranscriptFormat::WebVtt).is_err());
+        assert!(parse_transcript("{}", TranscriptFormat::Json).is_err());
+    }
+}
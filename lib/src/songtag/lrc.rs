@@ -51,7 +51,7 @@ lazy_static! {
             .unwrap();
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Lyric {
     /// Offset in milliseconds
     ///
@@ -62,7 +62,7 @@ pub struct Lyric {
     pub unsynced_captions: Vec<UnsyncedCaption>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UnsyncedCaption {
     /// Timestamp in milliseconds
     time_stamp: i64,
@@ -184,6 +184,12 @@ impl Lyric {
 }
 
 impl UnsyncedCaption {
+    /// Create a caption which is shown from `time_stamp` in milliseconds on
+    #[must_use]
+    pub fn new(time_stamp: i64, text: String) -> Self {
+        Self { time_stamp, text }
+    }
+
     fn parse_line(line: &mut String) -> Result<Self, ()> {
         //[00:12.00]Line 1 lyrics
         // !line.starts_with('[') | !line.contains(']')
@@ -27,11 +27,16 @@ use crate::utils::get_parent_folder;
 use anyhow::{bail, Context, Result};
 use id3::frame::Lyrics;
 use lofty::config::{ParseOptions, WriteOptions};
-use lofty::id3::v2::{Frame, Id3v2Tag, UnsynchronizedTextFrame};
+use lofty::id3::v2::{
+    Frame, FrameFlags, FrameId, Id3v2Tag, PopularimeterFrame, TextInformationFrame,
+    UnsynchronizedTextFrame,
+};
 use lofty::picture::{Picture, PictureType};
 use lofty::prelude::{Accessor, AudioFile, ItemKey, TagExt, TaggedFileExt};
 use lofty::tag::{ItemValue, Tag as LoftyTag, TagItem};
 use lofty::{file::FileType, mpeg::MpegFile, probe::Probe, TextEncoding};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::convert::From;
 use std::ffi::OsStr;
 use std::fs::rename;
@@ -41,6 +46,9 @@ use std::path::Path;
 use std::str::FromStr;
 use std::time::{Duration, SystemTime};
 
+/// Album artist of compilations that do not name one
+pub const VARIOUS_ARTISTS: &str = "Various Artists";
+
 // TODO: add some kind of identifier for easy printing, like a uri that is NOT optional
 #[derive(Clone, Debug)]
 pub struct Track {
@@ -69,12 +77,19 @@ pub struct Track {
     picture: Option<Picture>,
     album_photo: Option<String>,
     file_type: Option<FileType>,
-    // Date
-    // Track
+    /// Release year
+    year: Option<u32>,
+    /// Track number on the album
+    number: Option<u32>,
+    /// Disc number of a multi-disc album
+    disc: Option<u32>,
     genre: Option<String>,
-    // Composer
+    /// Artist of the whole album, used to group compilations
+    album_artist: Option<String>,
+    composer: Option<String>,
+    /// Rating from 1 to 5 stars, read from `POPM` or `RATING` tags
+    rating: Option<u8>,
     // Performer
-    // Disc
     // Comment
     pub media_type: MediaType,
     pub podcast_localfile: Option<String>,
@@ -86,7 +101,8 @@ impl PartialEq for Track {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MediaType {
     Music,
     Podcast,
@@ -121,7 +137,13 @@ impl Track {
             picture: None,
             album_photo: ep.image_url.clone(),
             file_type: None,
+            year: None,
+            number: None,
+            disc: None,
             genre: None,
+            album_artist: None,
+            composer: None,
+            rating: None,
             media_type: MediaType::Podcast,
             podcast_localfile,
         }
@@ -150,6 +172,22 @@ impl Track {
                 song.album = tag.album().map(std::borrow::Cow::into_owned);
                 song.title = tag.title().map(std::borrow::Cow::into_owned);
                 song.genre = tag.genre().map(std::borrow::Cow::into_owned);
+                song.year = tag.year();
+                song.number = tag.track();
+                song.disc = tag.disk();
+                song.album_artist = tag.get_string(&ItemKey::AlbumArtist).map(str::to_string);
+                // compilations without a album artist would otherwise be split by every track artist
+                if song.album_artist.is_none()
+                    && tag
+                        .get_string(&ItemKey::FlagCompilation)
+                        .is_some_and(|v| v == "1")
+                {
+                    song.album_artist = Some(VARIOUS_ARTISTS.to_string());
+                }
+                song.composer = tag.get_string(&ItemKey::Composer).map(str::to_string);
+                song.rating = tag
+                    .get(&ItemKey::Popularimeter)
+                    .and_then(|v| parse_rating(v.value()));
                 song.media_type = MediaType::Music;
 
                 if for_db {
@@ -267,7 +305,13 @@ impl Track {
             picture,
             album_photo,
             last_modified,
+            year: None,
+            number: None,
+            disc: None,
             genre,
+            album_artist: None,
+            composer: None,
+            rating: None,
             media_type: MediaType::Music,
             podcast_localfile: None,
         }
@@ -387,6 +431,55 @@ impl Track {
         self.genre = Some(genre.to_string());
     }
 
+    pub fn track_number(&self) -> Option<u32> {
+        self.number
+    }
+
+    pub fn set_track_number(&mut self, number: Option<u32>) {
+        self.number = number;
+    }
+
+    pub fn disc_number(&self) -> Option<u32> {
+        self.disc
+    }
+
+    pub fn set_disc_number(&mut self, disc: Option<u32>) {
+        self.disc = disc;
+    }
+
+    pub fn year(&self) -> Option<u32> {
+        self.year
+    }
+
+    /// Rating from 1 to 5 stars
+    pub fn rating(&self) -> Option<u8> {
+        self.rating
+    }
+
+    pub fn set_year(&mut self, year: Option<u32>) {
+        self.year = year;
+    }
+
+    /// Optionally return the artist of the whole album
+    /// If `None` the album is by [`artist`](Self::artist)
+    pub fn album_artist(&self) -> Option<&str> {
+        self.album_artist.as_deref()
+    }
+
+    /// Set the album artist, a empty value removes it
+    pub fn set_album_artist(&mut self, album_artist: &str) {
+        self.album_artist = Some(album_artist.to_string()).filter(|v| !v.is_empty());
+    }
+
+    pub fn composer(&self) -> Option<&str> {
+        self.composer.as_deref()
+    }
+
+    /// Set the composer, a empty value removes it
+    pub fn set_composer(&mut self, composer: &str) {
+        self.composer = Some(composer.to_string()).filter(|v| !v.is_empty());
+    }
+
     /// Optionally return the title of the song
     /// If `None` it wasn't able to read the tags
     pub fn title(&self) -> Option<&str> {
@@ -413,6 +506,10 @@ impl Track {
         self.duration
     }
 
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+    }
+
     pub fn duration_formatted(&self) -> String {
         Self::duration_formatted_short(&self.duration)
     }
@@ -439,6 +536,15 @@ impl Track {
                 if let Some(file_path) = self.file() {
                     let mut tag = Id3v2Tag::default();
                     self.update_tag(&mut tag);
+                    for (id, value) in [("TPE2", &self.album_artist), ("TCOM", &self.composer)] {
+                        if let Some(value) = value {
+                            tag.insert(Frame::Text(TextInformationFrame::new(
+                                FrameId::Valid(Cow::Borrowed(id)),
+                                TextEncoding::UTF8,
+                                value.clone(),
+                            )));
+                        }
+                    }
 
                     if !self.lyric_frames_is_empty() {
                         if let Some(lyric_frames) = self.lyric_frames() {
@@ -474,6 +580,14 @@ impl Track {
 
                     let mut tag = LoftyTag::new(tag_type);
                     self.update_tag(&mut tag);
+                    for (key, value) in [
+                        (ItemKey::AlbumArtist, &self.album_artist),
+                        (ItemKey::Composer, &self.composer),
+                    ] {
+                        if let Some(value) = value {
+                            tag.insert_text(key, value.clone());
+                        }
+                    }
 
                     if !self.lyric_frames_is_empty() {
                         if let Some(lyric_frames) = self.lyric_frames() {
@@ -560,6 +674,58 @@ impl Track {
 
         tag.set_album(self.album().map_or_else(String::new, str::to_string));
         tag.set_genre(self.genre().map_or_else(String::new, str::to_string));
+
+        match self.year {
+            Some(year) => tag.set_year(year),
+            None => tag.remove_year(),
+        }
+        match self.number {
+            Some(number) => tag.set_track(number),
+            None => tag.remove_track(),
+        }
+        match self.disc {
+            Some(disc) => tag.set_disk(disc),
+            None => tag.remove_disk(),
+        }
+    }
+}
+
+/// Convert a rating tag to 1 to 5 stars, `None` for unrated
+///
+/// `POPM` frames rate from 1 to 255, text ratings are used by other tag formats with differing scales:
+/// 1 to 5 stars, 0 to 100 percent or 0.0 to 1.0.
+fn parse_rating(value: &ItemValue) -> Option<u8> {
+    // the scale of `POPM`, as used by most players
+    let from_byte = |v: u8| match v {
+        0 => None,
+        1..=31 => Some(1),
+        32..=95 => Some(2),
+        96..=159 => Some(3),
+        160..=223 => Some(4),
+        _ => Some(5),
+    };
+
+    match value {
+        ItemValue::Binary(bytes) => {
+            PopularimeterFrame::parse(&mut &bytes[..], FrameFlags::default())
+                .ok()
+                .and_then(|v| from_byte(v.rating))
+        }
+        ItemValue::Text(text) => {
+            let text = text.trim();
+            if text.contains('.') {
+                let fraction: f32 = text.parse().ok().filter(|v| (0.0..=1.0).contains(v))?;
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                return Some((fraction * 5.0).round() as u8).filter(|v| *v > 0);
+            }
+            match text.parse::<u32>().ok()? {
+                0 => None,
+                v @ 1..=5 => u8::try_from(v).ok(),
+                v @ 6..=100 => u8::try_from((v + 10) / 20).ok().filter(|v| *v > 0),
+                v => from_byte(u8::try_from(v).ok()?),
+            }
+        }
+        ItemValue::Locator(_) => None,
     }
 }
 
@@ -576,3 +742,28 @@ fn create_lyrics(tag: &mut LoftyTag, lyric_frames: &mut Vec<Lyrics>) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_parse_ratings() {
+        let text = |v: &str| parse_rating(&ItemValue::Text(v.to_string()));
+        let popm = |rating: u8| {
+            let frame = PopularimeterFrame::new("player@example.com".to_string(), rating, 3);
+            parse_rating(&ItemValue::Binary(frame.as_bytes().unwrap()))
+        };
+
+        assert_eq!(popm(0), None);
+        assert_eq!(popm(1), Some(1));
+        assert_eq!(popm(196), Some(4));
+        assert_eq!(popm(255), Some(5));
+        assert_eq!(text("4"), Some(4));
+        assert_eq!(text("80"), Some(4));
+        assert_eq!(text("0.6"), Some(3));
+        assert_eq!(text("0"), None);
+        assert_eq!(text("good"), None);
+    }
+}
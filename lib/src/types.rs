@@ -1,9 +1,12 @@
 use crate::config::v2::tui::{keys::KeyBinding, theme::styles::ColorTermusic};
 use crate::invidious::{Instance, YoutubeVideo};
-use crate::podcast::{EpData, PodcastFeed, PodcastNoId};
+use crate::library_db::{DuplicateReport, KeepPolicy};
+use crate::podcast::{PodcastFeed, PodcastNoId};
+use crate::songtag::lrc::Lyric;
 use crate::songtag::SongTag;
 use anyhow::{anyhow, Result};
 use image::DynamicImage;
+use std::time::Duration;
 
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub enum Msg {
@@ -33,6 +36,9 @@ pub enum Msg {
     PlayerSpeedDown,
     PlayerSeekForward,
     PlayerSeekBackward,
+    PlayerSleepTimerCycle,
+    PlayerSleepTimerExtend,
+    PlayerSleepTimerCancel,
     Playlist(PLMsg),
     Podcast(PCMsg),
     QuitPopupCloseCancel,
@@ -42,6 +48,7 @@ pub enum Msg {
     SavePlaylistPopupCloseCancel,
     SavePlaylistPopupUpdate(String),
     SavePlaylistPopupCloseOk(String),
+    SavePlaylistPopupTogglePaths,
     SavePlaylistConfirmCloseCancel,
     SavePlaylistConfirmCloseOk(String),
     TagEditor(TEMsg),
@@ -333,12 +340,30 @@ pub enum LIMsg {
     SwitchRoot,
     AddRoot,
     RemoveRoot,
+    /// Search the library for likely duplicates in the background
+    FindDuplicates,
+    DuplicatesFound(DuplicateReport),
+    /// Change which file of each duplicate group is kept
+    DuplicatesKeep(KeepPolicy),
+    /// Go through the groups, asking to delete all but the kept file of each group
+    DuplicatesDelete,
+    /// Delete the files of the group waiting for confirmation and ask for the next group
+    DuplicatesDeleteGroup,
+    /// Keep the files of the group waiting for confirmation and ask for the next group
+    DuplicatesSkipGroup,
+    /// Stop asking for the remaining groups
+    DuplicatesDeleteStop,
+    DuplicatesClose,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DBMsg {
     AddAllToPlaylist,
     AddPlaylist(usize),
+    /// Add the track at INDEX to the front of the up-next queue
+    EnqueueNext(usize),
+    /// Add the track at INDEX to the end of the up-next queue
+    EnqueueLast(usize),
     CriteriaBlurDown,
     CriteriaBlurUp,
     SearchResult(usize),
@@ -356,25 +381,48 @@ pub enum PCMsg {
     EpisodeBlurDown,
     EpisodeBlurUp,
     PodcastAddPopupShow,
-    PodcastAddPopupCloseOk(String),
+    PodcastAddPopupCloseOk(PodcastAddInput),
     PodcastAddPopupCloseCancel,
-    SyncData((i64, PodcastNoId)),
-    NewData(PodcastNoId),
+    /// Show the download and retention policy editor for the feed at INDEX
+    PolicyPopupShow(usize),
+    PolicyPopupCloseOk(String),
+    PolicyPopupCloseCancel,
+    /// Show the folder editor for the feed at ROW of the feed list
+    FolderPopupShow(usize),
+    FolderPopupCloseOk(String),
+    FolderPopupCloseCancel,
+    SyncData((i64, Box<PodcastNoId>)),
+    NewData(Box<PodcastNoId>),
     Error(String, PodcastFeed),
+    /// Select the feed at ROW of the feed list, or fold / unfold the folder at it
     PodcastSelected(usize),
     DescriptionUpdate,
     EpisodeAdd(usize),
+    EpisodeEnqueueNext(usize),
+    EpisodeEnqueueLast(usize),
     EpisodeMarkPlayed(usize),
     EpisodeMarkAllPlayed,
+    /// Mark all episodes of the feed or folder at ROW of the feed list played
+    FeedsMarkAllPlayed(usize),
+    /// Refresh the feed at ROW of the feed list
     PodcastRefreshOne(usize),
+    /// Refresh all feeds, or only those of the folder selected in the feed list
     PodcastRefreshAll,
     FetchPodcastStart(String),
     EpisodeDownload(usize),
-    DLStart(EpData),
-    DLComplete(EpData),
-    DLResponseError(EpData),
-    DLFileCreateError(EpData),
-    DLFileWriteError(EpData),
+    EpisodeCancelDownload(usize),
+    DownloadQueueShow,
+    DownloadQueueClose,
+    /// Queue all failed downloads again
+    DownloadQueueRetry,
+    /// Move the item at INDEX in the download queue up, or down if `true`
+    DownloadQueueMove(usize, bool),
+    /// Pause or resume the item at INDEX in the download queue
+    DownloadQueueTogglePause(usize),
+    /// Remove the item at INDEX from the download queue, canceling it if running
+    DownloadQueueRemove(usize),
+    /// Remove all items that are not running from the download queue
+    DownloadQueueClear,
     EpisodeDeleteFile(usize),
     FeedDeleteShow,
     FeedDeleteCloseOk,
@@ -386,6 +434,17 @@ pub enum PCMsg {
     SearchItunesCloseOk(usize),
     SearchSuccess(Vec<PodcastFeed>),
     SearchError(String),
+    /// The time-synced transcript of the episode with URL was fetched
+    TranscriptLoaded(String, Box<Lyric>),
+    /// Show the notes of the episode at INDEX
+    ShowNotesShow(usize),
+    ShowNotesClose,
+    /// Seek to the position from the notes of the episode with URL, if it is playing
+    ShowNotesSeek(String, Duration),
+    /// Open a link from the notes with the default application
+    ShowNotesOpenLink(String),
+    /// Copy a link from the notes to the clipboard, through the terminal
+    ShowNotesCopyLink(String),
 }
 
 /// Playlist Library View messages
@@ -419,6 +478,52 @@ pub enum PLMsg {
     AddRandomAlbum,
     /// Start choosing random tracks to be added to the playlist
     AddRandomTracks,
+    /// Add a directory / file to the front of the up-next queue
+    EnqueueNext(String),
+    /// Add a directory / file to the end of the up-next queue
+    EnqueueLast(String),
+    /// Clear the up-next queue
+    QueueClear,
+    /// Undo the last playlist change
+    Undo,
+    /// Redo the last undone playlist change
+    Redo,
+    /// Switch to the next named playlist
+    NamedPlaylistNext,
+    /// Switch to the previous named playlist
+    NamedPlaylistPrev,
+    /// Show the popup to name a new playlist
+    NamedPlaylistCreateShow,
+    /// Show the popup to rename the active playlist
+    NamedPlaylistRenameShow,
+    /// Close the naming popup without any changes
+    NamedPlaylistNameCancel,
+    /// Create a new empty named playlist with NAME
+    NamedPlaylistCreate(String),
+    /// Rename the active named playlist to NAME
+    NamedPlaylistRename(String),
+    /// Copy the active named playlist
+    NamedPlaylistDuplicate,
+    /// Show the confirmation to delete the active named playlist
+    NamedPlaylistDeleteShow,
+    /// Close the delete confirmation without deleting
+    NamedPlaylistDeleteCancel,
+    /// Delete the active named playlist
+    NamedPlaylistDelete,
+    /// Add the tracks of the playlist import report
+    ImportConfirm,
+    /// Close the playlist import report without adding anything
+    ImportCancel,
+    /// Show the options to sort the playlist by
+    SortShow,
+    /// Sort the playlist by the option at INDEX of the sort popup
+    Sort(usize),
+    /// Show the options to remove duplicates from the playlist
+    DedupShow,
+    /// Remove duplicates with the option at INDEX of the dedup popup
+    Dedup(usize),
+    /// Close the sort / dedup popup without any changes
+    EditCancel,
 }
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GSMsg {
@@ -480,6 +585,16 @@ pub enum TFMsg {
     InputAlbumBlurUp,
     InputGenreBlurDown,
     InputGenreBlurUp,
+    InputAlbumArtistBlurDown,
+    InputAlbumArtistBlurUp,
+    InputComposerBlurDown,
+    InputComposerBlurUp,
+    InputYearBlurDown,
+    InputYearBlurUp,
+    InputTrackNumberBlurDown,
+    InputTrackNumberBlurUp,
+    InputDiscNumberBlurDown,
+    InputDiscNumberBlurUp,
     SelectLyricBlurDown,
     SelectLyricBlurUp,
     TableLyricOptionsBlurDown,
@@ -497,6 +612,9 @@ pub enum Id {
     DeleteConfirmRadioPopup,
     DeleteConfirmInputPopup,
     DownloadSpinner,
+    DownloadQueuePopup,
+    DuplicatesPopup,
+    DuplicatesConfirmPopup,
     Episode,
     ErrorPopup,
     GeneralSearchInput,
@@ -508,16 +626,25 @@ pub enum Id {
     Lyric,
     MessagePopup,
     Playlist,
+    PlaylistDeleteConfirm,
+    PlaylistEditPopup,
+    PlaylistImportPopup,
+    PlaylistNamePopup,
+    PlaylistTabs,
     Podcast,
     PodcastAddPopup,
+    PodcastFolderPopup,
+    PodcastPolicyPopup,
     PodcastSearchTablePopup,
     FeedDeleteConfirmRadioPopup,
     FeedDeleteConfirmInputPopup,
     Progress,
+    Queue,
     QuitPopup,
     SavePlaylistPopup,
     SavePlaylistLabel,
     SavePlaylistConfirm,
+    ShowNotesPopup,
     TagEditor(IdTagEditor),
     YoutubeSearchInputPopup,
     YoutubeSearchTablePopup,
@@ -531,6 +658,11 @@ pub enum IdTagEditor {
     InputTitle,
     InputAlbum,
     InputGenre,
+    InputAlbumArtist,
+    InputComposer,
+    InputYear,
+    InputTrackNumber,
+    InputDiscNumber,
     SelectLyric,
     TableLyricOptions,
     TextareaLyric,
@@ -677,6 +809,17 @@ pub struct ImageWrapper {
 }
 impl Eq for ImageWrapper {}
 
+/// The fields of the add-feed popup, as typed
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct PodcastAddInput {
+    /// Feed url, or the term to search feeds for
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    /// Extra headers, see [`FeedHeaders`](crate::podcast::feed::FeedHeaders)
+    pub headers: String,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct YoutubeOptions {
     pub items: Vec<YoutubeVideo>,
@@ -10,6 +10,7 @@ use std::{
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::config::ServerOverlay;
+use crate::playlist::PlaylistEntry;
 
 pub fn get_pin_yin(input: &str) -> String {
     let mut b = String::new();
@@ -108,19 +109,26 @@ pub fn create_podcast_dir(config: &ServerOverlay, pod_title: String) -> Result<P
 }
 
 pub fn playlist_get_vec(current_node: &str) -> Result<Vec<String>> {
+    let entries = playlist_get_entries(current_node)?;
+
+    // TODO: refactor to return better values
+    Ok(entries
+        .into_iter()
+        .map(|v| v.location.to_string())
+        .collect())
+}
+
+/// Read the playlist file at `current_node`, with relative paths resolved against the playlist's directory
+pub fn playlist_get_entries(current_node: &str) -> Result<Vec<PlaylistEntry>> {
     let p = Path::new(current_node);
     let p_base = absolute_path(p.parent().ok_or_else(|| anyhow!("cannot find path root"))?)?;
     let str = std::fs::read_to_string(p)?;
-    let items =
-        crate::playlist::decode(&str).map_err(|e| anyhow!("playlist decode error: {}", e))?;
-    let mut vec = vec![];
-    for mut item in items {
-        item.absoluteize(&p_base);
-
-        // TODO: refactor to return better values
-        vec.push(item.to_string());
+    let mut entries = crate::playlist::decode_entries(&str)
+        .map_err(|e| anyhow!("playlist decode error: {}", e))?;
+    for entry in &mut entries {
+        entry.location.absoluteize(&p_base);
     }
-    Ok(vec)
+    Ok(entries)
 }
 
 /// Some helper functions for dealing with Unicode strings.
@@ -46,6 +46,30 @@ pub mod player {
             }
         }
     }
+
+    impl From<termusiclib::library_db::PlaylistDB> for PlaylistInfo {
+        fn from(value: termusiclib::library_db::PlaylistDB) -> Self {
+            Self {
+                id: value.id,
+                name: value.name,
+                track_count: value.track_count as u64,
+                current_index: value.current_index as u64,
+                active: value.active,
+            }
+        }
+    }
+
+    impl From<termusiclib::library_db::TrackDB> for LibraryTrack {
+        fn from(value: termusiclib::library_db::TrackDB) -> Self {
+            Self {
+                file: value.file,
+                artist: value.artist,
+                title: value.title,
+                album: value.album,
+                duration: Some(value.duration.into()),
+            }
+        }
+    }
 }
 
 #[cfg(feature = "gst")]
@@ -57,16 +81,20 @@ mod rusty_backend;
 mod discord;
 mod mpris;
 pub mod playlist;
+mod playlist_file;
+mod playlist_history;
+mod sleep_timer;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
-pub use playlist::{Playlist, Status};
+pub use playlist::{DedupKey, Playlist, QueuePosition, SortKey, Status};
 use serde::{Deserialize, Serialize};
+pub use sleep_timer::{SleepTimer, SleepTimerMode};
 use std::time::Duration;
 use termusiclib::config::v2::server::config_extra::ServerConfigVersionedDefaulted;
 use termusiclib::config::{new_shared_server_settings, ServerOverlay, SharedServerSettings};
 use termusiclib::library_db::DataBase;
-use termusiclib::podcast::db::Database as DBPod;
+use termusiclib::podcast::db::{Database as DBPod, DownloadAction};
 use termusiclib::track::{MediaType, Track};
 use termusiclib::utils::get_app_config_path;
 use tokio::runtime::Handle;
@@ -177,7 +205,35 @@ pub enum PlayerCmd {
     CycleLoop,
     Eos,
     GetProgress,
+    /// Create a new empty named playlist, only used by clients
+    NamedPlaylistCreate(String),
+    /// Delete a named playlist by id, switching away first if it is the active one
+    NamedPlaylistDelete(u64),
+    /// Copy a named playlist by id under a new name, only used by clients
+    NamedPlaylistDuplicate(u64, String),
+    /// Fetch all named playlists, only used by clients
+    NamedPlaylistList,
+    /// Rename a named playlist by id, only used by clients
+    NamedPlaylistRename(u64, String),
+    /// Store the current playlist into the active named playlist
+    NamedPlaylistStore,
+    /// Switch the active named playlist by id
+    NamedPlaylistSwitch(u64),
     PlaySelected,
+    /// Remove duplicate tracks from the playlist
+    PlaylistDedup(DedupKey),
+    /// Redo the last undone playlist change
+    PlaylistRedo,
+    /// Sort the playlist, descending if `true`
+    PlaylistSort(SortKey, bool),
+    /// Refresh the podcast feed with the given id, or all feeds with `None`, only used by clients
+    PodcastRefresh(Option<i64>),
+    /// Refresh the podcast feeds in the given folder, only used by clients
+    PodcastRefreshFolder(String),
+    /// Change the podcast download queue for the given episodes, only used by clients
+    PodcastDownloads(DownloadAction, Vec<i64>),
+    /// Undo the last playlist change
+    PlaylistUndo,
     SkipPrevious,
     Pause,
     Play,
@@ -187,7 +243,12 @@ pub enum PlayerCmd {
     ReloadPlaylist,
     SeekBackward,
     SeekForward,
+    /// Seek to a absolute position in the current track
+    SeekTo(Duration),
     SkipNext,
+    SleepTimerCancel,
+    SleepTimerExtend(Duration),
+    SleepTimerSet(SleepTimerMode),
     SpeedDown,
     SpeedUp,
     Tick,
@@ -208,6 +269,11 @@ pub struct GeneralPlayer {
     pub db: DataBase,
     pub db_podcast: DBPod,
     pub cmd_tx: PlayerCmdSender,
+    pub sleep_timer: SleepTimer,
+    /// Position to seek to once the next track starts, instead of the track's remembered position
+    pub pending_position: Option<Duration>,
+    /// Playback was stopped by the sleep timer or at the end of the playlist and should only be started again by the user
+    stopped_on_purpose: bool,
 }
 
 impl GeneralPlayer {
@@ -252,6 +318,9 @@ impl GeneralPlayer {
             db_podcast,
             cmd_tx,
             current_track_updated: false,
+            sleep_timer: SleepTimer::default(),
+            pending_position: None,
+            stopped_on_purpose: false,
         })
     }
 
@@ -328,12 +397,21 @@ impl GeneralPlayer {
 
     /// Requires that the function is called on a thread with a entered tokio runtime
     pub fn start_play(&mut self) {
+        // a skip that did not end in a track change is not relevant anymore
+        self.sleep_timer.take_skip_requested();
         if self.playlist.is_stopped() | self.playlist.is_paused() {
             self.playlist.set_status(Status::Running);
         }
 
         self.playlist.proceed();
 
+        if self.playlist.take_stopped_at_end() {
+            info!("End of the playlist reached, stopping");
+            self.stop();
+            self.stopped_on_purpose = true;
+            return;
+        }
+
         if let Some(track) = self.playlist.current_track() {
             let track = track.clone();
 
@@ -358,7 +436,11 @@ impl GeneralPlayer {
             Handle::current().block_on(wait);
 
             self.add_and_play_mpris_discord();
-            self.player_restore_last_position();
+            if let Some(position) = self.pending_position.take() {
+                self.get_player_mut().seek_to(position);
+            } else {
+                self.player_restore_last_position();
+            }
             #[allow(irrefutable_let_patterns)]
             if let Backend::Rusty(ref mut backend) = self.backend {
                 backend.message_on_end();
@@ -394,6 +476,7 @@ impl GeneralPlayer {
     }
 
     pub fn next(&mut self) {
+        self.user_changed_track();
         if self.playlist.current_track().is_some() {
             info!("skip route 1 which is in most cases.");
             self.playlist.set_next_track(None);
@@ -411,6 +494,28 @@ impl GeneralPlayer {
         self.playlist.proceed_false();
         self.next();
     }
+    /// Note a track change requested by the user, which does not count as the end of a track for the sleep timer
+    fn user_changed_track(&mut self) {
+        if self.sleep_timer.is_active() {
+            self.sleep_timer.skip_requested();
+        }
+        self.stopped_on_purpose = false;
+    }
+
+    /// Whether playback is stopped until the user starts it again, see [`Self::sleep_timer_track_ended`]
+    #[must_use]
+    pub fn is_stopped_on_purpose(&self) -> bool {
+        self.stopped_on_purpose
+    }
+
+    /// Start playback again with the current track, if it was stopped on purpose
+    fn start_stopped(&mut self) {
+        if std::mem::take(&mut self.stopped_on_purpose) {
+            self.playlist.proceed_false();
+            self.start_play();
+        }
+    }
+
     pub fn toggle_pause(&mut self) {
         match self.playlist.status() {
             Status::Running => {
@@ -423,7 +528,7 @@ impl GeneralPlayer {
                 }
                 self.playlist.set_status(Status::Paused);
             }
-            Status::Stopped => {}
+            Status::Stopped => self.start_stopped(),
             Status::Paused => {
                 self.get_player_mut().resume();
                 if let Some(ref mut mpris) = self.mpris {
@@ -456,7 +561,8 @@ impl GeneralPlayer {
 
     pub fn play(&mut self) {
         match self.playlist.status() {
-            Status::Running | Status::Stopped => {}
+            Status::Running => {}
+            Status::Stopped => self.start_stopped(),
             Status::Paused => {
                 self.get_player_mut().resume();
                 if let Some(ref mut mpris) = self.mpris {
@@ -470,6 +576,131 @@ impl GeneralPlayer {
             }
         }
     }
+    /// Get the time until the sleep timer expires
+    ///
+    /// For track based timers this depends on the current progress and is [`None`] if the duration is not known
+    #[must_use]
+    pub fn sleep_timer_remaining(&self) -> Option<Duration> {
+        if !self.sleep_timer.is_active() {
+            return None;
+        }
+        if let Some(remaining) = self.sleep_timer.deadline_remaining() {
+            return Some(remaining);
+        }
+
+        let progress = self.get_progress()?;
+        let track_remaining = progress.total_duration?.saturating_sub(progress.position?);
+
+        if self.sleep_timer.is_end_of_album() {
+            let album_remaining: Duration = self
+                .playlist
+                .following_album_tracks()
+                .map(Track::duration)
+                .sum();
+            return Some(track_remaining + album_remaining);
+        }
+
+        Some(track_remaining)
+    }
+
+    /// Get the sleep timer state as used in the gRPC messages
+    #[must_use]
+    pub fn sleep_timer_state(&self) -> player::SleepTimerState {
+        player::SleepTimerState {
+            mode: self
+                .sleep_timer
+                .mode()
+                .map_or(SleepTimerMode::OFF, |v| v.as_u32()),
+            remaining: self.sleep_timer_remaining().map(Into::into),
+        }
+    }
+
+    /// Fade out the volume when the sleep timer is about to expire and pause once it did
+    ///
+    /// Should be called regularly, like on every [`PlayerCmd::Tick`]
+    pub fn sleep_timer_tick(&mut self) {
+        let Some(remaining) = self.sleep_timer_remaining() else {
+            return;
+        };
+
+        if self.playlist.status() == Status::Running {
+            let fade_out = Duration::from_secs(u64::from(
+                self.config.read().settings.player.sleep_timer_fade_out,
+            ));
+            let current_volume = self.volume();
+            if let Some(volume) = self
+                .sleep_timer
+                .fade_volume(remaining, fade_out, current_volume)
+            {
+                self.set_volume(volume);
+            }
+        }
+
+        // track based timers expire at the end of a track, see "sleep_timer_track_ended"
+        if self
+            .sleep_timer
+            .deadline_remaining()
+            .is_some_and(|v| v.is_zero())
+        {
+            self.sleep_timer_expire();
+        }
+    }
+
+    /// Whether a track based sleep timer expires once the current track ends
+    #[must_use]
+    pub fn sleep_timer_expires_after_track(&self) -> bool {
+        if !self.sleep_timer.is_active() || self.sleep_timer.is_skip_requested() {
+            return false;
+        }
+
+        if self.sleep_timer.is_end_of_track() {
+            true
+        } else if self.sleep_timer.is_end_of_album() {
+            !self.playlist.next_is_same_album()
+        } else {
+            false
+        }
+    }
+
+    /// Check if a track based sleep timer expires now that the current track ended, should be called
+    /// before the next track is started
+    ///
+    /// If it expired, the playlist moves on to the next track, but playback stays stopped until it is
+    /// started again. Returns `true` in that case.
+    pub fn sleep_timer_track_ended(&mut self) -> bool {
+        let expired = self.sleep_timer_expires_after_track();
+        self.sleep_timer.take_skip_requested();
+        if !expired {
+            return false;
+        }
+
+        info!("Sleep timer expired at the end of the track, stopping");
+        self.playlist.clear_current_track();
+        self.playlist.proceed();
+        // the timer decides over stopping, not the loop mode
+        self.playlist.take_stopped_at_end();
+        self.playlist.set_status(Status::Stopped);
+        self.playlist.set_next_track(None);
+        self.get_player_mut().stop();
+        self.stopped_on_purpose = true;
+        self.sleep_timer_cancel();
+
+        true
+    }
+
+    /// Cancel the sleep timer and restore the volume if it had already been faded
+    pub fn sleep_timer_cancel(&mut self) {
+        if let Some(volume) = self.sleep_timer.cancel() {
+            self.set_volume(volume);
+        }
+    }
+
+    fn sleep_timer_expire(&mut self) {
+        info!("Sleep timer expired, pausing");
+        self.pause();
+        self.sleep_timer_cancel();
+    }
+
     /// # Panics
     ///
     /// if the underlying "seek" returns a error (which current never happens)
@@ -492,6 +723,16 @@ impl GeneralPlayer {
         if !forward {
             offset = -offset;
         }
+        // seeking past the end skips to the next track
+        let skips = self.get_progress().is_some_and(|progress| {
+            let (Some(position), Some(total)) = (progress.position, progress.total_duration) else {
+                return false;
+            };
+            forward && position.as_secs().saturating_add_signed(offset) >= total.as_secs()
+        });
+        if skips {
+            self.user_changed_track();
+        }
         self.get_player_mut()
             .seek(offset)
             .expect("Error in player seek.");
@@ -535,7 +776,10 @@ impl GeneralPlayer {
                         error!("Saving last_position for podcast failed, Error: {:#?}", err);
                     }
                 }
-                MediaType::LiveRadio => (),
+                MediaType::LiveRadio => return,
+            }
+            if let Some(file) = track.file().map(ToString::to_string) {
+                self.playlist.set_resume_position(&file, position);
             }
         } else {
             info!("Not saving Last position as the position is lower than time_before_save");
@@ -558,20 +802,21 @@ impl GeneralPlayer {
             .remember_position
             .is_enabled_for(track.media_type)
         {
-            match track.media_type {
-                MediaType::Music => {
-                    if let Ok(last_pos) = self.db.get_last_position(track) {
-                        self.get_player_mut().seek_to(last_pos);
-                        restored = true;
-                    }
-                }
-                MediaType::Podcast => {
-                    if let Ok(last_pos) = self.db_podcast.get_last_position(track) {
-                        self.get_player_mut().seek_to(last_pos);
-                        restored = true;
-                    }
-                }
-                MediaType::LiveRadio => (),
+            let file = track.file().map(ToString::to_string);
+            let from_db = match track.media_type {
+                MediaType::Music => self.db.get_last_position(track).ok(),
+                MediaType::Podcast => self.db_podcast.get_last_position(track).ok(),
+                MediaType::LiveRadio => None,
+            };
+            // the playlist file still has the position if the database lost it
+            let from_playlist = file.and_then(|v| self.playlist.take_resume_position(&v));
+            if let Some(last_pos) = from_db
+                .filter(|v| !v.is_zero())
+                .or(from_playlist)
+                .or(from_db)
+            {
+                self.get_player_mut().seek_to(last_pos);
+                restored = true;
             }
         } else {
             info!(
@@ -588,6 +833,112 @@ impl GeneralPlayer {
             }
         }
     }
+    /// Make sure there is an active named playlist, creating one from the current playlist if necessary
+    ///
+    /// Returns the id of the active named playlist
+    ///
+    /// # Errors
+    ///
+    /// - if any database operation fails
+    pub fn named_playlist_init(&mut self) -> Result<u64> {
+        if let Some(active) = self.db.playlist_get_active()? {
+            return Ok(active.id);
+        }
+
+        // fallback to the first one, in case the active one got lost
+        let id = if let Some(first) = self.db.playlist_list()?.first() {
+            first.id
+        } else {
+            self.db.playlist_create(DEFAULT_PLAYLIST_NAME)?
+        };
+        self.db.playlist_set_active(id)?;
+        self.named_playlist_store()?;
+
+        Ok(id)
+    }
+
+    /// Store the current tracks, track index and position into the active named playlist
+    ///
+    /// # Errors
+    ///
+    /// - if any database operation fails
+    pub fn named_playlist_store(&mut self) -> Result<()> {
+        let Some(active) = self.db.playlist_get_active()? else {
+            return Ok(());
+        };
+        let position = self.get_player().position().unwrap_or_default();
+
+        self.db
+            .playlist_set_entries(active.id, &self.playlist.track_files())?;
+        self.db
+            .playlist_set_state(active.id, self.playlist.get_current_track_index(), position)?;
+
+        Ok(())
+    }
+
+    /// Switch the active named playlist to `id`, storing the state of the previous one
+    ///
+    /// The current track and position of the new playlist are restored and played
+    ///
+    /// # Errors
+    ///
+    /// - if the playlist does not exist
+    /// - if any database or file operation fails
+    pub fn named_playlist_switch(&mut self, id: u64) -> Result<()> {
+        let target = self
+            .db
+            .playlist_get(id)
+            .with_context(|| format!("no playlist with id {id}"))?;
+        if target.active {
+            return Ok(());
+        }
+
+        self.named_playlist_store()?;
+        let entries = self.db.playlist_get_entries(id)?;
+        self.playlist
+            .replace_tracks(&entries, target.current_index)?;
+        self.playlist.save()?;
+        self.db.playlist_set_active(id)?;
+
+        if !target.last_position.is_zero() {
+            self.pending_position = Some(target.last_position);
+        }
+        self.playlist.proceed_false();
+        self.next();
+
+        Ok(())
+    }
+
+    /// Delete the named playlist `id`, if it is the active one switch to its neighbor first
+    ///
+    /// Returns the id of the active named playlist afterwards
+    ///
+    /// # Errors
+    ///
+    /// - if it is the only named playlist
+    /// - if any database or file operation fails
+    pub fn named_playlist_delete(&mut self, id: u64) -> Result<u64> {
+        let list = self.db.playlist_list()?;
+        let index = list
+            .iter()
+            .position(|v| v.id == id)
+            .with_context(|| format!("no playlist with id {id}"))?;
+        let mut active = list.iter().find(|v| v.active).map_or(0, |v| v.id);
+
+        if active == id {
+            let Some(neighbor) = list
+                .get(index + 1)
+                .or_else(|| list.get(index.wrapping_sub(1)))
+            else {
+                bail!("cannot delete the only playlist");
+            };
+            active = neighbor.id;
+            self.named_playlist_switch(active)?;
+        }
+        self.db.playlist_delete(id)?;
+
+        Ok(active)
+    }
 }
 
 #[async_trait]
@@ -715,6 +1066,9 @@ pub type Speed = i32;
 // yes this is currently the same as speed, but for consistentcy with VolumeSigned (and maybe other types)
 pub type SpeedSigned = Speed;
 
+/// Name of the named playlist created from the existing playlist when there is none yet
+pub const DEFAULT_PLAYLIST_NAME: &str = "Default";
+
 pub const MIN_SPEED: Speed = 1;
 pub const MAX_SPEED: Speed = 30;
 
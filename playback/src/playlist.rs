@@ -1,17 +1,22 @@
+use crate::playlist_file::{parse_legacy, PlaylistFile, PlaylistFileEntry};
+use crate::playlist_history::PlaylistHistory;
 use anyhow::{bail, Context, Result};
-use pathdiff::diff_utf8_paths;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use termusiclib::config::v2::server::LoopMode;
 use termusiclib::config::SharedServerSettings;
 use termusiclib::podcast::{db::Database as DBPod, episode::Episode};
 use termusiclib::track::MediaType;
 use termusiclib::{
+    playlist::{encode, PlaylistEntry, PlaylistFormat},
     track::Track,
     utils::{filetype_supported, get_app_config_path, get_parent_folder},
 };
@@ -54,41 +59,237 @@ impl std::fmt::Display for Status {
     }
 }
 
+/// Values the tracks of a [`Playlist`] can be sorted by
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum SortKey {
+    Artist,
+    /// The album artist, or the artist for tracks without one
+    AlbumArtist,
+    /// The album, and the disc and track number within it
+    Album,
+    Title,
+    /// The disc and track number
+    TrackNumber,
+    Year,
+    Duration,
+    Path,
+    /// When the track was added to the playlist
+    DateAdded,
+}
+
+impl SortKey {
+    pub const ALL: [Self; 9] = [
+        Self::Artist,
+        Self::AlbumArtist,
+        Self::Album,
+        Self::Title,
+        Self::TrackNumber,
+        Self::Year,
+        Self::Duration,
+        Self::Path,
+        Self::DateAdded,
+    ];
+
+    #[must_use]
+    pub fn as_u32(&self) -> u32 {
+        match self {
+            SortKey::Artist => 0,
+            SortKey::Album => 1,
+            SortKey::Title => 2,
+            SortKey::TrackNumber => 3,
+            SortKey::Duration => 4,
+            SortKey::Path => 5,
+            SortKey::DateAdded => 6,
+            SortKey::AlbumArtist => 7,
+            SortKey::Year => 8,
+        }
+    }
+
+    #[must_use]
+    pub fn from_u32(key: u32) -> Self {
+        match key {
+            1 => SortKey::Album,
+            2 => SortKey::Title,
+            3 => SortKey::TrackNumber,
+            4 => SortKey::Duration,
+            5 => SortKey::Path,
+            6 => SortKey::DateAdded,
+            7 => SortKey::AlbumArtist,
+            8 => SortKey::Year,
+            _ => SortKey::Artist,
+        }
+    }
+
+    /// Compare two tracks by this key, tracks without a value are sorted after the ones with a value
+    ///
+    /// `added` holds when the tracks were added to the playlist, by path or url
+    fn compare(self, a: &Track, b: &Track, added: &HashMap<String, SystemTime>) -> Ordering {
+        fn text(v: Option<&str>) -> Option<String> {
+            v.map(str::to_lowercase)
+        }
+        fn none_last<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+            match (a, b) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+
+        let album_artist = |v: &Track| text(v.album_artist().or(v.artist()));
+        let disc_track = |a: &Track, b: &Track| {
+            none_last(a.disc_number(), b.disc_number())
+                .then_with(|| none_last(a.track_number(), b.track_number()))
+        };
+        let added_at = |v: &Track| v.file().and_then(|v| added.get(v));
+
+        match self {
+            SortKey::Artist => none_last(text(a.artist()), text(b.artist())),
+            SortKey::AlbumArtist => none_last(album_artist(a), album_artist(b)),
+            SortKey::Album => {
+                none_last(text(a.album()), text(b.album())).then_with(|| disc_track(a, b))
+            }
+            SortKey::Title => none_last(text(a.title()), text(b.title())),
+            SortKey::TrackNumber => disc_track(a, b),
+            SortKey::Year => none_last(a.year(), b.year()),
+            SortKey::Duration => a.duration().cmp(&b.duration()),
+            SortKey::Path => none_last(a.file(), b.file()),
+            SortKey::DateAdded => none_last(added_at(a), added_at(b)),
+        }
+    }
+}
+
+impl std::fmt::Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SortKey::Artist => "Artist",
+            SortKey::AlbumArtist => "Album artist",
+            SortKey::Album => "Album",
+            SortKey::Title => "Title",
+            SortKey::TrackNumber => "Track number",
+            SortKey::Year => "Year",
+            SortKey::Duration => "Duration",
+            SortKey::Path => "File path",
+            SortKey::DateAdded => "Date added",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// What makes tracks of a [`Playlist`] duplicates of each other
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+pub enum DedupKey {
+    Path,
+    /// Same artist and title, ignoring case; tracks missing either are never duplicates
+    ArtistTitle,
+}
+
+impl DedupKey {
+    #[must_use]
+    pub fn as_u32(&self) -> u32 {
+        match self {
+            DedupKey::Path => 0,
+            DedupKey::ArtistTitle => 1,
+        }
+    }
+
+    #[must_use]
+    pub fn from_u32(key: u32) -> Self {
+        match key {
+            1 => DedupKey::ArtistTitle,
+            _ => DedupKey::Path,
+        }
+    }
+
+    /// Get the value that is equal for duplicates, `None` if the track cannot have duplicates
+    fn value(self, track: &Track) -> Option<String> {
+        match self {
+            DedupKey::Path => track.file().map(ToString::to_string),
+            DedupKey::ArtistTitle => Some(format!(
+                "{}\n{}",
+                track.artist()?.to_lowercase(),
+                track.title()?.to_lowercase()
+            )),
+        }
+    }
+}
+
+/// Where to add tracks in the up-next queue
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QueuePosition {
+    /// Add the tracks before all other queued tracks
+    Next,
+    /// Add the tracks after all other queued tracks
+    Last,
+}
+
 #[derive(Default, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Playlist {
     tracks: Vec<Track>,
+    /// Tracks to play before continuing with the playlist, consumed as they are played
+    queue: VecDeque<Track>,
+    /// Indicates that the prefetched `next_track` was taken from the queue
+    next_track_from_queue: bool,
     current_track_index: usize,
     next_track_index: usize,
     played_index: Vec<usize>,
     current_track: Option<Track>,
+    /// Indicates that `current_track` was taken from the queue and has no playlist index
+    current_from_queue: bool,
     next_track: Option<Track>,
     status: Status,
     loop_mode: LoopMode,
     config: SharedServerSettings,
     need_proceed_to_next: bool,
+    /// Undo / redo steps of the track list
+    history: PlaylistHistory,
+    /// Indexes not yet played in [`LoopMode::Shuffle`], the next one is the last element
+    shuffle_order: Vec<usize>,
+    /// Set when [`LoopMode::Stop`] went past the last track
+    stopped_at_end: bool,
+    /// Where to resume tracks by path or url, persisted with the playlist
+    resume_positions: HashMap<String, Duration>,
+    /// When tracks were added to the playlist by path or url, persisted with the playlist
+    added: HashMap<String, SystemTime>,
 }
 
 impl Playlist {
     /// # Errors
     /// errors could happen when reading files
     pub fn new(config: SharedServerSettings) -> Result<Self> {
-        let (current_track_index, tracks) = Self::load()?;
-        // TODO: shouldnt "loop_mode" be combined with the config ones?
-        let loop_mode = config.read().settings.player.loop_mode;
+        let file = Self::load_file()?;
+        let tracks = Self::read_entries(&file.tracks)?;
+        let queue = Self::read_entries(&file.queue)?.into();
+        // the configured loop mode is only the default for playlists which do not have one yet
+        let loop_mode = file
+            .loop_mode
+            .unwrap_or_else(|| config.read().settings.player.loop_mode);
+        let current_track_index = file.current_track_index;
+        let resume_positions = Self::resume_positions_from(&file);
+        let added = Self::added_from(&file);
         let current_track = None;
 
         Ok(Self {
             tracks,
+            queue,
+            next_track_from_queue: false,
             next_track: None,
             // index: Some(0),
             status: Status::Stopped,
             loop_mode,
             current_track_index,
             current_track,
+            current_from_queue: false,
             played_index: Vec::new(),
             config,
             next_track_index: 0,
             need_proceed_to_next: false,
+            history: PlaylistHistory::default(),
+            shuffle_order: Vec::new(),
+            stopped_at_end: false,
+            resume_positions,
+            added,
         })
     }
 
@@ -97,6 +298,10 @@ impl Playlist {
         if self.need_proceed_to_next {
             self.next();
         } else {
+            if self.current_track.is_none() {
+                // the track at the current index is played again
+                self.current_from_queue = false;
+            }
             self.need_proceed_to_next = true;
         }
     }
@@ -105,35 +310,70 @@ impl Playlist {
         self.need_proceed_to_next = false;
     }
 
-    /// Load the playlist from the file
+    /// Load the playlist state from the file, migrating the files of previous versions if necessary
     ///
-    /// Path in `$config$/playlist.log`
+    /// Path in `$config$/playlist.toml`
     ///
     /// # Errors
-    /// errors could happen when reading file
-    /// # Panics
-    /// panics when error loading podcasts from db
-    pub fn load() -> Result<(usize, Vec<Track>)> {
+    /// errors could happen when reading or migrating the file
+    pub fn load_file() -> Result<PlaylistFile> {
         let path = get_playlist_path()?;
+        if path.exists() {
+            return PlaylistFile::load(&path);
+        }
 
-        let file = if let Ok(f) = File::open(path.as_path()) {
-            f
-        } else {
-            File::create(path.as_path())?;
-            File::open(path)?
+        Self::migrate_legacy(&path)
+    }
+
+    /// Convert the `playlist.log` and `queue.log` of previous versions into the structured playlist file at `path`
+    ///
+    /// The old files are kept with a `.bak` suffix.
+    fn migrate_legacy(path: &Path) -> Result<PlaylistFile> {
+        let config_dir = get_app_config_path()?;
+        let legacy_path = config_dir.join(LEGACY_PLAYLIST_FILENAME);
+        let legacy_queue_path = config_dir.join(LEGACY_QUEUE_FILENAME);
+        if !legacy_path.exists() && !legacy_queue_path.exists() {
+            return Ok(PlaylistFile::default());
+        }
+
+        info!("migrating {} to {}", legacy_path.display(), path.display());
+        let (current_track_index, items) =
+            parse_legacy(&fs::read_to_string(&legacy_path).unwrap_or_default());
+        let (_, queue_items) = parse_legacy(&format!(
+            "0\n{}",
+            fs::read_to_string(&legacy_queue_path).unwrap_or_default()
+        ));
+        let tracks = Self::read_tracks(items.into_iter())?;
+        let queue = Self::read_tracks(queue_items.into_iter())?;
+
+        let file = PlaylistFile {
+            current_track_index,
+            tracks: tracks
+                .iter()
+                .filter_map(|v| Self::entry_from_track(v, None, None))
+                .collect(),
+            queue: queue
+                .iter()
+                .filter_map(|v| Self::entry_from_track(v, None, None))
+                .collect(),
+            ..Default::default()
         };
-        let reader = BufReader::new(file);
-        let mut lines = reader
-            .lines()
-            .map(|line| line.unwrap_or_else(|_| "Error".to_string()));
-
-        let mut current_track_index = 0;
-        if let Some(index_line) = lines.next() {
-            if let Ok(index) = index_line.trim().parse() {
-                current_track_index = index;
+        file.save(path)?;
+
+        for legacy in [legacy_path, legacy_queue_path] {
+            if legacy.exists() {
+                let mut backup = legacy.clone().into_os_string();
+                backup.push(".bak");
+                fs::rename(&legacy, &backup)
+                    .with_context(|| format!("rename {}", legacy.display()))?;
             }
         }
 
+        Ok(file)
+    }
+
+    /// Create tracks from lines of paths or urls, as stored for named playlists
+    fn read_tracks(lines: impl Iterator<Item = String>) -> Result<Vec<Track>> {
         let mut playlist_items = Vec::new();
         let db_path = get_app_config_path()?;
         let db_podcast = DBPod::new(&db_path)?;
@@ -164,55 +404,256 @@ impl Playlist {
             }
         }
 
-        Ok((current_track_index, playlist_items))
+        Ok(playlist_items)
+    }
+
+    /// Create tracks from the entries of a [`PlaylistFile`]
+    ///
+    /// Music that cannot be read anymore is skipped, podcast episodes that are not in the database anymore
+    /// are kept as streams with their cached title and duration.
+    fn read_entries(entries: &[PlaylistFileEntry]) -> Result<Vec<Track>> {
+        let podcasts = if entries.iter().any(|v| v.media_type == MediaType::Podcast) {
+            let db_podcast = DBPod::new(&get_app_config_path()?)?;
+            db_podcast
+                .get_podcasts()
+                .with_context(|| "failed to get podcasts from db.")?
+        } else {
+            Vec::new()
+        };
+
+        let mut tracks = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match entry.media_type {
+                MediaType::Music => match Track::read_from_path(&entry.source, false) {
+                    Ok(track) => tracks.push(track),
+                    Err(e) => warn!("skipping playlist entry {}: {e:#}", entry.source),
+                },
+                MediaType::Podcast => {
+                    let episode = podcasts
+                        .iter()
+                        .flat_map(|v| &v.episodes)
+                        .find(|v| v.url == entry.source);
+                    if let Some(episode) = episode {
+                        tracks.push(Track::from_episode(episode));
+                    } else {
+                        let mut track = Track::new_radio(&entry.source);
+                        if let Some(title) = &entry.title {
+                            track.set_title(title);
+                        }
+                        if let Some(duration) = entry.duration() {
+                            track.set_duration(duration);
+                        }
+                        tracks.push(track);
+                    }
+                }
+                MediaType::LiveRadio => tracks.push(Track::new_radio(&entry.source)),
+            }
+        }
+
+        Ok(tracks)
+    }
+
+    /// Create the entry to store `track` with, `None` if the track has no source
+    fn entry_from_track(
+        track: &Track,
+        position: Option<Duration>,
+        added: Option<SystemTime>,
+    ) -> Option<PlaylistFileEntry> {
+        let duration = track.duration();
+        Some(PlaylistFileEntry {
+            media_type: track.media_type,
+            source: track.file()?.to_string(),
+            title: track.title().map(ToString::to_string),
+            duration_ms: (!duration.is_zero())
+                .then(|| u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)),
+            position_ms: position.map(|v| u64::try_from(v.as_millis()).unwrap_or(u64::MAX)),
+            added_ms: added
+                .and_then(|v| v.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map(|v| u64::try_from(v.as_millis()).unwrap_or(u64::MAX)),
+        })
+    }
+
+    /// Collect the resume positions of all entries, by path or url
+    fn resume_positions_from(file: &PlaylistFile) -> HashMap<String, Duration> {
+        file.tracks
+            .iter()
+            .chain(&file.queue)
+            .filter_map(|v| Some((v.source.clone(), v.position()?)))
+            .collect()
+    }
+
+    /// Collect when the tracks of `file` were added, by path or url
+    fn added_from(file: &PlaylistFile) -> HashMap<String, SystemTime> {
+        file.tracks
+            .iter()
+            .filter_map(|v| Some((v.source.clone(), v.added()?)))
+            .collect()
+    }
+
+    /// Remember that `tracks` are added now, unless they already are in the playlist
+    fn mark_added(&mut self, tracks: &[Track]) {
+        let now = SystemTime::now();
+        let present: HashSet<&str> = self.tracks.iter().filter_map(Track::file).collect();
+        for file in tracks.iter().filter_map(Track::file) {
+            if !present.contains(file) {
+                self.added.insert(file.to_string(), now);
+            }
+        }
     }
 
     /// # Errors
     /// Errors could happen when reading files
     pub fn reload_tracks(&mut self) -> Result<()> {
-        let (current_track_index, tracks) = Self::load()?;
+        let file = Self::load_file()?;
+        let tracks = Self::read_entries(&file.tracks)?;
+        // changes from clients only arrive this way, so record them here
+        if !Self::same_files(&self.tracks, &tracks) {
+            self.record_change();
+        }
         self.tracks = tracks;
-        self.current_track_index = current_track_index;
+        self.current_track_index = file.current_track_index;
+        self.queue = Self::read_entries(&file.queue)?.into();
+        self.resume_positions = Self::resume_positions_from(&file);
+        self.added = Self::added_from(&file);
+        Ok(())
+    }
+
+    /// Replace all tracks with the given paths or urls, as stored for a named playlist
+    ///
+    /// The up-next queue is kept, as it is not part of a named playlist
+    ///
+    /// # Errors
+    /// Errors could happen when reading the podcast database
+    pub fn replace_tracks<T: AsRef<str>>(
+        &mut self,
+        items: &[T],
+        current_track_index: usize,
+    ) -> Result<()> {
+        let lines = items.iter().map(|v| v.as_ref().to_string());
+        self.tracks = Self::read_tracks(lines)?;
+        self.added.clear();
+        let now = SystemTime::now();
+        for file in self.tracks.iter().filter_map(Track::file) {
+            self.added.insert(file.to_string(), now);
+        }
+        self.current_track_index = current_track_index.min(self.tracks.len().saturating_sub(1));
+        self.current_track = None;
+        self.current_from_queue = false;
+        self.next_track = None;
+        self.next_track_from_queue = false;
+        // the history belongs to the previous tracks
+        self.history.clear();
+        self.shuffle_order.clear();
+        Ok(())
+    }
+
+    /// Get the paths or urls of all tracks, as stored for a named playlist
+    #[must_use]
+    pub fn track_files(&self) -> Vec<String> {
+        self.tracks
+            .iter()
+            .filter_map(|v| v.file().map(ToString::to_string))
+            .collect()
+    }
+
+    /// Reload only the up-next queue from the playlist file
+    ///
+    /// # Errors
+    /// Errors could happen when reading files
+    pub fn reload_queue(&mut self) -> Result<()> {
+        let file = Self::load_file()?;
+        self.queue = Self::read_entries(&file.queue)?.into();
         Ok(())
     }
 
-    /// Save the current playlist and playing index to the playlist log
+    /// Save the tracks, playing index, up-next queue, loop mode, resume positions and when tracks were added to the playlist file
     ///
-    /// Path in `$config$/playlist.log`
+    /// Path in `$config$/playlist.toml`
     ///
     /// # Errors
     /// Errors could happen when writing files
     pub fn save(&mut self) -> Result<()> {
         let path = get_playlist_path()?;
+        let entries = |tracks: &mut dyn Iterator<Item = &Track>,
+                       added: Option<&HashMap<String, SystemTime>>|
+         -> Vec<PlaylistFileEntry> {
+            tracks
+                .filter_map(|v| {
+                    let position = v.file().and_then(|v| self.resume_positions.get(v));
+                    let added = v.file().and_then(|v| added?.get(v));
+                    Self::entry_from_track(v, position.copied(), added.copied())
+                })
+                .collect()
+        };
 
-        let file = File::create(path.as_path())?;
-        let mut writer = BufWriter::new(file);
-        let mut bytes = Vec::new();
-        bytes.extend(format!("{}", self.current_track_index).as_bytes());
-        bytes.extend("\n".as_bytes());
-        for i in &self.tracks {
-            if let Some(f) = i.file() {
-                bytes.extend(f.as_bytes());
-                bytes.extend("\n".as_bytes());
-            }
-        }
+        let file = PlaylistFile {
+            current_track_index: self.current_track_index,
+            loop_mode: Some(self.loop_mode),
+            tracks: entries(&mut self.tracks.iter(), Some(&self.added)),
+            queue: entries(&mut self.queue.iter(), None),
+            ..Default::default()
+        };
 
-        writer.write_all(&bytes)?;
-        writer.flush()?;
+        file.save(&path)
+    }
 
-        Ok(())
+    /// Remember where to resume `file` the next time it is played, a zero `position` forgets it
+    pub fn set_resume_position(&mut self, file: &str, position: Duration) {
+        if position.is_zero() {
+            self.resume_positions.remove(file);
+        } else {
+            self.resume_positions.insert(file.to_string(), position);
+        }
+    }
+
+    /// Take the position stored with [`set_resume_position`](Self::set_resume_position) for `file`
+    pub fn take_resume_position(&mut self, file: &str) -> Option<Duration> {
+        self.resume_positions.remove(file)
     }
 
     pub fn next(&mut self) {
-        self.played_index.push(self.current_track_index);
+        // a track from the queue does not have a playlist index, so the previous index is still the last played one
+        if !self.current_from_queue {
+            self.played_index.push(self.current_track_index);
+        }
+        self.current_track = None;
+        self.current_from_queue = false;
         if self.config.read().settings.player.gapless && self.has_next_track() {
-            self.current_track_index = self.next_track_index;
+            if self.next_track_from_queue {
+                // the queued track has already been given to the backend
+                self.queue.pop_front();
+                self.current_track = self.next_track.clone();
+                self.current_from_queue = true;
+            } else {
+                self.set_played_index(self.next_track_index);
+            }
             return;
         }
-        self.current_track_index = self.get_next_track_index();
+        if let Some(track) = self.queue.pop_front() {
+            self.current_track = Some(track);
+            self.current_from_queue = true;
+            return;
+        }
+        if let Some(index) = self.get_next_track_index() {
+            self.set_played_index(index);
+        } else {
+            // start from the beginning the next time playback is started
+            self.current_track_index = 0;
+            self.stopped_at_end = true;
+        }
+    }
+
+    /// Set the current index to a track that is now played, consuming it from the shuffle order
+    fn set_played_index(&mut self, index: usize) {
+        self.current_track_index = index;
+        self.current_from_queue = false;
+        if self.loop_mode == LoopMode::Shuffle {
+            self.shuffle_order.retain(|v| *v != index);
+        }
     }
 
-    fn get_next_track_index(&self) -> usize {
+    /// Get the index to play after the current one, `None` if playback should stop
+    fn get_next_track_index(&mut self) -> Option<usize> {
         let mut next_track_index = self.current_track_index;
         match self.loop_mode {
             LoopMode::Single => {}
@@ -226,19 +667,43 @@ impl Playlist {
             LoopMode::Random => {
                 next_track_index = self.get_random_index();
             }
+            LoopMode::Shuffle => {
+                next_track_index = self.get_shuffle_index();
+            }
+            LoopMode::Stop => {
+                next_track_index += 1;
+                if next_track_index >= self.len() {
+                    return None;
+                }
+            }
+            LoopMode::Album => {
+                let album = self.current_album_range();
+                next_track_index += 1;
+                if next_track_index >= album.end {
+                    next_track_index = album.start;
+                }
+            }
         }
-        next_track_index
+        Some(next_track_index)
     }
 
     pub fn previous(&mut self) {
+        self.current_from_queue = false;
         if !self.played_index.is_empty() {
             if let Some(index) = self.played_index.pop() {
+                if self.loop_mode == LoopMode::Shuffle {
+                    // play the track we came from again when going forward
+                    let current = self.current_track_index;
+                    self.shuffle_order.retain(|v| *v != current);
+                    self.shuffle_order.push(current);
+                }
                 self.current_track_index = index;
                 return;
             }
         }
         match self.loop_mode {
-            LoopMode::Single => {}
+            // without history there is nothing to go back to in shuffle, so restart the track
+            LoopMode::Single | LoopMode::Shuffle => {}
             LoopMode::Playlist => {
                 if self.current_track_index == 0 {
                     self.current_track_index = self.len() - 1;
@@ -249,9 +714,25 @@ impl Playlist {
             LoopMode::Random => {
                 self.current_track_index = self.get_random_index();
             }
+            LoopMode::Stop => {
+                self.current_track_index = self.current_track_index.saturating_sub(1);
+            }
+            LoopMode::Album => {
+                let album = self.current_album_range();
+                if self.current_track_index > album.start {
+                    self.current_track_index -= 1;
+                } else {
+                    self.current_track_index = album.end - 1;
+                }
+            }
         }
     }
 
+    /// Check and reset whether [`LoopMode::Stop`] went past the last track, in which case playback should stop
+    pub fn take_stopped_at_end(&mut self) -> bool {
+        std::mem::take(&mut self.stopped_at_end)
+    }
+
     #[must_use]
     pub fn len(&self) -> usize {
         self.tracks.len()
@@ -264,6 +745,7 @@ impl Playlist {
 
     pub fn swap_down(&mut self, index: usize) {
         if index < self.len() - 1 {
+            self.record_change();
             let track = self.tracks.remove(index);
             self.tracks.insert(index + 1, track);
             // handle index
@@ -277,6 +759,7 @@ impl Playlist {
 
     pub fn swap_up(&mut self, index: usize) {
         if index > 0 {
+            self.record_change();
             let track = self.tracks.remove(index);
             self.tracks.insert(index - 1, track);
             // handle index
@@ -314,7 +797,11 @@ impl Playlist {
     }
 
     pub fn fetch_next_track(&mut self) -> Option<&Track> {
-        self.next_track_index = self.get_next_track_index();
+        self.next_track_from_queue = !self.queue.is_empty();
+        if self.next_track_from_queue {
+            return self.queue.front();
+        }
+        self.next_track_index = self.get_next_track_index()?;
         self.tracks.get(self.next_track_index)
     }
 
@@ -340,80 +827,101 @@ impl Playlist {
     /// Cycle through the loop modes and return the new mode
     ///
     /// order:
-    /// [Random](LoopMode::Random) -> [Playlist](LoopMode::Playlist)
     /// [Playlist](LoopMode::Playlist) -> [Single](LoopMode::Single)
-    /// [Single](LoopMode::Single) -> [Random](LoopMode::Random)
+    /// [Single](LoopMode::Single) -> [Album](LoopMode::Album)
+    /// [Album](LoopMode::Album) -> [Shuffle](LoopMode::Shuffle)
+    /// [Shuffle](LoopMode::Shuffle) -> [Random](LoopMode::Random)
+    /// [Random](LoopMode::Random) -> [Stop](LoopMode::Stop)
+    /// [Stop](LoopMode::Stop) -> [Playlist](LoopMode::Playlist)
     pub fn cycle_loop_mode(&mut self) -> LoopMode {
-        match self.loop_mode {
-            LoopMode::Random => {
-                self.loop_mode = LoopMode::Playlist;
-            }
-            LoopMode::Playlist => {
-                self.loop_mode = LoopMode::Single;
-            }
-            LoopMode::Single => {
-                self.loop_mode = LoopMode::Random;
-            }
+        let loop_mode = match self.loop_mode {
+            LoopMode::Playlist => LoopMode::Single,
+            LoopMode::Single => LoopMode::Album,
+            LoopMode::Album => LoopMode::Shuffle,
+            LoopMode::Shuffle => LoopMode::Random,
+            LoopMode::Random => LoopMode::Stop,
+            LoopMode::Stop => LoopMode::Playlist,
         };
+        self.set_loop_mode(loop_mode);
+        self.loop_mode
+    }
+
+    pub fn set_loop_mode(&mut self, loop_mode: LoopMode) {
+        self.loop_mode = loop_mode;
+        // a new shuffle round starts whenever shuffle is entered
+        self.shuffle_order.clear();
+    }
+
+    #[must_use]
+    pub fn loop_mode(&self) -> LoopMode {
         self.loop_mode
     }
 
-    /// Export the current playlist to a `.m3u` playlist file
+    /// Export the current playlist to a playlist file, in the format matching the extension of `filename` (`.m3u` if unknown)
+    ///
+    /// If `relative` is set, paths are written relative to the folder of `filename`.
     ///
     /// might be confused with [save](Self::save)
     ///
     /// # Errors
     /// Error could happen when writing file to local disk.
-    pub fn save_m3u(&self, filename: &str) -> Result<()> {
+    pub fn export(&self, filename: &str, relative: bool) -> Result<()> {
         if self.tracks.is_empty() {
             bail!("Unable to save since the playlist is empty.");
         }
 
+        let format = PlaylistFormat::from_path(Path::new(filename)).unwrap_or_default();
         let parent_folder = get_parent_folder(filename);
+        let entries: Vec<PlaylistEntry> = self
+            .tracks
+            .iter()
+            .filter_map(PlaylistEntry::from_track)
+            .collect();
 
-        let m3u = self.get_m3u_file(&parent_folder);
+        let content = encode(
+            format,
+            &entries,
+            relative.then_some(Path::new(&parent_folder)),
+        );
 
-        std::fs::write(filename, m3u)?;
+        std::fs::write(filename, content)?;
         Ok(())
     }
 
-    fn get_m3u_file(&self, parent_folder: &str) -> String {
-        let mut m3u = String::from("#EXTM3U\n");
-        for track in &self.tracks {
-            if let Some(file) = track.file() {
-                let path_relative = diff_utf8_paths(file, parent_folder);
-
-                if let Some(p) = path_relative {
-                    let path = format!("{p}\n");
-                    m3u.push_str(&path);
-                }
-            }
-        }
-        m3u
-    }
-
     pub fn add_episode(&mut self, ep: &Episode) {
+        self.record_change();
         let track = Track::from_episode(ep);
+        self.mark_added(std::slice::from_ref(&track));
         self.tracks.push(track);
     }
 
     /// # Errors
     /// Error happens when track cannot be read from local file
     pub fn add_playlist<T: AsRef<str>>(&mut self, vec: &[T]) -> Result<()> {
+        let tracks = Self::tracks_from_items(vec)?;
+        self.record_change();
+        self.mark_added(&tracks);
+        self.tracks.extend(tracks);
+        Ok(())
+    }
+
+    /// Create tracks from local paths or urls, skipping unsupported files
+    fn tracks_from_items<T: AsRef<str>>(vec: &[T]) -> Result<Vec<Track>> {
+        let mut tracks = Vec::new();
         for item in vec.iter().map(AsRef::as_ref) {
             if item.starts_with("http") {
                 let track = Track::new_radio(item);
-                self.tracks.push(track);
+                tracks.push(track);
             } else if !filetype_supported(item) {
                 // TODO: add error on fail
                 error!("unsupported filetype: {:#?}", item);
                 continue;
             } else if PathBuf::from(item).exists() {
                 let track = Track::read_from_path(item, false)?;
-                self.tracks.push(track);
+                tracks.push(track);
             }
         }
-        Ok(())
+        Ok(tracks)
     }
 
     #[must_use]
@@ -421,7 +929,59 @@ impl Playlist {
         &self.tracks
     }
 
+    #[must_use]
+    pub fn queue(&self) -> &VecDeque<Track> {
+        &self.queue
+    }
+
+    /// Add tracks to the up-next queue, see [`add_playlist`](Self::add_playlist) for the accepted values
+    ///
+    /// # Errors
+    /// Error happens when track cannot be read from local file
+    pub fn add_queue<T: AsRef<str>>(&mut self, vec: &[T], position: QueuePosition) -> Result<()> {
+        let tracks = Self::tracks_from_items(vec)?;
+        self.add_queue_tracks(tracks, position);
+        Ok(())
+    }
+
+    pub fn add_queue_episode(&mut self, ep: &Episode, position: QueuePosition) {
+        self.add_queue_tracks(vec![Track::from_episode(ep)], position);
+    }
+
+    fn add_queue_tracks(&mut self, tracks: Vec<Track>, position: QueuePosition) {
+        match position {
+            QueuePosition::Next => {
+                // keep the order of "tracks" when inserting at the front
+                for track in tracks.into_iter().rev() {
+                    self.queue.push_front(track);
+                }
+            }
+            QueuePosition::Last => self.queue.extend(tracks),
+        }
+    }
+
+    pub fn clear_queue(&mut self) {
+        self.queue.clear();
+    }
+
+    /// Remove the first queued track and make it the current track
+    ///
+    /// Used by clients to mirror the server consuming the queue
+    pub fn pop_queue_as_current(&mut self) {
+        if let Some(track) = self.queue.pop_front() {
+            self.current_track = Some(track);
+            self.current_from_queue = true;
+        }
+    }
+
+    /// Returns `true` if the current track was taken from the up-next queue instead of the playlist
+    #[must_use]
+    pub fn is_current_from_queue(&self) -> bool {
+        self.current_from_queue
+    }
+
     pub fn remove(&mut self, index: usize) {
+        self.record_change();
         self.tracks.remove(index);
         // Handle index
         if index <= self.current_track_index {
@@ -434,12 +994,15 @@ impl Playlist {
     }
 
     pub fn clear(&mut self) {
+        self.record_change();
         self.tracks.clear();
         self.current_track_index = 0;
+        self.current_from_queue = false;
     }
 
     pub fn shuffle(&mut self) {
         if let Some(current_track_file) = self.get_current_track() {
+            self.record_change();
             self.tracks.shuffle(&mut thread_rng());
             if let Some(index) = self.find_index_from_file(&current_track_file) {
                 self.current_track_index = index;
@@ -447,6 +1010,132 @@ impl Playlist {
         }
     }
 
+    /// Sort the tracks stably by `key`, keeping the current track current
+    ///
+    /// Returns `false` if the order did not change
+    pub fn sort(&mut self, key: SortKey, descending: bool) -> bool {
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by(|a, b| {
+            let ordering = key.compare(&self.tracks[*a], &self.tracks[*b], &self.added);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+
+        if order.iter().enumerate().all(|(new, old)| new == *old) {
+            return false;
+        }
+
+        self.record_change();
+        let mut tracks: Vec<Option<Track>> = self.tracks.drain(..).map(Some).collect();
+        self.tracks = order.iter().filter_map(|v| tracks[*v].take()).collect();
+        if let Some(index) = order.iter().position(|v| *v == self.current_track_index) {
+            self.current_track_index = index;
+        }
+        true
+    }
+
+    /// Remove all but one of each group of duplicate tracks, keeping the current track current
+    ///
+    /// The first of each group is kept, unless the current track is part of the group.
+    /// Returns the amount of removed tracks.
+    pub fn remove_duplicates(&mut self, key: DedupKey) -> usize {
+        // the index to keep for each value
+        let mut keep: HashMap<String, usize> = HashMap::new();
+        for (index, track) in self.tracks.iter().enumerate() {
+            if let Some(value) = key.value(track) {
+                let kept = keep.entry(value).or_insert(index);
+                if index == self.current_track_index {
+                    *kept = index;
+                }
+            }
+        }
+
+        let retain: Vec<bool> = self
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(index, track)| key.value(track).map_or(true, |v| keep[&v] == index))
+            .collect();
+        let removed = retain.iter().filter(|v| !**v).count();
+        if removed == 0 {
+            return 0;
+        }
+
+        self.record_change();
+        let current = self.current_track_index;
+        self.current_track_index = current - retain[..current].iter().filter(|v| !**v).count();
+        let mut retain = retain.into_iter();
+        self.tracks.retain(|_| retain.next().unwrap_or(true));
+        removed
+    }
+
+    /// Undo the last change to the tracks, keeping the current track selected if it is still present
+    ///
+    /// Returns `false` if there was nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let playing = self.current_track_file();
+        if !self
+            .history
+            .undo(&mut self.tracks, &mut self.current_track_index)
+        {
+            return false;
+        }
+        self.shuffle_order.clear();
+        self.restore_current_track_index(playing.as_deref());
+        true
+    }
+
+    /// Redo the last undone change to the tracks, keeping the current track selected if it is still present
+    ///
+    /// Returns `false` if there was nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let playing = self.current_track_file();
+        if !self
+            .history
+            .redo(&mut self.tracks, &mut self.current_track_index)
+        {
+            return false;
+        }
+        self.shuffle_order.clear();
+        self.restore_current_track_index(playing.as_deref());
+        true
+    }
+
+    /// Amount of steps that can be undone and redone
+    #[must_use]
+    pub fn history_len(&self) -> (usize, usize) {
+        (self.history.undo_len(), self.history.redo_len())
+    }
+
+    /// The file of the track at the current index
+    fn current_track_file(&self) -> Option<String> {
+        self.tracks
+            .get(self.current_track_index)
+            .and_then(Track::file)
+            .map(ToString::to_string)
+    }
+
+    /// Point the current index at `playing` again, as it may have changed since the restored state was recorded
+    ///
+    /// Falls back to the recorded index, limited to the available tracks
+    fn restore_current_track_index(&mut self, playing: Option<&str>) {
+        if let Some(index) = playing.and_then(|file| self.find_index_from_file(file)) {
+            self.current_track_index = index;
+        } else {
+            self.current_track_index = self
+                .current_track_index
+                .min(self.tracks.len().saturating_sub(1));
+        }
+    }
+
+    /// Check whether both lists contain the same files in the same order
+    fn same_files(a: &[Track], b: &[Track]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.file() == b.file())
+    }
+
     fn find_index_from_file(&self, item: &str) -> Option<usize> {
         for (index, track) in self.tracks.iter().enumerate() {
             if let Some(file) = track.file() {
@@ -458,6 +1147,48 @@ impl Playlist {
         None
     }
 
+    /// Record the tracks for undo before they are changed, which also invalidates the shuffle order
+    fn record_change(&mut self) {
+        self.history.record(&self.tracks, self.current_track_index);
+        self.shuffle_order.clear();
+    }
+
+    /// Get the next index of the shuffle order, starting a new order once all tracks have been played
+    fn get_shuffle_index(&mut self) -> usize {
+        if self.shuffle_order.is_empty() {
+            let current = self.current_track_index;
+            self.shuffle_order = (0..self.len()).filter(|v| *v != current).collect();
+            self.shuffle_order.shuffle(&mut thread_rng());
+        }
+
+        self.shuffle_order
+            .last()
+            .copied()
+            .unwrap_or(self.current_track_index)
+    }
+
+    /// Get the range of consecutive tracks that have the same album as the current track
+    ///
+    /// A track without an album is treated as an album of its own
+    fn current_album_range(&self) -> Range<usize> {
+        let current = self.current_track_index;
+        let Some(album) = self.tracks.get(current).and_then(Track::album) else {
+            return current..current + 1;
+        };
+        let start = self.tracks[..current]
+            .iter()
+            .rposition(|v| v.album() != Some(album))
+            .map_or(0, |v| v + 1);
+        let end = current
+            + 1
+            + self.tracks[current + 1..]
+                .iter()
+                .take_while(|v| v.album() == Some(album))
+                .count();
+
+        start..end
+    }
+
     fn get_random_index(&self) -> usize {
         let mut rng = rand::thread_rng();
         let mut random_index = self.current_track_index;
@@ -470,6 +1201,7 @@ impl Playlist {
 
     pub fn remove_deleted_items(&mut self) {
         if let Some(current_track_file) = self.get_current_track() {
+            self.record_change();
             self.tracks
                 .retain(|x| x.file().map_or(false, |p| Path::new(p).exists()));
             match self.find_index_from_file(&current_track_file) {
@@ -502,6 +1234,41 @@ impl Playlist {
 
     pub fn set_current_track_index(&mut self, index: usize) {
         self.current_track_index = index;
+        self.current_from_queue = false;
+    }
+
+    /// Get the tracks following the current track that belong to the same album
+    ///
+    /// Only returns tracks if the playlist is played in order ([`LoopMode::Playlist`], [`LoopMode::Stop`] or [`LoopMode::Album`]) and does not wrap around
+    pub fn following_album_tracks(&self) -> impl Iterator<Item = &Track> {
+        let in_order = matches!(
+            self.loop_mode,
+            LoopMode::Playlist | LoopMode::Stop | LoopMode::Album
+        );
+        let album = self
+            .current_track()
+            .filter(|v| v.media_type == MediaType::Music && in_order)
+            .and_then(Track::album);
+
+        self.tracks
+            .iter()
+            .skip(self.current_track_index + 1)
+            .take_while(move |v| album.is_some() && v.album() == album)
+    }
+
+    /// Whether the track played after the current one belongs to the same album
+    ///
+    /// Podcast episodes and radio are treated as a album by themself, see [`Self::following_album_tracks`]
+    #[must_use]
+    pub fn next_is_same_album(&self) -> bool {
+        match self.queue.front() {
+            Some(queued) => self
+                .current_track()
+                .filter(|v| v.media_type == MediaType::Music)
+                .and_then(Track::album)
+                .is_some_and(|album| queued.album() == Some(album)),
+            None => self.following_album_tracks().next().is_some(),
+        }
     }
 
     #[must_use]
@@ -522,7 +1289,9 @@ impl Playlist {
     }
 }
 
-const PLAYLIST_SAVE_FILENAME: &str = "playlist.log";
+const PLAYLIST_SAVE_FILENAME: &str = "playlist.toml";
+const LEGACY_PLAYLIST_FILENAME: &str = "playlist.log";
+const LEGACY_QUEUE_FILENAME: &str = "queue.log";
 
 fn get_playlist_path() -> Result<PathBuf> {
     let mut path = get_app_config_path()?;
@@ -530,3 +1299,294 @@ fn get_playlist_path() -> Result<PathBuf> {
 
     Ok(path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn files(playlist: &Playlist) -> Vec<&str> {
+        playlist.tracks().iter().filter_map(Track::file).collect()
+    }
+
+    fn radio(url: &str) -> Track {
+        Track::new_radio(url)
+    }
+
+    #[test]
+    fn should_play_queue_before_playlist() {
+        let mut playlist = Playlist {
+            tracks: vec![radio("http://a"), radio("http://b")],
+            ..Default::default()
+        };
+
+        playlist.add_queue_tracks(vec![radio("http://q3")], QueuePosition::Last);
+        playlist.add_queue_tracks(
+            vec![radio("http://q1"), radio("http://q2")],
+            QueuePosition::Next,
+        );
+
+        let mut played = Vec::new();
+        for _ in 0..4 {
+            playlist.next();
+            played.push(
+                playlist
+                    .current_track()
+                    .and_then(Track::file)
+                    .unwrap()
+                    .to_string(),
+            );
+        }
+
+        assert_eq!(
+            played,
+            vec!["http://q1", "http://q2", "http://q3", "http://b"]
+        );
+        assert!(playlist.queue().is_empty());
+        assert!(!playlist.is_current_from_queue());
+    }
+
+    #[test]
+    fn should_go_back_to_the_track_before_the_queue() {
+        let mut playlist = Playlist {
+            tracks: vec![radio("http://a"), radio("http://b"), radio("http://c")],
+            ..Default::default()
+        };
+        playlist.add_queue_tracks(vec![radio("http://q")], QueuePosition::Last);
+
+        playlist.next();
+        assert!(playlist.is_current_from_queue());
+        // the player clears the current track when it ends, before going to the next one
+        playlist.clear_current_track();
+        assert!(playlist.is_current_from_queue());
+        playlist.next();
+        assert!(!playlist.is_current_from_queue());
+        assert_eq!(playlist.get_current_track_index(), 1);
+        assert_eq!(playlist.played_index, vec![0]);
+
+        playlist.previous();
+        assert_eq!(playlist.get_current_track_index(), 0);
+        assert!(playlist.played_index.is_empty());
+    }
+
+    #[test]
+    fn should_undo_clear_and_keep_current_track() {
+        let mut playlist = Playlist {
+            tracks: vec![radio("http://a"), radio("http://b"), radio("http://c")],
+            current_track_index: 2,
+            ..Default::default()
+        };
+
+        playlist.swap_up(2);
+        assert_eq!(playlist.get_current_track_index(), 1);
+        playlist.clear();
+        assert!(playlist.is_empty());
+
+        assert!(playlist.undo());
+        assert_eq!(files(&playlist), vec!["http://a", "http://c", "http://b"]);
+        assert_eq!(playlist.get_current_track_index(), 1);
+
+        // playback continued while the swap was in place
+        playlist.set_current_track_index(2);
+        assert!(playlist.undo());
+        assert_eq!(files(&playlist), vec!["http://a", "http://b", "http://c"]);
+        assert_eq!(playlist.get_current_track_index(), 1);
+        assert!(!playlist.undo());
+
+        assert!(playlist.redo());
+        assert_eq!(files(&playlist), vec!["http://a", "http://c", "http://b"]);
+        assert_eq!(playlist.get_current_track_index(), 2);
+    }
+
+    #[test]
+    fn should_shuffle_every_track_once() {
+        let mut playlist = Playlist {
+            tracks: (0..6).map(|v| radio(&format!("http://{v}"))).collect(),
+            loop_mode: LoopMode::Shuffle,
+            ..Default::default()
+        };
+
+        let mut played = vec![playlist.get_current_track_index()];
+        for _ in 1..6 {
+            playlist.next();
+            played.push(playlist.get_current_track_index());
+        }
+        let mut sorted = played.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..6).collect::<Vec<_>>());
+
+        // going back and forward again follows the same order
+        playlist.previous();
+        playlist.previous();
+        assert_eq!(playlist.get_current_track_index(), played[3]);
+        playlist.next();
+        assert_eq!(playlist.get_current_track_index(), played[4]);
+        playlist.next();
+        assert_eq!(playlist.get_current_track_index(), played[5]);
+    }
+
+    #[test]
+    fn should_stop_at_end_of_playlist() {
+        let mut playlist = Playlist {
+            tracks: vec![radio("http://a"), radio("http://b")],
+            loop_mode: LoopMode::Stop,
+            ..Default::default()
+        };
+
+        playlist.next();
+        assert_eq!(playlist.get_current_track_index(), 1);
+        assert!(!playlist.take_stopped_at_end());
+        assert!(playlist.fetch_next_track().is_none());
+
+        playlist.next();
+        assert!(playlist.take_stopped_at_end());
+        assert!(!playlist.take_stopped_at_end());
+        assert_eq!(playlist.get_current_track_index(), 0);
+    }
+
+    #[test]
+    fn should_repeat_current_album() {
+        let tracks = [("a", "x"), ("b", "y"), ("c", "y"), ("d", "y"), ("e", "z")]
+            .into_iter()
+            .map(|(file, album)| {
+                let mut track = radio(&format!("http://{file}"));
+                track.set_album(album);
+                track
+            })
+            .collect();
+        let mut playlist = Playlist {
+            tracks,
+            current_track_index: 2,
+            loop_mode: LoopMode::Album,
+            ..Default::default()
+        };
+
+        playlist.next();
+        assert_eq!(playlist.get_current_track_index(), 3);
+        playlist.next();
+        assert_eq!(playlist.get_current_track_index(), 1);
+
+        playlist.played_index.clear();
+        playlist.previous();
+        assert_eq!(playlist.get_current_track_index(), 3);
+    }
+
+    fn music(file: &str, artist: &str, title: &str) -> Track {
+        let mut track = radio(file);
+        track.set_artist(artist);
+        track.set_title(title);
+        track
+    }
+
+    #[test]
+    fn should_sort_stable_and_keep_current_track() {
+        let mut playlist = Playlist {
+            tracks: vec![
+                music("http://1", "B", "x"),
+                music("http://2", "a", "y"),
+                music("http://3", "b", "z"),
+                music("http://4", "A", "w"),
+            ],
+            current_track_index: 2,
+            ..Default::default()
+        };
+
+        assert!(playlist.sort(SortKey::Artist, false));
+        assert_eq!(
+            files(&playlist),
+            vec!["http://2", "http://4", "http://1", "http://3"]
+        );
+        assert_eq!(playlist.get_current_track_index(), 3);
+
+        assert!(playlist.sort(SortKey::Title, true));
+        assert_eq!(
+            files(&playlist),
+            vec!["http://3", "http://2", "http://1", "http://4"]
+        );
+        assert_eq!(playlist.get_current_track_index(), 0);
+        assert!(!playlist.sort(SortKey::Title, true));
+
+        assert!(playlist.undo());
+        assert_eq!(
+            files(&playlist),
+            vec!["http://2", "http://4", "http://1", "http://3"]
+        );
+    }
+
+    #[test]
+    fn should_sort_album_by_disc_and_track_number() {
+        let track = |file: &str, album: &str, disc: Option<u32>, number: Option<u32>| {
+            let mut track = music(file, "A", file);
+            track.set_album(album);
+            track.set_disc_number(disc);
+            track.set_track_number(number);
+            track
+        };
+        let mut playlist = Playlist {
+            tracks: vec![
+                track("http://1", "b", Some(2), Some(1)),
+                track("http://2", "b", None, None),
+                track("http://3", "a", None, Some(2)),
+                track("http://4", "b", Some(1), Some(3)),
+                track("http://5", "a", None, Some(1)),
+            ],
+            ..Default::default()
+        };
+
+        assert!(playlist.sort(SortKey::Album, false));
+        assert_eq!(
+            files(&playlist),
+            vec!["http://5", "http://3", "http://4", "http://1", "http://2"]
+        );
+    }
+
+    #[test]
+    fn should_sort_by_date_added() {
+        let mut playlist = Playlist {
+            tracks: vec![radio("http://old")],
+            ..Default::default()
+        };
+
+        playlist.add_playlist(&["http://b"]).unwrap();
+        // added before the tracks below
+        playlist
+            .added
+            .insert("http://b".to_string(), SystemTime::UNIX_EPOCH);
+        playlist.add_playlist(&["http://a", "http://b"]).unwrap();
+        assert!(playlist.added.contains_key("http://a"));
+        assert_eq!(playlist.added["http://b"], SystemTime::UNIX_EPOCH);
+
+        assert!(playlist.sort(SortKey::DateAdded, false));
+        assert_eq!(
+            files(&playlist),
+            vec!["http://b", "http://b", "http://a", "http://old"]
+        );
+    }
+
+    #[test]
+    fn should_remove_duplicates_and_keep_current_track() {
+        let mut playlist = Playlist {
+            tracks: vec![
+                music("http://1", "A", "x"),
+                music("http://2", "a", "X"),
+                music("http://1", "B", "y"),
+                music("http://3", "B", "y"),
+                music("http://4", "C", "z"),
+            ],
+            current_track_index: 3,
+            ..Default::default()
+        };
+
+        assert_eq!(playlist.remove_duplicates(DedupKey::Path), 1);
+        assert_eq!(
+            files(&playlist),
+            vec!["http://1", "http://2", "http://3", "http://4"]
+        );
+        assert_eq!(playlist.get_current_track_index(), 2);
+
+        assert_eq!(playlist.remove_duplicates(DedupKey::ArtistTitle), 1);
+        assert_eq!(files(&playlist), vec!["http://1", "http://3", "http://4"]);
+        assert_eq!(playlist.get_current_track_index(), 1);
+        assert_eq!(playlist.remove_duplicates(DedupKey::ArtistTitle), 0);
+    }
+}
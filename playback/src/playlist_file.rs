@@ -0,0 +1,219 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use termusiclib::config::v2::server::LoopMode;
+use termusiclib::track::MediaType;
+
+/// Version of the format written by [`PlaylistFile::save`], bumped on incompatible changes
+pub const PLAYLIST_FILE_VERSION: u32 = 1;
+
+/// The persisted state of a [`Playlist`](crate::Playlist)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlaylistFile {
+    pub version: u32,
+    #[serde(default)]
+    pub current_track_index: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loop_mode: Option<LoopMode>,
+    #[serde(default)]
+    pub tracks: Vec<PlaylistFileEntry>,
+    /// The up-next queue
+    #[serde(default)]
+    pub queue: Vec<PlaylistFileEntry>,
+}
+
+impl Default for PlaylistFile {
+    fn default() -> Self {
+        Self {
+            version: PLAYLIST_FILE_VERSION,
+            current_track_index: 0,
+            loop_mode: None,
+            tracks: Vec::new(),
+            queue: Vec::new(),
+        }
+    }
+}
+
+/// A single track of a [`PlaylistFile`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistFileEntry {
+    pub media_type: MediaType,
+    /// The path or url of the track
+    pub source: String,
+    /// Title at the time of saving, used if the source cannot be read anymore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Where to resume playing the track
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position_ms: Option<u64>,
+    /// When the track was added to the playlist, in milliseconds since the unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_ms: Option<u64>,
+}
+
+impl PlaylistFileEntry {
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration_ms.map(Duration::from_millis)
+    }
+
+    #[must_use]
+    pub fn position(&self) -> Option<Duration> {
+        self.position_ms.map(Duration::from_millis)
+    }
+
+    #[must_use]
+    pub fn added(&self) -> Option<SystemTime> {
+        self.added_ms
+            .and_then(|v| SystemTime::UNIX_EPOCH.checked_add(Duration::from_millis(v)))
+    }
+}
+
+impl PlaylistFile {
+    /// Parse a playlist file
+    ///
+    /// # Errors
+    /// - if the content is not valid
+    /// - if the file was written by a newer version
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: Self = toml::from_str(content)?;
+        if file.version > PLAYLIST_FILE_VERSION {
+            bail!(
+                "playlist file version {} is newer than the supported version {PLAYLIST_FILE_VERSION}",
+                file.version
+            );
+        }
+
+        Ok(file)
+    }
+
+    /// Read the playlist file at `path`
+    ///
+    /// # Errors
+    /// - if the file cannot be read
+    /// - if the file cannot be parsed, see [`parse`](Self::parse)
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("read playlist file {}", path.display()))?;
+
+        Self::parse(&content).with_context(|| format!("parse playlist file {}", path.display()))
+    }
+
+    /// Write the playlist file to `path`
+    ///
+    /// The content is written to a temporary file first and then renamed over `path`,
+    /// so that a crash while writing does not leave a partial playlist behind.
+    ///
+    /// # Errors
+    /// - if the temporary file cannot be written
+    /// - if the temporary file cannot be renamed
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = toml::to_string(self)?;
+        let tmp_path = tmp_path(path);
+
+        let mut file = File::create(&tmp_path)
+            .with_context(|| format!("create temporary file {}", tmp_path.display()))?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("rename {} to {}", tmp_path.display(), path.display()))?;
+
+        Ok(())
+    }
+}
+
+/// Get the path next to `path` that is written before renaming
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Parse the line based `playlist.log` of previous versions: a index line followed by a path or url per line
+#[must_use]
+pub fn parse_legacy(content: &str) -> (usize, Vec<String>) {
+    let mut lines = content.lines();
+    let current_track_index = lines
+        .next()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or_default();
+    let items = lines
+        .filter(|v| !v.trim().is_empty())
+        .map(ToString::to_string)
+        .collect();
+
+    (current_track_index, items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_roundtrip() {
+        let file = PlaylistFile {
+            version: PLAYLIST_FILE_VERSION,
+            current_track_index: 1,
+            loop_mode: Some(LoopMode::Shuffle),
+            tracks: vec![
+                PlaylistFileEntry {
+                    media_type: MediaType::Music,
+                    source: "/music/a.mp3".to_string(),
+                    title: Some("A".to_string()),
+                    duration_ms: Some(180_000),
+                    position_ms: Some(42_000),
+                    added_ms: Some(1_700_000_000_000),
+                },
+                PlaylistFileEntry {
+                    media_type: MediaType::LiveRadio,
+                    source: "http://radio".to_string(),
+                    title: None,
+                    duration_ms: None,
+                    position_ms: None,
+                    added_ms: None,
+                },
+            ],
+            queue: vec![PlaylistFileEntry {
+                media_type: MediaType::Podcast,
+                source: "http://podcast/1.mp3".to_string(),
+                title: Some("Episode 1".to_string()),
+                duration_ms: Some(3_600_000),
+                position_ms: None,
+                added_ms: None,
+            }],
+        };
+
+        let content = toml::to_string(&file).unwrap();
+        assert_eq!(PlaylistFile::parse(&content).unwrap(), file);
+    }
+
+    #[test]
+    fn should_reject_newer_version() {
+        let content = format!("version = {}\n", PLAYLIST_FILE_VERSION + 1);
+        assert!(PlaylistFile::parse(&content).is_err());
+        assert_eq!(
+            PlaylistFile::parse(&format!("version = {PLAYLIST_FILE_VERSION}\n")).unwrap(),
+            PlaylistFile::default()
+        );
+    }
+
+    #[test]
+    fn should_parse_legacy() {
+        assert_eq!(
+            parse_legacy("2\n/music/a.mp3\n\nhttp://radio\n"),
+            (
+                2,
+                vec!["/music/a.mp3".to_string(), "http://radio".to_string()]
+            )
+        );
+        assert_eq!(parse_legacy(""), (0, Vec::new()));
+    }
+}
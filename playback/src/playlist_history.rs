@@ -0,0 +1,268 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::Arc;
+use termusiclib::track::{MediaType, Track};
+
+/// Maximal amount of steps kept to undo, older steps are dropped
+pub const HISTORY_LIMIT: usize = 50;
+
+/// The state of a playlist before / after a mutation
+///
+/// Tracks are shared with the neighbouring snapshot by their file and modification time,
+/// so a step only costs the tracks it changed
+#[derive(Debug, Clone)]
+struct Snapshot {
+    tracks: Vec<Arc<Track>>,
+    current_track_index: usize,
+}
+
+impl Snapshot {
+    /// Create a snapshot of `tracks`, reusing the tracks of `neighbour` with the same file and modification time
+    fn new(tracks: &[Track], current_track_index: usize, neighbour: Option<&Snapshot>) -> Self {
+        let shared: HashMap<&str, &Arc<Track>> = neighbour
+            .map(|v| {
+                v.tracks
+                    .iter()
+                    .filter_map(|track| Some((track.file()?, track)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let tracks = tracks
+            .iter()
+            .map(|track| {
+                track
+                    .file()
+                    .and_then(|file| shared.get(file))
+                    .filter(|v| v.last_modified == track.last_modified)
+                    .map_or_else(|| Arc::new(track.clone()), |v| Arc::clone(v))
+            })
+            .collect();
+
+        Self {
+            tracks,
+            current_track_index,
+        }
+    }
+
+    /// Put this snapshot into `tracks` and `current_track_index` and return what was there before
+    ///
+    /// Music files changed since the snapshot was taken, like by editing their tags, are read again.
+    fn swap_into(self, tracks: &mut Vec<Track>, current_track_index: &mut usize) -> Snapshot {
+        let previous = Snapshot::new(tracks, *current_track_index, Some(&self));
+        *tracks = self
+            .tracks
+            .into_iter()
+            .map(|v| reload_if_modified(Arc::unwrap_or_clone(v)))
+            .collect();
+        *current_track_index = self.current_track_index;
+
+        previous
+    }
+}
+
+/// Read `track` again if it is a music file that was modified since it was read, keeping it if that fails
+fn reload_if_modified(track: Track) -> Track {
+    if track.media_type != MediaType::Music {
+        return track;
+    }
+    let Some(file) = track.file() else {
+        return track;
+    };
+    let modified = Path::new(file).metadata().and_then(|v| v.modified());
+    if modified.map_or(true, |v| v == track.last_modified) {
+        return track;
+    }
+
+    Track::read_from_path(file, false).unwrap_or(track)
+}
+
+/// Bounded undo / redo history of playlist mutations
+#[derive(Debug, Default)]
+pub struct PlaylistHistory {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl PlaylistHistory {
+    /// Record the state before a mutation, this invalidates all redo steps
+    pub fn record(&mut self, tracks: &[Track], current_track_index: usize) {
+        let snapshot = Snapshot::new(tracks, current_track_index, self.undo.back());
+        self.redo.clear();
+        if self.undo.len() >= HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(snapshot);
+    }
+
+    /// Restore the last recorded state into the given values, keeping the replaced state for redo
+    ///
+    /// Returns `false` if there was nothing to undo
+    pub fn undo(&mut self, tracks: &mut Vec<Track>, current_track_index: &mut usize) -> bool {
+        let Some(snapshot) = self.undo.pop_back() else {
+            return false;
+        };
+        self.redo
+            .push(snapshot.swap_into(tracks, current_track_index));
+
+        true
+    }
+
+    /// Restore the last undone state into the given values, keeping the replaced state for undo
+    ///
+    /// Returns `false` if there was nothing to redo
+    pub fn redo(&mut self, tracks: &mut Vec<Track>, current_track_index: &mut usize) -> bool {
+        let Some(snapshot) = self.redo.pop() else {
+            return false;
+        };
+        self.undo
+            .push_back(snapshot.swap_into(tracks, current_track_index));
+
+        true
+    }
+
+    /// Drop all undo and redo steps
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Amount of steps that can be undone
+    #[must_use]
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    /// Amount of steps that can be redone
+    #[must_use]
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs::File;
+
+    fn files(tracks: &[Track]) -> Vec<&str> {
+        tracks.iter().filter_map(Track::file).collect()
+    }
+
+    #[test]
+    fn should_undo_and_redo() {
+        let mut history = PlaylistHistory::default();
+        let mut tracks = vec![Track::new_radio("http://a")];
+        let mut index = 0;
+
+        history.record(&tracks, index);
+        tracks.push(Track::new_radio("http://b"));
+        index = 1;
+
+        assert!(history.undo(&mut tracks, &mut index));
+        assert_eq!(files(&tracks), vec!["http://a"]);
+        assert_eq!(index, 0);
+        assert!(!history.undo(&mut tracks, &mut index));
+
+        assert!(history.redo(&mut tracks, &mut index));
+        assert_eq!(files(&tracks), vec!["http://a", "http://b"]);
+        assert_eq!(index, 1);
+        assert!(!history.redo(&mut tracks, &mut index));
+    }
+
+    #[test]
+    fn should_drop_redo_on_record() {
+        let mut history = PlaylistHistory::default();
+        let mut tracks = Vec::new();
+        let mut index = 0;
+
+        history.record(&tracks, index);
+        tracks.push(Track::new_radio("http://a"));
+        assert!(history.undo(&mut tracks, &mut index));
+        assert_eq!(history.redo_len(), 1);
+
+        history.record(&tracks, index);
+        assert_eq!(history.redo_len(), 0);
+        assert_eq!(history.undo_len(), 1);
+    }
+
+    #[test]
+    fn should_share_unchanged_tracks_between_steps() {
+        let mut history = PlaylistHistory::default();
+        let mut tracks = vec![Track::new_radio("http://a"), Track::new_radio("http://b")];
+
+        history.record(&tracks, 0);
+        tracks.push(Track::new_radio("http://c"));
+        history.record(&tracks, 0);
+
+        let first = &history.undo[0].tracks;
+        let second = &history.undo[1].tracks;
+        assert_eq!(second.len(), 3);
+        assert!(Arc::ptr_eq(&first[0], &second[0]));
+        assert!(Arc::ptr_eq(&first[1], &second[1]));
+
+        // the state replaced by an undo shares the tracks of the restored step
+        tracks.push(Track::new_radio("http://d"));
+        assert!(history.undo(&mut tracks, &mut 0));
+        assert!(Arc::ptr_eq(
+            &history.undo[0].tracks[0],
+            &history.redo[0].tracks[0]
+        ));
+        assert_eq!(files(&tracks), vec!["http://a", "http://b", "http://c"]);
+    }
+
+    #[test]
+    fn should_not_share_or_restore_outdated_tracks() {
+        let dir =
+            std::env::temp_dir().join(format!("termusic-playlist-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("song.mp3");
+        std::fs::write(&path, b"").unwrap();
+        let modified = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1000);
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        let mut history = PlaylistHistory::default();
+        let mut old = Track::read_from_path(&path, false).unwrap();
+        old.set_title("Old");
+        let mut tracks = vec![old];
+        history.record(&tracks, 0);
+
+        // the tags were edited after the first step was recorded
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        let edited = Track::read_from_path(&path, false).unwrap();
+        tracks = vec![edited.clone(), Track::new_radio("http://a")];
+        history.record(&tracks, 0);
+        assert!(!Arc::ptr_eq(
+            &history.undo[0].tracks[0],
+            &history.undo[1].tracks[0]
+        ));
+
+        assert!(history.undo(&mut tracks, &mut 0));
+        assert!(history.undo(&mut tracks, &mut 0));
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].last_modified, edited.last_modified);
+        assert_eq!(tracks[0].title(), edited.title());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn should_be_bounded() {
+        let mut history = PlaylistHistory::default();
+        for _ in 0..HISTORY_LIMIT + 10 {
+            history.record(&[], 0);
+        }
+
+        assert_eq!(history.undo_len(), HISTORY_LIMIT);
+    }
+}
@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::Volume;
+
+/// The different ways a sleep timer can be set
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SleepTimerMode {
+    /// Pause after the given amount of time has passed
+    Duration(Duration),
+    /// Pause once the currently playing track has ended
+    EndOfTrack,
+    /// Pause once the current album (or podcast episode) has ended
+    EndOfAlbum,
+}
+
+impl SleepTimerMode {
+    /// The gRPC value used when no timer is set, see [`Self::as_u32`]
+    pub const OFF: u32 = 0;
+
+    #[must_use]
+    pub fn as_u32(&self) -> u32 {
+        match self {
+            Self::Duration(_) => 1,
+            Self::EndOfTrack => 2,
+            Self::EndOfAlbum => 3,
+        }
+    }
+
+    /// Convert the gRPC representation back to a mode
+    ///
+    /// `duration` is only used for [`SleepTimerMode::Duration`], returns [`None`] for [`Self::OFF`] or unknown values
+    #[must_use]
+    pub fn from_u32(mode: u32, duration: Duration) -> Option<Self> {
+        match mode {
+            1 => Some(Self::Duration(duration)),
+            2 => Some(Self::EndOfTrack),
+            3 => Some(Self::EndOfAlbum),
+            _ => None,
+        }
+    }
+}
+
+/// The active state of a sleep timer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimerKind {
+    /// Expire at a fixed point in time
+    Deadline(Instant),
+    EndOfTrack,
+    EndOfAlbum,
+}
+
+/// Server-side sleep timer, which pauses playback once it expires
+///
+/// The timer itself does not do anything, it only keeps track of the state,
+/// the actual fading and pausing is done by [`GeneralPlayer`](crate::GeneralPlayer)
+#[derive(Debug, Default)]
+pub struct SleepTimer {
+    kind: Option<TimerKind>,
+    /// The volume before fading started, used to restore the volume after the timer expired or was canceled
+    restore_volume: Option<Volume>,
+    /// Indicator that the next track change was caused by the user and should not count as the end of a track
+    skip_requested: bool,
+}
+
+impl SleepTimer {
+    /// Set (or replace) the timer
+    pub fn set(&mut self, mode: SleepTimerMode) {
+        self.kind = Some(match mode {
+            SleepTimerMode::Duration(duration) => TimerKind::Deadline(Instant::now() + duration),
+            SleepTimerMode::EndOfTrack => TimerKind::EndOfTrack,
+            SleepTimerMode::EndOfAlbum => TimerKind::EndOfAlbum,
+        });
+        self.skip_requested = false;
+    }
+
+    /// Extend the timer by `amount`
+    ///
+    /// Track based timers get converted to a time based one, starting from `remaining`.
+    /// If no timer is active, a new time based timer is started.
+    pub fn extend(&mut self, amount: Duration, remaining: Option<Duration>) {
+        let now = Instant::now();
+        let deadline = match self.kind {
+            Some(TimerKind::Deadline(deadline)) => deadline.max(now) + amount,
+            Some(TimerKind::EndOfTrack | TimerKind::EndOfAlbum) => {
+                now + remaining.unwrap_or_default() + amount
+            }
+            None => now + amount,
+        };
+        self.kind = Some(TimerKind::Deadline(deadline));
+    }
+
+    /// Cancel the timer
+    ///
+    /// Returns the volume to restore, if fading had already started
+    pub fn cancel(&mut self) -> Option<Volume> {
+        self.kind = None;
+        self.skip_requested = false;
+        self.restore_volume.take()
+    }
+
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.kind.is_some()
+    }
+
+    /// Get the current mode, if active
+    #[must_use]
+    pub fn mode(&self) -> Option<SleepTimerMode> {
+        self.kind.map(|kind| match kind {
+            TimerKind::Deadline(deadline) => {
+                SleepTimerMode::Duration(deadline.saturating_duration_since(Instant::now()))
+            }
+            TimerKind::EndOfTrack => SleepTimerMode::EndOfTrack,
+            TimerKind::EndOfAlbum => SleepTimerMode::EndOfAlbum,
+        })
+    }
+
+    /// Get the remaining time of a time based timer
+    ///
+    /// Track based timers need to be calculated with the current playback state and return [`None`] here
+    #[must_use]
+    pub fn deadline_remaining(&self) -> Option<Duration> {
+        match self.kind? {
+            TimerKind::Deadline(deadline) => {
+                Some(deadline.saturating_duration_since(Instant::now()))
+            }
+            TimerKind::EndOfTrack | TimerKind::EndOfAlbum => None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_end_of_track(&self) -> bool {
+        self.kind == Some(TimerKind::EndOfTrack)
+    }
+
+    #[must_use]
+    pub fn is_end_of_album(&self) -> bool {
+        self.kind == Some(TimerKind::EndOfAlbum)
+    }
+
+    /// Mark that the next track change is a user-requested skip
+    pub fn skip_requested(&mut self) {
+        self.skip_requested = true;
+    }
+
+    /// Get the skip indicator without resetting it, see [`Self::skip_requested`]
+    #[must_use]
+    pub fn is_skip_requested(&self) -> bool {
+        self.skip_requested
+    }
+
+    /// Get and reset the skip indicator, see [`Self::skip_requested`]
+    pub fn take_skip_requested(&mut self) -> bool {
+        std::mem::take(&mut self.skip_requested)
+    }
+
+    /// Calculate the volume to use while fading out
+    ///
+    /// `current_volume` is stored as the volume to restore on the first call.
+    /// Returns [`None`] if `remaining` is not within `fade_out`.
+    #[must_use]
+    pub fn fade_volume(
+        &mut self,
+        remaining: Duration,
+        fade_out: Duration,
+        current_volume: Volume,
+    ) -> Option<Volume> {
+        if fade_out.is_zero() || remaining >= fade_out {
+            return None;
+        }
+
+        let original = *self.restore_volume.get_or_insert(current_volume);
+        let factor = remaining.as_secs_f64() / fade_out.as_secs_f64();
+
+        // the result is always between 0 and "original"
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Some((f64::from(original) * factor).round() as Volume)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn should_fade_linearly() {
+        let mut timer = SleepTimer::default();
+        timer.set(SleepTimerMode::Duration(Duration::from_secs(60)));
+        let fade = Duration::from_secs(10);
+
+        assert_eq!(timer.fade_volume(Duration::from_secs(20), fade, 50), None);
+        assert_eq!(
+            timer.fade_volume(Duration::from_secs(5), fade, 50),
+            Some(25)
+        );
+        // the original volume is kept, even if the current volume is already lowered
+        assert_eq!(
+            timer.fade_volume(Duration::from_secs(2), fade, 25),
+            Some(10)
+        );
+        assert_eq!(timer.fade_volume(Duration::ZERO, fade, 10), Some(0));
+        assert_eq!(timer.cancel(), Some(50));
+        assert!(!timer.is_active());
+    }
+
+    #[test]
+    fn should_extend_track_timer_to_deadline() {
+        let mut timer = SleepTimer::default();
+        timer.set(SleepTimerMode::EndOfTrack);
+        assert_eq!(timer.deadline_remaining(), None);
+
+        timer.extend(Duration::from_secs(60), Some(Duration::from_secs(30)));
+        let remaining = timer.deadline_remaining().unwrap();
+        assert!(remaining > Duration::from_secs(85) && remaining <= Duration::from_secs(90));
+    }
+
+    #[test]
+    fn should_convert_mode() {
+        let mode = SleepTimerMode::EndOfAlbum;
+        assert_eq!(
+            SleepTimerMode::from_u32(mode.as_u32(), Duration::ZERO),
+            Some(mode)
+        );
+        assert_eq!(
+            SleepTimerMode::from_u32(SleepTimerMode::OFF, Duration::ZERO),
+            None
+        );
+    }
+}
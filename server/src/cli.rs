@@ -101,12 +101,17 @@ pub enum Action {
     Export {
         #[arg(value_name = "FILE")]
         file: PathBuf,
+        /// Include the username and password of private feeds in their urls.
+        #[arg(long)]
+        with_credentials: bool,
     },
     /// Export Podcast feeds to a opml file.
     Import {
         #[arg(value_name = "FILE")]
         file: PathBuf,
     },
+    /// Print likely duplicate tracks in the library as JSON.
+    Duplicates,
 }
 
 const DEFAULT_LOGFILE_FILENAME: &str = "termusic-server.log";
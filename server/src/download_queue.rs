@@ -0,0 +1,225 @@
+//! Run the podcast download queue, so that downloads are limited across all clients and continue without one
+use std::collections::HashMap;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use parking_lot::Mutex;
+use termusiclib::config::ServerOverlay;
+use termusiclib::podcast::db::{Database, DownloadAction, DownloadStatus, PodcastDBId};
+use termusiclib::podcast::{self, podcast_dir_name, CancelToken, DownloadResult};
+use termusiclib::utils::{create_podcast_dir, get_app_config_path};
+use tokio::runtime::Handle;
+
+use crate::PlayerStats;
+
+/// Request for the download queue thread
+#[derive(Debug)]
+pub enum QueueRequest {
+    /// Change the queue for the given episodes, as requested by a client
+    Control(DownloadAction, Vec<PodcastDBId>),
+    /// Episodes were queued directly in the database, start them if there are free slots
+    Run,
+    /// A download started by the queue ended
+    Finished(DownloadResult),
+}
+
+/// Spawn the thread that runs the download queue, changed by requests from `rx`
+///
+/// Downloads that were still running when the server last exited are queued again.
+/// Every change to the queue bumps [`PlayerStats::podcast_version`] and the progress of running downloads
+/// is kept in [`PlayerStats::downloads`], so that clients can show them.
+/// `tx` has to send to `rx`, it reports the finished downloads back to the thread.
+pub fn spawn(
+    config: ServerOverlay,
+    playerstats: Arc<Mutex<PlayerStats>>,
+    tx: Sender<QueueRequest>,
+    rx: Receiver<QueueRequest>,
+    handle: Handle,
+) -> Result<()> {
+    let db_path = get_app_config_path().context("get podcast database path")?;
+    let db = Database::new(&db_path)?;
+    // only the server runs downloads, so these were interrupted by it exiting
+    db.requeue_running_downloads()?;
+
+    let mut queue = DownloadQueue {
+        db,
+        config,
+        playerstats,
+        handle,
+        tx,
+        running: HashMap::new(),
+    };
+    std::thread::Builder::new()
+        .name("podcast downloads".into())
+        .spawn(move || queue.run(&rx))?;
+
+    Ok(())
+}
+
+struct DownloadQueue {
+    db: Database,
+    config: ServerOverlay,
+    playerstats: Arc<Mutex<PlayerStats>>,
+    handle: Handle,
+    tx: Sender<QueueRequest>,
+    /// Tokens to stop the running downloads, by episode id
+    running: HashMap<PodcastDBId, CancelToken>,
+}
+
+impl DownloadQueue {
+    fn run(&mut self, rx: &Receiver<QueueRequest>) {
+        let mut request = QueueRequest::Run;
+        loop {
+            if let Err(err) = self.apply(request) {
+                error!("Could not update the podcast download queue: {err:#}");
+            }
+            self.playerstats.lock().podcast_version += 1;
+
+            request = match rx.recv() {
+                Ok(v) => v,
+                Err(_) => return,
+            };
+        }
+    }
+
+    /// Apply `request` to the queue and start as many queued downloads as allowed
+    fn apply(&mut self, request: QueueRequest) -> Result<()> {
+        match request {
+            QueueRequest::Control(action, episode_ids) => self.control(action, &episode_ids)?,
+            QueueRequest::Run => (),
+            QueueRequest::Finished(result) => self.finished(&result)?,
+        }
+
+        self.start_downloads()
+    }
+
+    fn control(&mut self, action: DownloadAction, episode_ids: &[PodcastDBId]) -> Result<()> {
+        match action {
+            DownloadAction::Queue => self.db.queue_downloads(episode_ids)?,
+            DownloadAction::TogglePause => {
+                let queue = self.db.get_download_queue()?;
+                for item in queue.iter().filter(|v| episode_ids.contains(&v.episode_id)) {
+                    let status = match item.status {
+                        DownloadStatus::Queued => DownloadStatus::Paused,
+                        DownloadStatus::Paused => DownloadStatus::Queued,
+                        // the status is updated once the download actually stopped
+                        DownloadStatus::Running => {
+                            if let Some(token) = self.running.get(&item.episode_id) {
+                                token.pause();
+                            }
+                            continue;
+                        }
+                        DownloadStatus::Failed | DownloadStatus::Done => continue,
+                    };
+                    self.db.set_download_status(item.episode_id, status, None)?;
+                }
+            }
+            DownloadAction::Remove => {
+                for id in episode_ids {
+                    // removed from the queue once the download actually stopped
+                    if let Some(token) = self.running.get(id) {
+                        token.cancel();
+                    } else {
+                        self.db.remove_download(*id)?;
+                    }
+                }
+            }
+            DownloadAction::MoveUp | DownloadAction::MoveDown => {
+                for id in episode_ids {
+                    self.db
+                        .move_download(*id, action == DownloadAction::MoveDown)?;
+                }
+            }
+            DownloadAction::RetryFailed => self.db.retry_failed_downloads()?,
+            DownloadAction::Clear => self.db.clear_download_queue()?,
+        }
+
+        Ok(())
+    }
+
+    /// Record how a download ended
+    fn finished(&mut self, result: &DownloadResult) -> Result<()> {
+        let ep_data = result.ep_data();
+        self.running.remove(&ep_data.id);
+        self.playerstats.lock().downloads.remove(&ep_data.url);
+
+        let reason = match result {
+            DownloadResult::Complete(_) => {
+                if let Some(path) = &ep_data.file_path {
+                    self.db.insert_file(ep_data.id, path)?;
+                }
+                return self
+                    .db
+                    .set_download_status(ep_data.id, DownloadStatus::Done, None);
+            }
+            DownloadResult::Canceled(_) => return self.db.remove_download(ep_data.id),
+            DownloadResult::Paused(_) => {
+                return self
+                    .db
+                    .set_download_status(ep_data.id, DownloadStatus::Paused, None);
+            }
+            DownloadResult::ResponseError(_) => "No response from the website",
+            DownloadResult::FileCreateError(_) => "Unable to create a file",
+            DownloadResult::FileWriteError(_) => "Cannot write to the file",
+        };
+        warn!("Downloading episode \"{}\" failed: {reason}", ep_data.title);
+        self.db
+            .set_download_status(ep_data.id, DownloadStatus::Failed, Some(reason))
+    }
+
+    /// Start queued downloads until `concurrent_downloads_max` are running
+    fn start_downloads(&mut self) -> Result<()> {
+        let settings = &self.config.settings.podcast;
+        let max_running = usize::from(settings.concurrent_downloads_max.get());
+        let max_retries = usize::from(settings.max_download_retries);
+
+        let queue = self.db.get_download_queue()?;
+        for item in queue.iter().filter(|v| v.status == DownloadStatus::Queued) {
+            if self.running.len() >= max_running {
+                break;
+            }
+
+            // add directory for podcast, create if it does not exist
+            let Ok(dir) = create_podcast_dir(&self.config, podcast_dir_name(&item.podcast_title))
+            else {
+                self.db.set_download_status(
+                    item.episode_id,
+                    DownloadStatus::Failed,
+                    Some("Could not create the podcast directory"),
+                )?;
+                continue;
+            };
+
+            self.db
+                .set_download_status(item.episode_id, DownloadStatus::Running, None)?;
+            let cancel = CancelToken::default();
+            self.running.insert(item.episode_id, cancel.clone());
+
+            let ep_data = item.ep_data();
+            let auth = item.auth.clone();
+            let tx = self.tx.clone();
+            let playerstats = self.playerstats.clone();
+            self.handle.spawn(async move {
+                let url = ep_data.url.clone();
+                let result = podcast::download_file(
+                    ep_data,
+                    dir,
+                    &auth,
+                    max_retries,
+                    &cancel,
+                    |done, total| {
+                        playerstats
+                            .lock()
+                            .downloads
+                            .insert(url.clone(), (done, total));
+                    },
+                )
+                .await;
+                let _ = tx.send(QueueRequest::Finished(result));
+            });
+        }
+
+        Ok(())
+    }
+}
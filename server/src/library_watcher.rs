@@ -0,0 +1,60 @@
+//! Watch the music directories and apply changes to the library database as they happen
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use parking_lot::Mutex;
+use termusiclib::config::ServerOverlay;
+use termusiclib::library_db::DataBase;
+
+use crate::PlayerStats;
+
+/// How long to wait for a burst of events, like a download or a copied album, to settle
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Watch all `music_dirs` recursively and sync changed paths into the library database
+///
+/// Every change to the database bumps [`PlayerStats::library_version`], so that clients can refresh their views.
+/// The returned [`Debouncer`] has to be kept alive for as long as the directories should be watched.
+pub fn watch(
+    config: &ServerOverlay,
+    playerstats: Arc<Mutex<PlayerStats>>,
+) -> Result<Debouncer<RecommendedWatcher>> {
+    let mut db = DataBase::new(config)?;
+
+    let mut debouncer = new_debouncer(DEBOUNCE_TIMEOUT, move |res: DebounceEventResult| {
+        let events = match res {
+            Ok(v) => v,
+            Err(err) => {
+                warn!("Error while watching the music directories: {err}");
+                return;
+            }
+        };
+
+        let mut paths: Vec<PathBuf> = events.into_iter().map(|v| v.path).collect();
+        paths.sort();
+        paths.dedup();
+
+        match db.sync_paths(&paths) {
+            Ok(true) => {
+                debug!("Library changed on disk, {} paths synced", paths.len());
+                playerstats.lock().library_version += 1;
+            }
+            Ok(false) => (),
+            Err(err) => error!("Error syncing changed paths into the library: {err:#}"),
+        }
+    })
+    .context("create filesystem watcher")?;
+
+    for dir in &config.settings.player.music_dirs {
+        let dir = shellexpand::path::tilde(dir);
+        if let Err(err) = debouncer.watcher().watch(&dir, RecursiveMode::Recursive) {
+            warn!("Could not watch \"{}\": {err}", dir.display());
+        }
+    }
+
+    Ok(debouncer)
+}
@@ -1,33 +1,56 @@
 use anyhow::Result;
 use parking_lot::Mutex;
+use std::sync::mpsc::Sender;
 use std::sync::Arc;
+use termusiclib::library_db::{DataBase, PlaylistDB, Query};
+use termusiclib::podcast::db::DownloadAction;
 use termusicplayback::player::music_player_server::MusicPlayer;
 use termusicplayback::player::{
-    CycleLoopReply, CycleLoopRequest, EmptyReply, GetProgressRequest, GetProgressResponse,
-    PlaySelectedRequest, PlayerTime, ReloadConfigRequest, ReloadPlaylistRequest,
-    SeekBackwardRequest, SeekForwardRequest, SkipNextRequest, SkipNextResponse,
-    SkipPreviousRequest, SpeedDownRequest, SpeedReply, SpeedUpRequest, ToggleGaplessReply,
-    ToggleGaplessRequest, TogglePauseRequest, TogglePauseResponse, VolumeDownRequest, VolumeReply,
+    CancelSleepTimerRequest, ControlDownloadsRequest, CreatePlaylistRequest, CycleLoopReply,
+    CycleLoopRequest, DedupPlaylistRequest, DeletePlaylistRequest, DuplicatePlaylistRequest,
+    EmptyReply, ExtendSleepTimerRequest, GetProgressRequest, GetProgressResponse,
+    ListPlaylistsRequest, PlaySelectedRequest, PlayerTime, PlaylistEditReply, PlaylistHistoryReply,
+    PlaylistInfo, PlaylistList, RedoPlaylistRequest, RefreshPodcastsRequest, ReloadConfigRequest,
+    ReloadPlaylistRequest, RenamePlaylistRequest, SearchLibraryReply, SearchLibraryRequest,
+    SeekBackwardRequest, SeekForwardRequest, SeekToRequest, SetSleepTimerRequest, SkipNextRequest,
+    SkipNextResponse, SkipPreviousRequest, SleepTimerState, SortPlaylistRequest, SpeedDownRequest,
+    SpeedReply, SpeedUpRequest, SwitchPlaylistRequest, ToggleGaplessReply, ToggleGaplessRequest,
+    TogglePauseRequest, TogglePauseResponse, UndoPlaylistRequest, VolumeDownRequest, VolumeReply,
     VolumeUpRequest,
 };
-use termusicplayback::{PlayerCmd, PlayerCmdSender};
+use termusicplayback::{DedupKey, PlayerCmd, PlayerCmdSender, SleepTimerMode, SortKey};
 use tonic::{Request, Response, Status};
 
+use crate::download_queue::QueueRequest;
+use crate::podcast_scheduler::RefreshRequest;
 use crate::PlayerStats;
 
-#[derive(Debug)]
 pub struct MusicPlayerService {
     cmd_tx: PlayerCmdSender,
     pub(crate) player_stats: Arc<Mutex<PlayerStats>>,
+    /// Connection for the named playlist calls which dont need the player
+    db: Mutex<DataBase>,
+    /// Requests for the podcast scheduler thread
+    podcast_tx: Sender<RefreshRequest>,
+    /// Requests for the podcast download queue thread
+    download_tx: Sender<QueueRequest>,
 }
 
 impl MusicPlayerService {
-    pub fn new(cmd_tx: PlayerCmdSender) -> Self {
+    pub fn new(
+        cmd_tx: PlayerCmdSender,
+        db: DataBase,
+        podcast_tx: Sender<RefreshRequest>,
+        download_tx: Sender<QueueRequest>,
+    ) -> Self {
         let player_stats = Arc::new(Mutex::new(PlayerStats::new()));
 
         Self {
             cmd_tx,
             player_stats,
+            db: Mutex::new(db),
+            podcast_tx,
+            download_tx,
         }
     }
 }
@@ -38,16 +61,246 @@ impl MusicPlayerService {
             error!("error {cmd:?}: {e}");
         }
     }
+
+    /// Get a named playlist, with a fitting [`Status`] if it does not exist
+    #[allow(clippy::result_large_err)]
+    fn get_playlist(db: &mut DataBase, id: u64) -> Result<PlaylistDB, Status> {
+        let list = db.playlist_list().map_err(db_error)?;
+        list.into_iter()
+            .find(|v| v.id == id)
+            .ok_or_else(|| Status::not_found(format!("no playlist with id {id}")))
+    }
+
+    /// Check that `name` can be used for a named playlist
+    #[allow(clippy::result_large_err)]
+    fn check_playlist_name(db: &mut DataBase, name: &str) -> Result<(), Status> {
+        if name.trim().is_empty() {
+            return Err(Status::invalid_argument("playlist name cannot be empty"));
+        }
+        let list = db.playlist_list().map_err(db_error)?;
+        if list.iter().any(|v| v.name == name) {
+            return Err(Status::already_exists(format!(
+                "a playlist named \"{name}\" already exists"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert a database error to a [`Status`], for errors not already handled more specifically
+fn db_error<E: std::fmt::Display>(err: E) -> Status {
+    Status::internal(format!("database error: {err}"))
 }
 
 #[tonic::async_trait]
 impl MusicPlayer for MusicPlayerService {
+    async fn list_playlists(
+        &self,
+        _request: Request<ListPlaylistsRequest>,
+    ) -> Result<Response<PlaylistList>, Status> {
+        let playlists = self.db.lock().playlist_list().map_err(db_error)?;
+        let reply = PlaylistList {
+            playlists: playlists.into_iter().map(Into::into).collect(),
+        };
+
+        Ok(Response::new(reply))
+    }
+
+    async fn search_library(
+        &self,
+        request: Request<SearchLibraryRequest>,
+    ) -> Result<Response<SearchLibraryReply>, Status> {
+        let query = Query::parse(&request.into_inner().query)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let tracks = self.db.lock().query_tracks(&query).map_err(db_error)?;
+        let reply = SearchLibraryReply {
+            tracks: tracks.into_iter().map(Into::into).collect(),
+        };
+
+        Ok(Response::new(reply))
+    }
+
+    async fn refresh_podcasts(
+        &self,
+        request: Request<RefreshPodcastsRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        let request = request.into_inner();
+        let request = if request.podcast_id != 0 {
+            RefreshRequest::Podcast(request.podcast_id)
+        } else if !request.folder.is_empty() {
+            RefreshRequest::Folder(request.folder)
+        } else {
+            RefreshRequest::All
+        };
+        self.podcast_tx
+            .send(request)
+            .map_err(|_| Status::unavailable("the podcast scheduler is not running"))?;
+
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn control_downloads(
+        &self,
+        request: Request<ControlDownloadsRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        let request = request.into_inner();
+        let action = DownloadAction::from_u32(request.action)
+            .ok_or_else(|| Status::invalid_argument("unknown download action"))?;
+        self.download_tx
+            .send(QueueRequest::Control(action, request.episode_ids))
+            .map_err(|_| Status::unavailable("the podcast download queue is not running"))?;
+
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn create_playlist(
+        &self,
+        request: Request<CreatePlaylistRequest>,
+    ) -> Result<Response<PlaylistInfo>, Status> {
+        let name = request.into_inner().name;
+        let mut db = self.db.lock();
+        Self::check_playlist_name(&mut db, &name)?;
+        let id = db.playlist_create(&name).map_err(db_error)?;
+        let reply = Self::get_playlist(&mut db, id)?.into();
+
+        Ok(Response::new(reply))
+    }
+
+    async fn rename_playlist(
+        &self,
+        request: Request<RenamePlaylistRequest>,
+    ) -> Result<Response<PlaylistInfo>, Status> {
+        let request = request.into_inner();
+        let mut db = self.db.lock();
+        Self::get_playlist(&mut db, request.id)?;
+        Self::check_playlist_name(&mut db, &request.name)?;
+        db.playlist_rename(request.id, &request.name)
+            .map_err(db_error)?;
+        let reply = Self::get_playlist(&mut db, request.id)?.into();
+
+        Ok(Response::new(reply))
+    }
+
+    async fn duplicate_playlist(
+        &self,
+        request: Request<DuplicatePlaylistRequest>,
+    ) -> Result<Response<PlaylistInfo>, Status> {
+        let request = request.into_inner();
+        let source = Self::get_playlist(&mut self.db.lock(), request.id)?;
+        if source.active {
+            // the entries of the active playlist are only stored on changes, make sure they are current
+            self.command(&PlayerCmd::NamedPlaylistStore);
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        let mut db = self.db.lock();
+        Self::check_playlist_name(&mut db, &request.name)?;
+        let id = db
+            .playlist_duplicate(request.id, &request.name)
+            .map_err(db_error)?;
+        let reply = Self::get_playlist(&mut db, id)?.into();
+
+        Ok(Response::new(reply))
+    }
+
+    async fn delete_playlist(
+        &self,
+        request: Request<DeletePlaylistRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        let id = request.into_inner().id;
+        let mut db = self.db.lock();
+        if Self::get_playlist(&mut db, id)?.active {
+            if db.playlist_list().map_err(db_error)?.len() <= 1 {
+                return Err(Status::failed_precondition(
+                    "cannot delete the only playlist",
+                ));
+            }
+            // the player has to switch away from it first
+            self.command(&PlayerCmd::NamedPlaylistDelete(id));
+        } else {
+            db.playlist_delete(id).map_err(db_error)?;
+        }
+
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn switch_playlist(
+        &self,
+        request: Request<SwitchPlaylistRequest>,
+    ) -> Result<Response<EmptyReply>, Status> {
+        let id = request.into_inner().id;
+        Self::get_playlist(&mut self.db.lock(), id)?;
+        self.command(&PlayerCmd::NamedPlaylistSwitch(id));
+
+        Ok(Response::new(EmptyReply {}))
+    }
+
+    async fn undo_playlist(
+        &self,
+        _request: Request<UndoPlaylistRequest>,
+    ) -> Result<Response<PlaylistHistoryReply>, Status> {
+        // reset so that a stale "changed" is not reported
+        self.player_stats.lock().playlist_history = PlaylistHistoryReply::default();
+        self.command(&PlayerCmd::PlaylistUndo);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+
+        Ok(Response::new(r.playlist_history))
+    }
+
+    async fn redo_playlist(
+        &self,
+        _request: Request<RedoPlaylistRequest>,
+    ) -> Result<Response<PlaylistHistoryReply>, Status> {
+        // reset so that a stale "changed" is not reported
+        self.player_stats.lock().playlist_history = PlaylistHistoryReply::default();
+        self.command(&PlayerCmd::PlaylistRedo);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+
+        Ok(Response::new(r.playlist_history))
+    }
+
+    async fn sort_playlist(
+        &self,
+        request: Request<SortPlaylistRequest>,
+    ) -> Result<Response<PlaylistEditReply>, Status> {
+        let request = request.into_inner();
+        let key = SortKey::from_u32(request.key);
+        // reset so that a stale "changed" is not reported
+        self.player_stats.lock().playlist_edit = PlaylistEditReply::default();
+        self.command(&PlayerCmd::PlaylistSort(key, request.descending));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+
+        Ok(Response::new(r.playlist_edit))
+    }
+
+    async fn dedup_playlist(
+        &self,
+        request: Request<DedupPlaylistRequest>,
+    ) -> Result<Response<PlaylistEditReply>, Status> {
+        let key = DedupKey::from_u32(request.into_inner().key);
+        // reset so that a stale "changed" is not reported
+        self.player_stats.lock().playlist_edit = PlaylistEditReply::default();
+        self.command(&PlayerCmd::PlaylistDedup(key));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+
+        Ok(Response::new(r.playlist_edit))
+    }
+
     async fn cycle_loop(
         &self,
         _request: Request<CycleLoopRequest>,
     ) -> Result<Response<CycleLoopReply>, Status> {
-        let reply = CycleLoopReply {};
         self.command(&PlayerCmd::CycleLoop);
+        // This is to let the player update the loop mode within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+        let reply = CycleLoopReply {
+            loop_mode: r.loop_mode,
+        };
 
         Ok(Response::new(reply))
     }
@@ -121,6 +374,25 @@ impl MusicPlayer for MusicPlayerService {
         Ok(Response::new(reply))
     }
 
+    async fn seek_to(
+        &self,
+        request: Request<SeekToRequest>,
+    ) -> Result<Response<PlayerTime>, Status> {
+        let position = request
+            .into_inner()
+            .position
+            .map(Into::into)
+            .unwrap_or_default();
+        self.command(&PlayerCmd::SeekTo(position));
+        // This is to let the player update the position within loop
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let s = self.player_stats.lock();
+
+        let reply = s.as_playertime();
+
+        Ok(Response::new(reply))
+    }
+
     async fn skip_next(
         &self,
         _request: Request<SkipNextRequest>,
@@ -140,6 +412,54 @@ impl MusicPlayer for MusicPlayerService {
         Ok(Response::new(reply))
     }
 
+    async fn set_sleep_timer(
+        &self,
+        request: Request<SetSleepTimerRequest>,
+    ) -> Result<Response<SleepTimerState>, Status> {
+        let request = request.into_inner();
+        let Some(mode) = SleepTimerMode::from_u32(
+            request.mode,
+            request.duration.map(Into::into).unwrap_or_default(),
+        ) else {
+            return Err(Status::invalid_argument(format!(
+                "unknown sleep timer mode {}",
+                request.mode
+            )));
+        };
+        self.command(&PlayerCmd::SleepTimerSet(mode));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+
+        Ok(Response::new(r.sleep_timer))
+    }
+
+    async fn extend_sleep_timer(
+        &self,
+        request: Request<ExtendSleepTimerRequest>,
+    ) -> Result<Response<SleepTimerState>, Status> {
+        let amount = request
+            .into_inner()
+            .amount
+            .map(Into::into)
+            .unwrap_or_default();
+        self.command(&PlayerCmd::SleepTimerExtend(amount));
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+
+        Ok(Response::new(r.sleep_timer))
+    }
+
+    async fn cancel_sleep_timer(
+        &self,
+        _request: Request<CancelSleepTimerRequest>,
+    ) -> Result<Response<SleepTimerState>, Status> {
+        self.command(&PlayerCmd::SleepTimerCancel);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let r = self.player_stats.lock();
+
+        Ok(Response::new(r.sleep_timer))
+    }
+
     async fn speed_down(
         &self,
         _request: Request<SpeedDownRequest>,
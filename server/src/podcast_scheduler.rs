@@ -0,0 +1,283 @@
+//! Refresh the podcast feeds periodically and apply their download and retention policies
+use std::collections::HashMap;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use termusiclib::config::v2::server::PodcastSettings;
+use termusiclib::config::ServerOverlay;
+use termusiclib::podcast::db::{Database, PodcastDBId};
+use termusiclib::podcast::{self, FeedSyncReport, Podcast};
+use termusiclib::utils::get_app_config_path;
+use tokio::runtime::Handle;
+
+use crate::download_queue::QueueRequest;
+use crate::PlayerStats;
+
+/// How often to check whether a feed is due for a refresh
+const TICK: Duration = Duration::from_secs(60);
+
+/// Request to refresh podcasts right away, instead of waiting until they are due
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RefreshRequest {
+    All,
+    /// The podcast with the given id
+    Podcast(PodcastDBId),
+    /// All podcasts in the folder with the given name
+    Folder(String),
+}
+
+/// Spawn the thread that refreshes every feed once its refresh interval passed, and on requests from `rx`
+///
+/// Every change to the database bumps [`PlayerStats::podcast_version`] and progress is reported in
+/// [`PlayerStats::podcast_status`], so that clients can refresh their views.
+/// Episodes to download are added to the download queue, which `downloads` is told to run.
+/// The thread exits once all senders of `rx` are dropped.
+pub fn spawn(
+    config: ServerOverlay,
+    playerstats: Arc<Mutex<PlayerStats>>,
+    rx: Receiver<RefreshRequest>,
+    downloads: Sender<QueueRequest>,
+    handle: Handle,
+) -> Result<()> {
+    let db_path = get_app_config_path().context("get podcast database path")?;
+    let db = Database::new(&db_path)?;
+
+    let mut scheduler = Scheduler {
+        db,
+        config,
+        playerstats,
+        downloads,
+        handle,
+        failed_at: HashMap::new(),
+    };
+    std::thread::Builder::new()
+        .name("podcast scheduler".into())
+        .spawn(move || scheduler.run(&rx))?;
+
+    Ok(())
+}
+
+/// Whether `request` asks to refresh the podcast with `id` in `folder`
+fn is_requested(request: &RefreshRequest, id: PodcastDBId, folder: Option<&str>) -> bool {
+    match request {
+        RefreshRequest::All => true,
+        RefreshRequest::Podcast(requested) => *requested == id,
+        RefreshRequest::Folder(name) => folder == Some(name.as_str()),
+    }
+}
+
+/// Whether the refresh interval of the feed at `url` passed since it was last checked
+///
+/// A failed refresh counts as a check, so that broken feeds wait for the next interval.
+fn is_due(
+    settings: &PodcastSettings,
+    url: &str,
+    last_checked: DateTime<Utc>,
+    failed_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    let interval = settings.refresh_interval_for(url);
+    if interval == 0 {
+        return false;
+    }
+
+    let last = failed_at.map_or(last_checked, |v| last_checked.max(v));
+    now - last >= chrono::Duration::minutes(i64::from(interval))
+}
+
+struct Scheduler {
+    db: Database,
+    config: ServerOverlay,
+    playerstats: Arc<Mutex<PlayerStats>>,
+    /// Requests for the download queue thread
+    downloads: Sender<QueueRequest>,
+    handle: Handle,
+    /// When the last refresh of a podcast failed, so that broken feeds are not retried every tick
+    failed_at: HashMap<PodcastDBId, DateTime<Utc>>,
+}
+
+impl Scheduler {
+    fn run(&mut self, rx: &Receiver<RefreshRequest>) {
+        loop {
+            let requested = match rx.recv_timeout(TICK) {
+                Ok(request) => Some(request),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+
+            let podcasts = match self.db.get_podcasts() {
+                Ok(v) => v,
+                Err(err) => {
+                    error!("Could not get the podcasts to refresh: {err:#}");
+                    continue;
+                }
+            };
+            let now = Utc::now();
+            let due: Vec<&Podcast> = podcasts
+                .iter()
+                .filter(|pod| {
+                    requested
+                        .as_ref()
+                        .is_some_and(|v| is_requested(v, pod.id, pod.folder.as_deref()))
+                        || is_due(
+                            &self.config.settings.podcast,
+                            &pod.url,
+                            pod.last_checked,
+                            self.failed_at.get(&pod.id).copied(),
+                            now,
+                        )
+                })
+                .collect();
+
+            self.refresh(&due);
+        }
+    }
+
+    /// Sync all `podcasts` one after another, reporting the progress as status
+    fn refresh(&mut self, podcasts: &[&Podcast]) {
+        if podcasts.is_empty() {
+            return;
+        }
+
+        let total = podcasts.len();
+        let mut sum = FeedSyncReport::default();
+        let mut failed_feeds = 0;
+        let mut unchanged_feeds = 0;
+        for (idx, pod) in podcasts.iter().enumerate() {
+            self.set_status(format!(
+                "Refreshing podcast {}/{total}: {}",
+                idx + 1,
+                pod.title
+            ));
+
+            let result = self
+                .handle
+                .block_on(podcast::sync_podcast(&self.db, pod, &self.config));
+            match result {
+                Ok(report) => {
+                    self.failed_at.remove(&pod.id);
+                    if report.changed() {
+                        self.playerstats.lock().podcast_version += 1;
+                    }
+                    sum.added += report.added;
+                    sum.queued += report.queued;
+                    sum.removed += report.removed;
+                    unchanged_feeds += usize::from(report.skipped);
+                }
+                Err(err) => {
+                    warn!("Refreshing podcast \"{}\" failed: {err:#}", pod.url);
+                    self.failed_at.insert(pod.id, Utc::now());
+                    failed_feeds += 1;
+                }
+            }
+        }
+
+        if sum.queued > 0 {
+            let _ = self.downloads.send(QueueRequest::Run);
+        }
+
+        let mut status = format!(
+            "Refreshed {total} podcasts: {} new episodes, {} queued for download, {unchanged_feeds} feeds unchanged",
+            sum.added, sum.queued
+        );
+        if failed_feeds > 0 {
+            status = format!("{status}, {failed_feeds} feeds failed");
+        }
+        info!("{status}");
+        self.set_status(status);
+    }
+
+    fn set_status(&self, status: String) {
+        self.playerstats.lock().podcast_status = status;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn settings() -> PodcastSettings {
+        PodcastSettings {
+            refresh_interval: 60,
+            feed_refresh_intervals: [
+                ("http://often".to_string(), 10),
+                ("http://never".to_string(), 0),
+            ]
+            .into(),
+            ..Default::default()
+        }
+    }
+
+    fn minutes_ago(now: DateTime<Utc>, minutes: i64) -> DateTime<Utc> {
+        now - chrono::Duration::minutes(minutes)
+    }
+
+    #[test]
+    fn should_be_due_after_the_feed_interval() {
+        let settings = settings();
+        let now = Utc::now();
+        let due =
+            |url: &str, checked: i64| is_due(&settings, url, minutes_ago(now, checked), None, now);
+
+        assert!(!due("http://other", 59));
+        assert!(due("http://other", 60));
+        assert!(due("http://often", 10));
+        assert!(!due("http://often", 9));
+        assert!(!due("http://never", 60 * 24 * 365));
+    }
+
+    #[test]
+    fn should_wait_after_a_failed_refresh() {
+        let settings = settings();
+        let now = Utc::now();
+        let checked = minutes_ago(now, 120);
+
+        assert!(is_due(&settings, "http://other", checked, None, now));
+        assert!(!is_due(
+            &settings,
+            "http://other",
+            checked,
+            Some(minutes_ago(now, 5)),
+            now
+        ));
+        assert!(is_due(
+            &settings,
+            "http://other",
+            checked,
+            Some(minutes_ago(now, 60)),
+            now
+        ));
+        // a failure before the last successful check does not delay the next one
+        assert!(is_due(
+            &settings,
+            "http://other",
+            checked,
+            Some(minutes_ago(now, 180)),
+            now
+        ));
+    }
+
+    #[test]
+    fn should_match_requests() {
+        let requests = [
+            RefreshRequest::All,
+            RefreshRequest::Podcast(2),
+            RefreshRequest::Folder("News".to_string()),
+        ];
+        let matching = |id, folder| -> Vec<bool> {
+            requests
+                .iter()
+                .map(|v| is_requested(v, id, folder))
+                .collect()
+        };
+
+        assert_eq!(matching(1, None), vec![true, false, false]);
+        assert_eq!(matching(2, Some("Music")), vec![true, true, false]);
+        assert_eq!(matching(3, Some("News")), vec![true, false, true]);
+    }
+}
@@ -1,7 +1,11 @@
 mod cli;
+mod download_queue;
+mod library_watcher;
 mod logger;
 mod music_player_service;
+mod podcast_scheduler;
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
@@ -11,12 +15,16 @@ use clap::Parser;
 use music_player_service::MusicPlayerService;
 use parking_lot::Mutex;
 use termusiclib::config::v2::server::config_extra::ServerConfigVersionedDefaulted;
-use termusiclib::config::v2::server::ScanDepth;
+use termusiclib::config::v2::server::{LoopMode, ScanDepth};
 use termusiclib::config::ServerOverlay;
+use termusiclib::library_db::DataBase;
 use termusiclib::track::MediaType;
 use termusiclib::{podcast, utils};
 use termusicplayback::player::music_player_server::MusicPlayerServer;
-use termusicplayback::player::{GetProgressResponse, PlayerTime};
+use termusicplayback::player::{
+    DownloadProgress, GetProgressResponse, PlayerTime, PlaylistEditReply, PlaylistHistoryReply,
+    SleepTimerState,
+};
 use termusicplayback::{
     Backend, BackendSelect, GeneralPlayer, PlayerCmd, PlayerCmdReciever, PlayerCmdSender,
     PlayerProgress, PlayerTrait, SpeedSigned, Status, VolumeSigned,
@@ -43,7 +51,18 @@ struct PlayerStats {
     pub speed: i32,
     pub gapless: bool,
     pub current_track_updated: bool,
+    pub current_track_from_queue: bool,
     pub radio_title: String,
+    pub sleep_timer: SleepTimerState,
+    pub active_playlist: u64,
+    pub playlist_history: PlaylistHistoryReply,
+    pub playlist_edit: PlaylistEditReply,
+    pub loop_mode: u32,
+    pub library_version: u64,
+    pub podcast_version: u64,
+    pub podcast_status: String,
+    /// Downloaded and total bytes of the running episode downloads, by url
+    pub downloads: HashMap<String, (u64, Option<u64>)>,
 }
 
 impl PlayerStats {
@@ -59,7 +78,17 @@ impl PlayerStats {
             speed: 10,
             gapless: true,
             current_track_updated: false,
+            current_track_from_queue: false,
             radio_title: String::new(),
+            sleep_timer: SleepTimerState::default(),
+            active_playlist: 0,
+            playlist_history: PlaylistHistoryReply::default(),
+            playlist_edit: PlaylistEditReply::default(),
+            loop_mode: LoopMode::default().as_u32(),
+            library_version: 0,
+            podcast_version: 0,
+            podcast_status: String::new(),
+            downloads: HashMap::new(),
         }
     }
 
@@ -72,7 +101,22 @@ impl PlayerStats {
             speed: self.speed,
             gapless: self.gapless,
             current_track_updated: self.current_track_updated,
+            current_track_from_queue: self.current_track_from_queue,
             radio_title: self.radio_title.clone(),
+            sleep_timer: Some(self.sleep_timer),
+            active_playlist: self.active_playlist,
+            library_version: self.library_version,
+            podcast_version: self.podcast_version,
+            podcast_status: self.podcast_status.clone(),
+            downloads: self
+                .downloads
+                .iter()
+                .map(|(url, (done, total))| DownloadProgress {
+                    url: url.clone(),
+                    done: *done,
+                    total: total.unwrap_or_default(),
+                })
+                .collect(),
         }
     }
 
@@ -103,10 +147,42 @@ async fn actual_main() -> Result<()> {
 
     info!("Server starting...");
     let (cmd_tx, cmd_rx) = tokio::sync::mpsc::unbounded_channel();
-
-    let music_player_service: MusicPlayerService = MusicPlayerService::new(cmd_tx.clone());
+    let (podcast_tx, podcast_rx) = std::sync::mpsc::channel();
+    let (download_tx, download_rx) = std::sync::mpsc::channel();
+
+    let music_player_service: MusicPlayerService = MusicPlayerService::new(
+        cmd_tx.clone(),
+        DataBase::new(&config)?,
+        podcast_tx,
+        download_tx.clone(),
+    );
     let playerstats = music_player_service.player_stats.clone();
 
+    // keep the watcher alive until the server exits
+    let _library_watcher = library_watcher::watch(&config, playerstats.clone())
+        .map_err(|err| error!("Could not watch the music directories: {err:#}"))
+        .ok();
+
+    if let Err(err) = download_queue::spawn(
+        config.clone(),
+        playerstats.clone(),
+        download_tx.clone(),
+        download_rx,
+        Handle::current(),
+    ) {
+        error!("Could not start the podcast download queue: {err:#}");
+    }
+
+    if let Err(err) = podcast_scheduler::spawn(
+        config.clone(),
+        playerstats.clone(),
+        podcast_rx,
+        download_tx,
+        Handle::current(),
+    ) {
+        error!("Could not start refreshing podcasts: {err:#}");
+    }
+
     let cmd_tx_ctrlc = cmd_tx.clone();
     let cmd_tx_ticker = cmd_tx.clone();
 
@@ -166,6 +242,10 @@ fn player_loop(
     playerstats: Arc<Mutex<PlayerStats>>,
 ) -> Result<()> {
     let mut player = GeneralPlayer::new_backend(backend, config, cmd_tx)?;
+    match player.named_playlist_init() {
+        Ok(id) => playerstats.lock().active_playlist = id,
+        Err(e) => error!("error when initializing named playlists: {e:#}"),
+    }
     while let Some(cmd) = cmd_rx.blocking_recv() {
         #[allow(unreachable_patterns)]
         match cmd {
@@ -174,6 +254,8 @@ fn player_loop(
                 if !player.playlist.is_empty()
                     && !player.playlist.has_next_track()
                     && player.config.read().settings.player.gapless
+                    // the next track must not start if the sleep timer stops playback after this one
+                    && !player.sleep_timer_expires_after_track()
                 {
                     player.enqueue_next_from_playlist();
                 }
@@ -184,6 +266,9 @@ fn player_loop(
                 if let Err(e) = player.playlist.save() {
                     error!("error when saving playlist: {e}");
                 };
+                if let Err(e) = player.named_playlist_store() {
+                    error!("error when storing named playlist: {e:#}");
+                };
                 if let Err(e) =
                     ServerConfigVersionedDefaulted::save_config_path(&player.config.read().settings)
                 {
@@ -192,7 +277,8 @@ fn player_loop(
                 std::process::exit(0);
             }
             PlayerCmd::CycleLoop => {
-                player.config.write().settings.player.loop_mode = player.playlist.cycle_loop_mode();
+                let loop_mode = player.playlist.cycle_loop_mode();
+                playerstats.lock().loop_mode = loop_mode.as_u32();
             }
             PlayerCmd::Eos => {
                 info!("Eos received");
@@ -204,14 +290,90 @@ fn player_loop(
                     "current track index: {:?}",
                     player.playlist.get_current_track_index()
                 );
-                player.playlist.clear_current_track();
-                player.start_play();
+                if !player.sleep_timer_track_ended() {
+                    player.playlist.clear_current_track();
+                    player.start_play();
+                }
+                if player.playlist.is_current_from_queue() {
+                    // persist the consumed queue, clients reload it from there
+                    if let Err(e) = player.playlist.save() {
+                        error!("error when saving playlist: {e}");
+                    }
+                }
                 debug!(
                     "playing index is: {}",
                     player.playlist.get_current_track_index()
                 );
             }
-            PlayerCmd::GetProgress | PlayerCmd::ProcessID => {}
+            PlayerCmd::GetProgress
+            | PlayerCmd::ProcessID
+            | PlayerCmd::NamedPlaylistCreate(_)
+            | PlayerCmd::NamedPlaylistDuplicate(..)
+            | PlayerCmd::NamedPlaylistList
+            | PlayerCmd::NamedPlaylistRename(..)
+            | PlayerCmd::PodcastDownloads(..)
+            | PlayerCmd::PodcastRefresh(_)
+            | PlayerCmd::PodcastRefreshFolder(_) => {}
+            PlayerCmd::NamedPlaylistDelete(id) => {
+                info!("delete named playlist {id}");
+                player.player_save_last_position();
+                match player.named_playlist_delete(id) {
+                    Ok(active) => playerstats.lock().active_playlist = active,
+                    Err(e) => error!("error when deleting named playlist: {e:#}"),
+                }
+            }
+            PlayerCmd::NamedPlaylistStore => {
+                if let Err(e) = player.named_playlist_store() {
+                    error!("error when storing named playlist: {e:#}");
+                }
+            }
+            PlayerCmd::NamedPlaylistSwitch(id) => {
+                info!("switch to named playlist {id}");
+                player.player_save_last_position();
+                match player.named_playlist_switch(id) {
+                    Ok(()) => playerstats.lock().active_playlist = id,
+                    Err(e) => error!("error when switching named playlist: {e:#}"),
+                }
+            }
+            PlayerCmd::PlaylistUndo | PlayerCmd::PlaylistRedo => {
+                let changed = if matches!(cmd, PlayerCmd::PlaylistUndo) {
+                    player.playlist.undo()
+                } else {
+                    player.playlist.redo()
+                };
+                if changed {
+                    info!("playlist {cmd:?} applied");
+                    persist_playlist(&mut player);
+                }
+                let (undo_steps, redo_steps) = player.playlist.history_len();
+                playerstats.lock().playlist_history = PlaylistHistoryReply {
+                    changed,
+                    undo_steps: u32::try_from(undo_steps).unwrap_or(u32::MAX),
+                    redo_steps: u32::try_from(redo_steps).unwrap_or(u32::MAX),
+                };
+            }
+            PlayerCmd::PlaylistSort(key, descending) => {
+                let changed = player.playlist.sort(key, descending);
+                if changed {
+                    info!("playlist sorted by {key:?}, descending: {descending}");
+                    persist_playlist(&mut player);
+                }
+                playerstats.lock().playlist_edit = PlaylistEditReply {
+                    changed,
+                    removed: 0,
+                };
+            }
+            PlayerCmd::PlaylistDedup(key) => {
+                let removed = player.playlist.remove_duplicates(key);
+                if removed > 0 {
+                    info!("removed {removed} duplicates by {key:?} from playlist");
+                    persist_playlist(&mut player);
+                }
+                playerstats.lock().playlist_edit = PlaylistEditReply {
+                    changed: removed > 0,
+                    removed: u32::try_from(removed).unwrap_or(u32::MAX),
+                };
+            }
             PlayerCmd::PlaySelected => {
                 info!("play selected");
                 player.player_save_last_position();
@@ -230,6 +392,9 @@ fn player_loop(
             }
             PlayerCmd::ReloadPlaylist => {
                 player.playlist.reload_tracks().ok();
+                if let Err(e) = player.named_playlist_store() {
+                    error!("error when storing named playlist: {e:#}");
+                }
             }
             PlayerCmd::SeekBackward => {
                 player.seek_relative(false);
@@ -245,11 +410,39 @@ fn player_loop(
                     p_tick.progress = progress
                 }
             }
+            PlayerCmd::SeekTo(position) => {
+                player.seek_to(position);
+                let mut p_tick = playerstats.lock();
+                if let Some(progress) = player.get_progress() {
+                    p_tick.progress = progress
+                }
+            }
             PlayerCmd::SkipNext => {
                 info!("skip to next track.");
                 player.player_save_last_position();
                 player.next();
             }
+            PlayerCmd::SleepTimerCancel => {
+                info!("sleep timer canceled");
+                player.sleep_timer_cancel();
+                let mut p_tick = playerstats.lock();
+                p_tick.sleep_timer = player.sleep_timer_state();
+            }
+            PlayerCmd::SleepTimerExtend(amount) => {
+                let remaining = player.sleep_timer_remaining();
+                player.sleep_timer.extend(amount, remaining);
+                info!("sleep timer extended by {amount:?}");
+                let mut p_tick = playerstats.lock();
+                p_tick.sleep_timer = player.sleep_timer_state();
+            }
+            PlayerCmd::SleepTimerSet(mode) => {
+                // restore the volume in case a previous timer was already fading
+                player.sleep_timer_cancel();
+                player.sleep_timer.set(mode);
+                info!("sleep timer set to {mode:?}");
+                let mut p_tick = playerstats.lock();
+                p_tick.sleep_timer = player.sleep_timer_state();
+            }
             PlayerCmd::SpeedDown => {
                 let new_speed = player.add_speed(-SPEED_STEP);
                 info!("after speed down: {}", new_speed);
@@ -268,10 +461,12 @@ fn player_loop(
             PlayerCmd::Tick => {
                 // info!("tick received");
                 player.mpris_handle_events();
+                player.sleep_timer_tick();
                 let mut p_tick = playerstats.lock();
                 p_tick.status = player.playlist.status().as_u32();
+                p_tick.sleep_timer = player.sleep_timer_state();
                 // branch to auto-start playing if status is "stopped"(not paused) and playlist is not empty anymore
-                if player.playlist.status() == Status::Stopped {
+                if player.playlist.status() == Status::Stopped && !player.is_stopped_on_purpose() {
                     if player.playlist.is_empty() {
                         continue;
                     }
@@ -291,6 +486,7 @@ fn player_loop(
                 if player.current_track_updated {
                     p_tick.current_track_index = player.playlist.get_current_track_index() as u32;
                     p_tick.current_track_updated = player.current_track_updated;
+                    p_tick.current_track_from_queue = player.playlist.is_current_from_queue();
                     player.current_track_updated = false;
                 }
                 if let Some(track) = player.playlist.current_track() {
@@ -363,6 +559,16 @@ fn player_loop(
     Ok(())
 }
 
+/// Save the playlist after the server changed it, so that clients can reload it
+fn persist_playlist(player: &mut GeneralPlayer) {
+    if let Err(e) = player.playlist.save() {
+        error!("error when saving playlist: {e}");
+    }
+    if let Err(e) = player.named_playlist_store() {
+        error!("error when storing named playlist: {e:#}");
+    }
+}
+
 /// Spawn the thread that periodically sends [`PlayerCmd::Tick`]
 fn ticker_thread(cmd_tx: PlayerCmdSender) -> Result<()> {
     std::thread::Builder::new()
@@ -429,12 +635,22 @@ fn execute_action(action: cli::Action, config: &ServerOverlay) -> Result<()> {
             podcast::import_from_opml(&config_dir_path, &config.settings.podcast, &path)
                 .context("import opml")?;
         }
-        cli::Action::Export { file } => {
+        cli::Action::Export {
+            file,
+            with_credentials,
+        } => {
             println!("need to export to file {}", file.display());
             let path = utils::absolute_path(&file)?;
             let config_dir_path =
                 utils::get_app_config_path().context("getting app-config-path")?;
-            podcast::export_to_opml(&config_dir_path, &path).context("export opml")?;
+            podcast::export_to_opml(&config_dir_path, &path, with_credentials)
+                .context("export opml")?;
+        }
+        cli::Action::Duplicates => {
+            let report = DataBase::new(config)?
+                .find_duplicates()
+                .context("find duplicates")?;
+            println!("{}", serde_json::to_string_pretty(&report)?);
         }
     };
 
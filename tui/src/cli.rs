@@ -86,6 +86,9 @@ pub enum Action {
     Export {
         #[arg(value_name = "FILE")]
         file: PathBuf,
+        /// Include the username and password of private feeds in their urls.
+        #[arg(long)]
+        with_credentials: bool,
     },
     /// Export Podcast feeds to a opml file.
     Import {
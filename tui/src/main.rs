@@ -304,12 +304,16 @@ fn execute_action(action: cli::Action, config: &CombinedSettings) -> Result<()>
             )
             .context("import opml")?;
         }
-        cli::Action::Export { file } => {
+        cli::Action::Export {
+            file,
+            with_credentials,
+        } => {
             println!("need to export to file {}", file.display());
             let path = utils::absolute_path(&file)?;
             let config_dir_path =
                 utils::get_app_config_path().context("getting app-config-path")?;
-            podcast::export_to_opml(&config_dir_path, &path).context("export opml")?;
+            podcast::export_to_opml(&config_dir_path, &path, with_credentials)
+                .context("export opml")?;
         }
     };
 
@@ -1,9 +1,12 @@
+use crate::ui::components::DATABASE_SEARCH_TITLE;
 use crate::ui::Model;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use termusiclib::config::v2::server::ScanDepth;
 use termusiclib::config::SharedTuiSettings;
-use termusiclib::library_db::SearchCriteria;
+use termusiclib::library_db::{Query, SearchCriteria};
 use termusiclib::types::{DBMsg, Id, Msg};
-use termusiclib::utils::{is_playlist, playlist_get_vec};
+use termusiclib::utils::{is_playlist, playlist_get_entries};
+use termusicplayback::QueuePosition;
 use tui_realm_stdlib::List;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
 use tuirealm::props::Borders;
@@ -44,6 +47,8 @@ impl DBListCriteria {
                     TableBuilder::default()
                         .add_col(TextSpan::from("Artist"))
                         .add_row()
+                        .add_col(TextSpan::from("Album Artist"))
+                        .add_row()
                         .add_col(TextSpan::from("Album"))
                         .add_row()
                         .add_col(TextSpan::from("Genre"))
@@ -405,6 +410,18 @@ impl Component<Msg, NoUserEvent> for DBListSearchTracks {
             Event::Keyboard(keyevent) if keyevent == keys.database_keys.add_all.get() => {
                 return Some(Msg::DataBase(DBMsg::AddAllToPlaylist))
             }
+            Event::Keyboard(keyevent) if keyevent == keys.database_keys.enqueue_next.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::DataBase(DBMsg::EnqueueNext(index)));
+                }
+                CmdResult::None
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.database_keys.enqueue_last.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::DataBase(DBMsg::EnqueueLast(index)));
+                }
+                CmdResult::None
+            }
 
             Event::Keyboard(keyevent) if keyevent == keys.library_keys.search.get() => {
                 return Some(Msg::GeneralSearch(crate::ui::GSMsg::PopupShowDatabase))
@@ -417,6 +434,16 @@ impl Component<Msg, NoUserEvent> for DBListSearchTracks {
 }
 
 impl Model {
+    /// Add the track at `index` of the "Tracks" section to the up-next queue
+    pub fn database_enqueue(&mut self, index: usize, position: QueuePosition) {
+        if let Some(track) = self.dw.search_tracks.get(index) {
+            let file = track.file.clone();
+            if let Err(e) = self.queue_add(&file, position) {
+                self.mount_error_popup(e.context("queue add"));
+            }
+        }
+    }
+
     pub fn database_sync_tracks(&mut self) {
         let mut table: TableBuilder = TableBuilder::default();
 
@@ -457,44 +484,20 @@ impl Model {
     }
     pub fn database_sync_results(&mut self) {
         let mut table: TableBuilder = TableBuilder::default();
-        let mut index = 0;
+        let music_dirs = self.database_music_dirs();
         for (idx, record) in self.dw.search_results.iter().enumerate() {
-            let mut display_name = String::new();
-            match self.dw.criteria {
-                SearchCriteria::Playlist => {
-                    let path = Path::new(record);
-                    let path_string = path.to_string_lossy().to_string();
-                    let mut vec = path_string.split('/');
-                    if let Some(v1) = vec.next_back() {
-                        if let Some(v2) = vec.next_back() {
-                            display_name = format!("{v2}/{v1}");
-                        }
-                    }
-                }
-                SearchCriteria::Directory => {
-                    let path = Path::new(record);
-                    let path_string = path.to_string_lossy().to_string();
-                    let mut vec = path_string.split('/');
-                    if let Some(v1) = vec.next_back() {
-                        if let Some(v2) = vec.next_back() {
-                            display_name = format!("{v2}/{v1}/");
-                        }
-                    }
-                }
-                _ => {
-                    display_name.clone_from(record);
-                }
+            let display_name = match self.dw.criteria {
+                SearchCriteria::Directory => format!("{}/", display_path(&music_dirs, record)),
+                SearchCriteria::Playlist => display_path(&music_dirs, record),
+                _ => record.clone(),
             };
-            if !display_name.is_empty() {
-                if idx > 0 {
-                    table.add_row();
-                }
-                index += 1;
-                table
-                    .add_col(TextSpan::from(format!("{index}")))
-                    .add_col(TextSpan::from(" "))
-                    .add_col(TextSpan::from(display_name));
+            if idx > 0 {
+                table.add_row();
             }
+            table
+                .add_col(TextSpan::from(format!("{}", idx + 1)))
+                .add_col(TextSpan::from(" "))
+                .add_col(TextSpan::from(display_name));
         }
         if self.dw.search_results.is_empty() {
             table.add_col(TextSpan::from("empty results"));
@@ -527,35 +530,90 @@ impl Model {
         self.app.active(&Id::DBListSearchResult).ok();
     }
 
+    /// Re-query the shown search results and tracks after the library changed, without moving the focus
+    pub fn database_refresh(&mut self) {
+        if self.dw.criteria == SearchCriteria::Playlist || self.dw.search_results.is_empty() {
+            return;
+        }
+
+        if let Ok(results) = self.db.get_criterias(&self.dw.criteria) {
+            self.dw.search_results = results;
+        }
+        self.database_sync_results();
+
+        if self.dw.search_tracks.is_empty() {
+            return;
+        }
+        let Ok(State::One(StateValue::Usize(index))) = self.app.state(&Id::DBListSearchResult)
+        else {
+            return;
+        };
+        if let Some(result) = self.dw.search_results.get(index) {
+            if let Ok(vec) = self.db.get_record_by_criteria(result, &self.dw.criteria) {
+                self.dw.search_tracks = vec;
+            }
+        }
+        self.database_sync_tracks();
+    }
+
+    /// Get the configured music directories, with `~` expanded
+    fn database_music_dirs(&self) -> Vec<PathBuf> {
+        let config_server = self.config_server.read();
+        let mut dirs: Vec<PathBuf> = config_server
+            .settings
+            .player
+            .music_dirs
+            .iter()
+            .chain(config_server.music_dir_overwrite.as_ref())
+            .map(|v| shellexpand::path::tilde(v).into_owned())
+            .collect();
+        dirs.dedup();
+        dirs
+    }
+
+    /// Find all playlist files in the music directories, independent of the current library tree root
     fn database_get_playlist(&self) -> Vec<String> {
+        let max_depth = self.config_server.read().get_library_scan_depth();
         let mut vec = Vec::new();
 
-        let root = self.library.tree.root();
-        let p: &Path = Path::new(root.id());
-        let all_items = walkdir::WalkDir::new(p).follow_links(true);
-        for record in all_items
-            .into_iter()
-            .filter_map(std::result::Result::ok)
-            .filter(|p| is_playlist(&p.path().to_string_lossy()))
-        {
-            let full_path_name = record.path().to_string_lossy().to_string();
-            vec.push(full_path_name);
+        for dir in self.database_music_dirs() {
+            let mut walker = walkdir::WalkDir::new(dir).follow_links(true);
+            if let ScanDepth::Limited(limit) = max_depth {
+                walker = walker.max_depth(usize::try_from(limit).unwrap_or(usize::MAX));
+            }
+
+            for record in walker
+                .into_iter()
+                .filter_map(std::result::Result::ok)
+                .filter(|p| is_playlist(&p.path().to_string_lossy()))
+            {
+                vec.push(record.path().to_string_lossy().to_string());
+            }
         }
+        vec.sort();
+        vec.dedup();
         vec
     }
 
     pub fn database_update_search_tracks(&mut self, index: usize) {
         match self.dw.criteria {
             SearchCriteria::Playlist => {
-                if let Some(result) = self.dw.search_results.get(index) {
-                    if let Ok(vec) = playlist_get_vec(result) {
-                        let mut vec_db = Vec::new();
-                        for item in vec {
-                            if let Ok(i) = self.db.get_record_by_path(&item) {
-                                vec_db.push(i);
-                            }
+                if let Some(result) = self.dw.search_results.get(index).cloned() {
+                    // entries that moved inside the library are relinked, the ones not in the library are skipped
+                    match playlist_get_entries(&result)
+                        .and_then(|entries| Ok(self.db.relink(entries)?))
+                    {
+                        Ok(report) => {
+                            let tracks = report
+                                .files()
+                                .iter()
+                                .filter_map(|file| self.db.get_record_by_path(file).ok())
+                                .collect();
+                            self.dw.search_tracks = tracks;
+                        }
+                        Err(err) => {
+                            self.mount_error_popup(err.context("read playlist"));
                         }
-                        self.dw.search_tracks = vec_db;
                     }
                 }
             }
@@ -619,36 +677,37 @@ impl Model {
     }
 
     pub fn database_update_search(&mut self, input: &str) {
-        let mut table: TableBuilder = TableBuilder::default();
-        let mut idx = 0;
-        let search = format!("*{}*", input.to_lowercase());
-        let mut db_tracks = vec![];
-        if let Ok(tracks) = self.db.get_all_records() {
-            db_tracks.clone_from(&tracks);
-            for record in tracks {
-                if wildmatch::WildMatch::new(&search).matches(&record.artist.to_lowercase())
-                    | wildmatch::WildMatch::new(&search).matches(&record.title.to_lowercase())
-                {
-                    if idx > 0 {
-                        table.add_row();
-                    }
+        let query = match Query::parse(input) {
+            Ok(query) => query,
+            Err(e) => {
+                // keep the previous results until the query is valid again
+                self.general_search_update_title(&format!(" Error: {e} "));
+                return;
+            }
+        };
+        self.general_search_update_title(DATABASE_SEARCH_TITLE);
 
-                    let duration =
-                        termusiclib::track::Track::duration_formatted_short(&record.duration);
-                    let duration_string = format!("[{duration:^6.6}]");
-
-                    table
-                        .add_col(TextSpan::new(duration_string.as_str()))
-                        .add_col(
-                            TextSpan::new(record.artist)
-                                .fg(tuirealm::tui::style::Color::LightYellow),
-                        )
-                        .add_col(TextSpan::new(record.title).bold())
-                        .add_col(TextSpan::new(record.file));
-                    // .add_col(TextSpan::new(record.album().unwrap_or("Unknown Album")));
-                    idx += 1;
-                }
+        let mut table: TableBuilder = TableBuilder::default();
+        let db_tracks = match self.db.query_tracks(&query) {
+            Ok(tracks) => tracks,
+            Err(e) => {
+                error!("Error searching the database: {e}");
+                Vec::new()
+            }
+        };
+        for (idx, record) in db_tracks.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
             }
+
+            let duration = termusiclib::track::Track::duration_formatted_short(&record.duration);
+            let duration_string = format!("[{duration:^6.6}]");
+
+            table
+                .add_col(TextSpan::new(duration_string.as_str()))
+                .add_col(TextSpan::new(&record.artist).fg(tuirealm::tui::style::Color::LightYellow))
+                .add_col(TextSpan::new(&record.title).bold())
+                .add_col(TextSpan::new(&record.file));
         }
 
         if db_tracks.is_empty() {
@@ -661,3 +720,24 @@ impl Model {
         self.general_search_update_show(table);
     }
 }
+
+/// Get `path` relative to the music directory it is in, or its last two components if it is in none
+fn display_path(music_dirs: &[PathBuf], path: &str) -> String {
+    let path = Path::new(path);
+    for dir in music_dirs {
+        if let Ok(relative) = path.strip_prefix(dir) {
+            if relative.as_os_str().is_empty() {
+                break;
+            }
+            return relative.to_string_lossy().to_string();
+        }
+    }
+
+    let mut components = path.components().rev().take(2).collect::<Vec<_>>();
+    components.reverse();
+    components
+        .iter()
+        .map(|v| v.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
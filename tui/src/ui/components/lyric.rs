@@ -1,5 +1,6 @@
 use crate::ui::{model::TermusicLayout, Model};
 use termusiclib::podcast::episode::Episode;
+use termusiclib::podcast::Podcast;
 use termusiclib::track::MediaType;
 use termusiclib::types::{Id, LyricMsg, Msg};
 
@@ -132,28 +133,18 @@ impl Model {
     }
 
     pub fn lyric_update_for_podcast_by_current_track(&mut self) {
-        let mut need_update = false;
-        let mut pod_title = String::new();
-        let mut ep_for_lyric = Episode::default();
-        if let Some(track) = self.playlist.current_track().cloned() {
+        let mut found = None;
+        if let Some(track) = self.playlist.current_track() {
             if MediaType::Podcast == track.media_type {
                 if let Some(file) = track.file() {
-                    'outer: for pod in &self.podcast.podcasts {
-                        for ep in &pod.episodes {
-                            if ep.url == file {
-                                pod_title.clone_from(&pod.title);
-                                ep_for_lyric = ep.clone();
-                                need_update = true;
-                                break 'outer;
-                            }
-                        }
-                    }
+                    found = self.podcast_find_episode_by_url(file);
                 }
             }
         }
 
-        if need_update {
-            self.lyric_update_for_episode_after(&pod_title, &ep_for_lyric);
+        if let Some((pod, ep)) = found {
+            self.podcast_fetch_transcript(&pod, &ep);
+            self.lyric_update_for_episode_after(&pod, &ep);
         }
 
         self.lyric_update_title();
@@ -175,14 +166,36 @@ impl Model {
                 .get(episode_index)
                 .ok_or_else(|| anyhow!("get episode selected failed."))?;
 
-            self.lyric_update_for_episode_after(&podcast_selected.title, episode_selected);
+            self.lyric_update_for_episode_after(&podcast_selected, episode_selected);
         }
 
         self.lyric_update_title();
         Ok(())
     }
 
-    pub fn lyric_update_for_episode_after(&mut self, po_title: &str, ep: &Episode) {
+    /// Show the current caption of the transcript of the playing episode, if its details are shown
+    pub fn lyric_update_transcript(&mut self) {
+        let Some(transcript) = &mut self.podcast.transcript else {
+            return;
+        };
+        if self.podcast.details_url.as_ref() != Some(&transcript.url) {
+            return;
+        }
+        let line = transcript.lyric.get_text(self.time_pos).unwrap_or_default();
+        if line == transcript.line {
+            return;
+        }
+        transcript.line = line;
+
+        let url = transcript.url.clone();
+        if let Some((pod, ep)) = self.podcast_find_episode_by_url(&url) {
+            self.lyric_update_for_episode_after(&pod, &ep);
+        }
+    }
+
+    pub fn lyric_update_for_episode_after(&mut self, pod: &Podcast, ep: &Episode) {
+        self.podcast.details_url = Some(ep.url.clone());
+
         // convert <br/> tags to a single line break
         let br_to_lb = RE_BR_TAGS.replace_all(&ep.description, "\n");
 
@@ -219,8 +232,11 @@ impl Model {
             .collect();
 
         let mut final_vec: Vec<_> = Vec::new();
-        final_vec.push(PropValue::TextSpan(TextSpan::from(po_title).bold()));
+        final_vec.push(PropValue::TextSpan(TextSpan::from(&pod.title).bold()));
         final_vec.push(PropValue::TextSpan(TextSpan::from(&ep.title).bold()));
+        if let Some(season_episode) = ep.format_season_episode() {
+            final_vec.push(PropValue::TextSpan(TextSpan::from(season_episode).italic()));
+        }
         final_vec.push(PropValue::TextSpan(TextSpan::from("   ")));
 
         if let Some(date) = ep.pubdate {
@@ -233,10 +249,24 @@ impl Model {
             TextSpan::from(format!("Duration: {}", ep.format_duration())).italic(),
         ));
 
+        if let Some(transcript) = &self.podcast.transcript {
+            if transcript.url == ep.url {
+                final_vec.push(PropValue::TextSpan(TextSpan::from("   ")));
+                final_vec.push(PropValue::TextSpan(TextSpan::from("Transcript:").bold()));
+                final_vec.push(PropValue::TextSpan(TextSpan::from(&transcript.line)));
+            }
+        }
+
         final_vec.push(PropValue::TextSpan(TextSpan::from("   ")));
         final_vec.push(PropValue::TextSpan(TextSpan::from("Description:").bold()));
         final_vec.append(&mut lines_textspan);
 
+        let mut extras = episode_extras_lines(pod, ep);
+        if !extras.is_empty() {
+            final_vec.push(PropValue::TextSpan(TextSpan::from("   ")));
+            final_vec.append(&mut extras);
+        }
+
         self.app
             .attr(
                 &Id::Lyric,
@@ -367,3 +397,74 @@ impl Model {
             .ok();
     }
 }
+
+/// Lines for the `podcast:` namespace data of a episode and the funding links of its podcast
+fn episode_extras_lines(pod: &Podcast, ep: &Episode) -> Vec<PropValue> {
+    let mut lines = Vec::new();
+    let mut section = |title: &str, items: Vec<String>| {
+        if items.is_empty() {
+            return;
+        }
+        lines.push(PropValue::TextSpan(TextSpan::from(title).bold()));
+        lines.extend(
+            items
+                .into_iter()
+                .map(|v| PropValue::TextSpan(TextSpan::from(format!("  {v}")))),
+        );
+    };
+
+    let extras = &ep.extras;
+    section(
+        "People:",
+        extras
+            .persons
+            .iter()
+            .map(|v| format!("{} ({})", v.name, v.role.as_deref().unwrap_or("host")))
+            .collect(),
+    );
+    section(
+        "Soundbites:",
+        extras
+            .soundbites
+            .iter()
+            .map(|v| {
+                let start = v.start_time.as_secs();
+                format!(
+                    "{:02}:{:02}:{:02} ({}s) {}",
+                    start / 3600,
+                    start / 60 % 60,
+                    start % 60,
+                    v.duration.as_secs(),
+                    v.title.as_deref().unwrap_or_default()
+                )
+            })
+            .collect(),
+    );
+    section(
+        "Chapters:",
+        extras.chapters.iter().map(|v| v.url.clone()).collect(),
+    );
+    section(
+        "Transcripts:",
+        extras
+            .transcripts
+            .iter()
+            .map(|v| format!("{} ({})", v.url, v.mime_type))
+            .collect(),
+    );
+    section(
+        "Support the podcast:",
+        pod.funding
+            .iter()
+            .map(|v| {
+                if v.text.is_empty() {
+                    v.url.clone()
+                } else {
+                    format!("{}: {}", v.text, v.url)
+                }
+            })
+            .collect(),
+    );
+
+    lines
+}
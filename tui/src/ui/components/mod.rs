@@ -29,9 +29,11 @@ mod labels;
 mod lyric;
 mod music_library;
 mod playlist;
+mod playlist_tabs;
 mod podcast;
 mod popups;
 mod progress;
+mod queue;
 #[allow(
     clippy::match_bool,
     clippy::redundant_closure_for_method_calls,
@@ -50,9 +52,12 @@ pub use labels::{DownloadSpinner, LabelGeneric, LabelSpan};
 pub use lyric::Lyric;
 pub use music_library::MusicLibrary;
 pub use playlist::Playlist;
-pub use podcast::{EpisodeList, FeedsList};
-pub use popups::general_search::{GSInputPopup, GSTablePopup, Source};
+pub use playlist_tabs::PlaylistTabs;
+pub use podcast::{podcasts_for_view, EpisodeList, FeedsList};
+pub use popups::general_search::{GSInputPopup, GSTablePopup, Source, DATABASE_SEARCH_TITLE};
+pub use popups::{PlaylistEditMode, PlaylistNameMode};
 pub use progress::Progress;
+pub use queue::{Queue, QUEUE_MAX_VISIBLE};
 pub use tag_editor::*;
 use termusiclib::config::v2::tui::keys::Keys;
 use termusiclib::config::SharedTuiSettings;
@@ -160,6 +165,18 @@ impl Component<Msg, NoUserEvent> for GlobalListener {
                 Some(Msg::ConfigEditor(ConfigEditorMsg::Open))
             }
 
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.sleep_timer_cycle.get() => {
+                Some(Msg::PlayerSleepTimerCycle)
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.sleep_timer_extend.get() => {
+                Some(Msg::PlayerSleepTimerExtend)
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.player_keys.sleep_timer_cancel.get() => {
+                Some(Msg::PlayerSleepTimerCancel)
+            }
+
             Event::Keyboard(keyevent) if keyevent == keys.player_keys.save_playlist.get() => {
                 Some(Msg::SavePlaylistPopupShow)
             }
@@ -286,6 +303,18 @@ impl Model {
                 SubEventClause::Keyboard(keys.player_keys.save_playlist.get()),
                 Self::no_popup_mounted_clause(),
             ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.player_keys.sleep_timer_cycle.get()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.player_keys.sleep_timer_extend.get()),
+                Self::no_popup_mounted_clause(),
+            ),
+            Sub::new(
+                SubEventClause::Keyboard(keys.player_keys.sleep_timer_cancel.get()),
+                Self::no_popup_mounted_clause(),
+            ),
             Sub::new(
                 SubEventClause::Keyboard(keys.select_view_keys.view_podcasts.get()),
                 Self::no_popup_mounted_clause(),
@@ -332,7 +361,45 @@ impl Model {
             Box::new(SubClause::IsMounted(Id::FeedDeleteConfirmRadioPopup)),
             Box::new(SubClause::Or(
                 Box::new(SubClause::IsMounted(Id::FeedDeleteConfirmInputPopup)),
-                Box::new(SubClause::IsMounted(Id::PodcastSearchTablePopup)),
+                Box::new(SubClause::Or(
+                    Box::new(SubClause::IsMounted(Id::PodcastSearchTablePopup)),
+                    Box::new(SubClause::Or(
+                        Box::new(SubClause::IsMounted(Id::PlaylistNamePopup)),
+                        Box::new(SubClause::Or(
+                            Box::new(SubClause::IsMounted(Id::PlaylistDeleteConfirm)),
+                            Box::new(SubClause::Or(
+                                Box::new(SubClause::IsMounted(Id::PlaylistImportPopup)),
+                                Box::new(SubClause::Or(
+                                    Box::new(SubClause::IsMounted(Id::PlaylistEditPopup)),
+                                    Box::new(SubClause::Or(
+                                        Box::new(SubClause::IsMounted(Id::DuplicatesPopup)),
+                                        Box::new(SubClause::Or(
+                                            Box::new(SubClause::IsMounted(Id::PodcastPolicyPopup)),
+                                            Box::new(SubClause::Or(
+                                                Box::new(SubClause::IsMounted(
+                                                    Id::DownloadQueuePopup,
+                                                )),
+                                                Box::new(SubClause::Or(
+                                                    Box::new(SubClause::IsMounted(
+                                                        Id::ShowNotesPopup,
+                                                    )),
+                                                    Box::new(SubClause::Or(
+                                                        Box::new(SubClause::IsMounted(
+                                                            Id::PodcastFolderPopup,
+                                                        )),
+                                                        Box::new(SubClause::IsMounted(
+                                                            Id::DuplicatesConfirmPopup,
+                                                        )),
+                                                    )),
+                                                )),
+                                            )),
+                                        )),
+                                    )),
+                                )),
+                            )),
+                        )),
+                    )),
+                )),
             )),
         )))
     }
@@ -6,6 +6,7 @@ use std::path::{Path, PathBuf};
 use termusiclib::config::v2::server::config_extra::ServerConfigVersionedDefaulted;
 use termusiclib::config::v2::server::ScanDepth;
 use termusiclib::config::SharedTuiSettings;
+use termusiclib::library_db::{find_duplicates, DuplicateReport};
 use termusicplayback::PlayerCmd;
 use tui_realm_treeview::{Node, Tree, TreeView, TREE_CMD_CLOSE, TREE_CMD_OPEN, TREE_INITIAL_NODE};
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
@@ -13,6 +14,9 @@ use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
 use tuirealm::props::{Alignment, BorderType, Borders, TableBuilder, TextSpan};
 use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent, State, StateValue};
 
+const DUPLICATES_TITLE: &str = "Duplicates";
+const DUPLICATES_SEARCHING: &str = "Searching the library for duplicates...";
+
 #[derive(MockComponent)]
 pub struct MusicLibrary {
     component: TreeView,
@@ -144,6 +148,18 @@ impl Component<Msg, NoUserEvent> for MusicLibrary {
                 modifiers: KeyModifiers::NONE,
             }) => self.perform(Cmd::Move(Direction::Up)),
 
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.enqueue_next.get() => {
+                let current_node = self.component.tree_state().selected().unwrap();
+                return Some(Msg::Playlist(crate::ui::PLMsg::EnqueueNext(
+                    current_node.to_string(),
+                )));
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.enqueue_last.get() => {
+                let current_node = self.component.tree_state().selected().unwrap();
+                return Some(Msg::Playlist(crate::ui::PLMsg::EnqueueLast(
+                    current_node.to_string(),
+                )));
+            }
             Event::Keyboard(keyevent) if keyevent == keys.library_keys.load_dir.get() => {
                 let current_node = self.component.tree_state().selected().unwrap();
                 let p: &Path = Path::new(current_node);
@@ -205,6 +221,9 @@ impl Component<Msg, NoUserEvent> for MusicLibrary {
             Event::Keyboard(keyevent) if keyevent == keys.library_keys.remove_root.get() => {
                 return Some(Msg::Library(LIMsg::RemoveRoot))
             }
+            Event::Keyboard(keyevent) if keyevent == keys.library_keys.find_duplicates.get() => {
+                return Some(Msg::Library(LIMsg::FindDuplicates))
+            }
             Event::Keyboard(keyevent) if keyevent == keys.library_keys.search.get() => {
                 return Some(Msg::GeneralSearch(crate::ui::GSMsg::PopupShowLibrary))
             }
@@ -430,6 +449,121 @@ impl Model {
         Ok(())
     }
 
+    /// Search the whole library for likely duplicates in a background thread
+    ///
+    /// The report is shown once [`LIMsg::DuplicatesFound`] is received.
+    pub fn library_find_duplicates(&mut self) -> Result<()> {
+        let tracks = self.db.get_all_records()?;
+        let tx = self.tx_to_main.clone();
+        std::thread::spawn(move || {
+            let report = find_duplicates(&tracks);
+            let _ = tx.send(Msg::Library(LIMsg::DuplicatesFound(report)));
+        });
+        self.mount_message(DUPLICATES_TITLE, DUPLICATES_SEARCHING);
+        Ok(())
+    }
+
+    pub fn library_duplicates_found(&mut self, report: DuplicateReport) {
+        self.umount_message(DUPLICATES_TITLE, DUPLICATES_SEARCHING);
+        self.mount_duplicates(report);
+    }
+
+    /// Ask to delete all but the kept file of each duplicate group, one group at a time
+    pub fn library_before_delete_duplicates(&mut self) {
+        if self.duplicates.is_none() {
+            return;
+        }
+        self.duplicates_deleted = (0, 0);
+        self.library_confirm_duplicate_group(0);
+    }
+
+    /// Ask for the first group from `index` that has files to delete, or finish if there is none
+    fn library_confirm_duplicate_group(&mut self, index: usize) {
+        let next = self.duplicates.as_ref().and_then(|report| {
+            report
+                .groups
+                .iter()
+                .enumerate()
+                .skip(index)
+                .find(|(_, group)| !group.files_to_delete(self.duplicates_policy).is_empty())
+                .map(|(idx, _)| idx)
+        });
+        let Some(next) = next else {
+            self.library_duplicates_deleted();
+            return;
+        };
+
+        self.duplicates_confirm = Some(next);
+        // keep the report to go back to it once done
+        if self.app.mounted(&Id::DuplicatesPopup) {
+            let _drop = self.app.umount(&Id::DuplicatesPopup);
+        }
+        self.mount_duplicates_confirm(next);
+    }
+
+    /// Delete the files of the confirmed group and remove them from the database and playlist
+    pub fn library_delete_duplicate_group(&mut self) {
+        let Some(index) = self.duplicates_confirm.take() else {
+            return;
+        };
+        let Some(group) = self
+            .duplicates
+            .as_mut()
+            .filter(|v| index < v.groups.len())
+            .map(|v| v.groups.remove(index))
+        else {
+            return;
+        };
+
+        let mut deleted = Vec::new();
+        for file in group.files_to_delete(self.duplicates_policy) {
+            match remove_file(file) {
+                Ok(()) => deleted.push(PathBuf::from(file)),
+                Err(err) => {
+                    warn!("Could not delete \"{file}\": {err}");
+                    self.duplicates_deleted.1 += 1;
+                }
+            }
+        }
+        self.duplicates_deleted.0 += deleted.len();
+
+        if let Err(e) = self.db.sync_paths(&deleted) {
+            self.mount_error_popup(anyhow::Error::new(e).context("sync deleted duplicates"));
+        }
+        self.library_reload_tree();
+        self.playlist_update_library_delete();
+
+        // the following group moved to the index of the removed one
+        self.library_confirm_duplicate_group(index);
+    }
+
+    /// Keep the files of the group waiting for confirmation and ask for the next one
+    pub fn library_skip_duplicate_group(&mut self) {
+        if let Some(index) = self.duplicates_confirm.take() {
+            self.library_confirm_duplicate_group(index + 1);
+        }
+    }
+
+    /// Stop asking for duplicate groups, show what was deleted and go back to the remaining report
+    pub fn library_duplicates_deleted(&mut self) {
+        self.duplicates_confirm = None;
+        self.umount_duplicates_confirm();
+
+        let (deleted, failed) = std::mem::take(&mut self.duplicates_deleted);
+        if deleted > 0 || failed > 0 {
+            let message = if failed > 0 {
+                format!("Deleted {deleted} duplicate files, {failed} could not be deleted")
+            } else {
+                format!("Deleted {deleted} duplicate files")
+            };
+            self.show_message_timeout_label_help(message, None, None, None);
+        }
+
+        if let Some(report) = self.duplicates.take() {
+            self.mount_duplicates(report);
+        }
+    }
+
     pub fn library_yank(&mut self) {
         if let Ok(State::One(StateValue::String(node_id))) = self.app.state(&Id::Library) {
             self.library.yanked_node_id = Some(node_id);
@@ -1,3 +1,4 @@
+use crate::ui::components::{PlaylistTabs, Queue};
 use crate::ui::model::TermusicLayout;
 use crate::ui::Model;
 use anyhow::{anyhow, bail, Result};
@@ -7,9 +8,12 @@ use std::path::Path;
 use termusiclib::config::SharedTuiSettings;
 use termusiclib::library_db::SearchCriteria;
 use termusiclib::library_db::TrackDB;
+use termusiclib::playlist::PlaylistFormat;
 use termusiclib::track::Track;
 use termusiclib::types::{GSMsg, Id, Msg, PLMsg};
-use termusiclib::utils::{filetype_supported, get_parent_folder, is_playlist, playlist_get_vec};
+use termusiclib::utils::{
+    filetype_supported, get_parent_folder, is_playlist, playlist_get_entries,
+};
 use termusicplayback::PlayerCmd;
 
 use tui_realm_stdlib::Table;
@@ -175,6 +179,39 @@ impl Component<Msg, NoUserEvent> for Playlist {
             Event::Keyboard(key) if key == keys.playlist_keys.add_random_songs.get() => {
                 return Some(Msg::Playlist(PLMsg::AddRandomTracks));
             }
+            Event::Keyboard(key) if key == keys.playlist_keys.clear_queue.get() => {
+                return Some(Msg::Playlist(PLMsg::QueueClear));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.undo.get() => {
+                return Some(Msg::Playlist(PLMsg::Undo));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.redo.get() => {
+                return Some(Msg::Playlist(PLMsg::Redo));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.sort.get() => {
+                return Some(Msg::Playlist(PLMsg::SortShow));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.remove_duplicates.get() => {
+                return Some(Msg::Playlist(PLMsg::DedupShow));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.next_playlist.get() => {
+                return Some(Msg::Playlist(PLMsg::NamedPlaylistNext));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.previous_playlist.get() => {
+                return Some(Msg::Playlist(PLMsg::NamedPlaylistPrev));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.new_playlist.get() => {
+                return Some(Msg::Playlist(PLMsg::NamedPlaylistCreateShow));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.rename_playlist.get() => {
+                return Some(Msg::Playlist(PLMsg::NamedPlaylistRenameShow));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.duplicate_playlist.get() => {
+                return Some(Msg::Playlist(PLMsg::NamedPlaylistDuplicate));
+            }
+            Event::Keyboard(key) if key == keys.playlist_keys.delete_playlist.get() => {
+                return Some(Msg::Playlist(PLMsg::NamedPlaylistDeleteShow));
+            }
             _ => CmdResult::None,
         };
         Some(Msg::None)
@@ -191,8 +228,26 @@ impl Model {
                 Vec::new()
             )
             .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::Queue,
+                Box::new(Queue::new(&self.config_tui)),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::PlaylistTabs,
+                Box::new(PlaylistTabs::new(&self.config_tui)),
+                Vec::new()
+            )
+            .is_ok());
         self.playlist_switch_layout();
         self.playlist_sync();
+        self.queue_sync();
+        self.playlist_tabs_sync();
     }
 
     pub fn playlist_switch_layout(&mut self) {
@@ -254,14 +309,40 @@ impl Model {
         self.playlist_sync();
     }
 
+    /// Add the tracks of a playlist file, entries that do not resolve are relinked against the library
+    ///
+    /// If any entry did not resolve, the relink report is shown first and the tracks are added once confirmed
     fn playlist_add_playlist(&mut self, current_node: &str) -> Result<()> {
-        let vec = playlist_get_vec(current_node)?;
-        self.playlist.add_playlist(&vec)?;
+        let entries = playlist_get_entries(current_node)?;
+        let report = self.db.relink(entries)?;
+        if report.needs_review() {
+            self.mount_playlist_import(report);
+            return Ok(());
+        }
+
+        self.playlist.add_playlist(&report.files())?;
         self.player_sync_playlist()?;
         self.playlist_sync();
         Ok(())
     }
 
+    /// Add the tracks of the confirmed playlist import report
+    pub fn playlist_import_confirm(&mut self) {
+        let Some(report) = self.playlist_import.take() else {
+            return;
+        };
+        self.umount_playlist_import();
+
+        if let Err(e) = self.playlist.add_playlist(&report.files()) {
+            self.mount_error_popup(e.context("add imported playlist"));
+            return;
+        }
+        if let Err(e) = self.player_sync_playlist() {
+            self.mount_error_popup(e.context("player sync playlist"));
+        }
+        self.playlist_sync();
+    }
+
     pub fn playlist_add_episode(&mut self, episode_index: usize) -> Result<()> {
         if self.podcast.podcasts.is_empty() {
             return Ok(());
@@ -498,7 +579,7 @@ impl Model {
             .style
             .playlist
             .use_loop_mode_symbol;
-        let loop_mode = self.config_server.read().settings.player.loop_mode;
+        let loop_mode = self.playlist.loop_mode();
         let title = format!(
             "\u{2500} Playlist \u{2500}\u{2500}\u{2524} Total {} tracks | {} | Mode: {} \u{251c}\u{2500}",
             self.playlist.len(),
@@ -513,6 +594,30 @@ impl Model {
             )
             .ok();
     }
+    /// Reload the playlist after the server applied a undo / redo
+    pub fn playlist_history_applied(&mut self, changed: bool, action: &str) {
+        if !changed {
+            self.show_message_timeout_label_help(format!("Nothing to {action}"), None, None, None);
+            return;
+        }
+        self.playlist_reload_after_edit();
+    }
+
+    /// Reload the playlist after the server sorted it or removed duplicates
+    pub fn playlist_edit_applied(&mut self, changed: bool, message: &str) {
+        if changed {
+            self.playlist_reload_after_edit();
+        }
+        self.show_message_timeout_label_help(message, None, None, None);
+    }
+
+    fn playlist_reload_after_edit(&mut self) {
+        if let Err(e) = self.playlist.reload_tracks() {
+            self.mount_error_popup(e.context("reload playlist"));
+        }
+        self.playlist_sync();
+    }
+
     pub fn playlist_play_selected(&mut self, index: usize) {
         self.playlist.set_current_track_index(index);
         if let Err(e) = self.player_sync_playlist() {
@@ -620,7 +725,16 @@ impl Model {
         result
     }
 
-    pub fn playlist_save_m3u_before(&mut self, filename: &str) -> Result<()> {
+    /// Get the extension to add to `filename` when saving a playlist, empty if it already has a playlist extension
+    pub fn playlist_save_extension(filename: &str) -> String {
+        if PlaylistFormat::from_path(Path::new(filename)).is_some() {
+            String::new()
+        } else {
+            format!(".{}", PlaylistFormat::default().extension())
+        }
+    }
+
+    pub fn playlist_save_file_before(&mut self, filename: &str) -> Result<()> {
         let current_node: String = match self.app.state(&Id::Library).ok().unwrap() {
             State::One(StateValue::String(id)) => id,
             _ => bail!("Invalid node selected in library"),
@@ -628,20 +742,22 @@ impl Model {
 
         let parent_folder = get_parent_folder(&current_node);
 
-        let full_filename = format!("{parent_folder}/{filename}.m3u");
+        let extension = Self::playlist_save_extension(filename);
+        let full_filename = format!("{parent_folder}/{filename}{extension}");
 
-        let path_m3u = Path::new(&full_filename);
+        let path_playlist = Path::new(&full_filename);
 
-        if path_m3u.exists() {
+        if path_playlist.exists() {
             self.mount_save_playlist_confirm(&full_filename);
             return Ok(());
         }
 
-        self.playlist_save_m3u(&full_filename)
+        self.playlist_save_file(&full_filename)
     }
 
-    pub fn playlist_save_m3u(&mut self, filename: &str) -> Result<()> {
-        self.playlist.save_m3u(filename)?;
+    pub fn playlist_save_file(&mut self, filename: &str) -> Result<()> {
+        self.playlist
+            .export(filename, self.save_playlist_relative)?;
 
         self.library_reload_with_node_focus(Some(filename));
 
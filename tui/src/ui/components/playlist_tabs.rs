@@ -0,0 +1,164 @@
+use crate::ui::components::PlaylistNameMode;
+use crate::ui::Model;
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::types::{Id, Msg};
+use termusicplayback::player::PlaylistInfo;
+use termusicplayback::PlayerCmd;
+use tui_realm_stdlib::Span;
+use tuirealm::event::NoUserEvent;
+use tuirealm::props::{Alignment, PropPayload, PropValue, TextSpan};
+use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent};
+
+/// Tab-like selector of the named playlists, shown above the playlist
+#[derive(MockComponent)]
+pub struct PlaylistTabs {
+    component: Span,
+}
+
+impl PlaylistTabs {
+    pub fn new(config: &SharedTuiSettings) -> Self {
+        let config = config.read();
+        let component = Span::default()
+            .alignment(Alignment::Left)
+            .background(config.settings.theme.playlist_background())
+            .foreground(config.settings.theme.playlist_foreground());
+
+        Self { component }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for PlaylistTabs {
+    fn on(&mut self, _ev: Event<NoUserEvent>) -> Option<Msg> {
+        None
+    }
+}
+
+impl Model {
+    /// Store the named playlists as reported by the server and redraw the tabs
+    pub fn playlist_tabs_update(&mut self, playlists: Vec<PlaylistInfo>) {
+        self.playlists = playlists;
+        self.playlist_tabs_sync();
+    }
+
+    /// The active named playlist changed on the server, load its tracks
+    pub fn playlist_tabs_active_changed(&mut self, id: u64) {
+        self.active_playlist = id;
+        if let Err(e) = self.playlist.reload_tracks() {
+            self.mount_error_popup(e.context("reload playlist"));
+        }
+        self.playlist_sync();
+        self.queue_sync();
+        self.playlist_tabs_sync();
+        // names and counts may have changed too
+        self.command(&PlayerCmd::NamedPlaylistList);
+    }
+
+    /// Update the tabs widget from the current named playlists
+    pub fn playlist_tabs_sync(&mut self) {
+        let (highlight, foreground) = {
+            let config = self.config_tui.read();
+            (
+                config.settings.theme.playlist_highlight(),
+                config.settings.theme.playlist_foreground(),
+            )
+        };
+        let mut spans = Vec::with_capacity(self.playlists.len() * 2);
+
+        for (idx, playlist) in self.playlists.iter().enumerate() {
+            if idx > 0 {
+                spans.push(TextSpan::new("\u{2502}").fg(foreground));
+            }
+            let text = format!(" {} ({}) ", playlist.name, playlist.track_count);
+            if playlist.id == self.active_playlist {
+                spans.push(TextSpan::new(text).fg(highlight).bold().reversed());
+            } else {
+                spans.push(TextSpan::new(text).fg(foreground));
+            }
+        }
+
+        self.app
+            .attr(
+                &Id::PlaylistTabs,
+                Attribute::Text,
+                AttrValue::Payload(PropPayload::Vec(
+                    spans.into_iter().map(PropValue::TextSpan).collect(),
+                )),
+            )
+            .ok();
+    }
+
+    fn playlist_tabs_active(&self) -> Option<&PlaylistInfo> {
+        self.playlists.iter().find(|v| v.id == self.active_playlist)
+    }
+
+    /// Switch to the next (`forward`) or previous named playlist, wrapping around
+    pub fn playlist_tabs_cycle(&mut self, forward: bool) {
+        let len = self.playlists.len();
+        if len < 2 {
+            return;
+        }
+        let index = self
+            .playlists
+            .iter()
+            .position(|v| v.id == self.active_playlist)
+            .unwrap_or(0);
+        let index = if forward {
+            (index + 1) % len
+        } else {
+            (index + len - 1) % len
+        };
+        let id = self.playlists[index].id;
+        self.command(&PlayerCmd::NamedPlaylistSwitch(id));
+    }
+
+    pub fn playlist_tabs_create_show(&mut self) {
+        self.mount_playlist_name(PlaylistNameMode::Create, "");
+    }
+
+    pub fn playlist_tabs_rename_show(&mut self) {
+        let Some(name) = self.playlist_tabs_active().map(|v| v.name.clone()) else {
+            return;
+        };
+        self.mount_playlist_name(PlaylistNameMode::Rename, &name);
+    }
+
+    pub fn playlist_tabs_create(&mut self, name: &str) {
+        self.umount_playlist_name();
+        self.command(&PlayerCmd::NamedPlaylistCreate(name.trim().to_string()));
+    }
+
+    pub fn playlist_tabs_rename(&mut self, name: &str) {
+        self.umount_playlist_name();
+        self.command(&PlayerCmd::NamedPlaylistRename(
+            self.active_playlist,
+            name.trim().to_string(),
+        ));
+    }
+
+    /// Copy the active named playlist to a unused name like "Name (copy 2)"
+    pub fn playlist_tabs_duplicate(&mut self) {
+        let Some(active) = self.playlist_tabs_active() else {
+            return;
+        };
+        let mut name = format!("{} (copy)", active.name);
+        let mut counter = 2;
+        while self.playlists.iter().any(|v| v.name == name) {
+            name = format!("{} (copy {counter})", active.name);
+            counter += 1;
+        }
+        let id = active.id;
+        self.command(&PlayerCmd::NamedPlaylistDuplicate(id, name));
+    }
+
+    pub fn playlist_tabs_delete_show(&mut self) {
+        if self.playlist_tabs_active().is_none() {
+            return;
+        }
+        self.mount_playlist_delete_confirm();
+    }
+
+    pub fn playlist_tabs_delete(&mut self) {
+        self.umount_playlist_delete_confirm();
+        self.command(&PlayerCmd::NamedPlaylistDelete(self.active_playlist));
+    }
+}
@@ -1,14 +1,23 @@
+use crate::ui::model::FeedRow;
 use crate::ui::Model;
 use anyhow::{anyhow, bail, Result};
 use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
 use reqwest::ClientBuilder;
-use sanitize_filename::{sanitize_with_options, Options};
 use serde_json::Value;
+use std::collections::HashSet;
 use std::time::Duration;
 use termusiclib::config::SharedTuiSettings;
-use termusiclib::podcast::{download_list, EpData, PodcastFeed, PodcastNoId};
+use termusiclib::podcast::db::{Database, DownloadAction, DownloadStatus};
+use termusiclib::podcast::episode::Episode;
+use termusiclib::podcast::feed::{FeedAuth, FeedHeaders};
+use termusiclib::podcast::transcript::fetch_transcript;
+use termusiclib::podcast::{
+    normalize_folder, EpData, Podcast, PodcastFeed, PodcastNoId, PodcastPolicy,
+};
 use termusiclib::track::MediaType;
-use termusiclib::types::{Id, Msg, PCMsg};
+use termusiclib::types::{Id, Msg, PCMsg, PodcastAddInput};
+use termusicplayback::player::DownloadProgress;
+use termusicplayback::PlayerCmd;
 use tokio::runtime::Handle;
 use tui_realm_stdlib::List;
 use tuirealm::command::{Cmd, CmdResult, Direction, Position};
@@ -165,6 +174,27 @@ impl Component<Msg, NoUserEvent> for FeedsList {
                 return Some(Msg::Podcast(PCMsg::PodcastRefreshAll));
             }
 
+            Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.download_policy.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Podcast(PCMsg::PolicyPopupShow(index)));
+                }
+                CmdResult::None
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.set_folder.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Podcast(PCMsg::FolderPopupShow(index)));
+                }
+                CmdResult::None
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.mark_all_played.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Podcast(PCMsg::FeedsMarkAllPlayed(index)));
+                }
+                CmdResult::None
+            }
+
             Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.delete_feed.get() => {
                 return Some(Msg::Podcast(PCMsg::FeedDeleteShow));
             }
@@ -311,6 +341,20 @@ impl Component<Msg, NoUserEvent> for EpisodeList {
                 CmdResult::None
             }
 
+            Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.enqueue_next.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Podcast(PCMsg::EpisodeEnqueueNext(index)));
+                }
+                CmdResult::None
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.enqueue_last.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Podcast(PCMsg::EpisodeEnqueueLast(index)));
+                }
+                CmdResult::None
+            }
+
             Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.mark_played.get() => {
                 if let State::One(StateValue::Usize(index)) = self.state() {
                     return Some(Msg::Podcast(PCMsg::EpisodeMarkPlayed(index)));
@@ -329,6 +373,23 @@ impl Component<Msg, NoUserEvent> for EpisodeList {
                 CmdResult::None
             }
 
+            Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.download_queue.get() => {
+                return Some(Msg::Podcast(PCMsg::DownloadQueueShow));
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.show_notes.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Podcast(PCMsg::ShowNotesShow(index)));
+                }
+                CmdResult::None
+            }
+
+            Event::Keyboard(keyevent) if keyevent == keys.podcast_keys.cancel_download.get() => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Podcast(PCMsg::EpisodeCancelDownload(index)));
+                }
+                CmdResult::None
+            }
+
             Event::Keyboard(keyevent)
                 if keyevent == keys.podcast_keys.delete_local_episode.get() =>
             {
@@ -421,9 +482,24 @@ impl Model {
         });
     }
 
-    pub fn podcast_add(&mut self, url: &str) {
-        let feed = PodcastFeed::new(None, url, None);
+    /// Add the feed entered in the add-feed popup, with its optional credentials and headers
+    ///
+    /// The popup stays open if the headers are invalid, so they can be corrected.
+    pub fn podcast_add_from_input(&mut self, input: &PodcastAddInput) -> Result<()> {
+        let headers: FeedHeaders = input.headers.parse()?;
+        let non_empty = |v: &str| (!v.is_empty()).then(|| v.to_string());
+        let auth = FeedAuth {
+            username: non_empty(&input.username),
+            password: non_empty(&input.password),
+            headers,
+        };
+        self.umount_podcast_add_popup();
 
+        self.podcast_add(PodcastFeed::new(None, &input.url, None).with_auth(auth));
+        Ok(())
+    }
+
+    pub fn podcast_add(&mut self, feed: PodcastFeed) {
         crate::podcast::check_feed(
             feed,
             usize::from(
@@ -438,20 +514,24 @@ impl Model {
         );
     }
     pub fn podcast_sync_feeds_and_episodes(&mut self) {
+        self.podcast.rows = feed_rows(&self.podcast.podcasts, &self.podcast.collapsed_folders);
         let mut table: TableBuilder = TableBuilder::default();
 
-        for (idx, record) in self.podcast.podcasts.iter().enumerate() {
+        let titles = self
+            .podcast
+            .rows
+            .iter()
+            .filter_map(|row| self.podcast_row_title(row));
+        for (idx, (title, new)) in titles.enumerate() {
             if idx > 0 {
                 table.add_row();
             }
-            let new = record.num_unplayed();
-            let total = record.episodes.len();
             if new > 0 {
-                table.add_col(TextSpan::new(format!("{} ({new}/{total})", record.title)).bold());
+                table.add_col(TextSpan::new(title).bold());
                 continue;
             }
 
-            table.add_col(TextSpan::new(format!("{} ({new}/{total})", record.title)));
+            table.add_col(TextSpan::new(title));
         }
         if self.podcast.podcasts.is_empty() {
             table.add_col(TextSpan::from("empty feeds list"));
@@ -470,6 +550,42 @@ impl Model {
         }
     }
 
+    /// The title of a row of the feed list, with the number of unplayed episodes in it
+    fn podcast_row_title(&self, row: &FeedRow) -> Option<(String, usize)> {
+        match row {
+            FeedRow::Folder(name) => {
+                let (feeds, new) = self
+                    .podcast
+                    .podcasts
+                    .iter()
+                    .filter(|v| v.folder.as_ref() == Some(name))
+                    .fold((0, 0), |(feeds, new), v| {
+                        (feeds + 1, new + v.num_unplayed())
+                    });
+                let symbol = if self.podcast.collapsed_folders.contains(name) {
+                    "▸"
+                } else {
+                    "▾"
+                };
+                Some((format!("{symbol} {name} ({feeds} feeds, {new} new)"), new))
+            }
+            FeedRow::Podcast(index) => {
+                let record = self.podcast.podcasts.get(*index)?;
+                let new = record.num_unplayed();
+                let total = record.episodes.len();
+                let mut title = format!("{} ({new}/{total})", record.title);
+                if !record.policy.is_manual() {
+                    title = format!("{title} [{}]", record.policy);
+                }
+                // indent the podcasts below their folder
+                if record.folder.is_some() {
+                    title = format!("  {title}");
+                }
+                Some((title, new))
+            }
+        }
+    }
+
     pub fn podcast_sync_episodes(&mut self) -> Result<()> {
         if self.podcast.podcasts.is_empty() {
             let mut table: TableBuilder = TableBuilder::default();
@@ -502,9 +618,21 @@ impl Model {
             }
 
             let mut title = record.title.clone();
+            if let Some(season_episode) = record.format_season_episode() {
+                title = format!("{season_episode} {title}");
+            }
             // if let Some(_) = record.path {
             if record.path.is_some() {
                 title = format!("[D] {title}");
+            } else if let Some(progress) = self.download_tracker.progress(&record.url) {
+                title = format!("[{progress}] {title}");
+            } else if let Some(item) = self
+                .podcast
+                .download_queue
+                .iter()
+                .find(|v| v.episode_id == record.id && v.status != DownloadStatus::Done)
+            {
+                title = format!("[{}] {title}", item.status);
             }
             if record.played {
                 table.add_col(TextSpan::new(title).strikethrough());
@@ -582,6 +710,99 @@ impl Model {
         Ok(())
     }
 
+    /// Mark all episodes of the feed, or of all feeds in the folder, at `row` of the feed list played,
+    /// or unplayed if all of them are played already
+    pub fn feeds_mark_all_played(&mut self, row: usize) -> Result<()> {
+        let indexes: Vec<usize> = match self.podcast.rows.get(row) {
+            Some(FeedRow::Podcast(index)) => vec![*index],
+            Some(FeedRow::Folder(name)) => self
+                .podcast
+                .podcasts
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.folder.as_ref() == Some(name))
+                .map(|(idx, _)| idx)
+                .collect(),
+            None => return Ok(()),
+        };
+
+        let podcasts = self
+            .podcast
+            .podcasts
+            .iter_mut()
+            .enumerate()
+            .filter(|(idx, _)| indexes.contains(idx))
+            .map(|(_, v)| v);
+        let mut podcasts: Vec<&mut Podcast> = podcasts.collect();
+        let played = podcasts.iter().any(|v| v.num_unplayed() > 0);
+        let mut epid_vec = Vec::new();
+        for podcast in &mut podcasts {
+            for ep in &mut podcast.episodes {
+                epid_vec.push(ep.id);
+                ep.played = played;
+            }
+        }
+        self.podcast
+            .db_podcast
+            .set_all_played_status(&epid_vec, played)?;
+        self.podcast_sync_feeds_and_episodes();
+
+        Ok(())
+    }
+
+    /// Select the feed at `row` of the feed list, or fold / unfold the folder at it
+    pub fn podcast_select_row(&mut self, row: usize) -> Result<()> {
+        match self.podcast.rows.get(row).cloned() {
+            Some(FeedRow::Podcast(index)) => {
+                self.podcast.podcasts_index = index;
+                self.podcast_sync_episodes()
+            }
+            Some(FeedRow::Folder(name)) => {
+                if !self.podcast.collapsed_folders.remove(&name) {
+                    self.podcast.collapsed_folders.insert(name);
+                }
+                self.podcast_sync_feeds_and_episodes();
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Select the podcast at `index` in the feed list, unfolding its folder if necessary
+    fn podcast_select_row_of(&mut self, index: usize) {
+        let folder = self
+            .podcast
+            .podcasts
+            .get(index)
+            .and_then(|v| v.folder.as_ref());
+        if let Some(folder) = folder {
+            if self.podcast.collapsed_folders.remove(folder) {
+                self.podcast_sync_feeds_and_episodes();
+            }
+        }
+        let row = self
+            .podcast
+            .rows
+            .iter()
+            .position(|v| *v == FeedRow::Podcast(index))
+            .unwrap_or_default();
+        self.app
+            .attr(
+                &Id::Podcast,
+                Attribute::Value,
+                AttrValue::Payload(PropPayload::One(PropValue::Usize(row))),
+            )
+            .ok();
+    }
+
+    /// The index into the podcasts of the feed at `row` of the feed list, `None` for folders
+    pub fn podcast_index_of_row(&self, row: usize) -> Option<usize> {
+        match self.podcast.rows.get(row)? {
+            FeedRow::Podcast(index) => Some(*index),
+            FeedRow::Folder(_) => None,
+        }
+    }
+
     /// Handles the application logic for adding a new podcast, or
     /// synchronizing data from the RSS feed of an existing podcast.
     /// `pod_id` will be None if a new podcast is being added (i.e.,
@@ -593,23 +814,65 @@ impl Model {
             self.podcast.db_podcast.insert_podcast(pod)?;
         }
 
-        self.podcast.podcasts = self.podcast.db_podcast.get_podcasts()?;
+        self.podcast.podcasts = podcasts_for_view(&self.podcast.db_podcast)?;
+        if let Some(id) = pod_id {
+            self.podcast_apply_policy(id)?;
+        }
+        self.podcast_sync_feeds_and_episodes();
+
+        Ok(())
+    }
+
+    /// Enforce the retention policy of a synced podcast and start its automatic downloads
+    fn podcast_apply_policy(&mut self, pod_id: i64) -> Result<()> {
+        let Some(podcast) = self.podcast.podcasts.iter().find(|v| v.id == pod_id) else {
+            return Ok(());
+        };
+        if podcast.policy.is_manual() {
+            return Ok(());
+        }
+
+        let removed = self.podcast.db_podcast.enforce_retention(podcast)?;
+        let ep_data: Vec<EpData> = podcast
+            .policy
+            .episodes_to_download(&podcast.episodes)
+            .into_iter()
+            .map(EpData::from)
+            .collect();
+
+        if !removed.is_empty() {
+            self.podcast.podcasts = podcasts_for_view(&self.podcast.db_podcast)?;
+            self.episode_update_playlist();
+        }
+        self.podcast_download_episodes(&ep_data);
+        Ok(())
+    }
+
+    /// Parse and store the policy entered in the policy editor, then apply it right away
+    pub fn podcast_set_policy(&mut self, input: &str) -> Result<()> {
+        let Some(pod_id) = self.podcast.policy_podcast_id else {
+            self.umount_podcast_policy_popup();
+            return Ok(());
+        };
+        // keep the popup open on invalid input, so it can be corrected
+        let policy: PodcastPolicy = input.parse()?;
+        self.umount_podcast_policy_popup();
+
+        self.podcast
+            .db_podcast
+            .set_podcast_policy(pod_id, &policy)?;
+        self.podcast.podcasts = podcasts_for_view(&self.podcast.db_podcast)?;
+        self.podcast_apply_policy(pod_id)?;
         self.podcast_sync_feeds_and_episodes();
 
         Ok(())
     }
 
-    /// Synchronize RSS feed data for one or more podcasts.
+    /// Ask the server to synchronize RSS feed data for one or more podcasts.
+    ///
+    /// The results arrive through the podcast version reported by the server.
     pub fn podcast_refresh_feeds(&mut self, index: Option<usize>) -> Result<()> {
-        // We pull out the data we need here first, so we can
-        // stop borrowing the podcast list as quickly as possible.
-        // Slightly less efficient (two loops instead of
-        // one), but then it won't block other tasks that
-        // need to access the list.
-
-        let mut pod_data = Vec::new();
-        match index {
-            // just grab one podcast
+        let id = match index {
             Some(i) => {
                 if self.podcast.podcasts.is_empty() {
                     return Ok(());
@@ -619,45 +882,85 @@ impl Model {
                     .podcasts
                     .get(i)
                     .ok_or_else(|| anyhow!("get podcast selected failed."))?;
-                let pcf = PodcastFeed::new(
-                    Some(pod_selected.id),
-                    &pod_selected.url.clone(),
-                    Some(pod_selected.title.clone()),
-                );
-                pod_data.push(pcf);
+                Some(pod_selected.id)
             }
+            None => None,
+        };
+        self.command(&PlayerCmd::PodcastRefresh(id));
+        Ok(())
+    }
 
-            // get all of 'em!
-            None => {
-                pod_data = self
-                    .podcast
-                    .podcasts
-                    .iter()
-                    .map(|pod| {
-                        PodcastFeed::new(Some(pod.id), &pod.url.clone(), Some(pod.title.clone()))
-                    })
-                    .collect();
+    /// Ask the server to synchronize the feed at `row` of the feed list, or all feeds of the folder at it.
+    pub fn podcast_refresh_row(&mut self, row: usize) -> Result<()> {
+        match self.podcast.rows.get(row).cloned() {
+            Some(FeedRow::Podcast(index)) => self.podcast_refresh_feeds(Some(index)),
+            Some(FeedRow::Folder(name)) => {
+                self.command(&PlayerCmd::PodcastRefreshFolder(name));
+                Ok(())
             }
+            None => Ok(()),
         }
-        for feed in pod_data {
-            crate::podcast::check_feed(
-                feed,
-                usize::from(
-                    self.config_server
-                        .read()
-                        .settings
-                        .podcast
-                        .max_download_retries,
-                ),
-                &self.taskpool,
-                self.tx_to_main.clone(),
-            );
+    }
+
+    /// Ask the server to synchronize all feeds, or only those of the folder selected in the feed list.
+    pub fn podcast_refresh_all(&mut self) -> Result<()> {
+        let selected = self
+            .podcast_get_feed_row()
+            .ok()
+            .and_then(|row| self.podcast.rows.get(row));
+        if let Some(FeedRow::Folder(name)) = selected {
+            self.command(&PlayerCmd::PodcastRefreshFolder(name.clone()));
+            return Ok(());
         }
-        // self.update_tracker_notif();
+        self.podcast_refresh_feeds(None)
+    }
+
+    /// Store the folder entered in the folder editor, an empty one removes the feed from its folder
+    pub fn podcast_set_folder(&mut self, input: &str) -> Result<()> {
+        let Some(pod_id) = self.podcast.folder_podcast_id else {
+            self.umount_podcast_folder_popup();
+            return Ok(());
+        };
+        self.umount_podcast_folder_popup();
+
+        let folder = normalize_folder(input);
+        self.podcast
+            .db_podcast
+            .set_podcast_folder(pod_id, folder.as_deref())?;
+        self.podcast.podcasts = podcasts_for_view(&self.podcast.db_podcast)?;
         self.podcast_sync_feeds_and_episodes();
+        // keep the moved feed selected
+        if let Some(index) = self.podcast.podcasts.iter().position(|v| v.id == pod_id) {
+            self.podcast_select_row_of(index);
+        }
+
         Ok(())
     }
 
+    /// Reload the podcasts and the download queue after the server changed the podcast database
+    pub fn podcast_reload(&mut self) {
+        match podcasts_for_view(&self.podcast.db_podcast) {
+            Ok(podcasts) => self.podcast.podcasts = podcasts,
+            Err(e) => {
+                self.mount_error_popup(e.context("podcast reload"));
+                return;
+            }
+        }
+        self.podcast_sync_feeds_and_episodes();
+        self.episode_update_playlist();
+        if let Err(e) = self.download_queue_reload() {
+            self.mount_error_popup(e.context("podcast download queue"));
+        }
+    }
+
+    /// Show a new podcast refresh status reported by the server
+    pub fn podcast_status_update(&mut self, status: String) {
+        if !status.is_empty() {
+            self.show_message_timeout_label_help(&status, None, None, None);
+        }
+        self.podcast_status = status;
+    }
+
     pub fn episode_download(&mut self, index: Option<usize>) -> Result<()> {
         if self.podcast.podcasts.is_empty() {
             return Ok(());
@@ -668,11 +971,8 @@ impl Model {
             .get_mut(self.podcast.podcasts_index)
             .ok_or_else(|| anyhow!("get podcast selected failed."))?;
 
-        let pod_title;
         let mut ep_data = Vec::new();
         {
-            pod_title = podcast_selected.title.clone();
-
             // if we are selecting one specific episode, just grab that
             // one; otherwise, loop through them all
             match index {
@@ -683,16 +983,8 @@ impl Model {
                         .episodes
                         .get_mut(idx)
                         .ok_or_else(|| anyhow!("get episode selected failed"))?;
-                    let data = EpData {
-                        id: ep.id,
-                        pod_id: ep.pod_id,
-                        title: ep.title.clone(),
-                        url: ep.url.clone(),
-                        pubdate: ep.pubdate,
-                        file_path: None,
-                    };
-                    if ep.path.is_none() && !self.download_tracker.contains(&ep.url) {
-                        ep_data.push(data);
+                    if ep.path.is_none() {
+                        ep_data.push(EpData::from(&*ep));
                     }
                 }
                 None => {
@@ -700,20 +992,8 @@ impl Model {
                     ep_data = podcast_selected
                         .episodes
                         .iter()
-                        .filter_map(|ep| {
-                            if ep.path.is_none() && !self.download_tracker.contains(&ep.url) {
-                                Some(EpData {
-                                    id: ep.id,
-                                    pod_id: ep.pod_id,
-                                    title: ep.title.clone(),
-                                    url: ep.url.clone(),
-                                    pubdate: ep.pubdate,
-                                    file_path: None,
-                                })
-                            } else {
-                                None
-                            }
-                        })
+                        .filter(|ep| ep.path.is_none())
+                        .map(EpData::from)
                         .collect();
                 }
             }
@@ -723,58 +1003,115 @@ impl Model {
         // don't needlessly download them again
         // ep_data.retain(|ep| !self.download_tracker.contains(&ep.id));
 
-        if !ep_data.is_empty() {
-            // add directory for podcast, create if it does not exist
-            let dir_name = sanitize_with_options(
-                &pod_title,
-                Options {
-                    truncate: true,
-                    windows: true, // for simplicity, we'll just use Windows-friendly paths for everyone
-                    replacement: "",
-                },
-            );
-            match crate::utils::create_podcast_dir(&self.config_server.read(), dir_name) {
-                Ok(path) => {
-                    // for ep in ep_data.iter() {
-                    //     self.download_tracker.insert(ep.id);
-                    // }
-                    download_list(
-                        ep_data,
-                        &path,
-                        usize::from(
-                            self.config_server
-                                .read()
-                                .settings
-                                .podcast
-                                .max_download_retries,
-                        ),
-                        &self.taskpool,
-                        &self.tx_to_main,
-                    );
-                }
-                Err(_) => bail!("Could not create dir: {pod_title}"),
-            }
-        }
-
         // self.podcast_sync_feeds_and_episodes();
+        self.podcast_download_episodes(&ep_data);
         Ok(())
     }
 
-    pub fn episode_download_complete(&mut self, ep_data: EpData) -> Result<()> {
-        let file_path = ep_data.file_path.unwrap();
-        let res = self.podcast.db_podcast.insert_file(ep_data.id, &file_path);
-        if res.is_err() {
-            bail!(
-                "Could not add episode file to database: {}",
-                file_path.to_string_lossy()
-            );
+    /// Ask the server to add `ep_data` to the end of the download queue, which starts them once there is a free slot
+    fn podcast_download_episodes(&mut self, ep_data: &[EpData]) {
+        if ep_data.is_empty() {
+            return;
         }
 
-        let podcasts = self.podcast.db_podcast.get_podcasts()?;
-        self.podcast.podcasts = podcasts;
+        let ids: Vec<i64> = ep_data.iter().map(|v| v.id).collect();
+        self.show_message_timeout_label_help(
+            format!(" {} episodes queued for download ", ids.len()),
+            None,
+            None,
+            None,
+        );
+        self.command(&PlayerCmd::PodcastDownloads(DownloadAction::Queue, ids));
+    }
 
-        self.podcast_sync_feeds_and_episodes();
-        self.episode_update_playlist();
+    /// Reload the download queue from the database and update the views showing it
+    pub fn download_queue_reload(&mut self) -> Result<()> {
+        self.podcast.download_queue = self.podcast.db_podcast.get_download_queue()?;
+        self.download_queue_update_popup();
+        self.podcast_sync_episodes()
+    }
+
+    /// Show the progress of the downloads the server is running
+    pub fn download_progress_update(&mut self, downloads: &[DownloadProgress]) {
+        let progress = downloads
+            .iter()
+            .map(|v| (v.url.clone(), (v.done, Some(v.total).filter(|v| *v > 0))))
+            .collect();
+        if !self.download_tracker.replace_progress(progress) {
+            return;
+        }
+
+        self.download_queue_update_popup();
+        if let Err(e) = self.podcast_sync_episodes() {
+            self.mount_error_popup(e.context("podcast sync episodes"));
+        }
+    }
+
+    /// Ask the server to apply `action` to the item at `index` of the download queue
+    ///
+    /// The views are updated once the server reports that the queue changed.
+    fn download_queue_control(&mut self, action: DownloadAction, index: usize) {
+        let Some(item) = self.podcast.download_queue.get(index) else {
+            return;
+        };
+        let ids = vec![item.episode_id];
+        self.command(&PlayerCmd::PodcastDownloads(action, ids));
+    }
+
+    /// Pause a queued or running item of the download queue, or resume a paused one
+    pub fn download_queue_toggle_pause(&mut self, index: usize) {
+        self.download_queue_control(DownloadAction::TogglePause, index);
+    }
+
+    /// Remove a item from the download queue, canceling it if it is running
+    pub fn download_queue_remove(&mut self, index: usize) {
+        self.download_queue_control(DownloadAction::Remove, index);
+    }
+
+    /// Move a item of the download queue one place up, or down if `down` is set
+    pub fn download_queue_move(&mut self, index: usize, down: bool) {
+        let action = if down {
+            DownloadAction::MoveDown
+        } else {
+            DownloadAction::MoveUp
+        };
+        self.download_queue_control(action, index);
+    }
+
+    pub fn download_queue_retry(&mut self) {
+        self.command(&PlayerCmd::PodcastDownloads(
+            DownloadAction::RetryFailed,
+            Vec::new(),
+        ));
+    }
+
+    pub fn download_queue_clear(&mut self) {
+        self.command(&PlayerCmd::PodcastDownloads(
+            DownloadAction::Clear,
+            Vec::new(),
+        ));
+    }
+
+    /// Cancel the running download of the episode at `index` in the selected podcast
+    pub fn episode_cancel_download(&mut self, index: usize) -> Result<()> {
+        let Some(podcast_selected) = self.podcast.podcasts.get(self.podcast.podcasts_index) else {
+            return Ok(());
+        };
+        let ep = podcast_selected
+            .episodes
+            .get(index)
+            .ok_or_else(|| anyhow!("get episode selected failed"))?;
+        if !self
+            .podcast
+            .download_queue
+            .iter()
+            .any(|v| v.episode_id == ep.id && v.status == DownloadStatus::Running)
+        {
+            bail!("Episode \"{}\" is not being downloaded", ep.title);
+        }
+
+        let ids = vec![ep.id];
+        self.command(&PlayerCmd::PodcastDownloads(DownloadAction::Remove, ids));
         Ok(())
     }
 
@@ -896,11 +1233,19 @@ impl Model {
         Ok(())
     }
 
+    /// The index into the podcasts of the selected feed, an error if a folder is selected
     fn podcast_get_feed_index(&self) -> Result<usize> {
-        if let Ok(State::One(StateValue::Usize(feed_index))) = self.app.state(&Id::Podcast) {
-            return Ok(feed_index);
+        self.podcast_get_feed_row()
+            .ok()
+            .and_then(|row| self.podcast_index_of_row(row))
+            .ok_or_else(|| anyhow!("cannot get feed index"))
+    }
+
+    fn podcast_get_feed_row(&self) -> Result<usize> {
+        if let Ok(State::One(StateValue::Usize(feed_row))) = self.app.state(&Id::Podcast) {
+            return Ok(feed_row);
         }
-        Err(anyhow!("cannot get feed index"))
+        Err(anyhow!("cannot get feed row"))
     }
 
     fn podcast_get_episode_index(&self) -> Result<usize> {
@@ -953,6 +1298,54 @@ impl Model {
         None
     }
 
+    /// Find the episode with the given url, with the podcast it belongs to
+    pub fn podcast_find_episode_by_url(&self, url: &str) -> Option<(Podcast, Episode)> {
+        self.podcast.podcasts.iter().find_map(|pod| {
+            pod.episodes
+                .iter()
+                .find(|ep| ep.url == url)
+                .map(|ep| (pod.clone(), ep.clone()))
+        })
+    }
+
+    /// Fetch the time-synced transcript of the given episode, if it has one and it is not loaded yet,
+    /// and send it to `Model::tx_to_main` as [`PCMsg::TranscriptLoaded`].
+    ///
+    /// Requires that the current thread has a entered runtime
+    pub fn podcast_fetch_transcript(&mut self, pod: &Podcast, ep: &Episode) {
+        if self
+            .podcast
+            .transcript
+            .as_ref()
+            .is_some_and(|v| v.url == ep.url)
+        {
+            return;
+        }
+        self.podcast.transcript = None;
+        let Some(transcript) = ep.extras.synced_transcript().cloned() else {
+            return;
+        };
+
+        let auth = pod.auth.clone();
+        let url = ep.url.clone();
+        let max_retries = self
+            .config_server
+            .read()
+            .settings
+            .podcast
+            .max_download_retries;
+        let tx = self.tx_to_main.clone();
+        Handle::current().spawn(async move {
+            match fetch_transcript(&transcript, &auth, usize::from(max_retries)).await {
+                Ok(lyric) => {
+                    tx.send(Msg::Podcast(PCMsg::TranscriptLoaded(url, Box::new(lyric))))
+                        .ok();
+                }
+                Err(e) => warn!("Fetching transcript {} failed: {e:#}", transcript.url),
+            }
+        });
+    }
+
     // #[cfg(not(any(feature = "mpv", feature = "gst")))]
     // pub fn podcast_get_episode_index_by_url(&mut self, url: &str) -> Option<usize> {
     //     if self.podcasts.is_empty() {
@@ -1036,14 +1429,7 @@ impl Model {
     }
 
     pub fn podcast_locate_episode(&mut self, pod_index: usize, ep_index: usize) {
-        assert!(self
-            .app
-            .attr(
-                &Id::Podcast,
-                Attribute::Value,
-                AttrValue::Payload(PropPayload::One(PropValue::Usize(pod_index))),
-            )
-            .is_ok());
+        self.podcast_select_row_of(pod_index);
         self.podcast_sync_episodes().ok();
         assert!(self
             .app
@@ -1108,6 +1494,47 @@ impl Model {
     }
 }
 
+/// Group the podcasts by folder for the feed list, the folders sorted by name come first and
+/// the podcasts without folder after them
+/// Load the podcasts with their episodes grouped by season, like the episode list shows them
+pub fn podcasts_for_view(db: &Database) -> Result<Vec<Podcast>> {
+    let mut podcasts = db.get_podcasts()?;
+    for podcast in &mut podcasts {
+        podcast.sort_episodes_by_season();
+    }
+    Ok(podcasts)
+}
+
+fn feed_rows(podcasts: &[Podcast], collapsed: &HashSet<String>) -> Vec<FeedRow> {
+    let mut folders: Vec<&String> = podcasts.iter().filter_map(|v| v.folder.as_ref()).collect();
+    folders.sort_unstable_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()).then(a.cmp(b)));
+    folders.dedup();
+
+    let mut rows = Vec::new();
+    for folder in folders {
+        rows.push(FeedRow::Folder(folder.clone()));
+        if collapsed.contains(folder) {
+            continue;
+        }
+        rows.extend(
+            podcasts
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| v.folder.as_ref() == Some(folder))
+                .map(|(idx, _)| FeedRow::Podcast(idx)),
+        );
+    }
+    rows.extend(
+        podcasts
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.folder.is_none())
+            .map(|(idx, _)| FeedRow::Podcast(idx)),
+    );
+
+    rows
+}
+
 fn parse_itunes_results(data: &str) -> Option<Vec<PodcastFeed>> {
     if let Ok(value) = serde_json::from_str::<Value>(data) {
         // below two lines are left for debug purpose
@@ -1118,11 +1545,7 @@ fn parse_itunes_results(data: &str) -> Option<Vec<PodcastFeed>> {
         let array = value.get("results")?.as_array()?;
         for v in array {
             if let Some((title, url)) = parse_itunes_item(v) {
-                vec.push(PodcastFeed {
-                    id: None,
-                    url,
-                    title: Some(title),
-                });
+                vec.push(PodcastFeed::new(None, &url, Some(title)));
             }
         }
         return Some(vec);
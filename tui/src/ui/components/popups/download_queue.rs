@@ -0,0 +1,201 @@
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::podcast::db::DownloadStatus;
+use termusiclib::types::{Id, Msg, PCMsg};
+use tui_realm_stdlib::Table;
+use tuirealm::{
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent},
+    props::{Alignment, BorderType, Borders, Table as TableContent, TableBuilder, TextSpan},
+    AttrValue, Attribute, Component, Event, MockComponent, NoUserEvent, State, StateValue,
+};
+
+use crate::ui::model::Model;
+
+/// Shows the podcast download queue, to retry, reorder, pause and remove downloads
+#[derive(MockComponent)]
+pub struct DownloadQueuePopup {
+    component: Table,
+    config: SharedTuiSettings,
+}
+
+impl DownloadQueuePopup {
+    pub fn new(config: SharedTuiSettings, table: TableContent) -> Self {
+        let component = {
+            let config = config.read();
+            Table::default()
+                .background(config.settings.theme.library_background())
+                .foreground(config.settings.theme.library_foreground())
+                .borders(
+                    Borders::default()
+                        .color(config.settings.theme.library_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    " Downloads: p pause/resume, K/J move up/down, r retry failed, x remove, C clear ",
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(config.settings.theme.library_highlight())
+                .highlighted_str(&config.settings.theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .row_height(1)
+                .headers(&[" Status ", " Podcast ", " Episode ", " Error "])
+                .column_spacing(2)
+                .widths(&[10, 25, 45, 20])
+                .table(table)
+        };
+
+        Self { component, config }
+    }
+
+    fn selected(&self) -> Option<usize> {
+        match self.state() {
+            State::One(StateValue::Usize(index)) => Some(index),
+            _ => None,
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for DownloadQueuePopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Esc | Key::Char('q'),
+                ..
+            }) => return Some(Msg::Podcast(PCMsg::DownloadQueueClose)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('p'),
+                ..
+            }) => {
+                return self
+                    .selected()
+                    .map(|index| Msg::Podcast(PCMsg::DownloadQueueTogglePause(index)));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('x'),
+                ..
+            }) => {
+                return self
+                    .selected()
+                    .map(|index| Msg::Podcast(PCMsg::DownloadQueueRemove(index)));
+            }
+            // move the selection along, so the same item can be moved again
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('K'),
+                ..
+            }) => {
+                let index = self.selected()?;
+                self.perform(Cmd::Move(Direction::Up));
+                return Some(Msg::Podcast(PCMsg::DownloadQueueMove(index, false)));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('J'),
+                ..
+            }) => {
+                let index = self.selected()?;
+                self.perform(Cmd::Move(Direction::Down));
+                return Some(Msg::Podcast(PCMsg::DownloadQueueMove(index, true)));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('r'),
+                ..
+            }) => return Some(Msg::Podcast(PCMsg::DownloadQueueRetry)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('C'),
+                ..
+            }) => return Some(Msg::Podcast(PCMsg::DownloadQueueClear)),
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+impl Model {
+    pub fn mount_download_queue(&mut self) {
+        let table = self.download_queue_table();
+        assert!(self
+            .app
+            .remount(
+                Id::DownloadQueuePopup,
+                Box::new(DownloadQueuePopup::new(self.config_tui.clone(), table)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::DownloadQueuePopup).is_ok());
+    }
+
+    pub fn umount_download_queue(&mut self) {
+        if self.app.mounted(&Id::DownloadQueuePopup) {
+            assert!(self.app.umount(&Id::DownloadQueuePopup).is_ok());
+        }
+    }
+
+    /// Show the current download queue in the popup, if it is mounted
+    pub fn download_queue_update_popup(&mut self) {
+        if !self.app.mounted(&Id::DownloadQueuePopup) {
+            return;
+        }
+        let table = self.download_queue_table();
+        self.app
+            .attr(
+                &Id::DownloadQueuePopup,
+                Attribute::Content,
+                AttrValue::Table(table),
+            )
+            .ok();
+    }
+
+    fn download_queue_table(&self) -> TableContent {
+        let mut table = TableBuilder::default();
+        for (idx, item) in self.podcast.download_queue.iter().enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+            let status = match item.status {
+                DownloadStatus::Running => self
+                    .download_tracker
+                    .progress(&item.url)
+                    .unwrap_or_else(|| item.status.to_string()),
+                status => status.to_string(),
+            };
+            table
+                .add_col(TextSpan::new(status))
+                .add_col(TextSpan::new(&item.podcast_title))
+                .add_col(TextSpan::new(&item.title))
+                .add_col(TextSpan::new(item.error.as_deref().unwrap_or_default()));
+        }
+        if self.podcast.download_queue.is_empty() {
+            table.add_col(TextSpan::from("empty download queue"));
+        }
+
+        table.build()
+    }
+}
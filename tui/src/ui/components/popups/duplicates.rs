@@ -0,0 +1,240 @@
+use std::fmt::Write as _;
+
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::library_db::{DuplicateGroup, DuplicateReason, DuplicateReport, KeepPolicy};
+use termusiclib::types::{Id, LIMsg, Msg};
+use tui_realm_stdlib::Textarea;
+use tuirealm::{
+    command::{Cmd, Direction, Position},
+    event::{Key, KeyEvent},
+    props::{Alignment, BorderType, Borders, TextSpan},
+    Component, Event, MockComponent, NoUserEvent,
+};
+
+use crate::ui::model::Model;
+
+use super::{YNConfirm, YNConfirmStyle};
+
+/// Shows groups of likely duplicate tracks and which file of each group would be kept
+#[derive(MockComponent)]
+pub struct DuplicatesPopup {
+    component: Textarea,
+    config: SharedTuiSettings,
+}
+
+impl DuplicatesPopup {
+    pub fn new(config: SharedTuiSettings, report: &DuplicateReport, policy: KeepPolicy) -> Self {
+        let delete = report.files_to_delete(policy).len();
+        let title = format!(
+            " Duplicates: {} groups, {delete} files to delete ",
+            report.groups.len()
+        );
+
+        let mut rows = vec![TextSpan::new(format!(
+            "Keeping the best file by {policy}, b or f to keep by bitrate or format. Enter or d to delete the rest, confirming each group. Esc or q to close."
+        ))
+        .bold()];
+        for (idx, group) in report.groups.iter().enumerate() {
+            let reasons: Vec<&str> = group
+                .reasons
+                .iter()
+                .map(|v| match v {
+                    DuplicateReason::SameAudio => "same audio",
+                    DuplicateReason::SameArtistTitle => "same artist and title",
+                    DuplicateReason::SameFileName => "same file name",
+                })
+                .collect();
+            rows.push(TextSpan::new(format!("{}: {}", idx + 1, reasons.join(", "))).bold());
+
+            let best = group.best(policy);
+            for (idx, file) in group.files.iter().enumerate() {
+                let action = if group.is_hint() {
+                    "check "
+                } else if idx == best {
+                    "keep  "
+                } else {
+                    "delete"
+                };
+                let mut row = format!("  {action} {} ({}", file.file, file.ext);
+                if let Some(bitrate) = file.bitrate_kbps {
+                    let _ = write!(row, ", {bitrate} kbps");
+                }
+                #[allow(clippy::cast_precision_loss)]
+                let _ = write!(row, ", {:.1} MB)", file.size as f64 / 1_048_576.0);
+                rows.push(TextSpan::new(row));
+            }
+        }
+        if report.groups.is_empty() {
+            rows.push(TextSpan::new("No duplicates found."));
+        }
+
+        let component = {
+            let config = config.read();
+            Textarea::default()
+                .borders(
+                    Borders::default()
+                        .color(config.settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .background(config.settings.theme.fallback_background())
+                .foreground(config.settings.theme.fallback_foreground())
+                .title(title, Alignment::Center)
+                .step(4)
+                .text_rows(&rows)
+        };
+
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for DuplicatesPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('b'),
+                ..
+            }) => return Some(Msg::Library(LIMsg::DuplicatesKeep(KeepPolicy::Bitrate))),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('f'),
+                ..
+            }) => return Some(Msg::Library(LIMsg::DuplicatesKeep(KeepPolicy::Format))),
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter | Key::Char('d'),
+                ..
+            }) => return Some(Msg::Library(LIMsg::DuplicatesDelete)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Esc | Key::Char('q'),
+                ..
+            }) => return Some(Msg::Library(LIMsg::DuplicatesClose)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            _ => return None,
+        };
+        Some(Msg::None)
+    }
+}
+
+/// Asks to delete the files of a single duplicate group
+#[derive(MockComponent)]
+pub struct DuplicatesConfirmPopup {
+    component: YNConfirm,
+    config: SharedTuiSettings,
+}
+
+impl DuplicatesConfirmPopup {
+    pub fn new(
+        config: SharedTuiSettings,
+        number: usize,
+        group: &DuplicateGroup,
+        policy: KeepPolicy,
+    ) -> Self {
+        let keep = &group.files[group.best(policy)].file;
+        let keep = keep.rsplit('/').next().unwrap_or(keep);
+        let title = format!(
+            " Group {number}: delete {} files, keeping \"{keep}\"? ",
+            group.files_to_delete(policy).len()
+        );
+        let component = YNConfirm::new_with_cb(config.clone(), title, |config| YNConfirmStyle {
+            foreground_color: config.settings.theme.important_popup_foreground(),
+            background_color: config.settings.theme.important_popup_background(),
+            border_color: config.settings.theme.important_popup_border(),
+            title_alignment: Alignment::Left,
+        });
+
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for DuplicatesConfirmPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        match ev {
+            // "no" only skips the group, closing stops asking for the others
+            Event::Keyboard(key) if key == keys.escape.get() || key == keys.quit.get() => {
+                Some(Msg::Library(LIMsg::DuplicatesDeleteStop))
+            }
+            ev => self.component.on(
+                ev,
+                Msg::Library(LIMsg::DuplicatesDeleteGroup),
+                Msg::Library(LIMsg::DuplicatesSkipGroup),
+            ),
+        }
+    }
+}
+
+impl Model {
+    /// Show the duplicate report, keeping the files by the current policy
+    pub fn mount_duplicates(&mut self, report: DuplicateReport) {
+        assert!(self
+            .app
+            .remount(
+                Id::DuplicatesPopup,
+                Box::new(DuplicatesPopup::new(
+                    self.config_tui.clone(),
+                    &report,
+                    self.duplicates_policy
+                )),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::DuplicatesPopup).is_ok());
+        self.duplicates = Some(report);
+    }
+
+    /// Ask to delete the files of the group at `index` in the current report
+    pub fn mount_duplicates_confirm(&mut self, index: usize) {
+        let Some(group) = self.duplicates.as_ref().and_then(|v| v.groups.get(index)) else {
+            return;
+        };
+        let popup = DuplicatesConfirmPopup::new(
+            self.config_tui.clone(),
+            index + 1,
+            group,
+            self.duplicates_policy,
+        );
+        assert!(self
+            .app
+            .remount(Id::DuplicatesConfirmPopup, Box::new(popup), vec![])
+            .is_ok());
+        assert!(self.app.active(&Id::DuplicatesConfirmPopup).is_ok());
+    }
+
+    pub fn umount_duplicates_confirm(&mut self) {
+        if self.app.mounted(&Id::DuplicatesConfirmPopup) {
+            assert!(self.app.umount(&Id::DuplicatesConfirmPopup).is_ok());
+        }
+    }
+
+    pub fn umount_duplicates(&mut self) {
+        if self.app.mounted(&Id::DuplicatesPopup) {
+            assert!(self.app.umount(&Id::DuplicatesPopup).is_ok());
+        }
+        self.duplicates = None;
+    }
+}
@@ -32,6 +32,10 @@ use tuirealm::event::{Key, KeyEvent, KeyModifiers, NoUserEvent};
 use tuirealm::props::{Alignment, BorderType, Borders, InputType, TableBuilder, TextSpan};
 use tuirealm::{AttrValue, Attribute, Component, Event, MockComponent, State, StateValue};
 
+/// Title of the input when searching the database, replaced by parse errors of the query
+pub const DATABASE_SEARCH_TITLE: &str =
+    " Search for: (e.g. artist:name -live duration:<4m, with OR and ( ) ) ";
+
 #[derive(MockComponent)]
 pub struct GSInputPopup {
     component: Input,
@@ -57,6 +61,19 @@ impl GSInputPopup {
                     ),
                 source,
             },
+            Source::Database => Self {
+                component: Input::default()
+                    .background(config.settings.theme.fallback_background())
+                    .foreground(config.settings.theme.fallback_foreground())
+                    .borders(
+                        Borders::default()
+                            .color(config.settings.theme.fallback_border())
+                            .modifiers(BorderType::Rounded),
+                    )
+                    .input_type(InputType::Text)
+                    .title(DATABASE_SEARCH_TITLE, Alignment::Left),
+                source,
+            },
             _ => Self {
                 component: Input::default()
                     .background(config.settings.theme.fallback_background())
@@ -394,6 +411,17 @@ impl Component<Msg, NoUserEvent> for GSTablePopup {
 }
 
 impl Model {
+    /// Show `title` on the search input, used to show query errors inline
+    pub fn general_search_update_title(&mut self, title: &str) {
+        self.app
+            .attr(
+                &Id::GeneralSearchInput,
+                Attribute::Title,
+                AttrValue::Title((title.to_string(), Alignment::Left)),
+            )
+            .ok();
+    }
+
     pub fn general_search_update_show(&mut self, table: Vec<Vec<TextSpan>>) {
         self.app
             .attr(
@@ -95,6 +95,13 @@ impl HelpPopup {
                         .add_col(Self::key(&[&keys.player_keys.toggle_prefetch]))
                         .add_col(Self::comment("Toggle gapless playback"))
                         .add_row()
+                        .add_col(Self::key(&[
+                            &keys.player_keys.sleep_timer_cycle,
+                            &keys.player_keys.sleep_timer_extend,
+                            &keys.player_keys.sleep_timer_cancel,
+                        ]))
+                        .add_col(Self::comment("Cycle/Extend 5 minutes/Cancel sleep timer"))
+                        .add_row()
                         .add_col(Self::key(&[
                             &keys.lyric_keys.adjust_offset_forwards,
                             &keys.lyric_keys.adjust_offset_backwards,
@@ -163,6 +170,12 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Add one/all tracks to playlist"))
                         .add_row()
+                        .add_col(Self::key(&[
+                            &keys.library_keys.enqueue_next,
+                            &keys.library_keys.enqueue_last,
+                        ]))
+                        .add_col(Self::comment("Play track or folder next/last in queue"))
+                        .add_row()
                         .add_col(Self::key(&[&keys.library_keys.delete]))
                         .add_col(Self::comment("Delete track or folder"))
                         .add_row()
@@ -196,6 +209,11 @@ impl HelpPopup {
                         .add_col(Self::key(&[&keys.library_keys.remove_root]))
                         .add_col(Self::comment("Remove current root from root folder list"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.library_keys.find_duplicates]))
+                        .add_col(Self::comment(
+                            "Find duplicates, b/f: keep by bitrate/format, Enter: delete the rest",
+                        ))
+                        .add_row()
                         .add_col(TextSpan::new("Playlist").bold().fg(Color::LightYellow))
                         .add_row()
                         .add_col(Self::key(&[
@@ -228,6 +246,39 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Select random tracks/albums to playlist"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.playlist_keys.clear_queue]))
+                        .add_col(Self::comment("Clear the up-next queue"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.playlist_keys.undo,
+                            &keys.playlist_keys.redo,
+                        ]))
+                        .add_col(Self::comment("Undo/redo playlist change"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.playlist_keys.sort,
+                            &keys.playlist_keys.remove_duplicates,
+                        ]))
+                        .add_col(Self::comment("Sort playlist/remove duplicates"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.playlist_keys.previous_playlist,
+                            &keys.playlist_keys.next_playlist,
+                        ]))
+                        .add_col(Self::comment("Switch to previous/next named playlist"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.playlist_keys.new_playlist,
+                            &keys.playlist_keys.rename_playlist,
+                        ]))
+                        .add_col(Self::comment("Create new/rename named playlist"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.playlist_keys.duplicate_playlist,
+                            &keys.playlist_keys.delete_playlist,
+                        ]))
+                        .add_col(Self::comment("Duplicate/delete named playlist"))
+                        .add_row()
                         .add_col(TextSpan::new("Database").bold().fg(Color::LightYellow))
                         .add_row()
                         .add_col(Self::key(&[
@@ -236,6 +287,12 @@ impl HelpPopup {
                         ]))
                         .add_col(Self::comment("Add one/all track(s) to playlist"))
                         .add_row()
+                        .add_col(Self::key(&[
+                            &keys.database_keys.enqueue_next,
+                            &keys.database_keys.enqueue_last,
+                        ]))
+                        .add_col(Self::comment("Play track next/last in queue"))
+                        .add_row()
                         // TODO: add search key to database
                         .add_col(Self::key(&[&keys.library_keys.search]))
                         .add_col(Self::comment("Search in database"))
@@ -255,17 +312,44 @@ impl HelpPopup {
                             &keys.podcast_keys.refresh_feed,
                             &keys.podcast_keys.refresh_all_feeds,
                         ]))
-                        .add_col(Self::comment("Feeds : refresh one/all feeds"))
+                        .add_col(Self::comment(
+                            "Feeds : refresh one/all feeds, all of a folder",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.podcast_keys.download_policy]))
+                        .add_col(Self::comment(
+                            "Feeds : edit auto-download and retention policy",
+                        ))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.podcast_keys.set_folder]))
+                        .add_col(Self::comment("Feeds : move feed to folder, Enter folds it"))
                         .add_row()
                         .add_col(Self::key(&[
                             &keys.podcast_keys.mark_played,
                             &keys.podcast_keys.mark_all_played,
                         ]))
-                        .add_col(Self::comment("Episode: Mark one/all episodes played"))
+                        .add_col(Self::comment(
+                            "Episode: Mark one/all episodes played, all of a folder",
+                        ))
                         .add_row()
                         .add_col(Self::key(&[&keys.podcast_keys.download_episode]))
                         .add_col(Self::comment("Episode: Download episode"))
                         .add_row()
+                        .add_col(Self::key(&[&keys.podcast_keys.cancel_download]))
+                        .add_col(Self::comment("Episode: Cancel download"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.podcast_keys.download_queue]))
+                        .add_col(Self::comment("Episode: Show download queue"))
+                        .add_row()
+                        .add_col(Self::key(&[&keys.podcast_keys.show_notes]))
+                        .add_col(Self::comment("Episode: Show notes"))
+                        .add_row()
+                        .add_col(Self::key(&[
+                            &keys.podcast_keys.enqueue_next,
+                            &keys.podcast_keys.enqueue_last,
+                        ]))
+                        .add_col(Self::comment("Episode: play next/last in queue"))
+                        .add_row()
                         .add_col(Self::key(&[&keys.podcast_keys.delete_local_episode]))
                         .add_col(Self::comment("Episode: delete episode local file"))
                         .add_row()
@@ -26,9 +26,9 @@ pub struct YNConfirm {
 
 impl YNConfirm {
     /// Create a new instance with custom colors
-    pub fn new_with_cb<F: FnOnce(&TuiOverlay) -> YNConfirmStyle>(
+    pub fn new_with_cb<S: AsRef<str>, F: FnOnce(&TuiOverlay) -> YNConfirmStyle>(
         config: SharedTuiSettings,
-        title: &'static str,
+        title: S,
         cb: F,
     ) -> Self {
         let component = {
@@ -1,14 +1,20 @@
 #![allow(clippy::module_name_repetitions)]
 
 mod deleteconfirm;
+mod download_queue;
+mod duplicates;
 mod error;
 pub mod general_search;
 mod help;
 mod message;
 mod mock_yn_confirm;
+mod named_playlist;
+mod playlist_edit;
+mod playlist_import;
 mod podcast;
 mod quit;
 mod saveplaylist;
+mod show_notes;
 pub mod youtube_search;
 
 #[allow(unused_imports)]
@@ -20,10 +26,12 @@ pub use help::HelpPopup;
 #[allow(unused_imports)]
 pub use message::MessagePopup;
 pub use mock_yn_confirm::{YNConfirm, YNConfirmStyle};
+pub use named_playlist::PlaylistNameMode;
+pub use playlist_edit::PlaylistEditMode;
 #[allow(unused_imports)]
 pub use podcast::{
-    FeedDeleteConfirmInputPopup, FeedDeleteConfirmRadioPopup, PodcastAddPopup,
-    PodcastSearchTablePopup,
+    FeedDeleteConfirmInputPopup, FeedDeleteConfirmRadioPopup, PodcastAddPopup, PodcastFolderPopup,
+    PodcastPolicyPopup, PodcastSearchTablePopup,
 };
 #[allow(unused_imports)]
 pub use quit::QuitPopup;
@@ -0,0 +1,163 @@
+use termusiclib::config::{SharedTuiSettings, TuiOverlay};
+use termusiclib::types::{Id, Msg, PLMsg};
+use tui_realm_stdlib::Input;
+use tuirealm::{
+    command::{Cmd, Direction, Position},
+    event::{Key, KeyEvent, KeyModifiers},
+    props::{Alignment, BorderType, Borders, InputType},
+    Component, Event, MockComponent, NoUserEvent, State, StateValue,
+};
+
+use crate::ui::model::Model;
+
+use super::{YNConfirm, YNConfirmStyle};
+
+/// What the entered name of a [`PlaylistNamePopup`] is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistNameMode {
+    Create,
+    Rename,
+}
+
+#[derive(MockComponent)]
+pub struct PlaylistNamePopup {
+    component: Input,
+    mode: PlaylistNameMode,
+}
+
+impl PlaylistNamePopup {
+    pub fn new(config: &TuiOverlay, mode: PlaylistNameMode, value: &str) -> Self {
+        let settings = &config.settings;
+        let title = match mode {
+            PlaylistNameMode::Create => " New playlist name: (Enter to confirm) ",
+            PlaylistNameMode::Rename => " Rename playlist to: (Enter to confirm) ",
+        };
+        Self {
+            component: Input::default()
+                .foreground(settings.theme.fallback_foreground())
+                .background(settings.theme.fallback_background())
+                .borders(
+                    Borders::default()
+                        .color(settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .value(value)
+                .title(title, Alignment::Left),
+            mode,
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for PlaylistNamePopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Playlist(PLMsg::NamedPlaylistNameCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                if let State::One(StateValue::String(input_string)) = self.component.state() {
+                    return Some(Msg::Playlist(match self.mode {
+                        PlaylistNameMode::Create => PLMsg::NamedPlaylistCreate(input_string),
+                        PlaylistNameMode::Rename => PLMsg::NamedPlaylistRename(input_string),
+                    }));
+                }
+                return Some(Msg::None);
+            }
+            _ => return None,
+        };
+        Some(Msg::None)
+    }
+}
+
+#[derive(MockComponent)]
+pub struct PlaylistDeleteConfirmPopup {
+    component: YNConfirm,
+}
+
+impl PlaylistDeleteConfirmPopup {
+    pub fn new(config: SharedTuiSettings) -> Self {
+        let component =
+            YNConfirm::new_with_cb(config, " Delete this playlist? ", |config| YNConfirmStyle {
+                foreground_color: config.settings.theme.important_popup_foreground(),
+                background_color: config.settings.theme.important_popup_background(),
+                border_color: config.settings.theme.important_popup_border(),
+                title_alignment: Alignment::Center,
+            });
+
+        Self { component }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for PlaylistDeleteConfirmPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        self.component.on(
+            ev,
+            Msg::Playlist(PLMsg::NamedPlaylistDelete),
+            Msg::Playlist(PLMsg::NamedPlaylistDeleteCancel),
+        )
+    }
+}
+
+impl Model {
+    pub fn mount_playlist_name(&mut self, mode: PlaylistNameMode, value: &str) {
+        assert!(self
+            .app
+            .remount(
+                Id::PlaylistNamePopup,
+                Box::new(PlaylistNamePopup::new(&self.config_tui.read(), mode, value)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::PlaylistNamePopup).is_ok());
+    }
+
+    pub fn umount_playlist_name(&mut self) {
+        if self.app.mounted(&Id::PlaylistNamePopup) {
+            assert!(self.app.umount(&Id::PlaylistNamePopup).is_ok());
+        }
+    }
+
+    pub fn mount_playlist_delete_confirm(&mut self) {
+        assert!(self
+            .app
+            .remount(
+                Id::PlaylistDeleteConfirm,
+                Box::new(PlaylistDeleteConfirmPopup::new(self.config_tui.clone())),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::PlaylistDeleteConfirm).is_ok());
+    }
+
+    pub fn umount_playlist_delete_confirm(&mut self) {
+        if self.app.mounted(&Id::PlaylistDeleteConfirm) {
+            assert!(self.app.umount(&Id::PlaylistDeleteConfirm).is_ok());
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::types::{Id, Msg, PLMsg};
+use termusicplayback::{DedupKey, PlayerCmd, SortKey};
+use tui_realm_stdlib::Table;
+use tuirealm::{
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent},
+    props::{Alignment, BorderType, Borders, TableBuilder, TextSpan},
+    Component, Event, MockComponent, NoUserEvent, State, StateValue,
+};
+
+use crate::ui::model::Model;
+
+/// Which playlist operation a [`PlaylistEditPopup`] offers the options for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistEditMode {
+    Sort,
+    Dedup,
+}
+
+impl PlaylistEditMode {
+    /// Get the command for the option at `index`, as listed in the popup
+    pub fn command(self, index: usize) -> Option<PlayerCmd> {
+        match self {
+            PlaylistEditMode::Sort => {
+                let key = *SortKey::ALL.get(index / 2)?;
+                Some(PlayerCmd::PlaylistSort(key, index % 2 == 1))
+            }
+            PlaylistEditMode::Dedup => {
+                let key = *[DedupKey::Path, DedupKey::ArtistTitle].get(index)?;
+                Some(PlayerCmd::PlaylistDedup(key))
+            }
+        }
+    }
+
+    fn options(self) -> Vec<String> {
+        match self {
+            PlaylistEditMode::Sort => SortKey::ALL
+                .iter()
+                .flat_map(|key| [format!("{key} (ascending)"), format!("{key} (descending)")])
+                .collect(),
+            PlaylistEditMode::Dedup => vec![
+                "Same file path".to_string(),
+                "Same artist and title".to_string(),
+            ],
+        }
+    }
+}
+
+/// Lets the user pick how to sort the playlist or which tracks count as duplicates
+#[derive(MockComponent)]
+pub struct PlaylistEditPopup {
+    component: Table,
+    config: SharedTuiSettings,
+    mode: PlaylistEditMode,
+}
+
+impl PlaylistEditPopup {
+    pub fn new(config: SharedTuiSettings, mode: PlaylistEditMode) -> Self {
+        let title = match mode {
+            PlaylistEditMode::Sort => " Sort playlist by: ",
+            PlaylistEditMode::Dedup => " Remove duplicates with: ",
+        };
+        let mut table = TableBuilder::default();
+        for (index, option) in mode.options().into_iter().enumerate() {
+            if index > 0 {
+                table.add_row();
+            }
+            table.add_col(TextSpan::from(option));
+        }
+
+        let component = {
+            let config = config.read();
+            Table::default()
+                .background(config.settings.theme.fallback_background())
+                .foreground(config.settings.theme.fallback_foreground())
+                .borders(
+                    Borders::default()
+                        .color(config.settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(title, Alignment::Left)
+                .scroll(true)
+                .highlighted_color(config.settings.theme.fallback_highlight())
+                .highlighted_str(&config.settings.theme.style.playlist.highlight_symbol)
+                .rewind(true)
+                .step(4)
+                .row_height(1)
+                .widths(&[100])
+                .table(table.build())
+        };
+
+        Self {
+            component,
+            config,
+            mode,
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for PlaylistEditPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Playlist(PLMsg::EditCancel))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.quit.get() => {
+                return Some(Msg::Playlist(PLMsg::EditCancel))
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                if let State::One(StateValue::Usize(index)) = self.state() {
+                    return Some(Msg::Playlist(match self.mode {
+                        PlaylistEditMode::Sort => PLMsg::Sort(index),
+                        PlaylistEditMode::Dedup => PLMsg::Dedup(index),
+                    }));
+                }
+                CmdResult::None
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+impl Model {
+    pub fn mount_playlist_edit(&mut self, mode: PlaylistEditMode) {
+        assert!(self
+            .app
+            .remount(
+                Id::PlaylistEditPopup,
+                Box::new(PlaylistEditPopup::new(self.config_tui.clone(), mode)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::PlaylistEditPopup).is_ok());
+    }
+
+    pub fn umount_playlist_edit(&mut self) {
+        if self.app.mounted(&Id::PlaylistEditPopup) {
+            assert!(self.app.umount(&Id::PlaylistEditPopup).is_ok());
+        }
+    }
+
+    /// Send the command for the selected option of the [`PlaylistEditPopup`]
+    pub fn playlist_edit(&mut self, mode: PlaylistEditMode, index: usize) {
+        self.umount_playlist_edit();
+        if let Some(cmd) = mode.command(index) {
+            self.command(&cmd);
+        }
+    }
+}
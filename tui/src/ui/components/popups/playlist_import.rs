@@ -0,0 +1,138 @@
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::library_db::{RelinkReport, RelinkStatus};
+use termusiclib::types::{Id, Msg, PLMsg};
+use tui_realm_stdlib::Textarea;
+use tuirealm::{
+    command::{Cmd, Direction, Position},
+    event::{Key, KeyEvent},
+    props::{Alignment, BorderType, Borders, TextSpan},
+    Component, Event, MockComponent, NoUserEvent,
+};
+
+use crate::ui::model::Model;
+
+/// Shows which entries of a imported playlist were relinked, are ambiguous or missing before adding them
+#[derive(MockComponent)]
+pub struct PlaylistImportPopup {
+    component: Textarea,
+    config: SharedTuiSettings,
+}
+
+impl PlaylistImportPopup {
+    pub fn new(config: SharedTuiSettings, report: &RelinkReport) -> Self {
+        let relinked = report.count(|v| matches!(v, RelinkStatus::Relinked(_)));
+        let ambiguous = report.count(|v| matches!(v, RelinkStatus::Ambiguous(_)));
+        let missing = report.count(|v| *v == RelinkStatus::Missing);
+        let title = format!(
+            " Import playlist: {relinked} relinked, {ambiguous} ambiguous, {missing} missing "
+        );
+
+        let mut rows = vec![TextSpan::new(format!(
+            "Enter or y to add {} tracks, ambiguous and missing entries are skipped. Esc or n to cancel.",
+            report.files().len()
+        ))
+        .bold()];
+        for entry in &report.entries {
+            let location = &entry.entry.location;
+            match &entry.status {
+                RelinkStatus::Found => {}
+                RelinkStatus::Relinked(file) => {
+                    rows.push(TextSpan::new(format!("relinked: {location} -> {file}")));
+                }
+                RelinkStatus::Ambiguous(files) => {
+                    rows.push(TextSpan::new(format!(
+                        "ambiguous: {location} ({} candidates)",
+                        files.len()
+                    )));
+                }
+                RelinkStatus::Missing => {
+                    rows.push(TextSpan::new(format!("missing: {location}")));
+                }
+            }
+        }
+
+        let component = {
+            let config = config.read();
+            Textarea::default()
+                .borders(
+                    Borders::default()
+                        .color(config.settings.theme.fallback_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .background(config.settings.theme.fallback_background())
+                .foreground(config.settings.theme.fallback_foreground())
+                .title(title, Alignment::Center)
+                .step(4)
+                .text_rows(&rows)
+        };
+
+        Self { component, config }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for PlaylistImportPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter | Key::Char('y'),
+                ..
+            }) => return Some(Msg::Playlist(PLMsg::ImportConfirm)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Esc | Key::Char('n'),
+                ..
+            }) => return Some(Msg::Playlist(PLMsg::ImportCancel)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(key) if key == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(key) if key == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            _ => return None,
+        };
+        Some(Msg::None)
+    }
+}
+
+impl Model {
+    /// Show the relink report of a playlist import, the tracks are added once confirmed
+    pub fn mount_playlist_import(&mut self, report: RelinkReport) {
+        assert!(self
+            .app
+            .remount(
+                Id::PlaylistImportPopup,
+                Box::new(PlaylistImportPopup::new(self.config_tui.clone(), &report)),
+                vec![]
+            )
+            .is_ok());
+        assert!(self.app.active(&Id::PlaylistImportPopup).is_ok());
+        self.playlist_import = Some(report);
+    }
+
+    pub fn umount_playlist_import(&mut self) {
+        if self.app.mounted(&Id::PlaylistImportPopup) {
+            assert!(self.app.umount(&Id::PlaylistImportPopup).is_ok());
+        }
+        self.playlist_import = None;
+    }
+}
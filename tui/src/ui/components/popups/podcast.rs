@@ -1,25 +1,188 @@
 use termusiclib::config::{SharedTuiSettings, TuiOverlay};
-use termusiclib::types::{Id, Msg, PCMsg};
+use termusiclib::podcast::PodcastPolicy;
+use termusiclib::types::{Id, Msg, PCMsg, PodcastAddInput};
 use tui_realm_stdlib::{Input, Table};
 use tuirealm::{
     command::{Cmd, CmdResult, Direction, Position},
     event::{Key, KeyEvent, KeyModifiers},
     props::{Alignment, BorderType, Borders, InputType, TableBuilder, TextSpan},
-    Component, Event, MockComponent, NoUserEvent, State, StateValue,
+    tui::layout::{Constraint, Direction as LayoutDirection, Layout, Rect},
+    AttrValue, Attribute, Component, Event, Frame, MockComponent, NoUserEvent, State, StateValue,
 };
 
 use crate::ui::model::Model;
 
 use super::{YNConfirm, YNConfirmStyle};
 
-#[derive(MockComponent)]
+/// Add a feed by url, or search for one, with optional credentials and headers for private feeds
+///
+/// The fields are switched with `Tab` and `BackTab`, `Enter` confirms all of them.
 pub struct PodcastAddPopup {
-    component: Input,
+    fields: [Input; 4],
+    /// Index of the field that receives the input
+    selected: usize,
+    focus: bool,
 }
 
 impl PodcastAddPopup {
+    const URL: usize = 0;
+    const USERNAME: usize = 1;
+    const PASSWORD: usize = 2;
+    const HEADERS: usize = 3;
+
     pub fn new(config: &TuiOverlay) -> Self {
         let config = &config.settings;
+        let field = |title: &str, input_type: InputType| {
+            Input::default()
+                .foreground(config.theme.library_foreground())
+                .background(config.theme.library_background())
+                .borders(
+                    Borders::default()
+                        .color(config.theme.library_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                // .invalid_style(Style::default().fg(Color::Red))
+                .input_type(input_type)
+                .title(title, Alignment::Left)
+        };
+        Self {
+            fields: [
+                field(
+                    " Add or search podcast feed : (Enter to confirm, Tab for credentials) ",
+                    InputType::Text,
+                ),
+                field(" Username (optional) ", InputType::Text),
+                field(" Password (optional) ", InputType::Password('*')),
+                field(
+                    " Headers, e.g. User-Agent: App/1.0 | X-Token: abc (optional) ",
+                    InputType::Text,
+                ),
+            ],
+            selected: Self::URL,
+            focus: false,
+        }
+    }
+
+    fn value(&self, index: usize) -> String {
+        match self.fields[index].state() {
+            State::One(StateValue::String(value)) => value.trim().to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn input(&self) -> PodcastAddInput {
+        PodcastAddInput {
+            url: self.value(Self::URL),
+            username: self.value(Self::USERNAME),
+            password: self.value(Self::PASSWORD),
+            headers: self.value(Self::HEADERS),
+        }
+    }
+}
+
+impl MockComponent for PodcastAddPopup {
+    fn view(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let chunks = Layout::default()
+            .direction(LayoutDirection::Vertical)
+            .constraints([Constraint::Length(3); 4])
+            .split(area);
+        for (idx, (field, chunk)) in self.fields.iter_mut().zip(chunks.iter()).enumerate() {
+            field.attr(
+                Attribute::Focus,
+                AttrValue::Flag(self.focus && idx == self.selected),
+            );
+            field.view(frame, *chunk);
+        }
+    }
+
+    fn query(&self, attr: Attribute) -> Option<AttrValue> {
+        self.fields[self.selected].query(attr)
+    }
+
+    fn attr(&mut self, attr: Attribute, value: AttrValue) {
+        match attr {
+            Attribute::Focus => self.focus = value.unwrap_flag(),
+            attr => self.fields[self.selected].attr(attr, value),
+        }
+    }
+
+    fn state(&self) -> State {
+        self.fields[self.selected].state()
+    }
+
+    fn perform(&mut self, cmd: Cmd) -> CmdResult {
+        self.fields[self.selected].perform(cmd)
+    }
+}
+
+impl Component<Msg, NoUserEvent> for PodcastAddPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                self.selected = (self.selected + 1) % self.fields.len();
+                CmdResult::None
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::BackTab, ..
+            }) => {
+                self.selected = (self.selected + self.fields.len() - 1) % self.fields.len();
+                CmdResult::None
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Podcast(PCMsg::PodcastAddPopupCloseCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                let input = self.input();
+                if input.url.is_empty() {
+                    return Some(Msg::None);
+                }
+                return Some(Msg::Podcast(PCMsg::PodcastAddPopupCloseOk(input)));
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+/// Edit the automatic download and retention policy of a feed as `key=value` pairs
+#[derive(MockComponent)]
+pub struct PodcastPolicyPopup {
+    component: Input,
+}
+
+impl PodcastPolicyPopup {
+    pub fn new(config: &TuiOverlay, policy: &PodcastPolicy) -> Self {
+        let config = &config.settings;
+        let value = if policy.is_manual() {
+            String::new()
+        } else {
+            policy.to_string()
+        };
         Self {
             component: Input::default()
                 .foreground(config.theme.library_foreground())
@@ -29,17 +192,17 @@ impl PodcastAddPopup {
                         .color(config.theme.library_border())
                         .modifiers(BorderType::Rounded),
                 )
-                // .invalid_style(Style::default().fg(Color::Red))
                 .input_type(InputType::Text)
+                .value(value)
                 .title(
-                    " Add or search podcast feed : (Enter to confirm) ",
+                    " Policy, e.g. auto=3 keep=10 days=7 size=500 (empty for manual) ",
                     Alignment::Left,
                 ),
         }
     }
 }
 
-impl Component<Msg, NoUserEvent> for PodcastAddPopup {
+impl Component<Msg, NoUserEvent> for PodcastPolicyPopup {
     fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
         let _cmd_result = match ev {
             Event::Keyboard(KeyEvent {
@@ -66,24 +229,92 @@ impl Component<Msg, NoUserEvent> for PodcastAddPopup {
                 modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
             }) => self.perform(Cmd::Type(ch)),
             Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
-                return Some(Msg::Podcast(PCMsg::PodcastAddPopupCloseCancel));
+                return Some(Msg::Podcast(PCMsg::PolicyPopupCloseCancel));
             }
             Event::Keyboard(KeyEvent {
                 code: Key::Enter, ..
             }) => match self.component.state() {
                 State::One(StateValue::String(input_string)) => {
-                    return Some(Msg::Podcast(PCMsg::PodcastAddPopupCloseOk(input_string)));
+                    return Some(Msg::Podcast(PCMsg::PolicyPopupCloseOk(input_string)));
                 }
-                _ => return Some(Msg::None),
+                _ => return Some(Msg::Podcast(PCMsg::PolicyPopupCloseOk(String::new()))),
+            },
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+/// Edit the folder of a feed, empty to remove it from its folder
+///
+/// Nested folders are entered as a path, like `News/Local`
+#[derive(MockComponent)]
+pub struct PodcastFolderPopup {
+    component: Input,
+}
+
+impl PodcastFolderPopup {
+    pub fn new(config: &TuiOverlay, folder: Option<&str>) -> Self {
+        let config = &config.settings;
+        Self {
+            component: Input::default()
+                .foreground(config.theme.library_foreground())
+                .background(config.theme.library_background())
+                .borders(
+                    Borders::default()
+                        .color(config.theme.library_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .input_type(InputType::Text)
+                .value(folder.unwrap_or_default())
+                .title(
+                    " Folder, / for subfolders (empty for none) ",
+                    Alignment::Left,
+                ),
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for PodcastFolderPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                modifiers: KeyModifiers::SHIFT | KeyModifiers::NONE,
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                return Some(Msg::Podcast(PCMsg::FolderPopupCloseCancel));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => match self.component.state() {
+                State::One(StateValue::String(input_string)) => {
+                    return Some(Msg::Podcast(PCMsg::FolderPopupCloseOk(input_string)));
+                }
+                _ => return Some(Msg::Podcast(PCMsg::FolderPopupCloseOk(String::new()))),
             },
             _ => CmdResult::None,
         };
-        // match cmd_result {
-        //     CmdResult::Submit(State::One(StateValue::String(input_string))) => {
-        //         Some(Msg::SavePlaylistPopupUpdate(input_string))
-        //     }
         Some(Msg::None)
-        // }
     }
 }
 
@@ -412,4 +643,63 @@ impl Model {
             assert!(self.app.umount(&Id::PodcastAddPopup).is_ok());
         }
     }
+
+    /// Show the policy editor for the feed at `index`
+    pub fn mount_podcast_policy_popup(&mut self, index: usize) {
+        let Some(podcast) = self.podcast.podcasts.get(index) else {
+            return;
+        };
+        self.podcast.policy_podcast_id = Some(podcast.id);
+        assert!(self
+            .app
+            .remount(
+                Id::PodcastPolicyPopup,
+                Box::new(PodcastPolicyPopup::new(
+                    &self.config_tui.read(),
+                    &podcast.policy
+                )),
+                vec![]
+            )
+            .is_ok());
+
+        assert!(self.app.active(&Id::PodcastPolicyPopup).is_ok());
+    }
+
+    pub fn umount_podcast_policy_popup(&mut self) {
+        if self.app.mounted(&Id::PodcastPolicyPopup) {
+            assert!(self.app.umount(&Id::PodcastPolicyPopup).is_ok());
+        }
+        self.podcast.policy_podcast_id = None;
+    }
+
+    /// Show the folder editor for the feed at `row` of the feed list, nothing for folders
+    pub fn mount_podcast_folder_popup(&mut self, row: usize) {
+        let Some(podcast) = self
+            .podcast_index_of_row(row)
+            .and_then(|index| self.podcast.podcasts.get(index))
+        else {
+            return;
+        };
+        self.podcast.folder_podcast_id = Some(podcast.id);
+        assert!(self
+            .app
+            .remount(
+                Id::PodcastFolderPopup,
+                Box::new(PodcastFolderPopup::new(
+                    &self.config_tui.read(),
+                    podcast.folder.as_deref()
+                )),
+                vec![]
+            )
+            .is_ok());
+
+        assert!(self.app.active(&Id::PodcastFolderPopup).is_ok());
+    }
+
+    pub fn umount_podcast_folder_popup(&mut self) {
+        if self.app.mounted(&Id::PodcastFolderPopup) {
+            assert!(self.app.umount(&Id::PodcastFolderPopup).is_ok());
+        }
+        self.podcast.folder_podcast_id = None;
+    }
 }
@@ -32,7 +32,10 @@ impl SavePlaylistPopup {
                 )
                 // .invalid_style(Style::default().fg(Color::Red))
                 .input_type(InputType::Text)
-                .title(" Save Playlist as: (Enter to confirm) ", Alignment::Left),
+                .title(
+                    " Save Playlist as: (Enter to confirm, Tab to toggle relative paths) ",
+                    Alignment::Left,
+                ),
         }
     }
 }
@@ -72,6 +75,9 @@ impl Component<Msg, NoUserEvent> for SavePlaylistPopup {
             Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
                 return Some(Msg::SavePlaylistPopupCloseCancel);
             }
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                return Some(Msg::SavePlaylistPopupTogglePaths);
+            }
             Event::Keyboard(KeyEvent {
                 code: Key::Enter, ..
             }) => match self.component.state() {
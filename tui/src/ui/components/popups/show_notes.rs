@@ -0,0 +1,286 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::podcast::show_notes::{NoteLine, ShowNotes};
+use termusiclib::types::{Id, Msg, PCMsg};
+use termusicplayback::PlayerCmd;
+use tui_realm_stdlib::List;
+use tuirealm::{
+    command::{Cmd, CmdResult, Direction, Position},
+    event::{Key, KeyEvent},
+    props::{Alignment, BorderType, Borders, Table as TableContent, TextSpan},
+    Component, Event, MockComponent, NoUserEvent, State, StateValue,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::ui::model::Model;
+
+/// Shows the formatted notes of a episode, to seek to their timestamps and open or copy their links
+#[derive(MockComponent)]
+pub struct ShowNotesPopup {
+    component: List,
+    config: SharedTuiSettings,
+    lines: Vec<NoteLine>,
+    links: Vec<String>,
+    /// Url of the episode, to only seek if it is the one playing
+    episode_url: String,
+}
+
+impl ShowNotesPopup {
+    pub fn new(
+        config: SharedTuiSettings,
+        title: &str,
+        notes: ShowNotes,
+        episode_url: String,
+    ) -> Self {
+        let rows: TableContent = if notes.lines.is_empty() {
+            vec![vec![TextSpan::from("No show notes available.")]]
+        } else {
+            notes.lines.iter().map(line_to_row).collect()
+        };
+        let component = {
+            let config = config.read();
+            List::default()
+                .background(config.settings.theme.library_background())
+                .foreground(config.settings.theme.library_foreground())
+                .borders(
+                    Borders::default()
+                        .color(config.settings.theme.library_border())
+                        .modifiers(BorderType::Rounded),
+                )
+                .title(
+                    format!(" {title}: Enter seek to timestamp, o open link, y copy link "),
+                    Alignment::Left,
+                )
+                .scroll(true)
+                .highlighted_color(config.settings.theme.library_highlight())
+                .highlighted_str(&config.settings.theme.style.library.highlight_symbol)
+                .rewind(false)
+                .step(4)
+                .rows(rows)
+                .selected_line(0)
+        };
+
+        Self {
+            component,
+            config,
+            lines: notes.lines,
+            links: notes.links,
+            episode_url,
+        }
+    }
+
+    fn selected(&self) -> Option<&NoteLine> {
+        match self.state() {
+            State::One(StateValue::Usize(index)) => self.lines.get(index),
+            _ => None,
+        }
+    }
+
+    /// The first link numbered in the selected line
+    fn selected_link(&self) -> Option<String> {
+        let idx = *self.selected()?.links.first()?;
+        self.links.get(idx).cloned()
+    }
+}
+
+fn line_to_row(line: &NoteLine) -> Vec<TextSpan> {
+    if line.spans.is_empty() {
+        return vec![TextSpan::from(" ")];
+    }
+    line.spans
+        .iter()
+        .map(|span| {
+            let mut text = TextSpan::new(&span.text);
+            if span.style.bold {
+                text = text.bold();
+            }
+            if span.style.italic {
+                text = text.italic();
+            }
+            if span.style.underlined {
+                text = text.underlined();
+            }
+            text
+        })
+        .collect()
+}
+
+impl Component<Msg, NoUserEvent> for ShowNotesPopup {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let config = self.config.clone();
+        let keys = &config.read().settings.keys;
+        let _cmd_result = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Esc | Key::Char('q'),
+                ..
+            }) => return Some(Msg::Podcast(PCMsg::ShowNotesClose)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => {
+                let position = *self.selected()?.timestamps.first()?;
+                return Some(Msg::Podcast(PCMsg::ShowNotesSeek(
+                    self.episode_url.clone(),
+                    position,
+                )));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('o'),
+                ..
+            }) => {
+                return self
+                    .selected_link()
+                    .map(|url| Msg::Podcast(PCMsg::ShowNotesOpenLink(url)));
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Char('y'),
+                ..
+            }) => {
+                return self
+                    .selected_link()
+                    .map(|url| Msg::Podcast(PCMsg::ShowNotesCopyLink(url)));
+            }
+            Event::Keyboard(KeyEvent { code: Key::Up, .. }) => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::Down, ..
+            }) => self.perform(Cmd::Move(Direction::Down)),
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.down.get() => {
+                self.perform(Cmd::Move(Direction::Down))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.up.get() => {
+                self.perform(Cmd::Move(Direction::Up))
+            }
+            Event::Keyboard(KeyEvent {
+                code: Key::PageDown,
+                ..
+            }) => self.perform(Cmd::Scroll(Direction::Down)),
+            Event::Keyboard(KeyEvent {
+                code: Key::PageUp, ..
+            }) => self.perform(Cmd::Scroll(Direction::Up)),
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.goto_top.get() => {
+                self.perform(Cmd::GoTo(Position::Begin))
+            }
+            Event::Keyboard(keyevent) if keyevent == keys.navigation_keys.goto_bottom.get() => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            _ => CmdResult::None,
+        };
+        Some(Msg::None)
+    }
+}
+
+impl Model {
+    /// Show the notes of the episode at `index` of the selected podcast
+    pub fn mount_show_notes(&mut self, index: usize) -> Result<()> {
+        let podcast = self
+            .podcast
+            .podcasts
+            .get(self.podcast.podcasts_index)
+            .ok_or_else(|| anyhow!("get podcast selected failed."))?;
+        let episode = podcast
+            .episodes
+            .get(index)
+            .ok_or_else(|| anyhow!("get episode selected failed."))?;
+
+        // the popup is 80% of the terminal wide, minus the borders and the highlight symbol
+        let (term_width, _) = viuer::terminal_size();
+        let symbol_width = self
+            .config_tui
+            .read()
+            .settings
+            .theme
+            .style
+            .library
+            .highlight_symbol
+            .width();
+        let width = (usize::from(term_width) * 80 / 100).saturating_sub(2 + symbol_width);
+        let notes = ShowNotes::from_html(&episode.description, width.max(20));
+
+        let popup = ShowNotesPopup::new(
+            self.config_tui.clone(),
+            &episode.title,
+            notes,
+            episode.url.clone(),
+        );
+        self.app
+            .remount(Id::ShowNotesPopup, Box::new(popup), vec![])
+            .context("mount show notes")?;
+        self.app
+            .active(&Id::ShowNotesPopup)
+            .context("activate show notes")?;
+
+        Ok(())
+    }
+
+    pub fn umount_show_notes(&mut self) {
+        if self.app.mounted(&Id::ShowNotesPopup) {
+            assert!(self.app.umount(&Id::ShowNotesPopup).is_ok());
+        }
+    }
+
+    /// Seek to a timestamp from the notes of the episode with `url`, which has to be the playing one
+    pub fn show_notes_seek(&mut self, url: &str, position: std::time::Duration) {
+        let is_current = self
+            .playlist
+            .current_track()
+            .is_some_and(|v| v.file() == Some(url));
+        if !is_current {
+            self.update_show_message_timeout(
+                "Show notes",
+                "Play the episode to seek to its timestamps",
+                None,
+            );
+            return;
+        }
+        self.command(&PlayerCmd::SeekTo(position));
+    }
+
+    /// Open a link with the default application of the system
+    pub fn show_notes_open_link(url: &str) -> Result<()> {
+        #[cfg(target_os = "macos")]
+        let mut command = {
+            let mut command = Command::new("open");
+            command.arg(url);
+            command
+        };
+        #[cfg(target_os = "windows")]
+        let mut command = {
+            let mut command = Command::new("cmd");
+            command.args(["/C", "start", "", url]);
+            command
+        };
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let mut command = {
+            let mut command = Command::new("xdg-open");
+            command.arg(url);
+            command
+        };
+
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("open {url}"))?;
+        // reap the process once the opener exits, which may take as long as the application runs
+        std::thread::spawn(move || child.wait());
+
+        Ok(())
+    }
+
+    /// Copy a link to the clipboard with the OSC 52 escape sequence, which most terminals support
+    pub fn show_notes_copy_link(&mut self, url: &str) -> Result<()> {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(url);
+        let mut stdout = std::io::stdout();
+        write!(stdout, "\x1b]52;c;{encoded}\x07")?;
+        stdout.flush()?;
+        self.update_show_message_timeout("Show notes", "Link copied", None);
+
+        Ok(())
+    }
+}
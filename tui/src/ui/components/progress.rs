@@ -3,6 +3,8 @@ use std::time::Duration;
 use termusiclib::config::TuiOverlay;
 use termusiclib::track::{MediaType, Track};
 use termusiclib::types::{Id, Msg};
+use termusicplayback::player::SleepTimerState;
+use termusicplayback::SleepTimerMode;
 use tui_realm_stdlib::ProgressBar;
 use tuirealm::event::NoUserEvent;
 use tuirealm::props::{Alignment, BorderType, Borders, PropPayload, PropValue};
@@ -42,6 +44,32 @@ impl Component<Msg, NoUserEvent> for Progress {
 }
 
 impl Model {
+    /// Get the text to display for the sleep timer, [`None`] if no timer is set
+    fn sleep_timer_title(&self) -> Option<String> {
+        let mode = SleepTimerMode::from_u32(self.sleep_timer.mode, Duration::ZERO)?;
+        let remaining = self.sleep_timer.remaining.map(Duration::from);
+
+        let text = match (mode, remaining) {
+            (SleepTimerMode::Duration(_), Some(remaining)) => {
+                Track::duration_formatted_short(&remaining)
+            }
+            (SleepTimerMode::Duration(_), None) => String::from("??:??"),
+            (SleepTimerMode::EndOfTrack, _) => String::from("end of track"),
+            (SleepTimerMode::EndOfAlbum, _) => String::from("end of album"),
+        };
+
+        Some(text)
+    }
+
+    /// Update the stored sleep timer state, only redrawing the title if the displayed value changed
+    pub fn sleep_timer_update(&mut self, state: SleepTimerState) {
+        let old_title = self.sleep_timer_title();
+        self.sleep_timer = state;
+        if old_title != self.sleep_timer_title() {
+            self.progress_update_title();
+        }
+    }
+
     pub fn progress_reload(&mut self) {
         assert!(self
             .app
@@ -88,6 +116,13 @@ impl Model {
         }
 
         drop(config_server);
+        if let Some(sleep_timer) = self.sleep_timer_title() {
+            progress_title = if progress_title.is_empty() {
+                format!(" Sleep: {sleep_timer} ")
+            } else {
+                format!("{} | Sleep: {sleep_timer} ", progress_title.trim_end())
+            };
+        }
         self.app
             .attr(
                 &Id::Progress,
@@ -0,0 +1,150 @@
+use crate::ui::Model;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use termusiclib::config::SharedTuiSettings;
+use termusiclib::track::Track;
+use termusiclib::types::{Id, Msg};
+use termusiclib::utils::{is_playlist, playlist_get_vec};
+use termusicplayback::QueuePosition;
+use tui_realm_stdlib::Table;
+use tuirealm::event::NoUserEvent;
+use tuirealm::props::{Alignment, BorderType, Borders, TableBuilder, TextSpan};
+use tuirealm::{Component, Event, MockComponent};
+
+/// Maximal amount of queued tracks to show before the rest is cut off
+pub const QUEUE_MAX_VISIBLE: usize = 5;
+
+/// Display of the up-next queue, only shown while the queue is not empty
+#[derive(MockComponent)]
+pub struct Queue {
+    component: Table,
+}
+
+impl Queue {
+    pub fn new(config: &SharedTuiSettings) -> Self {
+        let config = config.read();
+        let component = Table::default()
+            .borders(
+                Borders::default()
+                    .modifiers(BorderType::Rounded)
+                    .color(config.settings.theme.playlist_border()),
+            )
+            .background(config.settings.theme.playlist_background())
+            .foreground(config.settings.theme.playlist_foreground())
+            .title(" Up Next ", Alignment::Left)
+            .row_height(1)
+            .column_spacing(2)
+            .widths(&[12, 20, 25, 43])
+            .table(TableBuilder::default().build());
+
+        Self { component }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for Queue {
+    fn on(&mut self, _ev: Event<NoUserEvent>) -> Option<Msg> {
+        Some(Msg::None)
+    }
+}
+
+impl Model {
+    /// Add a directory / file / playlist file to the up-next queue
+    pub fn queue_add(&mut self, current_node: &str, position: QueuePosition) -> Result<()> {
+        let p: &Path = Path::new(&current_node);
+        if !p.exists() {
+            return Ok(());
+        }
+        let items = if p.is_dir() {
+            Self::library_dir_children(p)
+        } else if is_playlist(current_node) {
+            playlist_get_vec(current_node)?
+        } else {
+            vec![current_node.to_string()]
+        };
+
+        self.playlist.add_queue(&items, position)?;
+        self.player_sync_playlist()?;
+        self.queue_sync();
+        Ok(())
+    }
+
+    pub fn queue_add_episode(
+        &mut self,
+        episode_index: usize,
+        position: QueuePosition,
+    ) -> Result<()> {
+        if self.podcast.podcasts.is_empty() {
+            return Ok(());
+        }
+        let podcast_selected = self
+            .podcast
+            .podcasts
+            .get(self.podcast.podcasts_index)
+            .ok_or_else(|| anyhow!("get podcast selected failed."))?;
+        let episode_selected = podcast_selected
+            .episodes
+            .get(episode_index)
+            .ok_or_else(|| anyhow!("get episode selected failed."))?;
+        self.playlist.add_queue_episode(episode_selected, position);
+        self.player_sync_playlist()?;
+        self.queue_sync();
+        Ok(())
+    }
+
+    pub fn queue_clear(&mut self) {
+        self.playlist.clear_queue();
+        if let Err(e) = self.player_sync_playlist() {
+            self.mount_error_popup(e.context("player sync playlist"));
+        }
+        self.queue_sync();
+    }
+
+    /// Update the queue widget from the current queue
+    pub fn queue_sync(&mut self) {
+        let queue = self.playlist.queue();
+        let mut table: TableBuilder = TableBuilder::default();
+
+        for (idx, record) in queue.iter().take(QUEUE_MAX_VISIBLE).enumerate() {
+            if idx > 0 {
+                table.add_row();
+            }
+
+            let duration = record.duration_formatted();
+            let duration_string = format!("[{duration:^7.7}]");
+
+            let name = record.name().unwrap_or("No Name");
+            let artist = record.artist().unwrap_or(name);
+            let title = record.title().unwrap_or("Unknown Title");
+            let album = record.album().unwrap_or("Unknown Album");
+
+            table
+                .add_col(TextSpan::new(duration_string.as_str()))
+                .add_col(TextSpan::new(artist).fg(tuirealm::tui::style::Color::LightYellow))
+                .add_col(TextSpan::new(title).bold())
+                .add_col(TextSpan::new(album));
+        }
+
+        let duration: std::time::Duration = queue.iter().map(Track::duration).sum();
+        let title = format!(
+            "\u{2500} Up Next \u{2500}\u{2500}\u{2524} {} tracks | {} \u{251c}\u{2500}",
+            queue.len(),
+            Track::duration_formatted_short(&duration),
+        );
+
+        let table = table.build();
+        self.app
+            .attr(
+                &Id::Queue,
+                tuirealm::Attribute::Content,
+                tuirealm::AttrValue::Table(table),
+            )
+            .ok();
+        self.app
+            .attr(
+                &Id::Queue,
+                tuirealm::Attribute::Title,
+                tuirealm::AttrValue::Title((title, Alignment::Left)),
+            )
+            .ok();
+    }
+}
@@ -289,6 +289,20 @@ impl Model {
             {
                 song.set_genre(&genre);
             }
+            if let Ok(State::One(StateValue::String(album_artist))) = self
+                .app
+                .state(&Id::TagEditor(IdTagEditor::InputAlbumArtist))
+            {
+                song.set_album_artist(album_artist.trim());
+            }
+            if let Ok(State::One(StateValue::String(composer))) =
+                self.app.state(&Id::TagEditor(IdTagEditor::InputComposer))
+            {
+                song.set_composer(composer.trim());
+            }
+            song.set_year(self.te_number_input(IdTagEditor::InputYear, "year")?);
+            song.set_track_number(self.te_number_input(IdTagEditor::InputTrackNumber, "track")?);
+            song.set_disc_number(self.te_number_input(IdTagEditor::InputDiscNumber, "disc")?);
             song.save_tag()?;
             self.init_by_song(&song);
             self.playlist_update_library_delete();
@@ -296,6 +310,22 @@ impl Model {
         Ok(())
     }
 
+    /// Get the value of a number input of the tag editor, `None` if it is empty
+    fn te_number_input(&self, id: IdTagEditor, name: &str) -> Result<Option<u32>> {
+        let Ok(State::One(StateValue::String(value))) = self.app.state(&Id::TagEditor(id)) else {
+            return Ok(None);
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        value
+            .parse()
+            .map(Some)
+            .with_context(|| format!("invalid {name} \"{value}\""))
+    }
+
     pub fn te_load_lyric_and_photo(&mut self, index: usize) -> Result<()> {
         if self.songtag_options.is_empty() {
             return Ok(());
@@ -79,17 +79,40 @@ impl Model {
                     .active(&Id::TagEditor(IdTagEditor::InputTitle))
                     .ok();
             }
-            TFMsg::InputTitleBlurDown | TFMsg::InputGenreBlurUp => {
+            TFMsg::InputTitleBlurDown | TFMsg::InputAlbumArtistBlurUp => {
                 self.app
                     .active(&Id::TagEditor(IdTagEditor::InputAlbum))
                     .ok();
             }
-            TFMsg::InputAlbumBlurDown | TFMsg::TableLyricOptionsBlurUp => {
+            TFMsg::InputAlbumBlurDown | TFMsg::InputComposerBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputAlbumArtist))
+                    .ok();
+            }
+            TFMsg::InputAlbumArtistBlurDown | TFMsg::InputGenreBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputComposer))
+                    .ok();
+            }
+            TFMsg::InputComposerBlurDown | TFMsg::InputYearBlurUp => {
                 self.app
                     .active(&Id::TagEditor(IdTagEditor::InputGenre))
                     .ok();
             }
-            TFMsg::InputGenreBlurDown | TFMsg::SelectLyricBlurUp => {
+            TFMsg::InputGenreBlurDown | TFMsg::InputTrackNumberBlurUp => {
+                self.app.active(&Id::TagEditor(IdTagEditor::InputYear)).ok();
+            }
+            TFMsg::InputYearBlurDown | TFMsg::InputDiscNumberBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputTrackNumber))
+                    .ok();
+            }
+            TFMsg::InputTrackNumberBlurDown | TFMsg::TableLyricOptionsBlurUp => {
+                self.app
+                    .active(&Id::TagEditor(IdTagEditor::InputDiscNumber))
+                    .ok();
+            }
+            TFMsg::InputDiscNumberBlurDown | TFMsg::SelectLyricBlurUp => {
                 self.app
                     .active(&Id::TagEditor(IdTagEditor::TableLyricOptions))
                     .ok();
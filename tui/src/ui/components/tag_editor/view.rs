@@ -23,8 +23,9 @@ use crate::ui::components::tag_editor::te_footer::TEFooter;
  * SOFTWARE.
  */
 use crate::ui::components::{
-    LabelGeneric, TECounterDelete, TEInputAlbum, TEInputArtist, TEInputGenre, TEInputTitle,
-    TESelectLyric, TETableLyricOptions, TETextareaLyric,
+    LabelGeneric, TECounterDelete, TEInputAlbum, TEInputAlbumArtist, TEInputArtist,
+    TEInputComposer, TEInputDiscNumber, TEInputGenre, TEInputTitle, TEInputTrackNumber,
+    TEInputYear, TESelectLyric, TETableLyricOptions, TETextareaLyric,
 };
 use crate::ui::model::Model;
 use crate::ui::utils::{draw_area_in_absolute, draw_area_top_right_absolute};
@@ -58,7 +59,7 @@ impl Model {
                                 Constraint::Length(1),
                                 Constraint::Length(3),
                                 Constraint::Length(3),
-                                // Constraint::Length(3),
+                                Constraint::Length(3),
                                 Constraint::Min(2),
                                 Constraint::Length(1),
                             ]
@@ -84,11 +85,23 @@ impl Model {
                             .as_ref(),
                         )
                         .split(chunks_main[2]);
+                    let chunks_row3 = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .margin(0)
+                        .constraints(
+                            [
+                                Constraint::Ratio(1, 3),
+                                Constraint::Ratio(1, 3),
+                                Constraint::Ratio(1, 3),
+                            ]
+                            .as_ref(),
+                        )
+                        .split(chunks_main[3]);
                     let chunks_row4 = Layout::default()
                         .direction(Direction::Horizontal)
                         .margin(0)
                         .constraints([Constraint::Ratio(3, 5), Constraint::Ratio(2, 5)].as_ref())
-                        .split(chunks_main[3]);
+                        .split(chunks_main[4]);
 
                     let chunks_row4_right = Layout::default()
                         .direction(Direction::Vertical)
@@ -117,12 +130,12 @@ impl Model {
                                 ]
                                 .as_ref(),
                             )
-                            .split(chunks_main[4]);
+                            .split(chunks_main[5]);
 
                         self.app.view(&Id::DownloadSpinner, f, chunks_footer[1]);
                         self.app.view(&Id::Label, f, chunks_footer[2]);
                     } else {
-                        self.app.view(&Id::Label, f, chunks_main[4]);
+                        self.app.view(&Id::Label, f, chunks_main[5]);
                     }
 
                     self.app
@@ -134,8 +147,30 @@ impl Model {
                         .view(&Id::TagEditor(IdTagEditor::InputTitle), f, chunks_row1[1]);
                     self.app
                         .view(&Id::TagEditor(IdTagEditor::InputAlbum), f, chunks_row2[0]);
+                    self.app.view(
+                        &Id::TagEditor(IdTagEditor::InputAlbumArtist),
+                        f,
+                        chunks_row2[1],
+                    );
+                    self.app.view(
+                        &Id::TagEditor(IdTagEditor::InputComposer),
+                        f,
+                        chunks_row2[2],
+                    );
                     self.app
-                        .view(&Id::TagEditor(IdTagEditor::InputGenre), f, chunks_row2[1]);
+                        .view(&Id::TagEditor(IdTagEditor::InputGenre), f, chunks_row2[3]);
+                    self.app
+                        .view(&Id::TagEditor(IdTagEditor::InputYear), f, chunks_row3[0]);
+                    self.app.view(
+                        &Id::TagEditor(IdTagEditor::InputTrackNumber),
+                        f,
+                        chunks_row3[1],
+                    );
+                    self.app.view(
+                        &Id::TagEditor(IdTagEditor::InputDiscNumber),
+                        f,
+                        chunks_row3[2],
+                    );
                     self.app.view(
                         &Id::TagEditor(IdTagEditor::TableLyricOptions),
                         f,
@@ -239,6 +274,46 @@ impl Model {
                 Vec::new()
             )
             .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputAlbumArtist),
+                Box::new(TEInputAlbumArtist::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputComposer),
+                Box::new(TEInputComposer::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputYear),
+                Box::new(TEInputYear::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputTrackNumber),
+                Box::new(TEInputTrackNumber::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
+        assert!(self
+            .app
+            .remount(
+                Id::TagEditor(IdTagEditor::InputDiscNumber),
+                Box::new(TEInputDiscNumber::new(self.config_tui.clone())),
+                Vec::new()
+            )
+            .is_ok());
         assert!(self
             .app
             .remount(
@@ -297,6 +372,19 @@ impl Model {
         self.app
             .umount(&Id::TagEditor(IdTagEditor::InputGenre))
             .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputAlbumArtist))
+            .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputComposer))
+            .ok();
+        self.app.umount(&Id::TagEditor(IdTagEditor::InputYear)).ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputTrackNumber))
+            .ok();
+        self.app
+            .umount(&Id::TagEditor(IdTagEditor::InputDiscNumber))
+            .ok();
         // self.app.umount(&Id::TagEditor(IdTagEditor::RadioTag)).ok();
         self.app
             .umount(&Id::TagEditor(IdTagEditor::TableLyricOptions))
@@ -363,6 +451,32 @@ impl Model {
                 .is_ok());
         }
 
+        for (id, value) in [
+            (
+                IdTagEditor::InputAlbumArtist,
+                s.album_artist().map(str::to_string),
+            ),
+            (IdTagEditor::InputComposer, s.composer().map(str::to_string)),
+            (IdTagEditor::InputYear, s.year().map(|v| v.to_string())),
+            (
+                IdTagEditor::InputTrackNumber,
+                s.track_number().map(|v| v.to_string()),
+            ),
+            (
+                IdTagEditor::InputDiscNumber,
+                s.disc_number().map(|v| v.to_string()),
+            ),
+        ] {
+            assert!(self
+                .app
+                .attr(
+                    &Id::TagEditor(id),
+                    Attribute::Value,
+                    AttrValue::String(value.unwrap_or_default()),
+                )
+                .is_ok());
+        }
+
         if s.lyric_frames_is_empty() {
             self.init_by_song_no_lyric();
             return;
@@ -99,7 +99,9 @@ impl UI {
             self.model.te_update_lyric_options();
             // self.model.update_player_msg();
             self.model.update_outside_msg();
-            if self.model.layout != TermusicLayout::Podcast {
+            if self.model.layout == TermusicLayout::Podcast {
+                self.model.lyric_update_transcript();
+            } else {
                 self.model.lyric_update();
             }
             if progress_interval == 0 {
@@ -169,15 +171,23 @@ impl UI {
         Ok(())
     }
 
-    fn handle_current_track_index(&mut self, current_track_index: usize) {
+    fn handle_current_track_index(&mut self, current_track_index: usize, from_queue: bool) {
         info!(
-            "index from player is:{current_track_index:?}, index in tui is:{:?}",
+            "index from player is:{current_track_index:?}, index in tui is:{:?}, from queue: {from_queue}",
             self.model.playlist.get_current_track_index()
         );
         self.model.playlist.clear_current_track();
         self.model
             .playlist
             .set_current_track_index(current_track_index);
+        if from_queue {
+            self.model.playlist.pop_queue_as_current();
+            // the server saved the queue after consuming it, so it is the source of truth
+            if let Err(e) = self.model.playlist.reload_queue() {
+                self.model.mount_error_popup(e.context("reload queue"));
+            }
+            self.model.queue_sync();
+        }
         self.model.playlist_locate(current_track_index);
         self.model.current_song = self.model.playlist.current_track().cloned();
         self.model.update_layout_for_current_track();
@@ -223,6 +233,7 @@ impl UI {
         }
     }
 
+    #[allow(clippy::too_many_lines)]
     async fn run_playback(&mut self) -> Result<()> {
         if let Ok(cmd) = self.cmd_rx.try_recv() {
             match cmd {
@@ -243,15 +254,41 @@ impl UI {
                         pprogress.total_duration.unwrap_or_default(),
                     );
                     if response.current_track_updated {
-                        self.handle_current_track_index(response.current_track_index as usize);
+                        self.handle_current_track_index(
+                            response.current_track_index as usize,
+                            response.current_track_from_queue,
+                        );
                     }
 
                     self.model.lyric_update_for_radio(response.radio_title);
+                    self.model
+                        .sleep_timer_update(response.sleep_timer.unwrap_or_default());
+                    if response.active_playlist != self.model.active_playlist {
+                        self.model
+                            .playlist_tabs_active_changed(response.active_playlist);
+                    }
+                    if response.library_version != self.model.library_version {
+                        self.model.library_version = response.library_version;
+                        self.model.library_reload_tree();
+                        self.model.database_refresh();
+                    }
+                    if response.podcast_version != self.model.podcast_version {
+                        self.model.podcast_version = response.podcast_version;
+                        self.model.podcast_reload();
+                    }
+                    if response.podcast_status != self.model.podcast_status {
+                        self.model.podcast_status_update(response.podcast_status);
+                    }
+                    self.model.download_progress_update(&response.downloads);
 
                     self.handle_status(Status::from_u32(response.status));
                 }
 
-                PlayerCmd::CycleLoop => self.playback.cycle_loop().await?,
+                PlayerCmd::CycleLoop => {
+                    let loop_mode = self.playback.cycle_loop().await?;
+                    self.model.playlist.set_loop_mode(loop_mode);
+                    self.model.playlist_update_title();
+                }
                 PlayerCmd::PlaySelected => {
                     self.playback.play_selected().await?;
                     // self.model.playlist.clear_current_track();
@@ -277,6 +314,14 @@ impl UI {
                     );
                     self.model.force_redraw();
                 }
+                PlayerCmd::SeekTo(position) => {
+                    let pprogress = self.playback.seek_to(position).await?;
+                    self.model.progress_update(
+                        pprogress.position,
+                        pprogress.total_duration.unwrap_or_default(),
+                    );
+                    self.model.force_redraw();
+                }
                 PlayerCmd::SpeedDown => {
                     self.model.config_server.write().settings.player.speed =
                         self.playback.speed_down().await?;
@@ -297,14 +342,104 @@ impl UI {
                     self.model.config_server.write().settings.player.volume = volume;
                     self.model.progress_update_title();
                 }
+                PlayerCmd::SleepTimerSet(mode) => {
+                    let state = self.playback.set_sleep_timer(mode).await?;
+                    self.model.sleep_timer_update(state);
+                }
+                PlayerCmd::SleepTimerExtend(amount) => {
+                    let state = self.playback.extend_sleep_timer(amount).await?;
+                    self.model.sleep_timer_update(state);
+                }
+                PlayerCmd::SleepTimerCancel => {
+                    let state = self.playback.cancel_sleep_timer().await?;
+                    self.model.sleep_timer_update(state);
+                }
                 PlayerCmd::VolumeUp => {
                     let volume = self.playback.volume_up().await?;
                     self.model.config_server.write().settings.player.volume = volume;
                     self.model.progress_update_title();
                 }
+                PlayerCmd::PlaylistUndo => {
+                    let reply = self.playback.undo_playlist().await?;
+                    self.model.playlist_history_applied(reply.changed, "undo");
+                }
+                PlayerCmd::PlaylistRedo => {
+                    let reply = self.playback.redo_playlist().await?;
+                    self.model.playlist_history_applied(reply.changed, "redo");
+                }
+                PlayerCmd::PlaylistSort(key, descending) => {
+                    let reply = self.playback.sort_playlist(key, descending).await?;
+                    let message = if reply.changed {
+                        format!("Sorted by {key}")
+                    } else {
+                        "Already sorted".to_string()
+                    };
+                    self.model.playlist_edit_applied(reply.changed, &message);
+                }
+                PlayerCmd::PlaylistDedup(key) => {
+                    let reply = self.playback.dedup_playlist(key).await?;
+                    let message = if reply.changed {
+                        format!("Removed {} duplicates", reply.removed)
+                    } else {
+                        "No duplicates found".to_string()
+                    };
+                    self.model.playlist_edit_applied(reply.changed, &message);
+                }
+                PlayerCmd::PodcastRefresh(id) => {
+                    if let Err(e) = self.playback.refresh_podcasts(id).await {
+                        self.model.mount_error_popup(e.context("refresh podcasts"));
+                    }
+                }
+                PlayerCmd::PodcastDownloads(action, episode_ids) => {
+                    if let Err(e) = self.playback.control_downloads(action, episode_ids).await {
+                        self.model
+                            .mount_error_popup(e.context("podcast download queue"));
+                    }
+                }
+                PlayerCmd::PodcastRefreshFolder(folder) => {
+                    if let Err(e) = self.playback.refresh_podcast_folder(folder).await {
+                        self.model
+                            .mount_error_popup(e.context("refresh podcast folder"));
+                    }
+                }
+                cmd @ (PlayerCmd::NamedPlaylistCreate(_)
+                | PlayerCmd::NamedPlaylistDelete(_)
+                | PlayerCmd::NamedPlaylistDuplicate(..)
+                | PlayerCmd::NamedPlaylistList
+                | PlayerCmd::NamedPlaylistRename(..)
+                | PlayerCmd::NamedPlaylistSwitch(_)) => {
+                    // errors like a already used name should not stop the tui
+                    if let Err(e) = self.run_named_playlist(cmd).await {
+                        self.model.mount_error_popup(e.context("named playlist"));
+                    }
+                }
                 _ => {}
             }
         }
         Ok(())
     }
+
+    /// Handle the named playlist commands and update the tabs afterwards
+    async fn run_named_playlist(&mut self, cmd: PlayerCmd) -> Result<()> {
+        match cmd {
+            PlayerCmd::NamedPlaylistCreate(name) => {
+                let info = self.playback.create_playlist(name).await?;
+                self.playback.switch_playlist(info.id).await?;
+            }
+            PlayerCmd::NamedPlaylistDelete(id) => self.playback.delete_playlist(id).await?,
+            PlayerCmd::NamedPlaylistDuplicate(id, name) => {
+                self.playback.duplicate_playlist(id, name).await?;
+            }
+            PlayerCmd::NamedPlaylistRename(id, name) => {
+                self.playback.rename_playlist(id, name).await?;
+            }
+            PlayerCmd::NamedPlaylistSwitch(id) => self.playback.switch_playlist(id).await?,
+            _ => {}
+        }
+
+        let playlists = self.playback.list_playlists().await?;
+        self.model.playlist_tabs_update(playlists);
+
+        Ok(())
+    }
 }
@@ -1,7 +1,9 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 pub struct DownloadTracker {
     items: HashSet<String>,
+    /// Downloaded and total bytes of the episode downloads running on the server, by url
+    progress: HashMap<String, (u64, Option<u64>)>,
     // pub time_stamp_for_cache: Instant,
 }
 
@@ -11,6 +13,7 @@ impl Default for DownloadTracker {
         // let time_stamp_for_cache = Instant::now();
         Self {
             items,
+            progress: HashMap::new(),
             // time_stamp_for_cache,
         }
     }
@@ -26,6 +29,26 @@ impl DownloadTracker {
         self.items.remove(url);
     }
 
+    /// Replace the progress of all running downloads, returns `false` if nothing changed
+    pub fn replace_progress(&mut self, progress: HashMap<String, (u64, Option<u64>)>) -> bool {
+        if progress == self.progress {
+            return false;
+        }
+        self.progress = progress;
+        true
+    }
+
+    /// Get the progress of the download of `url` for display, like `42%` or `12.3 MiB` if the size is unknown
+    pub fn progress(&self, url: &str) -> Option<String> {
+        let (done, total) = self.progress.get(url)?;
+        #[allow(clippy::cast_precision_loss)]
+        let text = match total {
+            Some(total) if *total > 0 => format!("{}%", done * 100 / total),
+            _ => format!("{:.1} MiB", *done as f64 / 1_048_576.0),
+        };
+        Some(text)
+    }
+
     pub fn contains(&self, url: &str) -> bool {
         self.items.contains(url)
     }
@@ -26,12 +26,16 @@ mod update;
 mod view;
 mod youtube_options;
 
+use crate::ui::components::podcasts_for_view;
 use crate::ui::Application;
 use crate::CombinedSettings;
 use download_tracker::DownloadTracker;
 use termusiclib::config::v2::tui::keys::Keys;
 use termusiclib::config::v2::tui::theme::ThemeWrap;
-use termusiclib::library_db::{DataBase, SearchCriteria};
+use termusiclib::library_db::{
+    DataBase, DuplicateReport, KeepPolicy, RelinkReport, SearchCriteria,
+};
+use termusiclib::songtag::lrc::Lyric;
 use termusiclib::types::{Id, Msg, SearchLyricState, YoutubeOptions};
 use termusiclib::xywh;
 
@@ -40,21 +44,37 @@ use termusiclib::track::{MediaType, Track};
 use termusiclib::ueberzug::UeInstance;
 
 use anyhow::{anyhow, Result};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::time::{Duration, Instant};
 use termusiclib::config::{ServerOverlay, SharedServerSettings, SharedTuiSettings};
 use termusiclib::library_db::TrackDB;
-use termusiclib::podcast::{db::Database as DBPod, Podcast, PodcastFeed};
+use termusiclib::podcast::db::{Database as DBPod, DownloadItem};
+use termusiclib::podcast::{Podcast, PodcastFeed};
 use termusiclib::songtag::SongTag;
 use termusiclib::taskpool::TaskPool;
 use termusiclib::utils::get_app_config_path;
-use termusicplayback::{PlayerCmd, Playlist};
+use termusicplayback::player::{PlaylistInfo, SleepTimerState};
+use termusicplayback::{PlayerCmd, Playlist, SleepTimerMode};
 use tokio::sync::mpsc::UnboundedSender;
 use tui_realm_treeview::Tree;
 use tuirealm::event::NoUserEvent;
 use tuirealm::terminal::TerminalBridge;
 
+/// Presets to cycle through with the sleep timer key, cycling past the last one cancels the timer
+const SLEEP_TIMER_PRESETS: [SleepTimerMode; 6] = [
+    SleepTimerMode::Duration(Duration::from_secs(15 * 60)),
+    SleepTimerMode::Duration(Duration::from_secs(30 * 60)),
+    SleepTimerMode::Duration(Duration::from_secs(45 * 60)),
+    SleepTimerMode::Duration(Duration::from_secs(60 * 60)),
+    SleepTimerMode::EndOfTrack,
+    SleepTimerMode::EndOfAlbum,
+];
+
+/// Amount to extend the sleep timer by
+const SLEEP_TIMER_EXTEND_STEP: Duration = Duration::from_secs(5 * 60);
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum TermusicLayout {
     TreeView,
@@ -114,6 +134,39 @@ pub struct PodcastWidgetData {
     pub db_podcast: DBPod,
     /// Podcast search results
     pub search_results: Option<Vec<PodcastFeed>>,
+    /// Id of the podcast whose policy is being edited
+    pub policy_podcast_id: Option<i64>,
+    /// Id of the podcast whose folder is being edited
+    pub folder_podcast_id: Option<i64>,
+    /// Rows of the displayed podcast list, folders and the podcasts in them
+    pub rows: Vec<FeedRow>,
+    /// Folders whose podcasts are hidden in the podcast list
+    pub collapsed_folders: HashSet<String>,
+    /// Last loaded download queue
+    pub download_queue: Vec<DownloadItem>,
+    /// Time-synced transcript of the playing episode, if it has one
+    pub transcript: Option<EpisodeTranscript>,
+    /// Url of the episode shown in the details pane
+    pub details_url: Option<String>,
+}
+
+/// A loaded transcript, shown in the details of its episode in sync with the playback
+#[derive(Debug)]
+pub struct EpisodeTranscript {
+    /// Url of the episode
+    pub url: String,
+    pub lyric: Lyric,
+    /// The caption currently shown
+    pub line: String,
+}
+
+/// A row of the podcast list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedRow {
+    /// Header of a folder, followed by its podcasts unless it is collapsed
+    Folder(String),
+    /// Index into [`PodcastWidgetData::podcasts`]
+    Podcast(usize),
 }
 
 /// All data specific to the Config Editor Widget / View
@@ -161,6 +214,32 @@ pub struct Model {
     pub time_pos: Duration,
     pub lyric_line: String,
     pub playlist: Playlist,
+    /// Last known sleep timer state of the server
+    pub sleep_timer: SleepTimerState,
+    /// Index into [`SLEEP_TIMER_PRESETS`] of the last preset set via cycling
+    sleep_timer_preset: Option<usize>,
+    /// Last known named playlists of the server
+    pub playlists: Vec<PlaylistInfo>,
+    /// Id of the active named playlist as last reported by the server, `0` if not known yet
+    pub active_playlist: u64,
+    /// Version of the library database as last reported by the server, changes when the music directories changed
+    pub library_version: u64,
+    /// Version of the podcast database as last reported by the server, changes when the server refreshed feeds
+    pub podcast_version: u64,
+    /// Last podcast refresh status reported by the server
+    pub podcast_status: String,
+    /// Whether playlists are saved with paths relative to the playlist file, toggled in the save playlist popup
+    pub save_playlist_relative: bool,
+    /// Relink report of a playlist import waiting to be confirmed
+    pub playlist_import: Option<RelinkReport>,
+    /// Duplicate report currently shown
+    pub duplicates: Option<DuplicateReport>,
+    /// Which file of each duplicate group is kept
+    pub duplicates_policy: KeepPolicy,
+    /// Index of the duplicate group waiting for the delete confirmation
+    pub duplicates_confirm: Option<usize>,
+    /// Amount of deleted and not deletable files since the duplicate groups were confirmed
+    pub duplicates_deleted: (usize, usize),
 
     #[cfg(all(feature = "cover-ueberzug", not(target_os = "windows")))]
     pub ueberzug_instance: UeInstance,
@@ -207,6 +286,7 @@ fn get_viuer_support() -> ViuerSupported {
 }
 
 impl Model {
+    #[allow(clippy::too_many_lines)]
     pub async fn new(config: CombinedSettings, cmd_tx: UnboundedSender<PlayerCmd>) -> Self {
         let CombinedSettings {
             server: config_server,
@@ -231,9 +311,7 @@ impl Model {
 
         let db_podcast = DBPod::new(&db_path).expect("error connecting to podcast db.");
 
-        let podcasts = db_podcast
-            .get_podcasts()
-            .expect("failed to get podcasts from db.");
+        let podcasts = podcasts_for_view(&db_podcast).expect("failed to get podcasts from db.");
         let taskpool = TaskPool::new(usize::from(
             config_server
                 .read()
@@ -294,6 +372,13 @@ impl Model {
                 podcasts_index: 0,
                 db_podcast,
                 search_results: None,
+                policy_podcast_id: None,
+                folder_podcast_id: None,
+                rows: Vec::new(),
+                collapsed_folders: HashSet::new(),
+                download_queue: Vec::new(),
+                transcript: None,
+                details_url: None,
             },
             config_editor: ConfigEditorData {
                 themes: Vec::new(),
@@ -307,6 +392,19 @@ impl Model {
             rx_to_main,
             download_tracker: DownloadTracker::default(),
             playlist,
+            sleep_timer: SleepTimerState::default(),
+            sleep_timer_preset: None,
+            playlists: Vec::new(),
+            active_playlist: 0,
+            library_version: 0,
+            podcast_version: 0,
+            podcast_status: String::new(),
+            save_playlist_relative: true,
+            playlist_import: None,
+            duplicates: None,
+            duplicates_policy: KeepPolicy::default(),
+            duplicates_confirm: None,
+            duplicates_deleted: (0, 0),
             cmd_tx,
             current_song: None,
             xywh,
@@ -341,6 +439,10 @@ impl Model {
         self.mount_label_help();
         self.db.sync_database(&self.library.tree_path);
         self.playlist_sync();
+        self.queue_sync();
+        if let Err(e) = self.download_queue_reload() {
+            self.mount_error_popup(e.context("podcast download queue"));
+        }
     }
 
     /// Initialize terminal
@@ -405,6 +507,31 @@ impl Model {
         self.command(&PlayerCmd::SkipPrevious);
     }
 
+    /// Set the sleep timer to the next preset, or cancel it after the last preset
+    pub fn player_sleep_timer_cycle(&mut self) {
+        // start from the beginning if the timer expired or was canceled elsewhere
+        if self.sleep_timer.mode == SleepTimerMode::OFF {
+            self.sleep_timer_preset = None;
+        }
+        let next = self.sleep_timer_preset.map_or(0, |v| v + 1);
+
+        if let Some(mode) = SLEEP_TIMER_PRESETS.get(next) {
+            self.sleep_timer_preset = Some(next);
+            self.command(&PlayerCmd::SleepTimerSet(*mode));
+        } else {
+            self.player_sleep_timer_cancel();
+        }
+    }
+
+    pub fn player_sleep_timer_extend(&mut self) {
+        self.command(&PlayerCmd::SleepTimerExtend(SLEEP_TIMER_EXTEND_STEP));
+    }
+
+    pub fn player_sleep_timer_cancel(&mut self) {
+        self.sleep_timer_preset = None;
+        self.command(&PlayerCmd::SleepTimerCancel);
+    }
+
     pub fn command(&mut self, cmd: &PlayerCmd) {
         if let Err(e) = self.cmd_tx.send(cmd.clone()) {
             self.mount_error_popup((anyhow!(e)).context(format!("{cmd:?}")));
@@ -1,13 +1,16 @@
+use crate::ui::components::PlaylistEditMode;
+use crate::ui::model::EpisodeTranscript;
 use crate::ui::{model::TermusicLayout, Model};
 use anyhow::anyhow;
 use std::thread::{self, sleep};
 use std::time::Duration;
 use termusiclib::library_db::SearchCriteria;
+use termusiclib::podcast::PodcastFeed;
 use termusiclib::track::MediaType;
 use termusiclib::types::{
     DBMsg, DLMsg, GSMsg, Id, IdTagEditor, LIMsg, LyricMsg, Msg, PCMsg, PLMsg, XYWHMsg, YSMsg,
 };
-use termusicplayback::PlayerCmd;
+use termusicplayback::{PlayerCmd, QueuePosition};
 /**
  * MIT License
  *
@@ -33,7 +36,7 @@ use termusicplayback::PlayerCmd;
  */
 // use termusicplayback::{PlayerMsg, PlayerTrait};
 use tuirealm::props::{AttrValue, Attribute};
-use tuirealm::Update;
+use tuirealm::{State, StateValue, Update};
 
 impl Update<Msg> for Model {
     #[allow(clippy::too_many_lines)]
@@ -93,7 +96,10 @@ impl Update<Msg> for Model {
                 | Msg::PlayerVolumeUp
                 | Msg::PlayerVolumeDown
                 | Msg::PlayerSeekForward
-                | Msg::PlayerSeekBackward => self.update_player(&msg),
+                | Msg::PlayerSeekBackward
+                | Msg::PlayerSleepTimerCycle
+                | Msg::PlayerSleepTimerExtend
+                | Msg::PlayerSleepTimerCancel => self.update_player(&msg),
 
                 Msg::HelpPopupShow => {
                     self.mount_help_popup();
@@ -145,8 +151,19 @@ impl Update<Msg> for Model {
                 }
                 Msg::SavePlaylistPopupCloseOk(filename) => {
                     self.umount_save_playlist();
-                    if let Err(e) = self.playlist_save_m3u_before(&filename) {
-                        self.mount_error_popup(e.context("save m3u playlist before"));
+                    if let Err(e) = self.playlist_save_file_before(&filename) {
+                        self.mount_error_popup(e.context("save playlist file before"));
+                    }
+                    None
+                }
+                Msg::SavePlaylistPopupTogglePaths => {
+                    self.save_playlist_relative = !self.save_playlist_relative;
+                    let filename = match self.app.state(&Id::SavePlaylistPopup) {
+                        Ok(State::One(StateValue::String(filename))) => filename,
+                        _ => String::new(),
+                    };
+                    if let Err(e) = self.remount_save_playlist_label(&filename) {
+                        self.mount_error_popup(e.context("remount save playlist label"));
                     }
                     None
                 }
@@ -161,8 +178,8 @@ impl Update<Msg> for Model {
                     None
                 }
                 Msg::SavePlaylistConfirmCloseOk(filename) => {
-                    if let Err(e) = self.playlist_save_m3u(&filename) {
-                        self.mount_error_popup(e.context("save m3u playlist"));
+                    if let Err(e) = self.playlist_save_file(&filename) {
+                        self.mount_error_popup(e.context("save playlist file"));
                     }
                     self.umount_save_playlist_confirm();
                     None
@@ -231,17 +248,36 @@ impl Model {
                 self.app.active(&Id::Podcast).ok();
             }
             PCMsg::PodcastAddPopupShow => self.mount_podcast_add_popup(),
-            PCMsg::PodcastAddPopupCloseOk(url) => {
-                self.umount_podcast_add_popup();
-
-                if url.starts_with("http") {
-                    self.podcast_add(url);
+            PCMsg::PodcastAddPopupCloseOk(input) => {
+                if input.url.starts_with("http") {
+                    if let Err(e) = self.podcast_add_from_input(input) {
+                        self.mount_error_popup(e.context("podcast add"));
+                    }
                 } else {
-                    self.podcast_search_itunes(url);
+                    self.umount_podcast_add_popup();
+                    self.podcast_search_itunes(&input.url);
                     self.mount_podcast_search_table();
                 }
             }
             PCMsg::PodcastAddPopupCloseCancel => self.umount_podcast_add_popup(),
+            PCMsg::PolicyPopupShow(index) => {
+                if let Some(index) = self.podcast_index_of_row(*index) {
+                    self.mount_podcast_policy_popup(index);
+                }
+            }
+            PCMsg::PolicyPopupCloseOk(input) => {
+                if let Err(e) = self.podcast_set_policy(input) {
+                    self.mount_error_popup(e.context("podcast set policy"));
+                }
+            }
+            PCMsg::PolicyPopupCloseCancel => self.umount_podcast_policy_popup(),
+            PCMsg::FolderPopupShow(index) => self.mount_podcast_folder_popup(*index),
+            PCMsg::FolderPopupCloseOk(input) => {
+                if let Err(e) = self.podcast_set_folder(input) {
+                    self.mount_error_popup(e.context("podcast set folder"));
+                }
+            }
+            PCMsg::FolderPopupCloseCancel => self.umount_podcast_folder_popup(),
             PCMsg::SyncData((id, pod)) => {
                 self.download_tracker.decrease_one(&pod.url);
                 self.show_message_timeout_label_help(
@@ -277,17 +313,43 @@ impl Model {
                 );
             }
             PCMsg::PodcastSelected(index) => {
-                self.podcast.podcasts_index = *index;
-                if let Err(e) = self.podcast_sync_episodes() {
+                if let Err(e) = self.podcast_select_row(*index) {
                     self.mount_error_popup(e.context("podcast sync episodes"));
                 }
             }
             PCMsg::DescriptionUpdate => self.lyric_update(),
+            PCMsg::TranscriptLoaded(url, lyric) => {
+                // the track may have changed while the transcript was fetched
+                let is_current = self
+                    .playlist
+                    .current_track()
+                    .is_some_and(|v| v.file() == Some(url.as_str()));
+                if is_current {
+                    self.podcast.transcript = Some(EpisodeTranscript {
+                        url: url.clone(),
+                        lyric: (**lyric).clone(),
+                        line: String::new(),
+                    });
+                    if self.podcast.details_url.as_ref() == Some(url) {
+                        self.lyric_update_for_podcast_by_current_track();
+                    }
+                }
+            }
             PCMsg::EpisodeAdd(index) => {
                 if let Err(e) = self.playlist_add_episode(*index) {
                     self.mount_error_popup(e.context("podcast playlist add episode"));
                 }
             }
+            PCMsg::EpisodeEnqueueNext(index) => {
+                if let Err(e) = self.queue_add_episode(*index, QueuePosition::Next) {
+                    self.mount_error_popup(e.context("podcast queue add episode"));
+                }
+            }
+            PCMsg::EpisodeEnqueueLast(index) => {
+                if let Err(e) = self.queue_add_episode(*index, QueuePosition::Last) {
+                    self.mount_error_popup(e.context("podcast queue add episode"));
+                }
+            }
             PCMsg::EpisodeMarkPlayed(index) => {
                 if let Err(e) = self.episode_mark_played(*index) {
                     self.mount_error_popup(e.context("podcast episode mark played"));
@@ -298,13 +360,18 @@ impl Model {
                     self.mount_error_popup(e.context("podcast episode mark all played"));
                 }
             }
+            PCMsg::FeedsMarkAllPlayed(index) => {
+                if let Err(e) = self.feeds_mark_all_played(*index) {
+                    self.mount_error_popup(e.context("podcast feeds mark all played"));
+                }
+            }
             PCMsg::PodcastRefreshOne(index) => {
-                if let Err(e) = self.podcast_refresh_feeds(Some(*index)) {
+                if let Err(e) = self.podcast_refresh_row(*index) {
                     self.mount_error_popup(e.context("podcast refresh feeds one"));
                 }
             }
             PCMsg::PodcastRefreshAll => {
-                if let Err(e) = self.podcast_refresh_feeds(None) {
+                if let Err(e) = self.podcast_refresh_all() {
                     self.mount_error_popup(e.context("podcast refresh feeds all"));
                 }
             }
@@ -322,60 +389,40 @@ impl Model {
                     self.mount_error_popup(e.context("podcast episode download"));
                 }
             }
-            PCMsg::DLStart(ep_data) => {
-                self.download_tracker.increase_one(&ep_data.url);
-                self.show_message_timeout_label_help(
-                    self.download_tracker.message_download_start(&ep_data.title),
-                    None,
-                    None,
-                    None,
-                );
+            PCMsg::EpisodeCancelDownload(index) => {
+                if let Err(e) = self.episode_cancel_download(*index) {
+                    self.mount_error_popup(e.context("podcast episode cancel download"));
+                }
             }
-            PCMsg::DLComplete(ep_data) => {
-                if let Err(e) = self.episode_download_complete(ep_data.clone()) {
-                    self.mount_error_popup(e.context("podcast episode download complete"));
+            PCMsg::DownloadQueueShow => {
+                if let Err(e) = self.download_queue_reload() {
+                    self.mount_error_popup(e.context("podcast download queue"));
                 }
-                self.download_tracker.decrease_one(&ep_data.url);
-                self.show_message_timeout_label_help(
-                    self.download_tracker.message_download_complete(),
-                    None,
-                    None,
-                    None,
-                );
+                self.mount_download_queue();
             }
-            PCMsg::DLResponseError(ep_data) => {
-                self.download_tracker.decrease_one(&ep_data.url);
-                self.mount_error_popup(anyhow!("download failed for episode: {}", ep_data.title));
-                self.show_message_timeout_label_help(
-                    self.download_tracker
-                        .message_download_error_response(&ep_data.title),
-                    None,
-                    None,
-                    None,
-                );
+            PCMsg::DownloadQueueClose => self.umount_download_queue(),
+            PCMsg::ShowNotesShow(index) => {
+                if let Err(e) = self.mount_show_notes(*index) {
+                    self.mount_error_popup(e.context("podcast show notes"));
+                }
             }
-            PCMsg::DLFileCreateError(ep_data) => {
-                self.download_tracker.decrease_one(&ep_data.url);
-                self.mount_error_popup(anyhow!("download failed for episode: {}", ep_data.title));
-                self.show_message_timeout_label_help(
-                    self.download_tracker
-                        .message_download_error_file_create(&ep_data.title),
-                    None,
-                    None,
-                    None,
-                );
+            PCMsg::ShowNotesClose => self.umount_show_notes(),
+            PCMsg::ShowNotesSeek(url, position) => self.show_notes_seek(url, *position),
+            PCMsg::ShowNotesOpenLink(url) => {
+                if let Err(e) = Self::show_notes_open_link(url) {
+                    self.mount_error_popup(e.context("show notes open link"));
+                }
             }
-            PCMsg::DLFileWriteError(ep_data) => {
-                self.download_tracker.decrease_one(&ep_data.url);
-                self.mount_error_popup(anyhow!("download failed for episode: {}", ep_data.title));
-                self.show_message_timeout_label_help(
-                    self.download_tracker
-                        .message_download_error_file_write(&ep_data.title),
-                    None,
-                    None,
-                    None,
-                );
+            PCMsg::ShowNotesCopyLink(url) => {
+                if let Err(e) = self.show_notes_copy_link(url) {
+                    self.mount_error_popup(e.context("show notes copy link"));
+                }
             }
+            PCMsg::DownloadQueueRetry => self.download_queue_retry(),
+            PCMsg::DownloadQueueMove(index, down) => self.download_queue_move(*index, *down),
+            PCMsg::DownloadQueueTogglePause(index) => self.download_queue_toggle_pause(*index),
+            PCMsg::DownloadQueueRemove(index) => self.download_queue_remove(*index),
+            PCMsg::DownloadQueueClear => self.download_queue_clear(),
             PCMsg::EpisodeDeleteFile(index) => {
                 if let Err(e) = self.episode_delete_file(*index) {
                     self.mount_error_popup(e.context("podcast episode delete"));
@@ -401,8 +448,8 @@ impl Model {
             PCMsg::SearchItunesCloseOk(index) => {
                 if let Some(vec) = &self.podcast.search_results {
                     if let Some(pod) = vec.get(*index) {
-                        let url = pod.url.clone();
-                        self.podcast_add(&url);
+                        let feed = PodcastFeed::new(None, &pod.url, None);
+                        self.podcast_add(feed);
                     }
                 }
             }
@@ -458,6 +505,15 @@ impl Model {
             Msg::PlayerToggleGapless => {
                 self.command(&PlayerCmd::ToggleGapless);
             }
+            Msg::PlayerSleepTimerCycle => {
+                self.player_sleep_timer_cycle();
+            }
+            Msg::PlayerSleepTimerExtend => {
+                self.player_sleep_timer_extend();
+            }
+            Msg::PlayerSleepTimerCancel => {
+                self.player_sleep_timer_cancel();
+            }
             _ => {}
         }
         None
@@ -587,6 +643,12 @@ impl Model {
                     }
                 }
             }
+            DBMsg::EnqueueNext(index) => {
+                self.database_enqueue(*index, QueuePosition::Next);
+            }
+            DBMsg::EnqueueLast(index) => {
+                self.database_enqueue(*index, QueuePosition::Last);
+            }
             DBMsg::AddAllToPlaylist => {
                 let db_search_tracks = self.dw.search_tracks.clone();
                 self.playlist_add_all_from_db(&db_search_tracks);
@@ -625,6 +687,23 @@ impl Model {
                     self.mount_error_popup(e.context("library remove root"));
                 }
             }
+            LIMsg::FindDuplicates => {
+                if let Err(e) = self.library_find_duplicates() {
+                    self.mount_error_popup(e.context("library find duplicates"));
+                }
+            }
+            LIMsg::DuplicatesFound(report) => self.library_duplicates_found(report.clone()),
+            LIMsg::DuplicatesKeep(policy) => {
+                self.duplicates_policy = *policy;
+                if let Some(report) = self.duplicates.take() {
+                    self.mount_duplicates(report);
+                }
+            }
+            LIMsg::DuplicatesDelete => self.library_before_delete_duplicates(),
+            LIMsg::DuplicatesDeleteGroup => self.library_delete_duplicate_group(),
+            LIMsg::DuplicatesSkipGroup => self.library_skip_duplicate_group(),
+            LIMsg::DuplicatesDeleteStop => self.library_duplicates_deleted(),
+            LIMsg::DuplicatesClose => self.umount_duplicates(),
         }
     }
 
@@ -837,10 +916,8 @@ impl Model {
                 self.playlist_play_selected(*index);
             }
             PLMsg::LoopModeCycle => {
+                // the title is updated once the server replied with the new mode
                 self.command(&PlayerCmd::CycleLoop);
-                self.config_server.write().settings.player.loop_mode =
-                    self.playlist.cycle_loop_mode();
-                self.playlist_update_title();
             }
             PLMsg::PlaylistTableBlurDown => match self.layout {
                 TermusicLayout::TreeView => assert!(self.app.active(&Id::Library).is_ok()),
@@ -874,6 +951,39 @@ impl Model {
             PLMsg::AddRandomTracks => {
                 self.playlist_add_random_tracks();
             }
+            PLMsg::EnqueueNext(current_node) => {
+                if let Err(e) = self.queue_add(current_node, QueuePosition::Next) {
+                    self.mount_error_popup(e.context("queue add"));
+                }
+            }
+            PLMsg::EnqueueLast(current_node) => {
+                if let Err(e) = self.queue_add(current_node, QueuePosition::Last) {
+                    self.mount_error_popup(e.context("queue add"));
+                }
+            }
+            PLMsg::QueueClear => {
+                self.queue_clear();
+            }
+            PLMsg::Undo => self.command(&PlayerCmd::PlaylistUndo),
+            PLMsg::Redo => self.command(&PlayerCmd::PlaylistRedo),
+            PLMsg::NamedPlaylistNext => self.playlist_tabs_cycle(true),
+            PLMsg::NamedPlaylistPrev => self.playlist_tabs_cycle(false),
+            PLMsg::NamedPlaylistCreateShow => self.playlist_tabs_create_show(),
+            PLMsg::NamedPlaylistRenameShow => self.playlist_tabs_rename_show(),
+            PLMsg::NamedPlaylistNameCancel => self.umount_playlist_name(),
+            PLMsg::NamedPlaylistCreate(name) => self.playlist_tabs_create(name),
+            PLMsg::NamedPlaylistRename(name) => self.playlist_tabs_rename(name),
+            PLMsg::NamedPlaylistDuplicate => self.playlist_tabs_duplicate(),
+            PLMsg::NamedPlaylistDeleteShow => self.playlist_tabs_delete_show(),
+            PLMsg::NamedPlaylistDeleteCancel => self.umount_playlist_delete_confirm(),
+            PLMsg::NamedPlaylistDelete => self.playlist_tabs_delete(),
+            PLMsg::ImportConfirm => self.playlist_import_confirm(),
+            PLMsg::ImportCancel => self.umount_playlist_import(),
+            PLMsg::SortShow => self.mount_playlist_edit(PlaylistEditMode::Sort),
+            PLMsg::Sort(index) => self.playlist_edit(PlaylistEditMode::Sort, *index),
+            PLMsg::DedupShow => self.mount_playlist_edit(PlaylistEditMode::Dedup),
+            PLMsg::Dedup(index) => self.playlist_edit(PlaylistEditMode::Dedup, *index),
+            PLMsg::EditCancel => self.umount_playlist_edit(),
             PLMsg::PlaylistTableBlurUp => match self.layout {
                 TermusicLayout::TreeView => assert!(self.app.active(&Id::Library).is_ok()),
                 TermusicLayout::DataBase => {
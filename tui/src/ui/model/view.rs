@@ -1,7 +1,7 @@
 use crate::ui::components::{
     DBListCriteria, DBListSearchResult, DBListSearchTracks, DownloadSpinner, EpisodeList,
     FeedsList, Footer, GSInputPopup, GSTablePopup, GlobalListener, LabelSpan, Lyric, MusicLibrary,
-    Playlist, Progress, Source,
+    Playlist, PlaylistTabs, Progress, Queue, Source, QUEUE_MAX_VISIBLE,
 };
 use crate::ui::model::{ConfigEditorLayout, Model, TermusicLayout};
 use crate::ui::utils::{
@@ -39,7 +39,7 @@ use termusiclib::utils::get_parent_folder;
 use tui_realm_treeview::Tree;
 use tuirealm::event::NoUserEvent;
 use tuirealm::props::{AttrValue, Attribute, Color, PropPayload, PropValue, TextSpan};
-use tuirealm::tui::layout::{Constraint, Direction, Layout};
+use tuirealm::tui::layout::{Constraint, Direction, Layout, Rect};
 use tuirealm::tui::widgets::Clear;
 use tuirealm::EventListenerCfg;
 use tuirealm::{Frame, State, StateValue};
@@ -106,6 +106,16 @@ impl Model {
                 vec![]
             )
             .is_ok());
+        assert!(app
+            .mount(Id::Queue, Box::new(Queue::new(config)), vec![])
+            .is_ok());
+        assert!(app
+            .mount(
+                Id::PlaylistTabs,
+                Box::new(PlaylistTabs::new(config)),
+                vec![]
+            )
+            .is_ok());
         assert!(app
             .mount(
                 Id::Progress,
@@ -190,6 +200,7 @@ impl Model {
     }
 
     pub fn view_layout_podcast(&mut self) {
+        let queue_len = self.playlist.queue().len();
         self.terminal
             .raw_mut()
             .draw(|f| {
@@ -224,7 +235,7 @@ impl Model {
 
                 self.app.view(&Id::Podcast, f, chunks_left[0]);
                 self.app.view(&Id::Episode, f, chunks_left[1]);
-                self.app.view(&Id::Playlist, f, chunks_right[0]);
+                Self::view_playlist_and_queue(f, &mut self.app, chunks_right[0], queue_len);
                 self.app.view(&Id::Lyric, f, chunks_right[1]);
                 self.app.view(&Id::Progress, f, chunks_main[1]);
                 self.app.view(&Id::Label, f, chunks_main[2]);
@@ -234,6 +245,7 @@ impl Model {
             .expect("Expected to draw without error");
     }
     pub fn view_layout_database(&mut self) {
+        let queue_len = self.playlist.queue().len();
         self.terminal
             .raw_mut()
             .draw(|f| {
@@ -280,7 +292,7 @@ impl Model {
                 self.app
                     .view(&Id::DBListSearchTracks, f, chunks_left_sections[2]);
 
-                self.app.view(&Id::Playlist, f, chunks_right[0]);
+                Self::view_playlist_and_queue(f, &mut self.app, chunks_right[0], queue_len);
                 self.app.view(&Id::Progress, f, chunks_right[1]);
                 self.app.view(&Id::Lyric, f, chunks_right[2]);
                 Self::view_layout_commons(f, &mut self.app, self.download_tracker.visible());
@@ -289,6 +301,7 @@ impl Model {
     }
 
     pub fn view_layout_treeview(&mut self) {
+        let queue_len = self.playlist.queue().len();
         self.terminal
             .raw_mut()
             .draw(|f| {
@@ -316,7 +329,7 @@ impl Model {
                     .split(chunks_left[1]);
 
                 self.app.view(&Id::Library, f, chunks_left[0]);
-                self.app.view(&Id::Playlist, f, chunks_right[0]);
+                Self::view_playlist_and_queue(f, &mut self.app, chunks_right[0], queue_len);
                 self.app.view(&Id::Progress, f, chunks_right[1]);
                 self.app.view(&Id::Lyric, f, chunks_right[2]);
                 self.app.view(&Id::Label, f, chunks_main[1]);
@@ -326,6 +339,37 @@ impl Model {
             .expect("Expected to draw without error");
     }
 
+    /// Draw the playlist in `area`, with the up-next queue above it if it is not empty
+    fn view_playlist_and_queue(
+        f: &mut Frame<'_>,
+        app: &mut Application<Id, Msg, NoUserEvent>,
+        area: Rect,
+        queue_len: usize,
+    ) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(0)
+            .constraints([Constraint::Length(1), Constraint::Min(2)].as_ref())
+            .split(area);
+        app.view(&Id::PlaylistTabs, f, chunks[0]);
+        let area = chunks[1];
+
+        if queue_len == 0 {
+            app.view(&Id::Playlist, f, area);
+            return;
+        }
+
+        // +2 for the borders
+        let queue_height = u16::try_from(queue_len.min(QUEUE_MAX_VISIBLE) + 2).unwrap_or(u16::MAX);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(0)
+            .constraints([Constraint::Length(queue_height), Constraint::Min(2)].as_ref())
+            .split(area);
+        app.view(&Id::Queue, f, chunks[0]);
+        app.view(&Id::Playlist, f, chunks[1]);
+    }
+
     #[allow(clippy::too_many_lines)]
     fn view_layout_commons(
         f: &mut Frame<'_>,
@@ -428,10 +472,50 @@ impl Model {
             let popup = draw_area_in_absolute(f.size(), 40, 3);
             f.render_widget(Clear, popup);
             app.view(&Id::SavePlaylistConfirm, f, popup);
+        } else if app.mounted(&Id::PlaylistNamePopup) {
+            let popup = draw_area_in_absolute(f.size(), 50, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::PlaylistNamePopup, f, popup);
+        } else if app.mounted(&Id::PlaylistDeleteConfirm) {
+            let popup = draw_area_in_absolute(f.size(), 40, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::PlaylistDeleteConfirm, f, popup);
+        } else if app.mounted(&Id::PlaylistEditPopup) {
+            let popup = draw_area_in_absolute(f.size(), 40, 16);
+            f.render_widget(Clear, popup);
+            app.view(&Id::PlaylistEditPopup, f, popup);
+        } else if app.mounted(&Id::PlaylistImportPopup) {
+            let popup = draw_area_in_relative(f.size(), 80, 60);
+            f.render_widget(Clear, popup);
+            app.view(&Id::PlaylistImportPopup, f, popup);
+        } else if app.mounted(&Id::DuplicatesConfirmPopup) {
+            let popup = draw_area_in_absolute(f.size(), 70, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::DuplicatesConfirmPopup, f, popup);
+        } else if app.mounted(&Id::DuplicatesPopup) {
+            let popup = draw_area_in_relative(f.size(), 80, 60);
+            f.render_widget(Clear, popup);
+            app.view(&Id::DuplicatesPopup, f, popup);
+        } else if app.mounted(&Id::DownloadQueuePopup) {
+            let popup = draw_area_in_relative(f.size(), 80, 60);
+            f.render_widget(Clear, popup);
+            app.view(&Id::DownloadQueuePopup, f, popup);
+        } else if app.mounted(&Id::ShowNotesPopup) {
+            let popup = draw_area_in_relative(f.size(), 80, 80);
+            f.render_widget(Clear, popup);
+            app.view(&Id::ShowNotesPopup, f, popup);
         } else if app.mounted(&Id::PodcastAddPopup) {
-            let popup = draw_area_in_absolute(f.size(), 65, 3);
+            let popup = draw_area_in_absolute(f.size(), 76, 12);
             f.render_widget(Clear, popup);
             app.view(&Id::PodcastAddPopup, f, popup);
+        } else if app.mounted(&Id::PodcastPolicyPopup) {
+            let popup = draw_area_in_absolute(f.size(), 65, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::PodcastPolicyPopup, f, popup);
+        } else if app.mounted(&Id::PodcastFolderPopup) {
+            let popup = draw_area_in_absolute(f.size(), 50, 3);
+            f.render_widget(Clear, popup);
+            app.view(&Id::PodcastFolderPopup, f, popup);
         }
         if app.mounted(&Id::MessagePopup) {
             let popup = draw_area_top_right_absolute(f.size(), 25, 4);
@@ -578,6 +662,12 @@ impl Model {
 
         let mut path_string = get_parent_folder(&current_node);
         path_string.push('/');
+        let extension = Self::playlist_save_extension(filename);
+        let paths = if self.save_playlist_relative {
+            " (relative paths)"
+        } else {
+            " (absolute paths)"
+        };
 
         let config = self.config_tui.read();
 
@@ -594,9 +684,10 @@ impl Model {
                             .fg(config.settings.theme.fallback_foreground())
                             .bold(),
                         TextSpan::new(filename).fg(Color::Cyan).bold(),
-                        TextSpan::new(".m3u")
+                        TextSpan::new(extension)
                             .fg(config.settings.theme.fallback_foreground())
                             .bold(),
+                        TextSpan::new(paths).fg(config.settings.theme.fallback_foreground()),
                     ],
                 )),
                 Vec::default(),
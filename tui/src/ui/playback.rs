@@ -1,12 +1,20 @@
 use anyhow::Result;
+use std::time::Duration;
+use termusiclib::config::v2::server::LoopMode;
+use termusiclib::podcast::db::DownloadAction;
 use termusicplayback::player::music_player_client::MusicPlayerClient;
 use termusicplayback::player::{
-    CycleLoopRequest, GetProgressRequest, GetProgressResponse, PlaySelectedRequest,
-    ReloadConfigRequest, ReloadPlaylistRequest, SeekBackwardRequest, SeekForwardRequest,
-    SkipNextRequest, SkipPreviousRequest, SpeedDownRequest, SpeedUpRequest, ToggleGaplessRequest,
-    TogglePauseRequest, VolumeDownRequest, VolumeUpRequest,
+    CancelSleepTimerRequest, ControlDownloadsRequest, CreatePlaylistRequest, CycleLoopRequest,
+    DedupPlaylistRequest, DeletePlaylistRequest, DuplicatePlaylistRequest, ExtendSleepTimerRequest,
+    GetProgressRequest, GetProgressResponse, ListPlaylistsRequest, PlaySelectedRequest,
+    PlaylistEditReply, PlaylistHistoryReply, PlaylistInfo, RedoPlaylistRequest,
+    RefreshPodcastsRequest, ReloadConfigRequest, ReloadPlaylistRequest, RenamePlaylistRequest,
+    SeekBackwardRequest, SeekForwardRequest, SeekToRequest, SetSleepTimerRequest, SkipNextRequest,
+    SkipPreviousRequest, SleepTimerState, SortPlaylistRequest, SpeedDownRequest, SpeedUpRequest,
+    SwitchPlaylistRequest, ToggleGaplessRequest, TogglePauseRequest, UndoPlaylistRequest,
+    VolumeDownRequest, VolumeUpRequest,
 };
-use termusicplayback::{PlayerProgress, Status};
+use termusicplayback::{DedupKey, PlayerProgress, SleepTimerMode, SortKey, Status};
 use tonic::transport::Channel;
 
 pub struct Playback {
@@ -61,12 +69,12 @@ impl Playback {
         Ok(response.volume.min(u32::from(u16::MAX)) as u16)
     }
 
-    pub async fn cycle_loop(&mut self) -> Result<()> {
+    pub async fn cycle_loop(&mut self) -> Result<LoopMode> {
         let request = tonic::Request::new(CycleLoopRequest {});
         let response = self.client.cycle_loop(request).await?;
         let response = response.into_inner();
         info!("Got response from server: {:?}", response);
-        Ok(())
+        Ok(LoopMode::from_u32(response.loop_mode))
     }
 
     pub async fn speed_up(&mut self) -> Result<i32> {
@@ -109,6 +117,16 @@ impl Playback {
         Ok(response.into())
     }
 
+    pub async fn seek_to(&mut self, position: Duration) -> Result<PlayerProgress> {
+        let request = tonic::Request::new(SeekToRequest {
+            position: Some(position.into()),
+        });
+        let response = self.client.seek_to(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response.into())
+    }
+
     pub async fn reload_config(&mut self) -> Result<()> {
         let request = tonic::Request::new(ReloadConfigRequest {});
         let response = self.client.reload_config(request).await?;
@@ -138,4 +156,159 @@ impl Playback {
         info!("Got response from server: {:?}", response);
         Ok(())
     }
+
+    pub async fn set_sleep_timer(&mut self, mode: SleepTimerMode) -> Result<SleepTimerState> {
+        let duration = match mode {
+            SleepTimerMode::Duration(duration) => Some(duration.into()),
+            SleepTimerMode::EndOfTrack | SleepTimerMode::EndOfAlbum => None,
+        };
+        let request = tonic::Request::new(SetSleepTimerRequest {
+            mode: mode.as_u32(),
+            duration,
+        });
+        let response = self.client.set_sleep_timer(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn extend_sleep_timer(&mut self, amount: Duration) -> Result<SleepTimerState> {
+        let request = tonic::Request::new(ExtendSleepTimerRequest {
+            amount: Some(amount.into()),
+        });
+        let response = self.client.extend_sleep_timer(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn cancel_sleep_timer(&mut self) -> Result<SleepTimerState> {
+        let request = tonic::Request::new(CancelSleepTimerRequest {});
+        let response = self.client.cancel_sleep_timer(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn undo_playlist(&mut self) -> Result<PlaylistHistoryReply> {
+        let request = tonic::Request::new(UndoPlaylistRequest {});
+        let response = self.client.undo_playlist(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn redo_playlist(&mut self) -> Result<PlaylistHistoryReply> {
+        let request = tonic::Request::new(RedoPlaylistRequest {});
+        let response = self.client.redo_playlist(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn sort_playlist(
+        &mut self,
+        key: SortKey,
+        descending: bool,
+    ) -> Result<PlaylistEditReply> {
+        let request = tonic::Request::new(SortPlaylistRequest {
+            key: key.as_u32(),
+            descending,
+        });
+        let response = self.client.sort_playlist(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn dedup_playlist(&mut self, key: DedupKey) -> Result<PlaylistEditReply> {
+        let request = tonic::Request::new(DedupPlaylistRequest { key: key.as_u32() });
+        let response = self.client.dedup_playlist(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn refresh_podcasts(&mut self, podcast_id: Option<i64>) -> Result<()> {
+        let request = tonic::Request::new(RefreshPodcastsRequest {
+            podcast_id: podcast_id.unwrap_or_default(),
+            folder: String::new(),
+        });
+        let response = self.client.refresh_podcasts(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn control_downloads(
+        &mut self,
+        action: DownloadAction,
+        episode_ids: Vec<i64>,
+    ) -> Result<()> {
+        let request = tonic::Request::new(ControlDownloadsRequest {
+            action: action.as_u32(),
+            episode_ids,
+        });
+        let response = self.client.control_downloads(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn refresh_podcast_folder(&mut self, folder: String) -> Result<()> {
+        let request = tonic::Request::new(RefreshPodcastsRequest {
+            podcast_id: 0,
+            folder,
+        });
+        let response = self.client.refresh_podcasts(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn list_playlists(&mut self) -> Result<Vec<PlaylistInfo>> {
+        let request = tonic::Request::new(ListPlaylistsRequest {});
+        let response = self.client.list_playlists(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response.playlists)
+    }
+
+    pub async fn create_playlist(&mut self, name: String) -> Result<PlaylistInfo> {
+        let request = tonic::Request::new(CreatePlaylistRequest { name });
+        let response = self.client.create_playlist(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn rename_playlist(&mut self, id: u64, name: String) -> Result<PlaylistInfo> {
+        let request = tonic::Request::new(RenamePlaylistRequest { id, name });
+        let response = self.client.rename_playlist(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn duplicate_playlist(&mut self, id: u64, name: String) -> Result<PlaylistInfo> {
+        let request = tonic::Request::new(DuplicatePlaylistRequest { id, name });
+        let response = self.client.duplicate_playlist(request).await?;
+        let response = response.into_inner();
+        info!("Got response from server: {:?}", response);
+        Ok(response)
+    }
+
+    pub async fn delete_playlist(&mut self, id: u64) -> Result<()> {
+        let request = tonic::Request::new(DeletePlaylistRequest { id });
+        let response = self.client.delete_playlist(request).await?;
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
+
+    pub async fn switch_playlist(&mut self, id: u64) -> Result<()> {
+        let request = tonic::Request::new(SwitchPlaylistRequest { id });
+        let response = self.client.switch_playlist(request).await?;
+        info!("Got response from server: {:?}", response);
+        Ok(())
+    }
 }